@@ -1,11 +1,9 @@
-use std::sync::Arc;
 use steam_session::login_session::connect_webapi;
+use steam_session::cookies::CookieDomain;
 use steam_session::request::StartLoginSessionWithCredentialsDetails;
-use steam_session::proto::steammessages_auth_steamclient::EAuthTokenPlatformType;
+use steam_session::enums::EAuthTokenPlatformType;
 use another_steam_totp::generate_auth_code;
 use log::LevelFilter;
-use reqwest::Client;
-use url::Url;
 use scraper::{Html, Selector};
 
 #[tokio::main]
@@ -19,7 +17,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut session = connect_webapi().await?;
     let response = session.start_with_credentials(StartLoginSessionWithCredentialsDetails {
         account_name,
-        password,
+        password: password.into(),
         platform_type: EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp,
         ..Default::default()
     }).await?;
@@ -32,27 +30,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
     
-    // Get the cookies
-    let cookies = session.get_web_cookies().await?;
-    
-    println!("Got {} cookies", cookies.len());
+    // Get a client with the cookies for steamcommunity.com already loaded
+    let client = session.build_authenticated_client(&[CookieDomain::Community]).await?;
     
     // Logging from here on out isn't useful
     simple_logging::log_to_stderr(LevelFilter::Error);
     
-    let client = {
-        // We need to add the cookies to a cookie jar before we can use them with reqwest
-        let jar = reqwest::cookie::Jar::default();
-        let url = "https://steamcommunity.com".parse::<Url>()?;
-        
-        for cookie in cookies {
-            jar.add_cookie_str(&cookie, &url)
-        }
-        
-        Client::builder()
-            .cookie_provider(Arc::new(jar))
-            .build()?
-    };
     // Let's give these cookies a test by fetching our profile
     let html = client.get("https://steamcommunity.com/my")
         .send()
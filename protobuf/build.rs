@@ -0,0 +1,61 @@
+//! Regenerates the Rust definitions from the vendored `.proto` files in `protos/` into `OUT_DIR` 
+//! when the `regen-protos` feature is enabled, and builds the crate from them instead of the 
+//! checked-in definitions in `src/`. Without the feature, nothing is generated.
+
+fn main() {
+    #[cfg(feature = "regen-protos")]
+    regen::run();
+}
+
+#[cfg(feature = "regen-protos")]
+mod regen {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    
+    const PROTOS_DIR: &str = "protos";
+    
+    pub fn run() {
+        println!("cargo:rerun-if-changed={PROTOS_DIR}");
+        
+        // build scripts may only write to OUT_DIR
+        let out_dir = Path::new(&std::env::var_os("OUT_DIR").expect("OUT_DIR is not set"))
+            .join("protos");
+        
+        fs::create_dir_all(&out_dir).expect("Failed to create output directory");
+        
+        let mut inputs = fs::read_dir(PROTOS_DIR)
+            .expect("Failed to read protos directory")
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "proto"))
+            .collect::<Vec<PathBuf>>();
+        
+        inputs.sort();
+        
+        protobuf_codegen::Codegen::new()
+            // Parses the .proto files in Rust, so protoc does not need to be installed
+            .pure()
+            .include(PROTOS_DIR)
+            .inputs(&inputs)
+            .out_dir(&out_dir)
+            .customize(protobuf_codegen::Customize::default().gen_mod_rs(false))
+            .run_from_script();
+        
+        // e.g. steammessages_auth.steamclient.proto is generated as steammessages_auth_steamclient.rs
+        // the modules are declared with absolute paths since they are included from src/lib.rs
+        let modules = inputs
+            .iter()
+            .filter_map(|path| path.file_stem())
+            .map(|stem| {
+                let module = stem.to_string_lossy().replace('.', "_");
+                let path = out_dir.join(format!("{module}.rs"));
+                
+                format!("#[path = {:?}]\npub mod {module};\n", path.display().to_string())
+            })
+            .collect::<String>();
+        
+        fs::write(out_dir.join("mod.rs"), modules)
+            .expect("Failed to write mod.rs");
+        println!("cargo:warning=Generated protobuf definitions in {}", out_dir.display());
+    }
+}
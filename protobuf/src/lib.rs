@@ -1,6 +1,15 @@
+#[cfg(feature = "regen-protos")]
+include!(concat!(env!("OUT_DIR"), "/protos/mod.rs"));
+
+#[cfg(not(feature = "regen-protos"))]
 pub mod custom;
+#[cfg(not(feature = "regen-protos"))]
 pub mod enums;
+#[cfg(not(feature = "regen-protos"))]
 pub mod steammessages_auth_steamclient;
+#[cfg(not(feature = "regen-protos"))]
 pub mod steammessages_base;
+#[cfg(not(feature = "regen-protos"))]
 pub mod steammessages_clientserver_login;
+#[cfg(not(feature = "regen-protos"))]
 pub mod steammessages_unified_base_steamclient;
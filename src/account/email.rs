@@ -0,0 +1,92 @@
+use super::{AccountClient, Error, RecoveryMethod, require_success};
+use super::wizard::WizardSession;
+use std::time::Duration;
+
+/// The help wizard's issue ID for changing the email address.
+const CHANGE_EMAIL_ISSUE_ID: &str = "409";
+
+/// An email address change in progress, started with [`AccountClient::begin_email_change`].
+/// Steps must be completed in order: verify with `send_code` followed by `poll_confirmation` or
+/// `verify_code`, then `change_email`, then `confirm_email` with the code sent to the new
+/// address.
+#[derive(Debug)]
+pub struct EmailChange<'a> {
+    wizard: WizardSession<'a>,
+    email: Option<String>,
+}
+
+impl AccountClient {
+    /// Starts changing the email address of the account.
+    pub async fn begin_email_change(&self) -> Result<EmailChange<'_>, Error> {
+        let wizard = WizardSession::begin(self, "HelpChangeEmail", CHANGE_EMAIL_ISSUE_ID).await?;
+        
+        Ok(EmailChange {
+            wizard,
+            email: None,
+        })
+    }
+}
+
+impl EmailChange<'_> {
+    /// Sends a code or confirmation to verify the change using `method`.
+    pub async fn send_code(&mut self, method: RecoveryMethod) -> Result<(), Error> {
+        self.wizard.send_code(method).await
+    }
+    
+    /// Checks whether the confirmation sent with [`RecoveryMethod::MobileApp`] has been accepted.
+    pub async fn poll_confirmation(&self) -> Result<bool, Error> {
+        self.wizard.poll_confirmation().await
+    }
+    
+    /// Polls every `interval` until the confirmation sent with [`RecoveryMethod::MobileApp`] is
+    /// accepted, failing with [`Error::Timeout`] after `timeout`.
+    pub async fn wait_for_confirmation(
+        &self,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.wizard.wait_for_confirmation(interval, timeout).await
+    }
+    
+    /// Verifies the change with the code sent with [`RecoveryMethod::Email`] or
+    /// [`RecoveryMethod::Sms`].
+    pub async fn verify_code(&self, code: &str) -> Result<(), Error> {
+        self.wizard.verify_code(code).await
+    }
+    
+    /// Requests changing the address to `email`. Steam sends a code to the new address, which
+    /// must be submitted with `confirm_email` to complete the change.
+    pub async fn change_email(&mut self, email: &str) -> Result<(), Error> {
+        let response = self.wizard.account.wizard_post("AjaxAccountRecoveryChangeEmail", vec![
+            ("s", self.wizard.s.clone()),
+            ("account", self.wizard.account.account_id().to_string()),
+            ("email", email.into()),
+        ]).await?;
+        
+        require_success(response)?;
+        self.email = Some(email.into());
+        
+        Ok(())
+    }
+    
+    /// Completes the change with the `code` sent to the new address.
+    pub async fn confirm_email(&self, code: &str) -> Result<(), Error> {
+        let email = self.email.clone()
+            .ok_or(Error::OutOfOrder("change_email must be called before confirm_email"))?;
+        let response = self.wizard.account.wizard_post("AjaxAccountRecoveryConfirmChangeEmail", vec![
+            ("s", self.wizard.s.clone()),
+            ("account", self.wizard.account.account_id().to_string()),
+            ("email", email),
+            ("email_change_code", code.into()),
+        ]).await?;
+        
+        require_success(response)?;
+        
+        Ok(())
+    }
+    
+    /// Gets the new address passed to `change_email`.
+    pub fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+}
@@ -0,0 +1,38 @@
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("reqwest error: {}", .0)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Login session error: {}", .0)]
+    LoginSession(#[from] crate::login_session::LoginSessionError),
+    #[error("WebAPI client error: {}", .0)]
+    WebApi(#[from] crate::web_api_client::Error),
+    #[error("Authentication client error: {}", .0)]
+    AuthenticationClient(#[from] crate::authentication_client::Error),
+    #[error("The session is not logged in to help.steampowered.com")]
+    NotLoggedIn,
+    #[error("Steam rejected the request: {}", .0)]
+    Rejected(String),
+    #[error("Unexpected response: {}", .0)]
+    UnexpectedResponse(&'static str),
+    #[error("Steps were completed out of order: {}", .0)]
+    OutOfOrder(&'static str),
+    #[error("Timed out waiting for confirmation")]
+    Timeout,
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::Reqwest(error) => crate::error::reqwest_kind(error),
+            Self::LoginSession(error) => error.kind(),
+            Self::WebApi(error) => error.kind(),
+            Self::AuthenticationClient(error) => error.kind(),
+            Self::NotLoggedIn => crate::ErrorKind::Token,
+            Self::Rejected(_) => crate::ErrorKind::Steam,
+            Self::UnexpectedResponse(_) => crate::ErrorKind::Decode,
+            Self::OutOfOrder(_) => crate::ErrorKind::State,
+            Self::Timeout => crate::ErrorKind::Timeout,
+        }
+    }
+}
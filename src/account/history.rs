@@ -0,0 +1,279 @@
+use super::{AccountClient, Error};
+use crate::enums::EAuthTokenPlatformType;
+use crate::types::DateTime;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use lazy_regex::{regex, regex_captures};
+use protobuf::Enum;
+use serde::{Deserialize, Deserializer};
+use serde::de;
+use serde_json::Value;
+
+/// A login to the account, from the login history on `help.steampowered.com`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginHistoryEntry {
+    /// When the login happened, as displayed.
+    pub login_time: String,
+    /// When the session ended, as displayed, if it has.
+    pub logout_time: Option<String>,
+    /// The operating system of the device, as displayed.
+    pub os: Option<String>,
+    /// The country the login came from, inferred from its IP address.
+    pub country: Option<String>,
+    /// The state or region the login came from.
+    pub state: Option<String>,
+    /// The city the login came from.
+    pub city: Option<String>,
+}
+
+/// A device authorized to the account, from `store.steampowered.com/account/authorizeddevices`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizedDevice {
+    /// The ID of the device's refresh token, which can be used to revoke it.
+    pub token_id: u64,
+    /// The name of the device.
+    pub description: String,
+    /// When the device's token was last updated.
+    pub time_updated: Option<DateTime>,
+    /// The platform the token was issued for.
+    pub platform_type: EAuthTokenPlatformType,
+    /// Whether the device is logged in. Devices that are not are remembered for Steam Guard only.
+    pub logged_in: bool,
+    /// The `EOSType` of the device, if known.
+    pub os_type: Option<i32>,
+    /// Where and when the device was first authorized.
+    pub first_seen: Option<DeviceEvent>,
+    /// Where and when the device was last seen.
+    pub last_seen: Option<DeviceEvent>,
+}
+
+/// Where and when a device was seen. Steam may fuzz or omit any of these.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceEvent {
+    /// When the device was seen.
+    pub time: Option<DateTime>,
+    /// The IP address the device was seen at.
+    pub ip: Option<IpAddr>,
+    /// The country code inferred from the IP address.
+    pub country: Option<String>,
+    /// The state code inferred from the IP address.
+    pub state: Option<String>,
+    /// The city inferred from the IP address.
+    pub city: Option<String>,
+}
+
+impl AccountClient {
+    /// Gets the recent logins to the account.
+    pub async fn login_history(&self) -> Result<Vec<LoginHistoryEntry>, Error> {
+        let html = self.client.get(self.help_url("accountdata/SteamLoginHistory"))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        
+        parse_login_history(&html)
+    }
+    
+    /// Gets the devices authorized to the account, along with where they were first and last
+    /// seen.
+    pub async fn authorized_devices(&self) -> Result<Vec<AuthorizedDevice>, Error> {
+        let html = self.client.get(self.store_url("account/authorizeddevices"))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        
+        parse_authorized_devices(&html)
+    }
+}
+
+fn parse_login_history(html: &str) -> Result<Vec<LoginHistoryEntry>, Error> {
+    if !html.contains("<table") {
+        return Err(Error::NotLoggedIn);
+    }
+    
+    let entries = regex!(r#"(?s)<tr[^>]*>(.*?)</tr>"#)
+        .captures_iter(html)
+        .filter_map(|row| {
+            let mut cells = regex!(r#"(?s)<td[^>]*>(.*?)</td>"#)
+                .captures_iter(&row[1])
+                .map(|cell| {
+                    let text = html_unescape(&regex!(r"<[^>]*>").replace_all(&cell[1], ""));
+                    let text = text.trim();
+                    
+                    Some(text.to_string()).filter(|text| !text.is_empty())
+                });
+            
+            Some(LoginHistoryEntry {
+                // header rows have no cells
+                login_time: cells.next()??,
+                logout_time: cells.next().flatten(),
+                os: cells.next().flatten(),
+                country: cells.next().flatten(),
+                state: cells.next().flatten(),
+                city: cells.next().flatten(),
+            })
+        })
+        .collect();
+    
+    Ok(entries)
+}
+
+fn parse_authorized_devices(html: &str) -> Result<Vec<AuthorizedDevice>, Error> {
+    let (_, devices) = regex_captures!(r#"data-active_devices="([^"]*)""#, html)
+        .ok_or(Error::NotLoggedIn)?;
+    let devices = serde_json::from_str::<Vec<RawDevice>>(&html_unescape(devices))
+        .map_err(|_| Error::UnexpectedResponse("authorized devices are not valid JSON"))?;
+    
+    Ok(devices.into_iter().map(AuthorizedDevice::from).collect())
+}
+
+/// Unescapes the entities used in text and attribute values.
+fn html_unescape(text: &str) -> String {
+    text
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDevice {
+    #[serde(deserialize_with = "u64_from_number_or_string")]
+    token_id: u64,
+    #[serde(default)]
+    token_description: String,
+    #[serde(default)]
+    time_updated: Option<i64>,
+    #[serde(default)]
+    platform_type: Option<i32>,
+    #[serde(default)]
+    logged_in: bool,
+    #[serde(default)]
+    os_type: Option<i32>,
+    #[serde(default)]
+    first_seen: Option<RawDeviceEvent>,
+    #[serde(default)]
+    last_seen: Option<RawDeviceEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDeviceEvent {
+    #[serde(default)]
+    time: Option<i64>,
+    #[serde(default)]
+    ip: Option<RawIpAddress>,
+    #[serde(default)]
+    country: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    city: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawIpAddress {
+    #[serde(default)]
+    v4: Option<u32>,
+    #[serde(default)]
+    v6: Option<String>,
+}
+
+impl From<RawDevice> for AuthorizedDevice {
+    fn from(device: RawDevice) -> Self {
+        Self {
+            token_id: device.token_id,
+            description: device.token_description,
+            time_updated: device.time_updated.and_then(|time| DateTime::from_timestamp(time, 0)),
+            platform_type: device.platform_type
+                .and_then(EAuthTokenPlatformType::from_i32)
+                .unwrap_or(EAuthTokenPlatformType::k_EAuthTokenPlatformType_Unknown),
+            logged_in: device.logged_in,
+            os_type: device.os_type,
+            first_seen: device.first_seen.map(DeviceEvent::from),
+            last_seen: device.last_seen.map(DeviceEvent::from),
+        }
+    }
+}
+
+impl From<RawDeviceEvent> for DeviceEvent {
+    fn from(event: RawDeviceEvent) -> Self {
+        let ip = event.ip.and_then(|ip| match (ip.v4, ip.v6) {
+            (Some(v4), _) => Some(IpAddr::V4(Ipv4Addr::from(v4))),
+            // IPv6 addresses are sent as base64-encoded bytes
+            (None, Some(v6)) => crate::helpers::decode_base64(v6.trim_end_matches('='))
+                .ok()
+                .and_then(|bytes| <[u8; 16]>::try_from(bytes).ok())
+                .map(|bytes| IpAddr::V6(Ipv6Addr::from(bytes))),
+            (None, None) => None,
+        });
+        
+        Self {
+            time: event.time.and_then(|time| DateTime::from_timestamp(time, 0)),
+            ip,
+            country: event.country.filter(|country| !country.is_empty()),
+            state: event.state.filter(|state| !state.is_empty()),
+            city: event.city.filter(|city| !city.is_empty()),
+        }
+    }
+}
+
+fn u64_from_number_or_string<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => s.parse::<u64>().map_err(de::Error::custom),
+        Value::Number(num) => num.as_u64().ok_or_else(|| de::Error::custom("invalid number")),
+        _ => Err(de::Error::custom("not a number")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_login_history() {
+        let html = r#"
+            <table class="account_table">
+                <tr><th>Login time</th><th>Logout time</th><th>OS type</th><th>Country</th><th>State</th><th>City</th></tr>
+                <tr>
+                    <td>3 Oct, 2024 @ 5:32am</td>
+                    <td>&nbsp;</td>
+                    <td>Windows</td>
+                    <td>United States</td>
+                    <td><span>Washington</span></td>
+                    <td>Seattle</td>
+                </tr>
+            </table>
+        "#;
+        let entries = parse_login_history(html).unwrap();
+        
+        assert_eq!(entries, vec![LoginHistoryEntry {
+            login_time: "3 Oct, 2024 @ 5:32am".into(),
+            logout_time: None,
+            os: Some("Windows".into()),
+            country: Some("United States".into()),
+            state: Some("Washington".into()),
+            city: Some("Seattle".into()),
+        }]);
+    }
+    
+    #[test]
+    fn parses_authorized_devices() {
+        let html = r#"<div id="application_config" data-active_devices="[{&quot;token_id&quot;:&quot;8921238791234&quot;,&quot;token_description&quot;:&quot;DESKTOP-1&quot;,&quot;time_updated&quot;:1700000000,&quot;platform_type&quot;:1,&quot;logged_in&quot;:true,&quot;os_type&quot;:20,&quot;last_seen&quot;:{&quot;time&quot;:1700000000,&quot;ip&quot;:{&quot;v4&quot;:3232235777},&quot;country&quot;:&quot;US&quot;,&quot;state&quot;:&quot;WA&quot;,&quot;city&quot;:&quot;Seattle&quot;}}]"></div>"#;
+        let devices = parse_authorized_devices(html).unwrap();
+        let last_seen = devices[0].last_seen.as_ref().unwrap();
+        
+        assert_eq!(devices[0].token_id, 8921238791234);
+        assert_eq!(devices[0].description, "DESKTOP-1");
+        assert_eq!(devices[0].platform_type, EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient);
+        assert!(devices[0].first_seen.is_none());
+        assert_eq!(last_seen.ip, Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))));
+        assert_eq!(last_seen.country.as_deref(), Some("US"));
+    }
+}
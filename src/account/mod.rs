@@ -0,0 +1,282 @@
+//! # Account management
+//! 
+//! Wrappers around the account management pages of `help.steampowered.com` and
+//! `store.steampowered.com`, for building account management tooling on top of a logged in
+//! session. Requests are sent with web cookies for those sites, and most changes must be
+//! re-verified with Steam Guard before they are made.
+//! 
+//! ```no_run
+//! # async fn example(mut session: steam_session::LoginSession<steam_session::WebApiTransport>) -> Result<(), steam_session::account::Error> {
+//! use steam_session::account::{AccountClient, RecoveryMethod};
+//! use std::time::Duration;
+//! 
+//! let account = AccountClient::from_session(&mut session).await?;
+//! let mut change = account.begin_password_change().await?;
+//! 
+//! // Approve the confirmation in the mobile app
+//! change.send_code(RecoveryMethod::MobileApp).await?;
+//! 
+//! change.wait_for_confirmation(Duration::from_secs(5), Duration::from_secs(300)).await?;
+//! 
+//! change.verify_password("username", "current password").await?;
+//! change.change_password("username", "new password").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+mod wizard;
+mod password;
+mod email;
+mod phone;
+mod history;
+
+pub use error::Error;
+pub use wizard::RecoveryMethod;
+pub use password::PasswordChange;
+pub use email::EmailChange;
+pub use phone::{PhoneClient, PhoneRemoval, TwoFactorStatus, SetPhoneNumberResponse, EmailConfirmationStatus};
+pub use history::{LoginHistoryEntry, AuthorizedDevice, DeviceEvent};
+
+use crate::authentication_client::{AuthenticationClient, AuthenticationClientConstructorOptions};
+use crate::cache_store::{CacheStore, MemoryCacheStore};
+use crate::cookies::CookieDomain;
+use crate::enums::{EAuthTokenPlatformType, ELanguage};
+use crate::helpers::DEFAULT_USER_AGENT;
+use crate::hosts::SteamHosts;
+use crate::login_session::LoginSession;
+use crate::retry::RetryPolicy;
+use crate::secret::Secret;
+use crate::transports::Transport;
+use crate::transports::web_api::WebApiTransport;
+use std::sync::Arc;
+use reqwest::{Client, Url};
+use serde_json::Value;
+use steamid_ng::SteamID;
+
+/// The default base URL for `help.steampowered.com`.
+pub const DEFAULT_HELP_URL: &str = "https://help.steampowered.com";
+/// The default base URL for `store.steampowered.com`.
+pub const DEFAULT_STORE_URL: &str = "https://store.steampowered.com";
+
+/// A client for managing an account through its web session.
+#[derive(Debug, Clone)]
+pub struct AccountClient {
+    client: Client,
+    steamid: SteamID,
+    sessionid: String,
+    help_url: String,
+    store_url: String,
+    hosts: SteamHosts,
+    cache_store: Arc<dyn CacheStore>,
+    authentication: Arc<AuthenticationClient<WebApiTransport>>,
+}
+
+impl AccountClient {
+    /// Creates a new [`AccountClient`]. `client` must hold web cookies for
+    /// `help.steampowered.com` for `steamid` containing `sessionid`.
+    pub fn new(client: Client, steamid: SteamID, sessionid: impl Into<String>) -> Self {
+        let hosts = SteamHosts::default();
+        let cache_store: Arc<dyn CacheStore> = Arc::new(MemoryCacheStore::new());
+        let authentication = authentication_client(&client, &hosts, &cache_store);
+        
+        Self {
+            client,
+            steamid,
+            sessionid: sessionid.into(),
+            help_url: DEFAULT_HELP_URL.into(),
+            store_url: DEFAULT_STORE_URL.into(),
+            hosts,
+            cache_store,
+            authentication,
+        }
+    }
+    
+    /// Creates a new [`AccountClient`] with a client built from `session`. See
+    /// [`LoginSession::build_authenticated_client`].
+    pub async fn from_session<T>(session: &mut LoginSession<T>) -> Result<Self, Error>
+    where
+        T: Transport,
+    {
+        let client = session.build_authenticated_client(&CookieDomain::DEFAULT).await?;
+        let steamid = session.steamid()
+            .ok_or(crate::login_session::LoginSessionError::NoRefreshToken)?;
+        
+        Ok(Self::new(client, steamid, session.get_sessionid())
+            .with_hosts(session.hosts().clone())
+            .with_cache_store(Arc::clone(session.cache_store())))
+    }
+    
+    /// The base URL for `help.steampowered.com`.
+    pub fn with_help_url(mut self, help_url: impl Into<String>) -> Self {
+        self.help_url = help_url.into();
+        self
+    }
+    
+    /// The base URL for `store.steampowered.com`.
+    pub fn with_store_url(mut self, store_url: impl Into<String>) -> Self {
+        self.store_url = store_url.into();
+        self
+    }
+    
+    /// The hosts WebAPI requests are sent to.
+    pub fn with_hosts(mut self, hosts: SteamHosts) -> Self {
+        self.hosts = hosts;
+        self.authentication = authentication_client(&self.client, &self.hosts, &self.cache_store);
+        self
+    }
+    
+    /// The store RSA keys used to encrypt passwords are cached in. Defaults to a 
+    /// [`MemoryCacheStore`] for this client.
+    pub fn with_cache_store(mut self, cache_store: Arc<dyn CacheStore>) -> Self {
+        self.cache_store = cache_store;
+        self.authentication = authentication_client(&self.client, &self.hosts, &self.cache_store);
+        self
+    }
+    
+    /// Gets the SteamID of the account.
+    pub fn steamid(&self) -> SteamID {
+        self.steamid
+    }
+    
+    /// Gets the account ID, the lower 32 bits of the SteamID.
+    pub fn account_id(&self) -> u32 {
+        u64::from(self.steamid) as u32
+    }
+    
+    fn help_url(&self, pathname: &str) -> String {
+        format!("{}/en/{pathname}", self.help_url.trim_end_matches('/'))
+    }
+    
+    fn store_url(&self, pathname: &str) -> String {
+        format!("{}/{pathname}", self.store_url.trim_end_matches('/'))
+    }
+    
+    /// Loads a page of the help wizard, returning the URL it ended up at after redirects.
+    async fn wizard_page(&self, pathname: &str, query: &[(&str, &str)]) -> Result<Url, Error> {
+        let response = self.client.get(self.help_url(&format!("wizard/{pathname}")))
+            .query(query)
+            .send()
+            .await?
+            .error_for_status()?;
+        let url = response.url().clone();
+        
+        if url.path().contains("/login") {
+            return Err(Error::NotLoggedIn);
+        }
+        
+        Ok(url)
+    }
+    
+    /// Sends a GET request to an AJAX endpoint of the help wizard.
+    async fn wizard_get(&self, pathname: &str, params: Vec<(&str, String)>) -> Result<Value, Error> {
+        let request = self.client.get(self.help_url(&format!("wizard/{pathname}")))
+            .query(&self.wizard_params(params));
+        
+        wizard_response(request.send().await?).await
+    }
+    
+    /// Sends a POST request to an AJAX endpoint of the help wizard.
+    async fn wizard_post(&self, pathname: &str, params: Vec<(&str, String)>) -> Result<Value, Error> {
+        let request = self.client.post(self.help_url(&format!("wizard/{pathname}")))
+            .form(&self.wizard_params(params));
+        
+        wizard_response(request.send().await?).await
+    }
+    
+    fn wizard_params<'a>(&self, mut params: Vec<(&'a str, String)>) -> Vec<(&'a str, String)> {
+        params.push(("sessionid", self.sessionid.clone()));
+        params.push(("wizard_ajax", "1".into()));
+        params.push(("gamepad", "0".into()));
+        params
+    }
+    
+    /// Encrypts `password` for `account_name` with the account's current RSA key. Returns the
+    /// encrypted password and the timestamp of the key.
+    async fn encrypt_password(
+        &self,
+        account_name: &str,
+        password: &str,
+    ) -> Result<(Secret<String>, u64), Error> {
+        let encrypted = self.authentication.encrypt_password(
+            account_name.into(),
+            password.into(),
+        ).await?;
+        
+        Ok((encrypted.encrypted_password, encrypted.key_timestamp))
+    }
+}
+
+/// Creates the client RSA keys for encrypting passwords are fetched with, so they are shared 
+/// with concurrent logins and cached like theirs.
+fn authentication_client(
+    client: &Client,
+    hosts: &SteamHosts,
+    cache_store: &Arc<dyn CacheStore>,
+) -> Arc<AuthenticationClient<WebApiTransport>> {
+    let transport = WebApiTransport::with_custom_client(client.clone())
+        .with_hosts(hosts.clone());
+    
+    Arc::new(AuthenticationClient::new(AuthenticationClientConstructorOptions {
+        platform_type: EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser,
+        transport,
+        client: client.clone(),
+        user_agent: DEFAULT_USER_AGENT,
+        machine_id: None,
+        language: ELanguage::default(),
+        website_id: None,
+        hosts: hosts.clone(),
+        device_friendly_name: None,
+        retry_policy: RetryPolicy::default(),
+        clock: crate::clock::system_clock(),
+        cache_store: Arc::clone(cache_store),
+    }))
+}
+
+/// Checks the response of a help wizard AJAX endpoint. These report failures with an `errorMsg`
+/// field rather than with the status code.
+async fn wizard_response(response: reqwest::Response) -> Result<Value, Error> {
+    let value = response.error_for_status()?.json::<Value>().await?;
+    
+    if let Some(message) = value.get("errorMsg").and_then(Value::as_str).filter(|message| !message.is_empty()) {
+        return Err(Error::Rejected(message.into()));
+    }
+    
+    Ok(value)
+}
+
+/// Whether the `success` field of a response is truthy. Some endpoints send it as a number, and 
+/// some omit it when successful.
+fn is_success(value: &Value) -> bool {
+    match value.get("success") {
+        Some(Value::Bool(success)) => *success,
+        Some(Value::Number(success)) => success.as_i64() != Some(0),
+        _ => true,
+    }
+}
+
+/// Fails with [`Error::Rejected`] unless the response is successful.
+fn require_success(value: Value) -> Result<Value, Error> {
+    if !is_success(&value) {
+        return Err(Error::Rejected("unsuccessful".into()));
+    }
+    
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn checks_wizard_success() {
+        assert!(is_success(&serde_json::json!({ "success": true })));
+        assert!(is_success(&serde_json::json!({ "success": 1 })));
+        assert!(is_success(&serde_json::json!({ "hash": "abc" })));
+        assert!(!is_success(&serde_json::json!({ "success": 0 })));
+        assert!(matches!(
+            require_success(serde_json::json!({ "success": false })),
+            Err(Error::Rejected(_)),
+        ));
+    }
+}
@@ -0,0 +1,96 @@
+use super::{AccountClient, Error, RecoveryMethod, require_success};
+use super::wizard::WizardSession;
+use std::time::Duration;
+use serde_json::Value;
+
+/// The help wizard's issue ID for changing the password.
+const CHANGE_PASSWORD_ISSUE_ID: &str = "406";
+
+/// A password change in progress, started with
+/// [`AccountClient::begin_password_change`]. Steps must be completed in order: verify with
+/// `send_code` followed by `poll_confirmation` or `verify_code`, then `verify_password`, then
+/// `change_password`.
+#[derive(Debug)]
+pub struct PasswordChange<'a> {
+    wizard: WizardSession<'a>,
+}
+
+impl AccountClient {
+    /// Starts changing the password of the account.
+    pub async fn begin_password_change(&self) -> Result<PasswordChange<'_>, Error> {
+        let wizard = WizardSession::begin(self, "HelpChangePassword", CHANGE_PASSWORD_ISSUE_ID).await?;
+        
+        Ok(PasswordChange {
+            wizard,
+        })
+    }
+}
+
+impl PasswordChange<'_> {
+    /// Sends a code or confirmation to verify the change using `method`.
+    pub async fn send_code(&mut self, method: RecoveryMethod) -> Result<(), Error> {
+        self.wizard.send_code(method).await
+    }
+    
+    /// Checks whether the confirmation sent with [`RecoveryMethod::MobileApp`] has been accepted.
+    pub async fn poll_confirmation(&self) -> Result<bool, Error> {
+        self.wizard.poll_confirmation().await
+    }
+    
+    /// Polls every `interval` until the confirmation sent with [`RecoveryMethod::MobileApp`] is 
+    /// accepted, failing with [`Error::Timeout`] after `timeout`.
+    pub async fn wait_for_confirmation(
+        &self,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.wizard.wait_for_confirmation(interval, timeout).await
+    }
+    
+    /// Verifies the change with the code sent with [`RecoveryMethod::Email`] or
+    /// [`RecoveryMethod::Sms`].
+    pub async fn verify_code(&self, code: &str) -> Result<(), Error> {
+        self.wizard.verify_code(code).await
+    }
+    
+    /// Verifies the current password of `account_name`.
+    pub async fn verify_password(&self, account_name: &str, password: &str) -> Result<(), Error> {
+        let (password, rsatimestamp) = self.wizard.account.encrypt_password(account_name, password).await?;
+        let response = self.wizard.account.wizard_post("AjaxAccountRecoveryVerifyPassword", vec![
+            ("s", self.wizard.s.clone()),
+            ("lost", "2".into()),
+            ("reset", "1".into()),
+            ("password", password.expose_secret().clone()),
+            ("rsatimestamp", rsatimestamp.to_string()),
+        ]).await?;
+        
+        require_success(response)?;
+        
+        Ok(())
+    }
+    
+    /// Changes the password of `account_name` to `new_password`. Fails with
+    /// [`Error::Rejected`] if Steam does not accept the new password, e.g. because it was used
+    /// before or is too weak.
+    pub async fn change_password(&self, account_name: &str, new_password: &str) -> Result<(), Error> {
+        let response = self.wizard.account.wizard_post("AjaxCheckPasswordAvailable", vec![
+            ("password", new_password.into()),
+        ]).await?;
+        
+        if response.get("available").and_then(Value::as_bool) == Some(false) {
+            return Err(Error::Rejected("new password is not available".into()));
+        }
+        
+        let (password, rsatimestamp) = self.wizard.account.encrypt_password(account_name, new_password).await?;
+        let response = self.wizard.account.wizard_post("AjaxAccountRecoveryChangePassword", vec![
+            ("s", self.wizard.s.clone()),
+            ("account", self.wizard.account.account_id().to_string()),
+            ("password", password.expose_secret().clone()),
+            ("rsatimestamp", rsatimestamp.to_string()),
+        ]).await?;
+        
+        require_success(response)?;
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use super::{AccountClient, Error, RecoveryMethod, require_success};
+use super::wizard::WizardSession;
+use crate::transports::Transport;
+use crate::web_api_client::WebApiClient;
+use std::time::Duration;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+/// The help wizard's issue ID for removing the phone number.
+const REMOVE_PHONE_ISSUE_ID: &str = "403";
+
+/// The Steam Guard status of an account, from `ITwoFactorService/QueryStatus`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TwoFactorStatus {
+    /// The state of the mobile authenticator. `0` when none is enrolled.
+    pub state: u32,
+    /// The type of the authenticator.
+    pub authenticator_type: u32,
+    /// Whether an authenticator can be added.
+    pub authenticator_allowed: bool,
+    /// The Steam Guard scheme: `1` for email, `2` for the mobile authenticator.
+    pub steamguard_scheme: u32,
+    /// Whether the account's email address is verified.
+    pub email_validated: bool,
+    /// The device ID of the enrolled authenticator.
+    pub device_identifier: Option<String>,
+    /// When the authenticator was enrolled, in seconds since the Unix epoch.
+    pub time_created: u64,
+    /// The number of times the revocation code can still be used.
+    pub revocation_attempts_remaining: u32,
+}
+
+/// The result of setting the phone number.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SetPhoneNumberResponse {
+    /// The address an email was sent to, which must be confirmed before a code is sent to the
+    /// phone.
+    pub confirmation_email_address: Option<String>,
+    /// The phone number as formatted by Steam.
+    pub phone_number_formatted: Option<String>,
+}
+
+/// Whether the email confirming a new phone number is still waiting to be clicked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EmailConfirmationStatus {
+    /// Whether the email has not been confirmed yet.
+    pub awaiting_email_confirmation: bool,
+    /// How long to wait before checking again.
+    pub seconds_to_wait: u64,
+}
+
+/// Adds and verifies the phone number of an account and checks its Steam Guard status, with the
+/// `IPhoneService` and `ITwoFactorService` WebAPI interfaces. A verified phone number is required
+/// to enroll a mobile authenticator. Phone numbers are removed with
+/// [`AccountClient::begin_phone_removal`].
+#[derive(Debug)]
+pub struct PhoneClient<T> {
+    api: WebApiClient<T>,
+}
+
+impl<T> PhoneClient<T>
+where
+    T: Transport,
+{
+    /// Creates a new [`PhoneClient`].
+    pub fn new(api: WebApiClient<T>) -> Self {
+        Self {
+            api,
+        }
+    }
+    
+    /// Consumes the client, returning the WebAPI client.
+    pub fn into_inner(self) -> WebApiClient<T> {
+        self.api
+    }
+    
+    /// Gets the Steam Guard status of the account.
+    pub async fn two_factor_status(&mut self) -> Result<TwoFactorStatus, Error> {
+        let steamid = self.api.session().steamid()
+            .ok_or(crate::login_session::LoginSessionError::NoRefreshToken)?;
+        
+        self.post("ITwoFactorService", "QueryStatus", &[("steamid", u64::from(steamid).to_string())]).await
+    }
+    
+    /// Checks whether the account has a verified phone number.
+    pub async fn has_verified_phone(&mut self) -> Result<bool, Error> {
+        #[derive(Default, Deserialize)]
+        struct Response {
+            #[serde(default)]
+            verified_phone: bool,
+        }
+        
+        let response: Response = self.post("IPhoneService", "AccountPhoneStatus", &[]).await?;
+        
+        Ok(response.verified_phone)
+    }
+    
+    /// Sets the phone number of the account to `phone_number`, including the country calling
+    /// code, e.g. `+1 5555550123`. `country_code` is the two letter country code, e.g. `US`.
+    /// Steam sends an email that must be confirmed before sending a code to the phone.
+    pub async fn set_phone_number(
+        &mut self,
+        phone_number: &str,
+        country_code: &str,
+    ) -> Result<SetPhoneNumberResponse, Error> {
+        self.post("IPhoneService", "SetAccountPhoneNumber", &[
+            ("phone_number", phone_number.into()),
+            ("phone_country_code", country_code.into()),
+        ]).await
+    }
+    
+    /// Checks whether the email sent by `set_phone_number` is still waiting to be confirmed.
+    pub async fn email_confirmation_status(&mut self) -> Result<EmailConfirmationStatus, Error> {
+        self.post("IPhoneService", "IsAccountWaitingForEmailConfirmation", &[]).await
+    }
+    
+    /// Polls until the email sent by `set_phone_number` is confirmed, waiting as long as Steam
+    /// asks between checks. Fails with [`Error::Timeout`] after `timeout`.
+    pub async fn wait_for_email_confirmation(&mut self, timeout: Duration) -> Result<(), Error> {
+        let started = std::time::Instant::now();
+        
+        loop {
+            let status = self.email_confirmation_status().await?;
+            
+            if !status.awaiting_email_confirmation {
+                return Ok(());
+            }
+            
+            let wait = Duration::from_secs(status.seconds_to_wait.max(1));
+            
+            if started.elapsed() + wait > timeout {
+                return Err(Error::Timeout);
+            }
+            
+            tokio::time::sleep(wait).await;
+        }
+    }
+    
+    /// Sends a verification code to the phone by SMS.
+    pub async fn send_verification_code(&mut self) -> Result<(), Error> {
+        let _: serde_json::Value = self.post("IPhoneService", "SendPhoneVerificationCode", &[]).await?;
+        
+        Ok(())
+    }
+    
+    /// Verifies the phone number with the `code` sent by `send_verification_code`.
+    pub async fn verify_with_code(&mut self, code: &str) -> Result<(), Error> {
+        let _: serde_json::Value = self.post("IPhoneService", "VerifyAccountPhoneWithCode", &[
+            ("code", code.into()),
+        ]).await?;
+        
+        Ok(())
+    }
+    
+    async fn post<R>(
+        &mut self,
+        interface: &str,
+        method: &str,
+        form: &[(&str, String)],
+    ) -> Result<R, Error>
+    where
+        R: DeserializeOwned + Default,
+    {
+        #[derive(Deserialize)]
+        struct Envelope<R> {
+            #[serde(default)]
+            response: Option<R>,
+        }
+        
+        let envelope: Envelope<R> = self.api.post_json(interface, method, 1, form).await?;
+        
+        Ok(envelope.response.unwrap_or_default())
+    }
+}
+
+/// A phone number removal in progress, started with [`AccountClient::begin_phone_removal`].
+/// Verify with `send_code` followed by `poll_confirmation` or `verify_code`, then call
+/// `remove_phone`.
+#[derive(Debug)]
+pub struct PhoneRemoval<'a> {
+    wizard: WizardSession<'a>,
+}
+
+impl AccountClient {
+    /// Starts removing the phone number from the account.
+    pub async fn begin_phone_removal(&self) -> Result<PhoneRemoval<'_>, Error> {
+        let wizard = WizardSession::begin(self, "HelpRemovePhoneNumber", REMOVE_PHONE_ISSUE_ID).await?;
+        
+        Ok(PhoneRemoval {
+            wizard,
+        })
+    }
+}
+
+impl PhoneRemoval<'_> {
+    /// Sends a code or confirmation to verify the removal using `method`.
+    pub async fn send_code(&mut self, method: RecoveryMethod) -> Result<(), Error> {
+        self.wizard.send_code(method).await
+    }
+    
+    /// Checks whether the confirmation sent with [`RecoveryMethod::MobileApp`] has been accepted.
+    pub async fn poll_confirmation(&self) -> Result<bool, Error> {
+        self.wizard.poll_confirmation().await
+    }
+    
+    /// Polls every `interval` until the confirmation sent with [`RecoveryMethod::MobileApp`] is
+    /// accepted, failing with [`Error::Timeout`] after `timeout`.
+    pub async fn wait_for_confirmation(
+        &self,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.wizard.wait_for_confirmation(interval, timeout).await
+    }
+    
+    /// Verifies the removal with the code sent with [`RecoveryMethod::Email`] or
+    /// [`RecoveryMethod::Sms`].
+    pub async fn verify_code(&self, code: &str) -> Result<(), Error> {
+        self.wizard.verify_code(code).await
+    }
+    
+    /// Removes the phone number.
+    pub async fn remove_phone(&self) -> Result<(), Error> {
+        let response = self.wizard.account.wizard_post("AjaxAccountRecoveryRemovePhoneNumber", vec![
+            ("s", self.wizard.s.clone()),
+            ("account", self.wizard.account.account_id().to_string()),
+        ]).await?;
+        
+        require_success(response)?;
+        
+        Ok(())
+    }
+}
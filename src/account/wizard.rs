@@ -0,0 +1,135 @@
+use super::{AccountClient, Error, is_success, require_success};
+use std::time::{Duration, Instant};
+use serde_json::Value;
+
+/// How the account owner re-verifies a change with Steam Guard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecoveryMethod {
+    /// A code sent to the account's email address.
+    Email,
+    /// A code sent to the account's phone number by SMS.
+    Sms,
+    /// A confirmation sent to the mobile app. It can be accepted in the app, or with
+    /// [`ConfirmationsClient`](crate::confirmations::ConfirmationsClient) as a confirmation of
+    /// type [`AccountRecovery`](crate::confirmations::ConfirmationType::AccountRecovery).
+    MobileApp,
+}
+
+impl RecoveryMethod {
+    /// Gets the value sent to the help wizard.
+    pub fn value(&self) -> u32 {
+        match self {
+            Self::Email => 2,
+            Self::Sms => 4,
+            Self::MobileApp => 8,
+        }
+    }
+}
+
+/// A session of the help wizard for an issue, verified with Steam Guard before changes are
+/// made.
+#[derive(Debug)]
+pub(super) struct WizardSession<'a> {
+    pub(super) account: &'a AccountClient,
+    pub(super) s: String,
+    issue_id: &'static str,
+    method: RecoveryMethod,
+}
+
+impl<'a> WizardSession<'a> {
+    /// Starts a session by loading `pathname`, which redirects to a URL holding the session ID.
+    pub(super) async fn begin(
+        account: &'a AccountClient,
+        pathname: &str,
+        issue_id: &'static str,
+    ) -> Result<Self, Error> {
+        let url = account.wizard_page(pathname, &[("redir", "store/account/")]).await?;
+        let s = url.query_pairs()
+            .find(|(key, _)| key == "s")
+            .map(|(_, value)| value.into_owned())
+            .ok_or(Error::UnexpectedResponse("help wizard did not start a session"))?;
+        
+        Ok(Self {
+            account,
+            s,
+            issue_id,
+            method: RecoveryMethod::MobileApp,
+        })
+    }
+    
+    pub(super) async fn send_code(&mut self, method: RecoveryMethod) -> Result<(), Error> {
+        self.method = method;
+        
+        let response = self.account.wizard_post("AjaxSendAccountRecoveryCode", vec![
+            ("s", self.s.clone()),
+            ("method", method.value().to_string()),
+            ("link", String::new()),
+        ]).await?;
+        
+        require_success(response)?;
+        
+        Ok(())
+    }
+    
+    pub(super) async fn poll_confirmation(&self) -> Result<bool, Error> {
+        let response = self.account.wizard_post("AjaxPollAccountRecoveryConfirmation", vec![
+            ("s", self.s.clone()),
+            ("reset", "1".into()),
+            ("lost", "0".into()),
+            ("method", self.method.value().to_string()),
+            ("issueid", self.issue_id.into()),
+        ]).await?;
+        let confirmed = is_success(&response) &&
+            response.get("continue").and_then(Value::as_bool).unwrap_or(false);
+        
+        if confirmed {
+            self.next_step().await?;
+        }
+        
+        Ok(confirmed)
+    }
+    
+    pub(super) async fn wait_for_confirmation(
+        &self,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let started = Instant::now();
+        
+        while !self.poll_confirmation().await? {
+            if started.elapsed() + interval > timeout {
+                return Err(Error::Timeout);
+            }
+            
+            tokio::time::sleep(interval).await;
+        }
+        
+        Ok(())
+    }
+    
+    pub(super) async fn verify_code(&self, code: &str) -> Result<(), Error> {
+        self.account.wizard_get("AjaxVerifyAccountRecoveryCode", vec![
+            ("code", code.into()),
+            ("s", self.s.clone()),
+            ("reset", "1".into()),
+            ("lost", "0".into()),
+            ("method", self.method.value().to_string()),
+            ("issueid", self.issue_id.into()),
+        ]).await?;
+        
+        self.next_step().await
+    }
+    
+    /// Advances the wizard past verification.
+    async fn next_step(&self) -> Result<(), Error> {
+        self.account.wizard_get("AjaxAccountRecoveryGetNextStep", vec![
+            ("s", self.s.clone()),
+            ("account", self.account.account_id().to_string()),
+            ("reset", "1".into()),
+            ("issueid", self.issue_id.into()),
+            ("lost", "0".into()),
+        ]).await?;
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! # Audit log
+//! 
+//! An [`AuditSink`] is told about security-relevant events, such as logins succeeding or failing
+//! and tokens being issued, rotated, or revoked, so they can be fed to a SIEM system. Set one with
+//! [`LoginSessionBuilder::audit_sink`](crate::login_session::LoginSessionBuilder::audit_sink) or
+//! [`LoginApproverBuilder::audit_sink`](crate::login_approver::LoginApproverBuilder::audit_sink).
+//! 
+//! Tokens are never passed to the sink. They are identified by a [`token_fingerprint`] instead,
+//! which can be compared against the fingerprints of stored tokens without revealing them.
+//! 
+//! ```
+//! use steam_session::audit::{AuditSink, AuditEvent};
+//! 
+//! #[derive(Debug)]
+//! struct StderrAuditSink;
+//! 
+//! impl AuditSink for StderrAuditSink {
+//!     fn record(&self, event: &AuditEvent) {
+//!         eprintln!("{:?} steamid={:?} token={:?}", event.kind, event.steamid, event.token_fingerprint);
+//!     }
+//! }
+//! ```
+
+use crate::types::DateTime;
+use crate::helpers::TokenKind;
+use crate::ErrorKind;
+use std::fmt;
+use std::net::IpAddr;
+use sha2::{Digest, Sha256};
+use steamid_ng::SteamID;
+
+/// Receives security-relevant events. Implementations should return quickly, e.g. by forwarding
+/// events to a channel, since they are called inline.
+pub trait AuditSink: fmt::Debug + Send + Sync {
+    /// Records `event`.
+    fn record(&self, event: &AuditEvent);
+}
+
+/// A security-relevant event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// What happened.
+    pub kind: AuditEventKind,
+    /// When it happened.
+    pub time: DateTime,
+    /// The account it happened to, if known.
+    pub steamid: Option<SteamID>,
+    /// The IP address involved, if known.
+    pub ip: Option<IpAddr>,
+    /// The fingerprint of the token involved, if any.
+    pub token_fingerprint: Option<String>,
+}
+
+/// What happened in an [`AuditEvent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuditEventKind {
+    /// A login completed. The fingerprint is of the refresh token issued.
+    LoginSucceeded,
+    /// A login failed, timed out, or was aborted.
+    LoginFailed {
+        /// The kind of failure.
+        kind: ErrorKind,
+        /// A description of the failure.
+        reason: String,
+    },
+    /// A token was issued from a refresh token.
+    TokenIssued(TokenKind),
+    /// The refresh token was renewed. The fingerprint is of the new refresh token.
+    TokenRotated,
+    /// The refresh token was revoked.
+    TokenRevoked,
+    /// A login was approved or denied from the mobile app.
+    DeviceApproved {
+        /// The client ID of the login.
+        client_id: u64,
+        /// Whether the login was approved rather than denied.
+        approved: bool,
+    },
+}
+
+impl AuditEvent {
+    /// Creates an event of `kind` that happened at `time`.
+    pub fn new(kind: AuditEventKind, time: DateTime) -> Self {
+        Self {
+            kind,
+            time,
+            steamid: None,
+            ip: None,
+            token_fingerprint: None,
+        }
+    }
+    
+    /// Sets the account the event happened to.
+    pub fn steamid(mut self, steamid: Option<SteamID>) -> Self {
+        self.steamid = steamid;
+        self
+    }
+    
+    /// Sets the IP address involved.
+    pub fn ip(mut self, ip: Option<IpAddr>) -> Self {
+        self.ip = ip;
+        self
+    }
+    
+    /// Sets the fingerprint of `token`.
+    pub fn token(mut self, token: &str) -> Self {
+        self.token_fingerprint = Some(token_fingerprint(token));
+        self
+    }
+}
+
+/// Gets a fingerprint identifying `token`: the first 8 bytes of its SHA-256 hash, hex-encoded.
+pub fn token_fingerprint(token: &str) -> String {
+    let hash = Sha256::digest(token.as_bytes());
+    
+    data_encoding::HEXLOWER.encode(&hash[..8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn fingerprints_tokens() {
+        assert_eq!(token_fingerprint("abc"), "ba7816bf8f01cfea");
+        assert_ne!(token_fingerprint("abc"), token_fingerprint("abd"));
+    }
+}
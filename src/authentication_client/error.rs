@@ -1,4 +1,5 @@
 use crate::enums::{EResult, EAuthTokenPlatformType};
+use crate::ErrorKind;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -28,4 +29,39 @@ pub enum Error {
     WebAPI(#[from] crate::transports::web_api::Error),
     #[error("Received EResult other than OK: {:?}", .0)]
     EResultNotOK(EResult),
-}
\ No newline at end of file
+    #[error(transparent)]
+    Shared(std::sync::Arc<Error>),
+}
+
+impl Error {
+    /// Gets the result given by Steam, if the request failed because of one.
+    pub fn eresult(&self) -> Option<EResult> {
+        match self {
+            Self::EResultNotOK(eresult) |
+            Self::WebSocketCM(crate::transports::websocket::Error::EResultNotOK(eresult)) |
+            Self::WebAPI(crate::transports::web_api::Error::EResultNotOK(eresult)) => Some(*eresult),
+            Self::Shared(error) => error.eresult(),
+            _ => None,
+        }
+    }
+    
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::UnsupportedPlatformType(_) |
+            Self::InvalidHeaderValue(_) |
+            Self::InvalidHeaderName(_) |
+            Self::SerdeQS(_) |
+            Self::NoJob => ErrorKind::InvalidInput,
+            Self::Decode(error) => error.kind(),
+            Self::RecvError(_) => ErrorKind::Connection,
+            Self::BadUint(_) |
+            Self::RSA(_) => ErrorKind::Decode,
+            Self::Reqwest(error) => crate::error::reqwest_kind(error),
+            Self::WebSocketCM(error) => error.kind(),
+            Self::WebAPI(error) => error.kind(),
+            Self::EResultNotOK(_) => ErrorKind::Steam,
+            Self::Shared(error) => error.kind(),
+        }
+    }
+}
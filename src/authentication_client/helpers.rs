@@ -1,8 +1,15 @@
-use crate::enums::{EOSType, EResult};
+use crate::enums::{EOSType, EResult, ELanguage};
 use crate::proto::steammessages_auth_steamclient::{
     CAuthentication_DeviceDetails,
     EAuthTokenPlatformType,
 };
+use crate::request::WebsiteId;
+use crate::hosts::SteamHosts;
+use crate::retry::RetryPolicy;
+use crate::secret::Secret;
+use crate::clock::Clock;
+use crate::cache_store::CacheStore;
+use std::sync::Arc;
 use crate::serializers::from_number_or_string;
 use reqwest::Client;
 use reqwest::header::HeaderMap;
@@ -11,7 +18,7 @@ use steam_machine_id::MachineID;
 
 #[derive(Debug, Clone)]
 pub struct EncryptedPassword {
-    pub encrypted_password: String,
+    pub encrypted_password: Secret<String>,
     pub key_timestamp: u64,
 }
 
@@ -22,6 +29,13 @@ pub struct AuthenticationClientConstructorOptions<T> {
     pub client: Client,
     pub user_agent: &'static str,
     pub machine_id: Option<Vec<u8>>,
+    pub language: ELanguage,
+    pub website_id: Option<WebsiteId>,
+    pub hosts: SteamHosts,
+    pub device_friendly_name: Option<String>,
+    pub retry_policy: RetryPolicy,
+    pub clock: Arc<dyn Clock>,
+    pub cache_store: Arc<dyn CacheStore>,
 }
 
 #[derive(Debug, Clone)]
@@ -2,15 +2,23 @@ mod error;
 mod helpers;
 
 pub use error::Error;
-pub (crate) use helpers::{EncryptedPassword, AuthenticationClientConstructorOptions};
+pub (crate) use helpers::{EncryptedPassword, AuthenticationClientConstructorOptions, get_machine_id};
 
-use helpers::{PlatformData, DeviceDetails, CheckMachineAuthResponse, get_machine_id};
+use helpers::{PlatformData, DeviceDetails, CheckMachineAuthResponse};
 
-use crate::enums::{EOSType, EAuthTokenPlatformType, ETokenRenewalType, EAuthSessionGuardType};
+use crate::enums::{EOSType, EAuthTokenPlatformType, ETokenRenewalType, EAuthSessionGuardType, ELanguage};
 use crate::helpers::{JwtPayload, encode_base64, get_spoofed_hostname, create_api_headers, DecodeError};
 use crate::net::ApiRequest;
+use crate::hosts::SteamHosts;
+use crate::retry::RetryPolicy;
 use crate::transports::Transport;
-use crate::request::{StartAuthSessionWithCredentialsRequest, MobileConfirmationRequest};
+use crate::single_flight::SingleFlight;
+use crate::secret::Secret;
+use crate::clock::Clock;
+use crate::cache_store::CacheStore;
+use crate::emulation::{MOBILE_APP_USER_AGENT, MOBILE_CLIENT_VERSION};
+use crate::request::{StartAuthSessionWithCredentialsRequest, MobileConfirmationRequest, WebsiteId, GenerateAccessTokenOptions};
+use crate::response::GeneratedAccessToken;
 use crate::proto::steammessages_auth_steamclient::{
     CAuthentication_DeviceDetails,
     CAuthentication_UpdateAuthSessionWithSteamGuardCode_Request,
@@ -23,17 +31,34 @@ use crate::proto::steammessages_auth_steamclient::{
     CAuthentication_AccessToken_GenerateForApp_Response,
     CAuthentication_GetAuthSessionInfo_Request,
     CAuthentication_GetAuthSessionInfo_Response,
+    CAuthentication_GetAuthSessionsForAccount_Request,
+    CAuthentication_GetAuthSessionsForAccount_Response,
     CAuthentication_BeginAuthSessionViaCredentials_Response,
+    CAuthentication_BeginAuthSessionViaQR_Request,
+    CAuthentication_BeginAuthSessionViaQR_Response,
     CAuthentication_PollAuthSessionStatus_Request,
     CAuthentication_PollAuthSessionStatus_Response,
+    CAuthentication_Token_Revoke_Request,
+    CAuthentication_Token_Revoke_Response,
+    CAuthentication_RefreshToken_Revoke_Request,
+    CAuthentication_RefreshToken_Revoke_Response,
+    EAuthTokenRevokeAction,
 };
 use crate::proto::custom::CAuthentication_BeginAuthSessionViaCredentials_Request_BinaryGuardData;
 use std::str::FromStr;
+use std::sync::Arc;
 use reqwest::Client;
 use steamid_ng::SteamID;
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT, ORIGIN, REFERER, COOKIE, CONTENT_TYPE};
 use serde::Serialize;
 use rsa::{RsaPublicKey, Pkcs1v15Encrypt, BigUint};
+use protobuf::Message;
+
+/// How long RSA keys are cached for, unless set with 
+/// [`AuthenticationClient::set_rsa_key_ttl`].
+pub const DEFAULT_RSA_KEY_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+type RsaKeyResult = Result<CAuthentication_GetPasswordRSAPublicKey_Response, Arc<Error>>;
 
 /// A client for handling authentication requests.
 #[derive(Debug)]
@@ -43,6 +68,17 @@ pub struct AuthenticationClient<T> {
     client: Client,
     user_agent: &'static str,
     machine_id: Option<Vec<u8>>,
+    language: ELanguage,
+    website_id: Option<WebsiteId>,
+    hosts: SteamHosts,
+    device_friendly_name: Option<String>,
+    retry_policy: RetryPolicy,
+    clock: Arc<dyn Clock>,
+    cache_store: Arc<dyn CacheStore>,
+    rsa_key_ttl: std::time::Duration,
+    /// In-flight requests for RSA keys, keyed by account name. These are kept per client since 
+    /// clients may send requests through different transports or proxies.
+    rsa_key_requests: SingleFlight<String, RsaKeyResult>,
 }
 
 impl<T> AuthenticationClient<T>
@@ -59,28 +95,73 @@ where
             client: options.client,
             user_agent: options.user_agent,
             machine_id: options.machine_id,
+            language: options.language,
+            website_id: options.website_id,
+            hosts: options.hosts,
+            device_friendly_name: options.device_friendly_name,
+            retry_policy: options.retry_policy,
+            clock: options.clock,
+            cache_store: options.cache_store,
+            rsa_key_ttl: DEFAULT_RSA_KEY_TTL,
+            rsa_key_requests: SingleFlight::default(),
         }
     }
     
+    /// Gets the language requests are made in.
+    pub fn language(&self) -> ELanguage {
+        self.language
+    }
+    
+    /// Gets the hosts web requests are sent to.
+    pub fn hosts(&self) -> &SteamHosts {
+        &self.hosts
+    }
+    
+    /// Gets the store RSA keys are cached in.
+    pub fn cache_store(&self) -> &Arc<dyn CacheStore> {
+        &self.cache_store
+    }
+    
+    /// Gets the policy for retrying requests after transient failures.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+    
+    /// Gets the transport requests are sent over.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+    
+    /// Replaces the transport requests are sent over, returning the previous one.
+    pub fn set_transport(&mut self, transport: T) -> T {
+        std::mem::replace(&mut self.transport, transport)
+    }
+    
+    /// Sets the HTTP client used for web requests.
+    pub fn set_client(&mut self, client: Client) {
+        self.client = client;
+    }
+    
     /// Encrypts `password` for `account_name`.
     pub async fn encrypt_password(
         &self,
         account_name: String,
         password: String,
     ) -> Result<EncryptedPassword, Error> {
+        let password = Secret::new(password);
         let rsa_info = self.get_rsa_key(account_name).await?;
         let n = BigUint::parse_bytes(rsa_info.publickey_mod().as_bytes(), 16)
             .ok_or_else(|| Error::BadUint(rsa_info.publickey_mod().into()))?;
         let e = BigUint::parse_bytes(rsa_info.publickey_exp().as_bytes(), 16)
             .ok_or_else(|| Error::BadUint(rsa_info.publickey_exp().into()))?;
         let key = RsaPublicKey::new(n, e)?;
-        let encrypted_password = key.encrypt(
+        let encrypted_password = Secret::new(key.encrypt(
             &mut rand::thread_rng(),
             Pkcs1v15Encrypt::default(),
-            password.as_bytes(),
-        )?;
+            password.expose_secret().as_bytes(),
+        )?);
         let key_timestamp = rsa_info.timestamp();
-        let encrypted_password = encode_base64(encrypted_password);
+        let encrypted_password = Secret::new(encode_base64(encrypted_password.expose_secret()));
         
         Ok(EncryptedPassword {
             encrypted_password,
@@ -88,19 +169,105 @@ where
         })
     }
     
-    /// Gets RSA public key for `account_name`.
+    /// Gets RSA public key for `account_name`. Concurrent calls on this client for the same 
+    /// account share a single request, and keys are cached for a short time so that retries and 
+    /// repeated logins do not fetch them again.
     pub async fn get_rsa_key(
         &self,
         account_name: String,
     ) -> Result<CAuthentication_GetPasswordRSAPublicKey_Response, Error> {
-        let mut msg = CAuthentication_GetPasswordRSAPublicKey_Request::new();
+        if let Some(cached) = self.cached_rsa_key(&account_name).await {
+            return Ok(cached);
+        }
         
-        msg.set_account_name(account_name);
+        let rsa_key = self.rsa_key_requests.run(account_name.clone(), || async {
+            let mut msg = CAuthentication_GetPasswordRSAPublicKey_Request::new();
+            
+            msg.set_account_name(account_name.clone());
+            
+            self.send_request_with_retry(
+                msg,
+                None,
+            ).await.map_err(Arc::new)
+        })
+            .await
+            .map_err(|error| Arc::try_unwrap(error).unwrap_or_else(Error::Shared))?;
         
-        self.send_request(
-            msg,
-            None,
-        ).await
+        if !self.rsa_key_ttl.is_zero() {
+            self.cache_rsa_key(&account_name, &rsa_key).await;
+        }
+        
+        Ok(rsa_key)
+    }
+    
+    /// Sets how long RSA keys are cached for. Defaults to [`DEFAULT_RSA_KEY_TTL`]. A zero 
+    /// duration disables caching.
+    pub fn set_rsa_key_ttl(&mut self, rsa_key_ttl: std::time::Duration) {
+        self.rsa_key_ttl = rsa_key_ttl;
+    }
+    
+    /// Removes the cached RSA key for `account_name`, so the next login fetches a new one.
+    pub async fn invalidate_rsa_key(&self, account_name: &str) {
+        if let Err(error) = self.cache_store.remove(&self.rsa_key_cache_key(account_name)).await {
+            warn!("Failed to remove cached RSA key: {error}");
+        }
+    }
+    
+    /// Removes all cached RSA keys for the API host.
+    pub async fn clear_rsa_keys(&self) {
+        if let Err(error) = self.cache_store.clear(&self.rsa_key_cache_key("")).await {
+            warn!("Failed to clear cached RSA keys: {error}");
+        }
+    }
+    
+    /// Gets the key RSA keys for `account_name` are cached under. Keys are fetched per API host, 
+    /// so the host is included for stores shared with clients using other hosts.
+    fn rsa_key_cache_key(&self, account_name: &str) -> String {
+        format!("rsa_key:{}:{account_name}", self.hosts.api)
+    }
+    
+    /// Gets the cached RSA key for `account_name`, if it has not expired. Store errors and 
+    /// undecodable keys are treated as a miss.
+    async fn cached_rsa_key(
+        &self,
+        account_name: &str,
+    ) -> Option<CAuthentication_GetPasswordRSAPublicKey_Response> {
+        if self.rsa_key_ttl.is_zero() {
+            return None;
+        }
+        
+        let bytes = match self.cache_store.get(&self.rsa_key_cache_key(account_name)).await {
+            Ok(bytes) => bytes?,
+            Err(error) => {
+                warn!("Failed to get cached RSA key: {error}");
+                return None;
+            },
+        };
+        
+        CAuthentication_GetPasswordRSAPublicKey_Response::parse_from_bytes(&bytes)
+            .map_err(|error| warn!("Failed to decode cached RSA key: {error}"))
+            .ok()
+    }
+    
+    /// Caches `rsa_key` for `account_name`.
+    async fn cache_rsa_key(
+        &self,
+        account_name: &str,
+        rsa_key: &CAuthentication_GetPasswordRSAPublicKey_Response,
+    ) {
+        let result = match rsa_key.write_to_bytes() {
+            Ok(bytes) => self.cache_store
+                .set(&self.rsa_key_cache_key(account_name), &bytes, self.rsa_key_ttl)
+                .await,
+            Err(error) => {
+                warn!("Failed to encode RSA key: {error}");
+                return;
+            },
+        };
+        
+        if let Err(error) = result {
+            warn!("Failed to cache RSA key: {error}");
+        }
     }
     
     /// Starts session with credentials.
@@ -112,12 +279,22 @@ where
         let platform_data = self.get_platform_data()?;
         let mut device_details: CAuthentication_DeviceDetails = platform_data.device_details.into();
         
+        if let Some(device_friendly_name) = &self.device_friendly_name {
+            device_details.set_device_friendly_name(device_friendly_name.clone());
+        }
+        
         msg.set_account_name(details.account_name);
-        msg.set_encrypted_password(details.encrypted_password);
+        msg.set_encrypted_password(details.encrypted_password.expose_secret().clone());
         msg.set_encryption_timestamp(details.encryption_timestamp);
         msg.set_remember_login(details.remember_login);
         msg.set_persistence(details.persistence);
-        msg.set_website_id(platform_data.website_id.into());
+        let website_id = self.website_id
+            .as_ref()
+            .map(WebsiteId::as_str)
+            .unwrap_or(platform_data.website_id);
+        
+        msg.set_website_id(website_id.into());
+        msg.set_language(self.language as u32);
         
         if details.platform_type == EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient {
             if let Some(machine_id) = &self.machine_id {
@@ -132,7 +309,41 @@ where
         if let Some(steam_guard_machine_token) = details.steam_guard_machine_token {
             msg.set_guard_data(steam_guard_machine_token);
         }
-		
+        
+        let account_name = msg.account_name().to_owned();
+        let result = self.send_request(msg, None).await;
+        
+        if result.is_err() {
+            // the password may have been encrypted with a key that was rotated
+            self.invalidate_rsa_key(&account_name).await;
+        }
+        
+        result
+    }
+    
+    /// Starts session with a QR code. The challenge URL in the response is rendered as a QR code 
+    /// and scanned with the Steam mobile app.
+    pub async fn start_session_with_qr(
+        &self,
+    ) -> Result<CAuthentication_BeginAuthSessionViaQR_Response, Error> {
+        let mut msg = CAuthentication_BeginAuthSessionViaQR_Request::new();
+        let platform_data = self.get_platform_data()?;
+        let mut device_details: CAuthentication_DeviceDetails = platform_data.device_details.into();
+        
+        if let Some(device_friendly_name) = &self.device_friendly_name {
+            device_details.set_device_friendly_name(device_friendly_name.clone());
+        }
+        
+        let website_id = self.website_id
+            .as_ref()
+            .map(WebsiteId::as_str)
+            .unwrap_or(platform_data.website_id);
+        
+        msg.set_device_friendly_name(device_details.device_friendly_name().to_string());
+        msg.set_platform_type(self.platform_type);
+        msg.set_website_id(website_id.into());
+        msg.device_details = Some(device_details).into();
+        
         self.send_request(msg, None).await
     }
     
@@ -140,14 +351,14 @@ where
     pub async fn submit_steam_guard_code(
         &self,
         client_id: u64,
-        steamid: u64,
+        steamid: SteamID,
         code: String,
         code_type: EAuthSessionGuardType,
     ) -> Result<CAuthentication_UpdateAuthSessionWithSteamGuardCode_Response, Error> {
         let mut msg = CAuthentication_UpdateAuthSessionWithSteamGuardCode_Request::new();
         
         msg.set_client_id(client_id);
-        msg.set_steamid(steamid);
+        msg.set_steamid(u64::from(steamid));
         msg.set_code(code);
         msg.set_code_type(code_type);
         
@@ -176,7 +387,7 @@ where
         let form = reqwest::multipart::Form::new()
             .text("clientid", client_id.to_string())
             .text("steamid", u64::from(steamid).to_string());
-        let response = self.client.post("https://login.steampowered.com/jwt/checkdevice")
+        let response = self.client.post(self.hosts.login_url("jwt/checkdevice"))
             .headers(headers)
             .multipart(form)
             .send()
@@ -198,7 +409,7 @@ where
         msg.set_client_id(client_id);
         msg.set_request_id(request_id);
         
-        self.send_request(msg, None).await
+        self.send_request_with_retry(msg, None).await
     }
     
     /// Gets auth session info.
@@ -211,7 +422,18 @@ where
         
         msg.set_client_id(client_id);
         
-        self.send_request(msg, Some(access_token)).await
+        self.send_request_with_retry(msg, Some(access_token)).await
+    }
+    
+    /// Gets the client IDs of the active auth sessions for the account `access_token` belongs 
+    /// to. The access token must be for the mobile app.
+    pub async fn get_auth_sessions_for_account(
+        &self,
+        access_token: String,
+    ) -> Result<CAuthentication_GetAuthSessionsForAccount_Response, Error> {
+        let msg = CAuthentication_GetAuthSessionsForAccount_Request::new();
+        
+        self.send_request_with_retry(msg, Some(access_token)).await
     }
     
     /// Submits mobile confirmation.
@@ -224,7 +446,7 @@ where
         
         msg.set_version(details.version as i32);
         msg.set_client_id(details.client_id);
-        msg.set_steamid(details.steamid);
+        msg.set_steamid(u64::from(details.steamid));
         msg.set_signature(details.signature);
         msg.set_confirm(details.confirm);
         msg.set_persistence(details.persistence);
@@ -232,25 +454,128 @@ where
         self.send_request(msg, Some(access_token)).await
     }
     
+    /// Revokes `token`, which can be a refresh token or an access token.
+    pub async fn revoke_token(
+        &self,
+        token: String,
+        revoke_action: EAuthTokenRevokeAction,
+        access_token: Option<String>,
+    ) -> Result<CAuthentication_Token_Revoke_Response, Error> {
+        let mut msg = CAuthentication_Token_Revoke_Request::new();
+        
+        msg.set_token(token);
+        msg.set_revoke_action(revoke_action);
+        
+        self.send_request(msg, access_token).await
+    }
+    
+    /// Revokes the refresh token with the ID `token_id`, as listed when enumerating tokens. 
+    /// `signature` is an HMAC-SHA256 of the token ID signed with the account's shared secret.
+    pub async fn revoke_refresh_token(
+        &self,
+        token_id: u64,
+        revoke_action: EAuthTokenRevokeAction,
+        signature: Option<Vec<u8>>,
+        access_token: Option<String>,
+    ) -> Result<CAuthentication_RefreshToken_Revoke_Response, Error> {
+        let mut msg = CAuthentication_RefreshToken_Revoke_Request::new();
+        
+        msg.set_token_id(token_id);
+        msg.set_revoke_action(revoke_action);
+        
+        if let Some(signature) = signature {
+            msg.set_signature(signature);
+        }
+        
+        self.send_request(msg, access_token).await
+    }
+    
     /// Generates access token for app.
     pub async fn generate_access_token_for_app(
         &self,
         refresh_token: String,
         renew_refresh: bool,
     ) -> Result<CAuthentication_AccessToken_GenerateForApp_Response, Error> {
-        let decoded = JwtPayload::from_str(&refresh_token)?;
-        let mut msg = CAuthentication_AccessToken_GenerateForApp_Request::new();
         let renewal_type = if renew_refresh {
             ETokenRenewalType::k_ETokenRenewalType_Allow
         } else {
             ETokenRenewalType::k_ETokenRenewalType_None
         };
         
+        self.send_generate_access_token_for_app(refresh_token, GenerateAccessTokenOptions {
+            renewal_type,
+            ..Default::default()
+        }).await
+    }
+    
+    /// Generates an access token with `options`, returning it along with the details decoded from 
+    /// it and the refresh token if one was issued.
+    pub async fn generate_access_token_with_options(
+        &self,
+        refresh_token: String,
+        options: GenerateAccessTokenOptions,
+    ) -> Result<GeneratedAccessToken, Error> {
+        let response = self.send_generate_access_token_for_app(refresh_token, options).await?;
+        
+        Ok(GeneratedAccessToken::try_from(response)?)
+    }
+    
+    async fn send_generate_access_token_for_app(
+        &self,
+        refresh_token: String,
+        options: GenerateAccessTokenOptions,
+    ) -> Result<CAuthentication_AccessToken_GenerateForApp_Response, Error> {
+        let steamid = match options.steamid {
+            Some(steamid) => steamid,
+            None => JwtPayload::from_str(&refresh_token)?.sub,
+        };
+        let renew_refresh = options.renewal_type != ETokenRenewalType::k_ETokenRenewalType_None;
+        let mut msg = CAuthentication_AccessToken_GenerateForApp_Request::new();
+        
         msg.set_refresh_token(refresh_token);
-        msg.set_steamid(u64::from(decoded.sub));
-        msg.set_renewal_type(renewal_type);
+        msg.set_steamid(u64::from(steamid));
+        msg.set_renewal_type(options.renewal_type);
         
-        self.send_request(msg, None).await
+        // Renewing the refresh token invalidates the previous one, so only retry when not renewing
+        if renew_refresh {
+            self.send_request(msg, None).await
+        } else {
+            self.send_request_with_retry(msg, None).await
+        }
+    }
+    
+    /// Sends a request, retrying it according to the retry policy. Only used for requests that 
+    /// are safe to repeat.
+    async fn send_request_with_retry<Msg>(
+        &self,
+        msg: Msg,
+        access_token: Option<String>,
+    ) -> Result<Msg::Response, Error>
+    where
+        Msg: ApiRequest,
+        <Msg as ApiRequest>::Response: Send,
+    {
+        let mut attempts = 0;
+        
+        loop {
+            attempts += 1;
+            
+            let error = match self.send_request(msg.clone(), access_token.clone()).await {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+            let backoff = self.retry_policy
+                .backoff(attempts)
+                .filter(|_| self.retry_policy.is_retryable(error.kind(), error.eresult()));
+            
+            match backoff {
+                Some(backoff) => {
+                    debug!("Retrying {} in {backoff:?} after error: {error}", <Msg as ApiRequest>::NAME);
+                    self.clock.sleep(backoff).await;
+                },
+                None => return Err(error),
+            }
+        }
     }
     
     /// Sends a request.
@@ -295,15 +620,16 @@ where
         match self.platform_type {
             EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient => {
                 let local_hostname = get_spoofed_hostname();
+                let webapi_base_url = self.hosts.api_url("");
                 let referer_query = RefererQuery {
                     in_client: "true",
                     website_id: "Client",
                     local_hostname: &local_hostname,
-                    webapi_base_url: "https://api.steampowered.com/",
+                    webapi_base_url: &webapi_base_url,
                     store_base_url: "https://store.steampowered.com/",
                     use_popups: "true",
                     dev_mode: "false",
-                    language: "english",
+                    language: self.language.api_name(),
                     platform: "windows",
                     country: "US",
                     launcher_type: "0",
@@ -350,8 +676,8 @@ where
             EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp => {
                 let mut headers = HeaderMap::new();
                 
-                headers.append(USER_AGENT, HeaderValue::from_str("okhttp/3.12.12")?);
-                headers.append(COOKIE, HeaderValue::from_str("mobileClient=android; mobileClientVersion=777777 3.0.0")?);
+                headers.append(USER_AGENT, HeaderValue::from_str(MOBILE_APP_USER_AGENT)?);
+                headers.append(COOKIE, HeaderValue::from_str(&format!("mobileClient=android; mobileClientVersion={MOBILE_CLIENT_VERSION}"))?);
                 
                 Ok(PlatformData {
                     website_id: "Mobile",
@@ -370,4 +696,111 @@ where
             },
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache_store::MemoryCacheStore;
+    use crate::transports::web_api::WebApiTransport;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    
+    /// Serves RSA keys with `publickey_mod`, returning the hosts to send requests to and the 
+    /// number of requests received.
+    async fn serve_rsa_key(publickey_mod: &str) -> (SteamHosts, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hosts = SteamHosts {
+            api: format!("http://{}", listener.local_addr().unwrap()),
+            ..Default::default()
+        };
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        let mut response = CAuthentication_GetPasswordRSAPublicKey_Response::new();
+        
+        response.set_publickey_mod(publickey_mod.into());
+        response.set_publickey_exp(String::from("010001"));
+        
+        let body = response.write_to_bytes().unwrap();
+        
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                
+                counter.fetch_add(1, Ordering::SeqCst);
+                // keeps the request in flight while the other client sends its own
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len(),
+                );
+                
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(&body).await;
+            }
+        });
+        
+        (hosts, requests)
+    }
+    
+    fn authentication_client(transport_hosts: SteamHosts) -> AuthenticationClient<WebApiTransport> {
+        let client = Client::new();
+        
+        AuthenticationClient::new(AuthenticationClientConstructorOptions {
+            platform_type: EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser,
+            transport: WebApiTransport::with_custom_client(client.clone()).with_hosts(transport_hosts),
+            client,
+            user_agent: crate::helpers::DEFAULT_USER_AGENT,
+            machine_id: None,
+            language: ELanguage::default(),
+            website_id: None,
+            // the same hosts, so only the transports tell the clients apart
+            hosts: SteamHosts::default(),
+            device_friendly_name: None,
+            retry_policy: RetryPolicy::default(),
+            clock: crate::clock::system_clock(),
+            cache_store: Arc::new(MemoryCacheStore::new()),
+        })
+    }
+    
+    #[tokio::test]
+    async fn does_not_share_rsa_key_requests_between_clients() {
+        let (hosts_a, requests_a) = serve_rsa_key("aa").await;
+        let (hosts_b, requests_b) = serve_rsa_key("bb").await;
+        let client_a = authentication_client(hosts_a);
+        let client_b = authentication_client(hosts_b);
+        let (key_a, key_b) = tokio::join!(
+            client_a.get_rsa_key(String::from("alice")),
+            client_b.get_rsa_key(String::from("alice")),
+        );
+        
+        assert_eq!(key_a.unwrap().publickey_mod(), "aa");
+        assert_eq!(key_b.unwrap().publickey_mod(), "bb");
+        assert_eq!(requests_a.load(Ordering::SeqCst), 1);
+        assert_eq!(requests_b.load(Ordering::SeqCst), 1);
+    }
+    
+    #[tokio::test]
+    async fn shares_concurrent_rsa_key_requests_on_a_client() {
+        let (hosts, requests) = serve_rsa_key("aa").await;
+        let client = authentication_client(hosts);
+        let (key_a, key_b) = tokio::join!(
+            client.get_rsa_key(String::from("alice")),
+            client.get_rsa_key(String::from("alice")),
+        );
+        
+        assert_eq!(key_a.unwrap().publickey_mod(), "aa");
+        assert_eq!(key_b.unwrap().publickey_mod(), "aa");
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
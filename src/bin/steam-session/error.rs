@@ -0,0 +1,95 @@
+use std::io;
+use steam_session::{DecodeError, ErrorKind, LoginSessionError};
+use steam_session::token_store;
+
+/// Exit codes, stable across releases so scripts can branch on them.
+pub mod exit_code {
+    /// The command succeeded.
+    pub const OK: i32 = 0;
+    /// An error not covered by another code, e.g. a local IO error.
+    pub const FAILURE: i32 = 1;
+    /// The arguments were invalid. This is also the code clap exits with.
+    pub const USAGE: i32 = 2;
+    /// No tokens are stored for the account.
+    pub const NOT_FOUND: i32 = 3;
+    /// The login was not approved before the timeout, or a request timed out.
+    pub const TIMEOUT: i32 = 4;
+    /// A token was rejected, expired, or malformed. Log in again.
+    pub const TOKEN: i32 = 5;
+    /// Steam responded with a result other than OK, e.g. a wrong password or Steam Guard code.
+    pub const STEAM: i32 = 6;
+    /// Steam could not be reached, or the proxy could not be connected through.
+    pub const CONNECTION: i32 = 7;
+    /// A response from Steam could not be decoded.
+    pub const DECODE: i32 = 8;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", .0)]
+    LoginSession(#[from] LoginSessionError),
+    #[error("Token store: {}", .0)]
+    TokenStore(#[from] token_store::Error),
+    #[error("Malformed token: {}", .0)]
+    Decode(#[from] DecodeError),
+    #[error("{}", .0)]
+    Io(#[from] io::Error),
+    #[error("Failed to render QR code: {}", .0)]
+    Qr(#[from] steam_session::qr::Error),
+    #[error("Failed to generate Steam Guard code: {}", .0)]
+    Totp(String),
+    #[error("No refresh token is stored for {}", .0)]
+    NotFound(String),
+    #[error("The account name is unknown; pass it with --account")]
+    UnknownAccountName,
+    #[error("The login was not approved in time")]
+    NotApproved,
+}
+
+impl Error {
+    /// Gets the kind of error, if it has one.
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            Self::LoginSession(error) => Some(error.kind()),
+            Self::TokenStore(error) => Some(error.kind()),
+            Self::Decode(_) => Some(ErrorKind::Token),
+            Self::NotApproved => Some(ErrorKind::Timeout),
+            Self::UnknownAccountName => Some(ErrorKind::InvalidInput),
+            _ => None,
+        }
+    }
+    
+    /// Gets the code to exit with.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::NotFound(_) => return exit_code::NOT_FOUND,
+            Self::TokenStore(_) | Self::Io(_) => return exit_code::FAILURE,
+            _ => {},
+        }
+        
+        match self.kind() {
+            Some(ErrorKind::Timeout) => exit_code::TIMEOUT,
+            Some(ErrorKind::Token) => exit_code::TOKEN,
+            Some(ErrorKind::Steam) => exit_code::STEAM,
+            Some(ErrorKind::Connection | ErrorKind::Proxy) => exit_code::CONNECTION,
+            Some(ErrorKind::Decode) => exit_code::DECODE,
+            Some(ErrorKind::InvalidInput) => exit_code::USAGE,
+            _ => exit_code::FAILURE,
+        }
+    }
+}
+
+/// Gets the name of `kind` used in JSON output.
+pub fn kind_name(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Proxy => "proxy",
+        ErrorKind::Connection => "connection",
+        ErrorKind::Timeout => "timeout",
+        ErrorKind::Steam => "steam",
+        ErrorKind::Token => "token",
+        ErrorKind::Decode => "decode",
+        ErrorKind::State => "state",
+        ErrorKind::InvalidInput => "invalid_input",
+        _ => "other",
+    }
+}
@@ -0,0 +1,370 @@
+//! # steam-session
+//! 
+//! A command-line client for logging in to Steam and managing session tokens. Tokens are kept in
+//! a [`FileTokenStore`] keyed by account name, so `refresh`, `cookies`, and `revoke` can be run
+//! later without logging in again.
+//! 
+//! ```text
+//! steam-session login --account name --shared-secret secret
+//! steam-session cookies --account name --domain community
+//! ```
+//! 
+//! With `--json`, results and errors are printed to stdout as JSON. The process exits with one of 
+//! the codes in [`exit_code`](error::exit_code), which are stable across releases.
+
+mod error;
+mod output;
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use clap::{Parser, Subcommand, ValueEnum};
+use steam_session::prelude::*;
+use steam_session::JwtPayload;
+use steam_session::token_store::{TokenStore, FileTokenStore};
+use tokio::sync::broadcast;
+use error::{Error, exit_code};
+use output::{TokensOutput, RevokedOutput, ErrorOutput, print_json};
+
+/// How long to wait for a login to be approved on another device.
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Parser)]
+#[command(name = "steam-session", version, about = "Log in to Steam and manage session tokens")]
+struct Cli {
+    /// Path to the token store.
+    #[arg(long, global = true, env = "STEAM_SESSION_STORE", default_value = "tokens.json")]
+    store: PathBuf,
+    /// Print results and errors as JSON.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Log in with an account name and password.
+    Login {
+        /// The account name.
+        #[arg(long, short)]
+        account: String,
+        /// The password. Prompted for if not given.
+        #[arg(long, env = "STEAM_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+        /// The shared secret used to generate Steam Guard codes.
+        #[arg(long, env = "STEAM_SHARED_SECRET", hide_env_values = true)]
+        shared_secret: Option<String>,
+        /// A Steam Guard code. Prompted for if one is needed and no shared secret is given.
+        #[arg(long)]
+        code: Option<String>,
+        /// The platform to log in as.
+        #[arg(long, value_enum, default_value_t = Platform::Mobile)]
+        platform: Platform,
+    },
+    /// Log in by scanning a QR code with the Steam mobile app.
+    Qr {
+        /// The name to store the tokens under. Defaults to the name of the account that approves
+        /// the login.
+        #[arg(long, short)]
+        account: Option<String>,
+        /// The platform to log in as.
+        #[arg(long, value_enum, default_value_t = Platform::Mobile)]
+        platform: Platform,
+    },
+    /// Renew the access token, and the refresh token if Steam issues a new one.
+    Refresh {
+        /// The account name.
+        #[arg(long, short)]
+        account: String,
+    },
+    /// Print web cookies, one per line.
+    Cookies {
+        /// The account name.
+        #[arg(long, short)]
+        account: String,
+        /// The domains to get cookies for. Defaults to the community, store, and help sites.
+        #[arg(long = "domain", value_enum)]
+        domains: Vec<Domain>,
+        /// Print the cookies in the Netscape cookie-jar (`cookies.txt`) format.
+        #[arg(long)]
+        netscape: bool,
+    },
+    /// Revoke the refresh token and remove the account from the store.
+    Revoke {
+        /// The account name.
+        #[arg(long, short)]
+        account: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Platform {
+    Web,
+    Mobile,
+    Client,
+}
+
+impl From<Platform> for EAuthTokenPlatformType {
+    fn from(platform: Platform) -> Self {
+        match platform {
+            Platform::Web => EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser,
+            Platform::Mobile => EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp,
+            Platform::Client => EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Domain {
+    Community,
+    Store,
+    Help,
+    Checkout,
+    Tv,
+}
+
+impl From<Domain> for CookieDomain {
+    fn from(domain: Domain) -> Self {
+        match domain {
+            Domain::Community => CookieDomain::Community,
+            Domain::Store => CookieDomain::Store,
+            Domain::Help => CookieDomain::Help,
+            Domain::Checkout => CookieDomain::Checkout,
+            Domain::Tv => CookieDomain::TV,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let json = cli.json;
+    let code = match run(cli).await {
+        Ok(()) => exit_code::OK,
+        Err(error) => {
+            if json {
+                print_json(&ErrorOutput::from(&error));
+            } else {
+                eprintln!("error: {error}");
+            }
+            
+            error.exit_code()
+        },
+    };
+    
+    std::process::exit(code);
+}
+
+async fn run(cli: Cli) -> Result<(), Error> {
+    let store = FileTokenStore::new(cli.store);
+    let json = cli.json;
+    
+    match cli.command {
+        Command::Login { account, password, shared_secret, code, platform } => {
+            let password = match password {
+                Some(password) => password,
+                None => rpassword::prompt_password("Password: ")?,
+            };
+            let mut session = new_session(platform.into(), None)?;
+            let response = session.start_with_credentials(StartLoginSessionWithCredentialsDetails {
+                account_name: account.clone(),
+                password: password.into(),
+                platform_type: platform.into(),
+                ..Default::default()
+            }).await?;
+            let requires_device_code = response.requires_device_code();
+            
+            if let StartSessionResponse::ActionRequired(actions) = response {
+                let needs_code = actions
+                    .iter()
+                    .any(|action| is_code_guard(action.r#type));
+                
+                if needs_code {
+                    let code = match (code, shared_secret) {
+                        (Some(code), _) => code,
+                        (None, Some(shared_secret)) if requires_device_code => {
+                            let timestamp = chrono::Utc::now().timestamp();
+                            
+                            steam_session::guard::generate_auth_code(&shared_secret, timestamp)
+                                .map_err(|error| Error::Totp(error.to_string()))?
+                        },
+                        _ => prompt("Steam Guard code: ")?,
+                    };
+                    
+                    session.submit_steam_guard_code(code).await?;
+                } else {
+                    eprintln!("Approve the login in the Steam mobile app or from the confirmation email");
+                    session.poll().await?;
+                }
+            }
+            
+            let tokens = save_session(&store, &account, &mut session).await?;
+            
+            print_tokens(json, &account, &tokens);
+        },
+        Command::Qr { account, platform } => {
+            let mut session = new_session(platform.into(), None)?;
+            let qr_changes = tokio::spawn(print_qr_changes(session.subscribe()));
+            
+            if let StartSessionResponse::QrChallenge(challenge_url) = session.start_with_qr().await? {
+                let qr = steam_session::qr::render_unicode(&challenge_url)?;
+                
+                eprintln!("{qr}");
+                eprintln!("Scan the QR code with the Steam mobile app");
+            }
+            
+            let result = session.poll().await;
+            
+            qr_changes.abort();
+            result?;
+            
+            let account = account
+                .or_else(|| session.get_account_name().cloned())
+                .ok_or(Error::UnknownAccountName)?;
+            let tokens = save_session(&store, &account, &mut session).await?;
+            
+            print_tokens(json, &account, &tokens);
+        },
+        Command::Refresh { account } => {
+            let mut session = load_session(&store, &account).await?;
+            
+            session.renew_refresh_token().await?;
+            
+            let tokens = save_session(&store, &account, &mut session).await?;
+            
+            print_tokens(json, &account, &tokens);
+        },
+        Command::Cookies { account, domains, netscape } => {
+            let mut session = load_session(&store, &account).await?;
+            let domains = if domains.is_empty() {
+                CookieDomain::DEFAULT.to_vec()
+            } else {
+                domains.into_iter().map(CookieDomain::from).collect()
+            };
+            let tokens = session.get_session_tokens(&domains).await?;
+            
+            store.save(&account, &tokens).await?;
+            
+            if json {
+                print_json(&TokensOutput::new(&account, &tokens));
+            } else if netscape {
+                print!("{}", tokens.to_netscape_cookie_jar());
+            } else {
+                for cookie in &tokens.cookies {
+                    println!("{cookie}");
+                }
+            }
+        },
+        Command::Revoke { account } => {
+            let mut session = load_session(&store, &account).await?;
+            
+            session.revoke_refresh_token().await?;
+            store.remove(&account).await?;
+            
+            if json {
+                print_json(&RevokedOutput {
+                    account: &account,
+                    revoked: true,
+                });
+            } else {
+                eprintln!("Revoked the refresh token for {account}");
+            }
+        },
+    }
+    
+    Ok(())
+}
+
+/// Creates a session for `platform_type`, restoring `refresh_token` if given.
+fn new_session(
+    platform_type: EAuthTokenPlatformType,
+    refresh_token: Option<String>,
+) -> Result<LoginSession<WebApiTransport>, Error> {
+    let mut session = LoginSession::builder(WebApiTransport::new(), platform_type)
+        .login_timeout(APPROVAL_TIMEOUT)
+        .build()?;
+    
+    if let Some(refresh_token) = refresh_token {
+        session.set_refresh_token(refresh_token)?;
+    }
+    
+    Ok(session)
+}
+
+/// Creates a session from the refresh token stored for `account`. The platform is taken from the
+/// token.
+async fn load_session(
+    store: &FileTokenStore,
+    account: &str,
+) -> Result<LoginSession<WebApiTransport>, Error> {
+    let refresh_token = store.load(account).await?
+        .and_then(|tokens| tokens.refresh_token)
+        .ok_or_else(|| Error::NotFound(account.to_owned()))?
+        .expose_secret()
+        .clone();
+    let platform_type = JwtPayload::from_str(&refresh_token)?.platform_type();
+    
+    new_session(platform_type, Some(refresh_token))
+}
+
+/// Gets the tokens and web cookies from an authenticated session and saves them for `account`.
+async fn save_session(
+    store: &FileTokenStore,
+    account: &str,
+    session: &mut LoginSession<WebApiTransport>,
+) -> Result<SessionTokens, Error> {
+    if session.get_refresh_token().is_none() {
+        return Err(Error::NotApproved);
+    }
+    
+    let tokens = session.get_session_tokens(&CookieDomain::DEFAULT).await?;
+    
+    store.save(account, &tokens).await?;
+    
+    Ok(tokens)
+}
+
+/// Prints the result of a login or refresh.
+fn print_tokens(json: bool, account: &str, tokens: &SessionTokens) {
+    if json {
+        print_json(&TokensOutput::new(account, tokens));
+    } else {
+        eprintln!("Saved tokens for {account} ({})", u64::from(tokens.steamid));
+    }
+}
+
+/// Prints the new QR code each time Steam rotates the challenge while polling.
+async fn print_qr_changes(mut events: broadcast::Receiver<LoginSessionEvent>) {
+    loop {
+        match events.recv().await {
+            Ok(LoginSessionEvent::QrChallengeChanged(challenge)) => {
+                if let Ok(qr) = steam_session::qr::render_unicode(&challenge.url) {
+                    eprintln!("{qr}");
+                    eprintln!("The QR code changed; scan the new one with the Steam mobile app");
+                }
+            },
+            Ok(_) => {},
+            Err(broadcast::error::RecvError::Lagged(_)) => {},
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Whether `guard_type` is answered with a code.
+fn is_code_guard(guard_type: EAuthSessionGuardType) -> bool {
+    guard_type == EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceCode ||
+    guard_type == EAuthSessionGuardType::k_EAuthSessionGuardType_EmailCode
+}
+
+/// Reads a line from stdin after printing `label`.
+fn prompt(label: &str) -> io::Result<String> {
+    let mut line = String::new();
+    
+    eprint!("{label}");
+    io::stderr().flush()?;
+    io::stdin().lock().read_line(&mut line)?;
+    
+    Ok(line.trim().to_string())
+}
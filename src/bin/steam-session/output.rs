@@ -0,0 +1,81 @@
+use crate::error::{Error, kind_name};
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use steam_session::{JwtPayload, SessionTokens};
+
+/// The tokens for an account, as printed with `--json`.
+#[derive(Debug, Serialize)]
+pub struct TokensOutput<'a> {
+    pub account: &'a str,
+    /// The SteamID as a string, since it does not fit in a JavaScript number.
+    pub steamid: String,
+    pub refresh_token: Option<&'a str>,
+    pub refresh_token_expires_at: Option<DateTime<Utc>>,
+    pub access_token: Option<&'a str>,
+    pub access_token_expires_at: Option<DateTime<Utc>>,
+    pub cookies: &'a [String],
+    pub cookies_expire_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> TokensOutput<'a> {
+    pub fn new(account: &'a str, tokens: &'a SessionTokens) -> Self {
+        let refresh_token = tokens.refresh_token
+            .as_ref()
+            .map(|token| token.expose_secret().as_str());
+        let access_token = tokens.access_token
+            .as_ref()
+            .map(|token| token.expose_secret().as_str());
+        
+        Self {
+            account,
+            steamid: u64::from(tokens.steamid).to_string(),
+            refresh_token,
+            refresh_token_expires_at: refresh_token.and_then(token_expiry),
+            access_token,
+            access_token_expires_at: access_token.and_then(token_expiry),
+            cookies: &tokens.cookies,
+            cookies_expire_at: tokens.web_cookies_expiry(),
+        }
+    }
+}
+
+/// The result of revoking an account's tokens, as printed with `--json`.
+#[derive(Debug, Serialize)]
+pub struct RevokedOutput<'a> {
+    pub account: &'a str,
+    pub revoked: bool,
+}
+
+/// An error, as printed with `--json`.
+#[derive(Debug, Serialize)]
+pub struct ErrorOutput {
+    pub error: String,
+    pub kind: &'static str,
+    pub exit_code: i32,
+}
+
+impl From<&Error> for ErrorOutput {
+    fn from(error: &Error) -> Self {
+        Self {
+            error: error.to_string(),
+            kind: error.kind().map(kind_name).unwrap_or("other"),
+            exit_code: error.exit_code(),
+        }
+    }
+}
+
+/// Prints `value` to stdout as JSON.
+pub fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{json}"),
+        Err(error) => eprintln!("error: {error}"),
+    }
+}
+
+/// Gets the time `token` expires.
+fn token_expiry(token: &str) -> Option<DateTime<Utc>> {
+    let decoded = JwtPayload::from_str(token).ok()?;
+    
+    DateTime::from_timestamp(decoded.exp as i64, 0)
+}
@@ -0,0 +1,17 @@
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to create runtime: {}", .0)]
+    Runtime(#[from] std::io::Error),
+    #[error("{}", .0)]
+    LoginSession(#[from] crate::login_session::LoginSessionError),
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::Runtime(_) => crate::ErrorKind::State,
+            Self::LoginSession(error) => error.kind(),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! # Blocking
+//! 
+//! A blocking wrapper around [`crate::login_session::LoginSession`] for applications that don't 
+//! use async. Each session owns a small runtime which drives requests and any background tasks 
+//! of the transport. Requires the `blocking` feature.
+//! 
+//! Methods must not be called from within an async runtime.
+//! 
+//! ## Examples
+//! ```no_run
+//! use steam_session::blocking::LoginSession;
+//! use steam_session::request::StartLoginSessionWithCredentialsDetails;
+//! 
+//! let mut session = LoginSession::connect_webapi()?;
+//! 
+//! session.start_with_credentials(StartLoginSessionWithCredentialsDetails {
+//!     account_name: String::from("username"),
+//!     password: "password".into(),
+//!     steam_guard_code: Some(String::from("ABC12")),
+//!     ..Default::default()
+//! })?;
+//! 
+//! let refresh_token = session.get_refresh_token();
+//! # Ok::<(), steam_session::blocking::Error>(())
+//! ```
+
+mod error;
+
+pub use error::Error;
+
+use crate::cookies::CookieDomain;
+use crate::login_session::{
+    self,
+    LoginSessionBuilder,
+    LoginSessionError,
+    SessionTokens,
+};
+use crate::request::{StartLoginSessionWithCredentialsDetails, GenerateAccessTokenOptions};
+use crate::response::{StartSessionResponse, GeneratedAccessToken};
+use crate::transports::{Transport, WebSocketCMTransport};
+use crate::transports::web_api::WebApiTransport;
+use std::future::Future;
+use steamid_ng::SteamID;
+use tokio::runtime::Runtime;
+
+/// A blocking [`login_session::LoginSession`].
+#[derive(Debug)]
+pub struct LoginSession<T> {
+    // declared before the runtime so it is dropped while the runtime is still alive
+    inner: login_session::LoginSession<T>,
+    runtime: Runtime,
+}
+
+impl LoginSession<WebSocketCMTransport> {
+    /// Connects to a CM server and creates a session using the WebSocket transport.
+    pub fn connect_ws() -> Result<Self, Error> {
+        let runtime = create_runtime()?;
+        let inner = runtime.block_on(login_session::connect_ws())?;
+        
+        Ok(Self {
+            inner,
+            runtime,
+        })
+    }
+}
+
+impl LoginSession<WebApiTransport> {
+    /// Creates a session using the WebAPI transport.
+    pub fn connect_webapi() -> Result<Self, Error> {
+        let runtime = create_runtime()?;
+        let inner = runtime.block_on(login_session::connect_webapi())?;
+        
+        Ok(Self {
+            inner,
+            runtime,
+        })
+    }
+}
+
+impl<T> LoginSession<T>
+where
+    T: Transport,
+{
+    /// Creates a session from the [`LoginSessionBuilder`] returned by `builder`. `builder` is 
+    /// run within the session's runtime, which is required for creating transports that spawn 
+    /// background tasks.
+    /// 
+    /// # Examples
+    /// ```no_run
+    /// use steam_session::blocking::LoginSession;
+    /// use steam_session::login_session::{self, LoginSessionError};
+    /// use steam_session::transports::WebSocketCMTransport;
+    /// use steam_session::authentication_client::Error as AuthenticationClientError;
+    /// use steam_session::enums::EAuthTokenPlatformType;
+    /// 
+    /// let session = LoginSession::from_builder(|| async {
+    ///     let transport = WebSocketCMTransport::connect()
+    ///         .await
+    ///         .map_err(AuthenticationClientError::WebSocketCM)?;
+    ///     
+    ///     Ok::<_, LoginSessionError>(login_session::LoginSession::builder(
+    ///         transport,
+    ///         EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient,
+    ///     ))
+    /// })?;
+    /// # Ok::<(), steam_session::blocking::Error>(())
+    /// ```
+    pub fn from_builder<F, Fut>(builder: F) -> Result<Self, Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<LoginSessionBuilder<T>, LoginSessionError>>,
+    {
+        let runtime = create_runtime()?;
+        let inner = runtime.block_on(async {
+            builder().await?.build()
+        })?;
+        
+        Ok(Self {
+            inner,
+            runtime,
+        })
+    }
+    
+    /// Gets a reference to the underlying async session.
+    pub fn inner(&self) -> &login_session::LoginSession<T> {
+        &self.inner
+    }
+    
+    /// Gets a mutable reference to the underlying async session.
+    pub fn inner_mut(&mut self) -> &mut login_session::LoginSession<T> {
+        &mut self.inner
+    }
+    
+    /// Runs `future` to completion on the session's runtime.
+    pub fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        self.runtime.block_on(future)
+    }
+    
+    /// See [`login_session::LoginSession::start_with_credentials`].
+    pub fn start_with_credentials(
+        &mut self,
+        details: StartLoginSessionWithCredentialsDetails,
+    ) -> Result<StartSessionResponse, LoginSessionError> {
+        self.runtime.block_on(self.inner.start_with_credentials(details))
+    }
+    
+    /// See [`login_session::LoginSession::submit_steam_guard_code`].
+    pub fn submit_steam_guard_code(
+        &mut self,
+        auth_code: String,
+    ) -> Result<(), LoginSessionError> {
+        self.runtime.block_on(self.inner.submit_steam_guard_code(auth_code))
+    }
+    
+    /// See [`login_session::LoginSession::poll`].
+    pub fn poll(&mut self) -> Result<(), LoginSessionError> {
+        self.runtime.block_on(self.inner.poll())
+    }
+    
+    /// See [`login_session::LoginSession::refresh_access_token`].
+    pub fn refresh_access_token(&mut self) -> Result<(), LoginSessionError> {
+        self.runtime.block_on(self.inner.refresh_access_token())
+    }
+    
+    /// See [`login_session::LoginSession::renew_refresh_token`].
+    pub fn renew_refresh_token(&mut self) -> Result<bool, LoginSessionError> {
+        self.runtime.block_on(self.inner.renew_refresh_token())
+    }
+    
+    /// See [`login_session::LoginSession::generate_access_token`].
+    pub fn generate_access_token(
+        &mut self,
+        options: GenerateAccessTokenOptions,
+    ) -> Result<GeneratedAccessToken, LoginSessionError> {
+        self.runtime.block_on(self.inner.generate_access_token(options))
+    }
+    
+    /// See [`login_session::LoginSession::get_web_cookies`].
+    pub fn get_web_cookies(
+        &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<Vec<String>, LoginSessionError> {
+        self.runtime.block_on(self.inner.get_web_cookies(domains))
+    }
+    
+    /// See [`login_session::LoginSession::get_session_tokens`].
+    pub fn get_session_tokens(
+        &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<SessionTokens, LoginSessionError> {
+        self.runtime.block_on(self.inner.get_session_tokens(domains))
+    }
+    
+    /// Gets the SteamID.
+    pub fn steamid(&self) -> Option<SteamID> {
+        self.inner.steamid()
+    }
+    
+    /// Gets the account name.
+    pub fn get_account_name(&self) -> Option<&String> {
+        self.inner.get_account_name()
+    }
+    
+    /// Gets the access token.
+    pub fn get_access_token(&self) -> Option<&String> {
+        self.inner.get_access_token()
+    }
+    
+    /// Gets the refresh token.
+    pub fn get_refresh_token(&self) -> Option<&String> {
+        self.inner.get_refresh_token()
+    }
+    
+    /// Sets the refresh token.
+    pub fn set_refresh_token(&mut self, token: String) -> Result<(), LoginSessionError> {
+        self.inner.set_refresh_token(token)
+    }
+}
+
+/// Creates the runtime for a session. A worker thread is used so background tasks, such as the 
+/// WebSocket read loop, continue to run between calls.
+fn create_runtime() -> Result<Runtime, std::io::Error> {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+}
@@ -0,0 +1,26 @@
+use crate::ErrorKind;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("IO error: {}", .0)]
+    Io(#[from] std::io::Error),
+    #[error("Serde error: {}", .0)]
+    Serde(#[from] serde_json::Error),
+    #[cfg(feature = "redis")]
+    #[error("Redis error: {}", .0)]
+    Redis(#[from] redis::RedisError),
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io(error) => crate::error::io_kind(error),
+            Self::Serde(_) => ErrorKind::Decode,
+            #[cfg(feature = "redis")]
+            Self::Redis(error) if error.is_timeout() => ErrorKind::Timeout,
+            #[cfg(feature = "redis")]
+            Self::Redis(_) => ErrorKind::Connection,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use super::{CacheStore, Error};
+use crate::types::DateTime;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+
+/// A cache store that keeps each value in a file in a directory, which can be on a volume shared
+/// between processes. Writes go to a temporary file which then replaces the value, so readers
+/// never see a partial write.
+#[derive(Debug)]
+pub struct FileCacheStore {
+    directory: PathBuf,
+}
+
+/// The form values are stored in.
+#[derive(Serialize, Deserialize)]
+struct StoredValue {
+    expires_at: DateTime,
+    value: String,
+}
+
+impl FileCacheStore {
+    /// Creates a new [`FileCacheStore`] in `directory`. The directory is created when a value is
+    /// first set.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+    
+    /// Gets the path to the directory.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+    
+    /// Gets the name of the file for `key`. Keys are hex-encoded so that any key is a valid file
+    /// name and keys sharing a prefix share a prefix of their file names.
+    fn file_name(key: &str) -> String {
+        data_encoding::HEXLOWER.encode(key.as_bytes())
+    }
+    
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory.join(Self::file_name(key))
+    }
+}
+
+#[async_trait]
+impl CacheStore for FileCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let bytes = match tokio::fs::read(self.path(key)).await {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let stored: StoredValue = serde_json::from_slice(&bytes)?;
+        
+        if stored.expires_at <= Utc::now() {
+            return Ok(None);
+        }
+        
+        let value = data_encoding::BASE64.decode(stored.value.as_bytes())
+            .map_err(|error| std::io::Error::new(ErrorKind::InvalidData, error))?;
+        
+        Ok(Some(value))
+    }
+    
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<(), Error> {
+        let expires_at = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+            .unwrap_or(DateTime::MAX_UTC);
+        let stored = StoredValue {
+            expires_at,
+            value: data_encoding::BASE64.encode(value),
+        };
+        let bytes = serde_json::to_vec(&stored)?;
+        let path = self.path(key);
+        let mut tmp_path = path.clone().into_os_string();
+        
+        // unique per process so concurrent writers on a shared volume do not collide
+        tmp_path.push(format!(".{}.tmp", std::process::id()));
+        tokio::fs::create_dir_all(&self.directory).await?;
+        tokio::fs::write(&tmp_path, &bytes).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        
+        Ok(())
+    }
+    
+    async fn remove(&self, key: &str) -> Result<(), Error> {
+        match tokio::fs::remove_file(self.path(key)).await {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+    
+    async fn clear(&self, prefix: &str) -> Result<(), Error> {
+        let prefix = Self::file_name(prefix);
+        let mut entries = match tokio::fs::read_dir(&self.directory).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+        
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                match tokio::fs::remove_file(entry.path()).await {
+                    Err(error) if error.kind() != ErrorKind::NotFound => return Err(error.into()),
+                    _ => {},
+                }
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn sets_and_gets_values() {
+        let directory = std::env::temp_dir().join(format!("steam-session-cache-{}", std::process::id()));
+        let store = FileCacheStore::new(&directory);
+        
+        store.set("rsa_key:account", b"key", Duration::from_secs(60)).await.unwrap();
+        store.set("rsa_key:expired", b"key", Duration::ZERO).await.unwrap();
+        
+        assert_eq!(store.get("rsa_key:account").await.unwrap().as_deref(), Some(&b"key"[..]));
+        assert_eq!(store.get("rsa_key:expired").await.unwrap(), None);
+        
+        store.clear("rsa_key:").await.unwrap();
+        
+        assert_eq!(store.get("rsa_key:account").await.unwrap(), None);
+        
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,74 @@
+use super::{CacheStore, Error};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use dashmap::DashMap;
+
+/// A cache store that keeps values in memory. This is the default.
+#[derive(Debug, Default)]
+pub struct MemoryCacheStore {
+    values: DashMap<String, CachedValue>,
+}
+
+#[derive(Debug)]
+struct CachedValue {
+    value: Vec<u8>,
+    expires_at: Instant,
+}
+
+impl MemoryCacheStore {
+    /// Creates a new empty [`MemoryCacheStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CacheStore for MemoryCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let now = Instant::now();
+        
+        self.values.remove_if(key, |_, cached| cached.expires_at <= now);
+        
+        Ok(self.values.get(key).map(|cached| cached.value.clone()))
+    }
+    
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<(), Error> {
+        self.values.insert(key.to_owned(), CachedValue {
+            value: value.to_vec(),
+            expires_at: Instant::now() + ttl,
+        });
+        Ok(())
+    }
+    
+    async fn remove(&self, key: &str) -> Result<(), Error> {
+        self.values.remove(key);
+        Ok(())
+    }
+    
+    async fn clear(&self, prefix: &str) -> Result<(), Error> {
+        self.values.retain(|key, _| !key.starts_with(prefix));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn expires_values() {
+        let store = MemoryCacheStore::new();
+        
+        store.set("a:1", b"one", Duration::from_secs(60)).await.unwrap();
+        store.set("a:2", b"two", Duration::ZERO).await.unwrap();
+        store.set("b:1", b"three", Duration::from_secs(60)).await.unwrap();
+        
+        assert_eq!(store.get("a:1").await.unwrap().as_deref(), Some(&b"one"[..]));
+        assert_eq!(store.get("a:2").await.unwrap(), None);
+        
+        store.clear("a:").await.unwrap();
+        
+        assert_eq!(store.get("a:1").await.unwrap(), None);
+        assert!(store.get("b:1").await.unwrap().is_some());
+    }
+}
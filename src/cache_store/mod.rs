@@ -0,0 +1,50 @@
+//! # Cache stores
+//! 
+//! Caches that are expensive to warm, such as the CM server list and the RSA keys used to
+//! encrypt passwords, are kept in a [`CacheStore`]. By default they are kept in memory, but
+//! horizontally-scaled deployments can share them between processes with a
+//! [`FileCacheStore`] on a shared volume or, with the `redis` feature, a `RedisCacheStore`.
+//! 
+//! ```no_run
+//! # async fn example() {
+//! use steam_session::cache_store::FileCacheStore;
+//! use steam_session::transports::websocket::DEFAULT_CM_LIST;
+//! use std::sync::Arc;
+//! 
+//! let store = Arc::new(FileCacheStore::new("/var/cache/steam-session"));
+//! 
+//! DEFAULT_CM_LIST.lock().await.set_cache_store(store);
+//! # }
+//! ```
+
+mod error;
+mod file;
+mod memory;
+#[cfg(feature = "redis")]
+mod redis;
+
+pub use error::Error;
+pub use file::FileCacheStore;
+pub use memory::MemoryCacheStore;
+#[cfg(feature = "redis")]
+pub use self::redis::RedisCacheStore;
+
+use async_trait::async_trait;
+use std::fmt;
+use std::time::Duration;
+
+/// Storage for cached values. Values are opaque bytes which expire after a time-to-live.
+#[async_trait]
+pub trait CacheStore: fmt::Debug + Send + Sync {
+    /// Gets the value for `key`. Returns `None` if there is no value or it has expired.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error>;
+    
+    /// Sets the value for `key`, expiring after `ttl`.
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<(), Error>;
+    
+    /// Removes the value for `key`.
+    async fn remove(&self, key: &str) -> Result<(), Error>;
+    
+    /// Removes the values for all keys beginning with `prefix`.
+    async fn clear(&self, prefix: &str) -> Result<(), Error>;
+}
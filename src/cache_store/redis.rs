@@ -0,0 +1,91 @@
+use super::{CacheStore, Error};
+use std::time::Duration;
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+
+/// A cache store that keeps values in Redis, so they are shared by every process connected to
+/// the same server. Keys are prefixed with `steam-session:` unless set with
+/// [`RedisCacheStore::with_prefix`].
+#[derive(Clone)]
+pub struct RedisCacheStore {
+    connection: ConnectionManager,
+    prefix: String,
+}
+
+impl std::fmt::Debug for RedisCacheStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisCacheStore")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisCacheStore {
+    /// Connects to the Redis server of `client`. The connection is re-established automatically
+    /// if it is lost.
+    pub async fn new(client: redis::Client) -> Result<Self, Error> {
+        Ok(Self {
+            connection: ConnectionManager::new(client).await?,
+            prefix: "steam-session:".into(),
+        })
+    }
+    
+    /// Sets the prefix added to keys.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+    
+    fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl CacheStore for RedisCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let mut connection = self.connection.clone();
+        
+        Ok(connection.get(self.key(key)).await?)
+    }
+    
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<(), Error> {
+        let mut connection = self.connection.clone();
+        // redis rejects an expiry of zero
+        let seconds = ttl.as_secs().max(1);
+        
+        connection.set_ex::<_, _, ()>(self.key(key), value, seconds).await?;
+        Ok(())
+    }
+    
+    async fn remove(&self, key: &str) -> Result<(), Error> {
+        let mut connection = self.connection.clone();
+        
+        connection.del::<_, ()>(self.key(key)).await?;
+        Ok(())
+    }
+    
+    async fn clear(&self, prefix: &str) -> Result<(), Error> {
+        let mut connection = self.connection.clone();
+        let pattern = format!("{}*", self.key(prefix));
+        let keys = {
+            let mut iter = connection.scan_match::<_, String>(pattern).await?;
+            let mut keys = Vec::new();
+            
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            
+            keys
+        };
+        
+        if !keys.is_empty() {
+            let mut connection = self.connection.clone();
+            
+            connection.del::<_, ()>(keys).await?;
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+//! # Clocks and randomness
+//! 
+//! Timeouts, poll intervals, and token ages are measured with a [`Clock`], and random values such 
+//! as sessionids, websocket keys, and job IDs are generated with a [`RandomSource`]. Both default 
+//! to the system, and can be replaced with [`MockClock`] and [`SeededRandom`] so that tests are 
+//! deterministic and can skip ahead in time, e.g. to a poll expiring.
+//! 
+//! ```
+//! # async fn example() {
+//! use steam_session::clock::{Clock, MockClock};
+//! use std::time::Duration;
+//! 
+//! let clock = MockClock::default();
+//! let started = clock.now();
+//! 
+//! // Returns immediately, advancing the clock
+//! clock.sleep(Duration::from_secs(30)).await;
+//! 
+//! assert_eq!((clock.now() - started).num_seconds(), 30);
+//! # }
+//! ```
+
+use crate::types::DateTime;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use async_trait::async_trait;
+use rand::{Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+
+/// A source of the current time.
+#[async_trait]
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Gets the current time.
+    fn now(&self) -> DateTime;
+    
+    /// Waits for `duration` to pass.
+    async fn sleep(&self, duration: Duration);
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime {
+        chrono::Utc::now()
+    }
+    
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// A clock that only moves when advanced. Sleeping advances the clock by the duration and 
+/// returns immediately.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime>,
+}
+
+impl Default for MockClock {
+    /// Creates a clock starting at the current time.
+    fn default() -> Self {
+        Self::new(chrono::Utc::now())
+    }
+}
+
+impl MockClock {
+    /// Creates a clock starting at `now`.
+    pub fn new(now: DateTime) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+    
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let duration = chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX);
+        let mut now = self.now.lock().unwrap_or_else(PoisonError::into_inner);
+        
+        *now = now.checked_add_signed(duration).unwrap_or(DateTime::MAX_UTC);
+    }
+    
+    /// Sets the current time.
+    pub fn set(&self, now: DateTime) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+}
+
+#[async_trait]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    
+    async fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+        // Lets other tasks observe the new time
+        tokio::task::yield_now().await;
+    }
+}
+
+/// A source of random bytes.
+pub trait RandomSource: fmt::Debug + Send + Sync {
+    /// Fills `dest` with random bytes.
+    fn fill_bytes(&self, dest: &mut [u8]);
+}
+
+impl dyn RandomSource + '_ {
+    /// Generates an array of random bytes.
+    pub fn bytes<const N: usize>(&self) -> [u8; N] {
+        let mut bytes = [0; N];
+        
+        self.fill_bytes(&mut bytes);
+        bytes
+    }
+}
+
+/// Random bytes from the thread-local generator.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadRandom;
+
+impl RandomSource for ThreadRandom {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        rand::thread_rng().fill(dest);
+    }
+}
+
+/// Random bytes from a generator with a fixed seed, producing the same sequence each time.
+#[derive(Debug)]
+pub struct SeededRandom {
+    rng: Mutex<StdRng>,
+}
+
+impl SeededRandom {
+    /// Creates a generator from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        self.rng.lock().unwrap_or_else(PoisonError::into_inner).fill_bytes(dest);
+    }
+}
+
+/// The default clock.
+pub(crate) fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// The default source of random bytes.
+pub(crate) fn thread_random() -> Arc<dyn RandomSource> {
+    Arc::new(ThreadRandom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn mock_clock_sleep_advances() {
+        let clock = MockClock::default();
+        let started = clock.now();
+        
+        clock.sleep(Duration::from_secs(90)).await;
+        
+        assert_eq!((clock.now() - started).num_seconds(), 90);
+    }
+    
+    #[test]
+    fn seeded_random_is_repeatable() {
+        let a: Arc<dyn RandomSource> = Arc::new(SeededRandom::new(7));
+        let b: Arc<dyn RandomSource> = Arc::new(SeededRandom::new(7));
+        
+        assert_eq!(a.bytes::<16>(), b.bytes::<16>());
+    }
+}
@@ -0,0 +1,52 @@
+use crate::enums::EResult;
+use crate::ErrorKind;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Websocket CM: {}", .0)]
+    WebSocketCM(#[from] crate::transports::websocket::Error),
+    #[error("Decode error: {}", .0)]
+    Decode(#[from] crate::helpers::DecodeError),
+    #[error("Protobuf error: {}", .0)]
+    Proto(#[from] protobuf::Error),
+    #[error("The provided token is an access token, not a refresh token")]
+    ExpectedRefreshToken,
+    #[error("{}", .0)]
+    TokenPlatformDifferent(#[from] crate::helpers::PlatformMismatchError),
+    #[error("No game server login token was provided")]
+    MissingGameServerToken,
+    #[error("The message receiver for this transport has already been taken")]
+    MessageReceiverTaken,
+    #[error("Connection to the CM server was closed")]
+    Disconnected,
+    #[error("Timed out waiting for ClientLogOnResponse from {endpoint} after {elapsed:?}")]
+    Timeout {
+        /// The endpoint of the CM server.
+        endpoint: String,
+        /// How long was waited.
+        elapsed: std::time::Duration,
+    },
+    #[error("Logon failed with result: {:?}", .0)]
+    LogonFailed(EResult),
+    #[error("Unknown EResult: {}", .0)]
+    UnknownEResult(i32),
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::WebSocketCM(error) => error.kind(),
+            Self::Decode(error) => error.kind(),
+            Self::Proto(_) |
+            Self::UnknownEResult(_) => ErrorKind::Decode,
+            Self::ExpectedRefreshToken |
+            Self::TokenPlatformDifferent(_) => ErrorKind::Token,
+            Self::MissingGameServerToken => ErrorKind::InvalidInput,
+            Self::MessageReceiverTaken => ErrorKind::State,
+            Self::Disconnected => ErrorKind::Connection,
+            Self::Timeout { .. } => ErrorKind::Timeout,
+            Self::LogonFailed(_) => ErrorKind::Steam,
+        }
+    }
+}
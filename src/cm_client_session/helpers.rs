@@ -0,0 +1,235 @@
+use crate::enums::EResult;
+use crate::secret::Secret;
+use steamid_ng::SteamID;
+
+/// Details for logging on to a CM server.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(default))]
+pub struct LogOnDetails {
+    /// A refresh token issued for [`crate::enums::EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient`].
+    pub refresh_token: Secret<String>,
+    /// The machine ID. If not set, one will be generated from the SteamID.
+    pub machine_id: Option<Vec<u8>>,
+    /// The machine name. If not set, a spoofed hostname will be used.
+    pub machine_name: Option<String>,
+    /// The client language. Defaults to `english`.
+    pub client_language: String,
+}
+
+impl Default for LogOnDetails {
+    fn default() -> Self {
+        Self {
+            refresh_token: Secret::default(),
+            machine_id: None,
+            machine_name: None,
+            client_language: String::from("english"),
+        }
+    }
+}
+
+/// Details for logging on to a CM server as a persistent game server.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameServerLogOnDetails {
+    /// The game server login token (GSLT), as created at 
+    /// <https://steamcommunity.com/dev/managegameservers>.
+    pub token: Secret<String>,
+    /// The app ID of the game the server is for.
+    pub app_id: u32,
+}
+
+/// The credentials a session logs on with.
+#[derive(Debug, Clone)]
+pub(crate) enum LogOnCredentials {
+    Client(LogOnDetails),
+    GameServer(GameServerLogOnDetails),
+}
+
+/// The state of a logged-on CM session.
+#[derive(Debug, Clone)]
+pub struct CmSessionState {
+    /// The SteamID of the logged-on account.
+    pub steamid: SteamID,
+    /// The cell ID assigned by the CM server.
+    pub cell_id: u32,
+    /// The public IP address as seen by the CM server, if provided.
+    pub public_ip: Option<std::net::Ipv4Addr>,
+    /// The interval at which heartbeats are sent, in seconds.
+    pub heartbeat_seconds: u32,
+    /// The result the CM server gave when logging the session off. `None` while logged on.
+    pub logged_off: Option<EResult>,
+    /// The limitations of the account. `None` until the CM server sends them shortly after 
+    /// logging on.
+    pub limitations: Option<AccountLimitations>,
+}
+
+/// The limitations on an account, as sent by the CM server in `ClientIsLimitedAccount`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountLimitations {
+    /// Whether the account is limited, e.g. because it has not spent $5 on Steam. Limited 
+    /// accounts cannot send friend invites, trade, or use the market.
+    pub limited: bool,
+    /// Whether the account is banned from Steam Community.
+    pub community_banned: bool,
+    /// Whether the account is locked, e.g. after being reported as stolen.
+    pub locked: bool,
+    /// Whether the account can invite friends despite being limited.
+    pub can_invite_friends: bool,
+}
+
+impl AccountLimitations {
+    /// Whether the account has none of the limitations.
+    pub fn is_unrestricted(&self) -> bool {
+        !self.limited && !self.community_banned && !self.locked
+    }
+    
+    /// Decodes a `CMsgClientIsLimitedAccount` message body. The message is not among the 
+    /// generated protobuf types, so its four boolean fields are read directly.
+    pub(crate) fn parse_from_bytes(body: &[u8]) -> Result<Self, protobuf::Error> {
+        let mut input = protobuf::CodedInputStream::from_bytes(body);
+        let mut unknown_fields = protobuf::UnknownFields::new();
+        let mut limitations = Self::default();
+        
+        while let Some(tag) = input.read_raw_tag_or_eof()? {
+            match tag {
+                8 => limitations.limited = input.read_bool()?,
+                16 => limitations.community_banned = input.read_bool()?,
+                24 => limitations.locked = input.read_bool()?,
+                32 => limitations.can_invite_friends = input.read_bool()?,
+                tag => protobuf::rt::read_unknown_or_skip_group(tag, &mut input, &mut unknown_fields)?,
+            }
+        }
+        
+        Ok(limitations)
+    }
+}
+
+/// Why a CM session was logged off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogOffReason {
+    /// The session was logged off by calling `log_off`.
+    Requested,
+    /// The account logged in elsewhere.
+    LoggedInElsewhere,
+    /// The session was replaced by a newer session.
+    LogonSessionReplaced,
+    /// Steam is temporarily unavailable.
+    ServiceUnavailable,
+    /// The CM server asked the client to connect to another CM server.
+    TryAnotherCM,
+    /// The refresh token was revoked or has expired.
+    TokenRevoked,
+    /// The connection to the CM server was lost.
+    Disconnected,
+    /// Any other result.
+    Other(EResult),
+}
+
+impl LogOffReason {
+    /// Whether logging on again can be expected to succeed without user intervention.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            Self::LogonSessionReplaced |
+            Self::ServiceUnavailable |
+            Self::TryAnotherCM |
+            Self::Disconnected
+        )
+    }
+}
+
+impl From<EResult> for LogOffReason {
+    fn from(eresult: EResult) -> Self {
+        match eresult {
+            EResult::OK => Self::Requested,
+            EResult::LoggedInElsewhere => Self::LoggedInElsewhere,
+            EResult::LogonSessionReplaced => Self::LogonSessionReplaced,
+            EResult::ServiceUnavailable => Self::ServiceUnavailable,
+            EResult::TryAnotherCM => Self::TryAnotherCM,
+            EResult::NoConnection => Self::Disconnected,
+            EResult::Revoked |
+            EResult::Expired |
+            EResult::AccessDenied |
+            EResult::InvalidPassword => Self::TokenRevoked,
+            eresult => Self::Other(eresult),
+        }
+    }
+}
+
+/// An event emitted by a [`super::CmClientSession`].
+#[derive(Debug, Clone)]
+pub enum CmSessionEvent {
+    /// The session was logged off.
+    LoggedOff {
+        /// The reason for being logged off.
+        reason: LogOffReason,
+        /// The result given by the CM server.
+        eresult: EResult,
+    },
+    /// The session logged on again after being logged off.
+    Relogged {
+        /// The number of attempts it took.
+        attempts: u32,
+    },
+    /// Logging on again failed and no more attempts will be made.
+    ReloginFailed {
+        /// The number of attempts made.
+        attempts: u32,
+    },
+    /// The CM server sent the limitations of the account.
+    Limitations(AccountLimitations),
+}
+
+/// Whether a session logs on again after being logged off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReloginPolicy {
+    /// Never log on again.
+    #[default]
+    Never,
+    /// Log on again when the reason for being logged off is recoverable (see 
+    /// [`LogOffReason::is_recoverable`]).
+    OnRecoverable {
+        /// The maximum number of attempts.
+        max_attempts: u32,
+        /// The delay before each attempt.
+        delay: std::time::Duration,
+    },
+}
+
+impl ReloginPolicy {
+    /// Gets the delay before the next attempt after being logged off for `reason`, or `None` if 
+    /// no further attempts should be made.
+    pub fn next_attempt(&self, reason: LogOffReason, attempts: u32) -> Option<std::time::Duration> {
+        match self {
+            Self::Never => None,
+            Self::OnRecoverable { max_attempts, delay } => {
+                if reason.is_recoverable() && attempts < *max_attempts {
+                    Some(*delay)
+                } else {
+                    None
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_account_limitations() {
+        // limited and can invite friends, with an unknown field 5 set to 1
+        let limitations = AccountLimitations::parse_from_bytes(&[8, 1, 16, 0, 32, 1, 40, 1]).unwrap();
+        
+        assert_eq!(limitations, AccountLimitations {
+            limited: true,
+            community_banned: false,
+            locked: false,
+            can_invite_friends: true,
+        });
+        assert!(!limitations.is_unrestricted());
+    }
+}
@@ -0,0 +1,501 @@
+//! # CM Client Session
+//! 
+//! Maintains a logged-on session with a CM server using a refresh token issued for 
+//! [`EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient`]. This is required for any 
+//! CM functionality beyond authentication. Game servers can also log on using a game server 
+//! login token (GSLT) with [`CmClientSession::log_on_game_server`].
+
+mod error;
+mod helpers;
+
+pub use error::Error;
+pub use helpers::{LogOnDetails, GameServerLogOnDetails, CmSessionState, CmSessionEvent, LogOffReason, ReloginPolicy, AccountLimitations};
+
+use crate::authentication_client::get_machine_id;
+use crate::enums::{EMsg, EResult, EOSType, EAuthTokenPlatformType};
+use crate::helpers::{JwtPayload, get_spoofed_hostname};
+use crate::net::MessagePriority;
+use crate::proto::steammessages_clientserver_login::{
+    CMsgClientLogon,
+    CMsgClientLogonResponse,
+    CMsgClientLogOff,
+    CMsgClientLoggedOff,
+    CMsgClientHeartBeat,
+};
+use crate::transports::websocket::{Message, PROTOCOL_VERSION};
+use crate::transports::WebSocketCMTransport;
+use std::str::FromStr;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use protobuf::Message as ProtoMessage;
+use steamid_ng::SteamID;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use helpers::LogOnCredentials;
+
+const LOGON_TIMEOUT_SECONDS: u64 = 10;
+const DEFAULT_HEARTBEAT_SECONDS: u32 = 9;
+const CLIENT_PACKAGE_VERSION: u32 = 1771;
+const EVENT_CHANNEL_CAPACITY: usize = 16;
+/// The SteamID used in the header when logging on as a game server: an anonymous game server in 
+/// the public universe. The CM server assigns the actual SteamID.
+const ANON_GAME_SERVER_STEAMID: u64 = 0x0140_0000_0000_0000;
+
+type MessageReceiver = mpsc::Receiver<Result<Message, crate::transports::websocket::Error>>;
+
+/// State shared between a session and its background tasks.
+#[derive(Debug)]
+struct Shared {
+    transport: RwLock<Arc<WebSocketCMTransport>>,
+    state: RwLock<CmSessionState>,
+    relogin_policy: RwLock<ReloginPolicy>,
+    credentials: LogOnCredentials,
+    events: broadcast::Sender<CmSessionEvent>,
+    /// Set once the session has been logged off and will not log on again.
+    ended: AtomicBool,
+}
+
+impl Shared {
+    fn transport(&self) -> Arc<WebSocketCMTransport> {
+        Arc::clone(&self.transport.read().unwrap_or_else(PoisonError::into_inner))
+    }
+    
+    fn read_state(&self) -> RwLockReadGuard<'_, CmSessionState> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+    
+    fn write_state(&self) -> RwLockWriteGuard<'_, CmSessionState> {
+        self.state.write().unwrap_or_else(PoisonError::into_inner)
+    }
+    
+    fn relogin_policy(&self) -> ReloginPolicy {
+        *self.relogin_policy.read().unwrap_or_else(PoisonError::into_inner)
+    }
+    
+    fn emit(&self, event: CmSessionEvent) {
+        // an error only means there are no subscribers
+        let _ = self.events.send(event);
+    }
+}
+
+/// Background tasks for a session. The tasks are aborted when this is dropped.
+#[derive(Debug)]
+struct Tasks(Vec<JoinHandle<()>>);
+
+impl Tasks {
+    fn spawn(tasks: Vec<JoinHandle<()>>) -> Self {
+        crate::metrics::active_cm_sessions(1.0);
+        Self(tasks)
+    }
+}
+
+impl Drop for Tasks {
+    fn drop(&mut self) {
+        for task in &self.0 {
+            task.abort();
+        }
+        
+        crate::metrics::active_cm_sessions(-1.0);
+    }
+}
+
+/// A logged-on session with a CM server. Heartbeats are sent in the background for as long as 
+/// the session is alive. When the session is logged off a [`CmSessionEvent::LoggedOff`] event is 
+/// emitted, and the session logs on again according to its [`ReloginPolicy`].
+/// 
+/// This is a cheap-to-clone handle. Clones share the same connection and state, and the 
+/// background tasks stop once every clone has been dropped.
+#[derive(Debug, Clone)]
+pub struct CmClientSession {
+    shared: Arc<Shared>,
+    tasks: Arc<Tasks>,
+}
+
+impl CmClientSession {
+    /// Logs on to the CM server `transport` is connected to.
+    pub async fn log_on(
+        transport: WebSocketCMTransport,
+        details: LogOnDetails,
+    ) -> Result<Self, Error> {
+        Self::start(transport, LogOnCredentials::Client(details)).await
+    }
+    
+    /// Logs on to the CM server `transport` is connected to as a persistent game server using a 
+    /// game server login token (GSLT). The SteamID assigned to the server is available from 
+    /// [`CmClientSession::steamid`] once logged on.
+    pub async fn log_on_game_server(
+        transport: WebSocketCMTransport,
+        details: GameServerLogOnDetails,
+    ) -> Result<Self, Error> {
+        Self::start(transport, LogOnCredentials::GameServer(details)).await
+    }
+    
+    async fn start(
+        transport: WebSocketCMTransport,
+        credentials: LogOnCredentials,
+    ) -> Result<Self, Error> {
+        let (messages, state) = perform_log_on(&transport, &credentials).await?;
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let shared = Arc::new(Shared {
+            transport: RwLock::new(Arc::new(transport)),
+            state: RwLock::new(state),
+            relogin_policy: RwLock::new(ReloginPolicy::default()),
+            credentials,
+            events,
+            ended: AtomicBool::new(false),
+        });
+        let tasks = Arc::new(Tasks::spawn(vec![
+            tokio::spawn(supervise(Arc::clone(&shared), messages)),
+            tokio::spawn(send_heartbeats(Arc::clone(&shared))),
+        ]));
+        
+        Ok(Self {
+            shared,
+            tasks,
+        })
+    }
+    
+    /// Sets the policy for logging on again after being logged off. Defaults to 
+    /// [`ReloginPolicy::Never`].
+    pub fn set_relogin_policy(&self, policy: ReloginPolicy) {
+        *self.shared.relogin_policy.write().unwrap_or_else(PoisonError::into_inner) = policy;
+    }
+    
+    /// Subscribes to events for this session.
+    pub fn subscribe(&self) -> broadcast::Receiver<CmSessionEvent> {
+        self.shared.events.subscribe()
+    }
+    
+    /// Gets the SteamID of the logged-on account.
+    pub fn steamid(&self) -> SteamID {
+        self.shared.read_state().steamid
+    }
+    
+    /// Gets the cell ID assigned by the CM server.
+    pub fn cell_id(&self) -> u32 {
+        self.shared.read_state().cell_id
+    }
+    
+    /// Gets a snapshot of the session state.
+    pub fn state(&self) -> CmSessionState {
+        self.shared.read_state().clone()
+    }
+    
+    /// Gets the limitations of the account, such as being limited, community banned, or locked. 
+    /// `None` until the CM server sends them, shortly after logging on.
+    pub fn limitations(&self) -> Option<AccountLimitations> {
+        self.shared.read_state().limitations
+    }
+    
+    /// Waits up to `timeout` for the CM server to send the limitations of the account, so 
+    /// accounts that can't perform a task can be skipped right after logging on.
+    pub async fn wait_for_limitations(&self, timeout: Duration) -> Option<AccountLimitations> {
+        let mut events = self.subscribe();
+        
+        if let Some(limitations) = self.limitations() {
+            return Some(limitations);
+        }
+        
+        tokio::time::timeout(timeout, async {
+            loop {
+                match events.recv().await {
+                    Ok(CmSessionEvent::Limitations(limitations)) => return Some(limitations),
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        }).await.ok().flatten()
+    }
+    
+    /// Whether the session is still logged on.
+    pub fn is_logged_on(&self) -> bool {
+        self.shared.read_state().logged_off.is_none()
+    }
+    
+    /// Gets the transport the session is logged on with. This changes when the session logs on 
+    /// again.
+    pub fn transport(&self) -> Arc<WebSocketCMTransport> {
+        self.shared.transport()
+    }
+    
+    /// Logs off from the CM server. The session will not log on again.
+    pub async fn log_off(&self) -> Result<(), Error> {
+        self.shared.write_state().logged_off = Some(EResult::OK);
+        self.shared.transport()
+            .send_message(EMsg::ClientLogOff, CMsgClientLogOff::new(), None)
+            .await?;
+        
+        Ok(())
+    }
+}
+
+/// Sends `ClientLogon` and waits for a successful response.
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "cm.log_on", skip_all))]
+async fn perform_log_on(
+    transport: &WebSocketCMTransport,
+    credentials: &LogOnCredentials,
+) -> Result<(MessageReceiver, CmSessionState), Error> {
+    let (steamid, logon) = match credentials {
+        LogOnCredentials::Client(details) => client_logon(details)?,
+        LogOnCredentials::GameServer(details) => game_server_logon(details)?,
+    };
+    let mut messages = transport.take_message_receiver()
+        .ok_or(Error::MessageReceiverTaken)?;
+    
+    transport.set_steamid(steamid);
+    transport.send_message(EMsg::ClientLogon, logon, None).await?;
+    
+    let logon_timeout = Duration::from_secs(LOGON_TIMEOUT_SECONDS);
+    let response = tokio::time::timeout(
+        logon_timeout,
+        wait_for_logon_response(&mut messages),
+    ).await.map_err(|_| Error::Timeout {
+        endpoint: transport.endpoint().to_string(),
+        elapsed: logon_timeout,
+    })??;
+    let eresult = EResult::try_from(response.eresult())
+        .map_err(|_| Error::UnknownEResult(response.eresult()))?;
+    
+    if eresult != EResult::OK {
+        return Err(Error::LogonFailed(eresult));
+    }
+    
+    let heartbeat_seconds = u32::try_from(response.heartbeat_seconds())
+        .ok()
+        .filter(|seconds| *seconds > 0)
+        .unwrap_or(DEFAULT_HEARTBEAT_SECONDS);
+    let public_ip = Some(response.deprecated_public_ip())
+        .filter(|ip| *ip != 0)
+        .map(std::net::Ipv4Addr::from);
+    let state = CmSessionState {
+        steamid: transport.steamid(),
+        cell_id: response.cell_id(),
+        public_ip,
+        heartbeat_seconds,
+        logged_off: None,
+        limitations: None,
+    };
+    
+    debug!("Logged on as {} (cell {})", u64::from(state.steamid), state.cell_id);
+    
+    Ok((messages, state))
+}
+
+/// Builds the `ClientLogon` message for a user account logging on with a refresh token.
+fn client_logon(details: &LogOnDetails) -> Result<(SteamID, CMsgClientLogon), Error> {
+    let decoded = JwtPayload::from_str(details.refresh_token.expose_secret())?;
+    
+    if !decoded.is_refresh_token() {
+        return Err(Error::ExpectedRefreshToken);
+    }
+    
+    decoded.assert_platform(EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient)?;
+    
+    let steamid = decoded.sub;
+    let machine_id = details.machine_id.clone()
+        .unwrap_or_else(|| get_machine_id(&u64::from(steamid).to_string()));
+    let mut logon = CMsgClientLogon::new();
+    
+    logon.set_protocol_version(PROTOCOL_VERSION);
+    logon.set_cell_id(0);
+    logon.set_client_language(details.client_language.clone());
+    logon.set_client_os_type(EOSType::Win11 as i32 as u32);
+    logon.set_should_remember_password(true);
+    logon.set_qos_level(2);
+    logon.set_machine_id(machine_id);
+    logon.set_machine_name(details.machine_name.clone().unwrap_or_else(get_spoofed_hostname));
+    logon.set_client_package_version(CLIENT_PACKAGE_VERSION);
+    logon.set_supports_rate_limit_response(true);
+    logon.set_access_token(details.refresh_token.expose_secret().clone());
+    
+    Ok((steamid, logon))
+}
+
+/// Builds the `ClientLogon` message for a game server logging on with a login token.
+fn game_server_logon(details: &GameServerLogOnDetails) -> Result<(SteamID, CMsgClientLogon), Error> {
+    if details.token.expose_secret().is_empty() {
+        return Err(Error::MissingGameServerToken);
+    }
+    
+    let mut logon = CMsgClientLogon::new();
+    
+    logon.set_protocol_version(PROTOCOL_VERSION);
+    logon.set_cell_id(0);
+    logon.set_client_os_type(EOSType::Win11 as i32 as u32);
+    logon.set_machine_id(get_machine_id(details.token.expose_secret()));
+    logon.set_game_server_app_id(details.app_id as i32);
+    logon.set_game_server_token(details.token.expose_secret().clone());
+    
+    Ok((SteamID::from(ANON_GAME_SERVER_STEAMID), logon))
+}
+
+/// Waits for the `ClientLogOnResponse` message, skipping any other messages.
+async fn wait_for_logon_response(
+    messages: &mut MessageReceiver,
+) -> Result<CMsgClientLogonResponse, Error> {
+    while let Some(message) = messages.recv().await {
+        let message = message?;
+        
+        if message.emsg == EMsg::ClientLogOnResponse {
+            return Ok(CMsgClientLogonResponse::parse_from_bytes(&message.body)?);
+        }
+        
+        debug!("Skipping {:?} while waiting for ClientLogOnResponse", message.emsg);
+    }
+    
+    Err(Error::Disconnected)
+}
+
+/// Handles messages until the session is logged off, then logs on again according to the 
+/// session's relogin policy.
+async fn supervise(
+    shared: Arc<Shared>,
+    mut messages: MessageReceiver,
+) {
+    loop {
+        let eresult = handle_messages(&shared, &mut messages).await;
+        let eresult = {
+            let mut state = shared.write_state();
+            
+            // a logoff we requested takes precedence over the connection closing
+            *state.logged_off.get_or_insert(eresult)
+        };
+        let reason = LogOffReason::from(eresult);
+        
+        debug!("CM session logged off: {reason:?} ({eresult:?})");
+        shared.emit(CmSessionEvent::LoggedOff {
+            reason,
+            eresult,
+        });
+        
+        match relogin(&shared, reason).await {
+            Some(new_messages) => messages = new_messages,
+            None => {
+                shared.ended.store(true, Ordering::Relaxed);
+                return;
+            },
+        }
+    }
+}
+
+/// Handles messages for the session until it is logged off. Returns the logoff result.
+async fn handle_messages(
+    shared: &Shared,
+    messages: &mut MessageReceiver,
+) -> EResult {
+    while let Some(message) = messages.recv().await {
+        let message = match message {
+            Ok(message) => message,
+            Err(error) if error.is_disconnected() => {
+                debug!("CM session disconnected: {error}");
+                return EResult::NoConnection;
+            },
+            Err(error) => {
+                warn!("Error received from CM session: {error}");
+                continue;
+            },
+        };
+        
+        match message.emsg {
+            EMsg::ClientLoggedOff => {
+                return CMsgClientLoggedOff::parse_from_bytes(&message.body)
+                    .ok()
+                    .and_then(|logged_off| EResult::try_from(logged_off.eresult()).ok())
+                    .unwrap_or(EResult::Fail);
+            },
+            EMsg::ClientLogOnResponse if message.eresult == EResult::TryAnotherCM => {
+                return EResult::TryAnotherCM;
+            },
+            EMsg::ClientIsLimitedAccount => {
+                match AccountLimitations::parse_from_bytes(&message.body) {
+                    Ok(limitations) => {
+                        shared.write_state().limitations = Some(limitations);
+                        shared.emit(CmSessionEvent::Limitations(limitations));
+                    },
+                    Err(error) => warn!("Failed to decode ClientIsLimitedAccount: {error}"),
+                }
+            },
+            emsg => {
+                debug!("Received unhandled message in CM session: {emsg:?}");
+            },
+        }
+        
+        if shared.read_state().logged_off.is_some() {
+            break;
+        }
+    }
+    
+    EResult::NoConnection
+}
+
+/// Attempts to log on again after being logged off for `reason`. Returns the message receiver 
+/// for the new connection on success.
+async fn relogin(
+    shared: &Shared,
+    reason: LogOffReason,
+) -> Option<MessageReceiver> {
+    let mut attempts = 0;
+    
+    while let Some(delay) = shared.relogin_policy().next_attempt(reason, attempts) {
+        attempts += 1;
+        tokio::time::sleep(delay).await;
+        
+        let result = match shared.transport().reconnect().await {
+            Ok(transport) => perform_log_on(&transport, &shared.credentials)
+                .await
+                .map(|(messages, state)| (transport, messages, state)),
+            Err(error) => Err(error.into()),
+        };
+        
+        match result {
+            Ok((transport, messages, state)) => {
+                *shared.transport.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(transport);
+                *shared.write_state() = state;
+                shared.emit(CmSessionEvent::Relogged {
+                    attempts,
+                });
+                
+                return Some(messages);
+            },
+            Err(error) => {
+                warn!("Failed to log on again (attempt {attempts}): {error}");
+            },
+        }
+    }
+    
+    if attempts > 0 {
+        shared.emit(CmSessionEvent::ReloginFailed {
+            attempts,
+        });
+    }
+    
+    None
+}
+
+/// Sends heartbeats while the session is logged on. Stops once the session has been logged off 
+/// and will not log on again.
+async fn send_heartbeats(shared: Arc<Shared>) {
+    while !shared.ended.load(Ordering::Relaxed) {
+        let heartbeat_seconds = shared.read_state().heartbeat_seconds;
+        
+        tokio::time::sleep(Duration::from_secs(heartbeat_seconds as u64)).await;
+        
+        // read after sleeping, as the session may have been logged off in the meantime
+        if shared.read_state().logged_off.is_some() {
+            continue;
+        }
+        
+        if let Err(error) = shared.transport()
+            .send_message_with_priority(
+                EMsg::ClientHeartBeat,
+                CMsgClientHeartBeat::new(),
+                None,
+                MessagePriority::Urgent,
+            )
+            .await
+        {
+            warn!("Failed to send heartbeat: {error}");
+        }
+    }
+}
@@ -0,0 +1,26 @@
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Error reading config file: {}", .0)]
+    Io(#[from] std::io::Error),
+    #[error("Error parsing config file: {}", .0)]
+    Toml(#[from] toml::de::Error),
+    #[error("{}", .0)]
+    Proxy(#[from] crate::transports::Socks5ProxyConfigError),
+    #[error("Unknown platform: {}", .0)]
+    UnknownPlatform(String),
+    #[error("Invalid value for environment variable {name}: {value}")]
+    InvalidEnvVar {
+        name: String,
+        value: String,
+    },
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::Proxy(error) => error.kind(),
+            _ => crate::ErrorKind::InvalidInput,
+        }
+    }
+}
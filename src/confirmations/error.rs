@@ -0,0 +1,23 @@
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("reqwest error: {}", .0)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Decode error: {}", .0)]
+    Decode(#[from] crate::helpers::DecodeError),
+    #[error("The session is not logged in to steamcommunity.com or the identity secret is wrong")]
+    NeedsAuth,
+    #[error("Confirmation request was unsuccessful: {}", .0.as_deref().unwrap_or("no message"))]
+    Unsuccessful(Option<String>),
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::Reqwest(error) => crate::error::reqwest_kind(error),
+            Self::Decode(error) => error.kind(),
+            Self::NeedsAuth => crate::ErrorKind::Token,
+            Self::Unsuccessful(_) => crate::ErrorKind::Steam,
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//! # Mobile confirmations
+//! 
+//! Lists, accepts, and cancels the trade, market, and other confirmations of a mobile
+//! authenticator. Requests are sent to `steamcommunity.com` using a client holding web cookies
+//! for it, such as one built with
+//! [`LoginSession::build_authenticated_client`](crate::LoginSession::build_authenticated_client).
+//! Each request is signed with a key generated from the account's `identity_secret`.
+//! 
+//! ```no_run
+//! # async fn example(client: reqwest::Client, steamid: steamid_ng::SteamID) -> Result<(), steam_session::confirmations::Error> {
+//! use steam_session::confirmations::{ConfirmationsClient, ConfirmationType};
+//! 
+//! let confirmations = ConfirmationsClient::new(client, steamid, "aWRlbnRpdHkgc2VjcmV0");
+//! let trades = confirmations.get_confirmations().await?
+//!     .into_iter()
+//!     .filter(|confirmation| confirmation.kind == ConfirmationType::Trade)
+//!     .collect::<Vec<_>>();
+//! 
+//! // Accepted in batches rather than one request each
+//! confirmations.accept_many(&trades).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+
+pub use error::Error;
+
+use crate::clock::{Clock, system_clock};
+use crate::guard::{ConfirmationTag, generate_confirmation_key};
+use crate::helpers::create_sha1;
+use crate::secret::Secret;
+use crate::types::DateTime;
+use std::sync::Arc;
+use reqwest::Client;
+use serde::{Deserialize, Deserializer};
+use serde::de;
+use serde_json::Value;
+use steamid_ng::SteamID;
+
+/// The default base URL for `steamcommunity.com`.
+pub const DEFAULT_COMMUNITY_URL: &str = "https://steamcommunity.com";
+/// The most confirmations accepted or cancelled in a single request by `accept_many` and
+/// `cancel_many`. Larger slices are sent in multiple requests.
+pub const MAX_BATCH_SIZE: usize = 50;
+
+/// The type of a confirmation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfirmationType {
+    /// A generic confirmation.
+    Generic,
+    /// A trade offer.
+    Trade,
+    /// A market listing.
+    MarketListing,
+    /// Opting out of a feature, e.g. Steam Guard.
+    FeatureOptOut,
+    /// Changing the phone number.
+    PhoneNumberChange,
+    /// Recovering the account.
+    AccountRecovery,
+    /// Creating a WebAPI key.
+    ApiKey,
+    /// Any other type.
+    Unknown(u32),
+}
+
+impl From<u32> for ConfirmationType {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::Generic,
+            2 => Self::Trade,
+            3 => Self::MarketListing,
+            4 => Self::FeatureOptOut,
+            5 => Self::PhoneNumberChange,
+            6 => Self::AccountRecovery,
+            9 => Self::ApiKey,
+            value => Self::Unknown(value),
+        }
+    }
+}
+
+/// A pending confirmation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Confirmation {
+    /// The ID of the confirmation.
+    #[serde(deserialize_with = "u64_from_number_or_string")]
+    pub id: u64,
+    /// The key sent along with the ID when responding to the confirmation.
+    pub nonce: String,
+    /// The ID of the object the confirmation is for, e.g. the trade offer ID or market listing
+    /// ID.
+    #[serde(deserialize_with = "u64_from_number_or_string")]
+    pub creator_id: u64,
+    /// The type of the confirmation.
+    #[serde(rename = "type", deserialize_with = "confirmation_type")]
+    pub kind: ConfirmationType,
+    /// The name of the type as displayed, e.g. `Trade Offer`.
+    #[serde(default)]
+    pub type_name: String,
+    /// The headline as displayed, e.g. the name of the trade partner.
+    #[serde(default)]
+    pub headline: String,
+    /// The lines of the summary as displayed, e.g. the items being traded.
+    #[serde(default)]
+    pub summary: Vec<String>,
+    /// When the confirmation was created.
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub creation_time: DateTime,
+    /// The URL of the icon.
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+/// A client for the mobile confirmations of an account.
+#[derive(Debug, Clone)]
+pub struct ConfirmationsClient {
+    client: Client,
+    steamid: SteamID,
+    identity_secret: Secret<String>,
+    device_id: String,
+    time_offset: i64,
+    clock: Arc<dyn Clock>,
+    community_url: String,
+}
+
+impl ConfirmationsClient {
+    /// Creates a new [`ConfirmationsClient`]. `client` must hold web cookies for
+    /// `steamcommunity.com` for `steamid`, and `identity_secret` is the base64-encoded identity
+    /// secret of the account's mobile authenticator.
+    pub fn new(
+        client: Client,
+        steamid: SteamID,
+        identity_secret: impl Into<Secret<String>>,
+    ) -> Self {
+        Self {
+            client,
+            steamid,
+            identity_secret: identity_secret.into(),
+            device_id: device_id(steamid),
+            time_offset: 0,
+            clock: system_clock(),
+            community_url: DEFAULT_COMMUNITY_URL.into(),
+        }
+    }
+    
+    /// The device ID of the mobile authenticator. Defaults to the ID derived from the SteamID
+    /// with [`device_id`].
+    pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.device_id = device_id.into();
+        self
+    }
+    
+    /// The difference in seconds between Steam's time and the local time.
+    pub fn with_time_offset(mut self, time_offset: i64) -> Self {
+        self.time_offset = time_offset;
+        self
+    }
+    
+    /// The clock used for the current time.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// The base URL for `steamcommunity.com`.
+    pub fn with_community_url(mut self, community_url: impl Into<String>) -> Self {
+        self.community_url = community_url.into();
+        self
+    }
+    
+    /// Gets the pending confirmations.
+    pub async fn get_confirmations(&self) -> Result<Vec<Confirmation>, Error> {
+        let query = self.signed_params(ConfirmationTag::Conf)?;
+        let response = self.client.get(self.url("mobileconf/getlist"))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ListResponse>()
+            .await?;
+        
+        if response.needauth {
+            return Err(Error::NeedsAuth);
+        }
+        
+        if !response.success {
+            return Err(Error::Unsuccessful(response.message));
+        }
+        
+        Ok(response.conf)
+    }
+    
+    /// Accepts `confirmation`.
+    pub async fn accept(&self, confirmation: &Confirmation) -> Result<(), Error> {
+        self.respond(ConfirmationTag::Allow, std::slice::from_ref(confirmation)).await
+    }
+    
+    /// Cancels `confirmation`.
+    pub async fn cancel(&self, confirmation: &Confirmation) -> Result<(), Error> {
+        self.respond(ConfirmationTag::Cancel, std::slice::from_ref(confirmation)).await
+    }
+    
+    /// Accepts `confirmations`, sending up to [`MAX_BATCH_SIZE`] in each request. Stops at the
+    /// first request that fails, in which case earlier batches will have been accepted.
+    pub async fn accept_many(&self, confirmations: &[Confirmation]) -> Result<(), Error> {
+        self.respond(ConfirmationTag::Allow, confirmations).await
+    }
+    
+    /// Cancels `confirmations`, sending up to [`MAX_BATCH_SIZE`] in each request. Stops at the
+    /// first request that fails, in which case earlier batches will have been cancelled.
+    pub async fn cancel_many(&self, confirmations: &[Confirmation]) -> Result<(), Error> {
+        self.respond(ConfirmationTag::Cancel, confirmations).await
+    }
+    
+    /// Accepts or cancels `confirmations` in batches with `multiajaxop`.
+    async fn respond(
+        &self,
+        tag: ConfirmationTag,
+        confirmations: &[Confirmation],
+    ) -> Result<(), Error> {
+        for batch in confirmations.chunks(MAX_BATCH_SIZE) {
+            let mut form = self.signed_params(tag)?;
+            
+            form.push(("op", tag.to_string()));
+            
+            for confirmation in batch {
+                form.push(("cid[]", confirmation.id.to_string()));
+                form.push(("ck[]", confirmation.nonce.clone()));
+            }
+            
+            let response = self.client.post(self.url("mobileconf/multiajaxop"))
+                .form(&form)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<OperationResponse>()
+                .await?;
+            
+            if response.needauth {
+                return Err(Error::NeedsAuth);
+            }
+            
+            if !response.success {
+                return Err(Error::Unsuccessful(response.message));
+            }
+            
+            debug!("Responded to {} confirmations with {tag}", batch.len());
+        }
+        
+        Ok(())
+    }
+    
+    /// Gets the parameters identifying the device and signing the request with `tag`.
+    fn signed_params(&self, tag: ConfirmationTag) -> Result<Vec<(&'static str, String)>, Error> {
+        let timestamp = self.clock.now().timestamp() + self.time_offset;
+        let key = generate_confirmation_key(self.identity_secret.expose_secret(), timestamp, tag)?;
+        
+        Ok(vec![
+            ("p", self.device_id.clone()),
+            ("a", u64::from(self.steamid).to_string()),
+            ("k", key),
+            ("t", timestamp.to_string()),
+            ("m", "react".into()),
+            ("tag", tag.to_string()),
+        ])
+    }
+    
+    fn url(&self, pathname: &str) -> String {
+        format!("{}/{pathname}", self.community_url.trim_end_matches('/'))
+    }
+}
+
+/// Gets the device ID the Steam mobile app uses for `steamid`.
+pub fn device_id(steamid: SteamID) -> String {
+    let hash = data_encoding::HEXLOWER.encode(&create_sha1(u64::from(steamid).to_string().as_bytes()));
+    
+    format!(
+        "android:{}-{}-{}-{}-{}",
+        &hash[0..8],
+        &hash[8..12],
+        &hash[12..16],
+        &hash[16..20],
+        &hash[20..32],
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct ListResponse {
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    needauth: bool,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    conf: Vec<Confirmation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OperationResponse {
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    needauth: bool,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// IDs are sent as strings, as they can exceed the range of a JavaScript number.
+fn u64_from_number_or_string<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => s.parse::<u64>().map_err(de::Error::custom),
+        Value::Number(num) => num.as_u64().ok_or_else(|| de::Error::custom("invalid number")),
+        _ => Err(de::Error::custom("not a number")),
+    }
+}
+
+fn confirmation_type<'de, D>(deserializer: D) -> Result<ConfirmationType, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = u64_from_number_or_string(deserializer)?;
+    let value = u32::try_from(value).map_err(de::Error::custom)?;
+    
+    Ok(value.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn gets_device_id() {
+        assert_eq!(
+            device_id(SteamID::from(76561197960287930)),
+            "android:6d3f10d9-6369-a1ae-97a0-94df28b95192",
+        );
+    }
+    
+    #[test]
+    fn parses_confirmations() {
+        let response: ListResponse = serde_json::from_str(r#"{
+            "success": true,
+            "needauth": false,
+            "conf": [{
+                "type": 2,
+                "type_name": "Trade Offer",
+                "id": "13807564932",
+                "creator_id": "6530349720",
+                "nonce": "5390628837906184227",
+                "creation_time": 1700000000,
+                "cancel": "Cancel",
+                "accept": "Send Offer",
+                "icon": "https://avatars.steamstatic.com/avatar.jpg",
+                "multi": false,
+                "headline": "Trade partner",
+                "summary": ["You will give up 1 item", "You will receive nothing"],
+                "warn": null
+            }, {
+                "type": 12,
+                "id": "13807564933",
+                "creator_id": "1",
+                "nonce": "1",
+                "creation_time": 1700000000
+            }]
+        }"#).unwrap();
+        let confirmation = &response.conf[0];
+        
+        assert!(response.success);
+        assert_eq!(confirmation.id, 13807564932);
+        assert_eq!(confirmation.creator_id, 6530349720);
+        assert_eq!(confirmation.kind, ConfirmationType::Trade);
+        assert_eq!(confirmation.headline, "Trade partner");
+        assert_eq!(confirmation.summary.len(), 2);
+        assert_eq!(confirmation.creation_time.timestamp(), 1700000000);
+        assert_eq!(response.conf[1].kind, ConfirmationType::Unknown(12));
+    }
+}
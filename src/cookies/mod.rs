@@ -0,0 +1,347 @@
+//! Types for working with Steam web cookies.
+
+mod set;
+
+pub use crate::helpers::{generate_sessionid, generate_sessionid_with};
+pub use set::{CookieSet, WebCookie};
+
+use crate::helpers::JwtPayload;
+use crate::types::DateTime;
+use std::fmt;
+use std::str::FromStr;
+use cookie::Cookie;
+use reqwest::header::COOKIE;
+use url::form_urlencoded;
+
+/// A Steam website that web cookies can be issued for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum CookieDomain {
+    /// `steamcommunity.com`
+    Community,
+    /// `store.steampowered.com`
+    Store,
+    /// `help.steampowered.com`
+    Help,
+    /// `checkout.steampowered.com`
+    Checkout,
+    /// `steam.tv`
+    TV,
+}
+
+impl CookieDomain {
+    /// The domains cookies are issued for by default.
+    pub const DEFAULT: [CookieDomain; 3] = [
+        CookieDomain::Community,
+        CookieDomain::Store,
+        CookieDomain::Help,
+    ];
+
+    /// The domains cookies are needed on to make purchases, including 
+    /// `checkout.steampowered.com`.
+    pub const PURCHASE: [CookieDomain; 4] = [
+        CookieDomain::Community,
+        CookieDomain::Store,
+        CookieDomain::Help,
+        CookieDomain::Checkout,
+    ];
+
+    /// Every known domain.
+    pub const ALL: [CookieDomain; 5] = [
+        CookieDomain::Community,
+        CookieDomain::Store,
+        CookieDomain::Help,
+        CookieDomain::Checkout,
+        CookieDomain::TV,
+    ];
+
+    /// Gets the hostname for this domain.
+    pub fn hostname(&self) -> &'static str {
+        match self {
+            Self::Community => "steamcommunity.com",
+            Self::Store => "store.steampowered.com",
+            Self::Help => "help.steampowered.com",
+            Self::Checkout => "checkout.steampowered.com",
+            Self::TV => "steam.tv",
+        }
+    }
+
+    /// Gets the domain for `hostname`, if it is known.
+    pub fn from_hostname(hostname: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|domain| domain.hostname() == hostname)
+    }
+}
+
+/// Why cookies could not be issued for a domain.
+#[derive(Debug, thiserror::Error)]
+pub enum DomainCookieError {
+    /// Steam did not include the domain in the transfer info returned by `finalizelogin`.
+    #[error("Steam did not return transfer info for the domain")]
+    NoTransferInfo,
+    /// The `settoken` request for the domain could not be sent.
+    #[error("{}", .0)]
+    Reqwest(#[from] reqwest::Error),
+    /// The `settoken` request for the domain was rejected, e.g. when blocked by a CDN.
+    #[error("Request was rejected with status {}", .0)]
+    Status(reqwest::StatusCode),
+    /// The `settoken` request succeeded but no `steamLoginSecure` cookie was set.
+    #[error("No steamLoginSecure cookie was returned")]
+    NoLoginCookie,
+}
+
+impl DomainCookieError {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::NoTransferInfo |
+            Self::Status(_) => crate::ErrorKind::Steam,
+            Self::Reqwest(error) => crate::error::reqwest_kind(error),
+            Self::NoLoginCookie => crate::ErrorKind::Decode,
+        }
+    }
+}
+
+/// The cookies issued for a domain, or why none were.
+#[derive(Debug)]
+pub struct DomainCookies {
+    /// The domain.
+    pub domain: CookieDomain,
+    /// The cookies for the domain, including `sessionid`.
+    pub result: Result<Vec<String>, DomainCookieError>,
+}
+
+/// Web cookies generated for several domains, some of which may have failed. Returned by
+/// [`LoginSession::get_web_cookies_by_domain`](crate::login_session::LoginSession::get_web_cookies_by_domain).
+#[derive(Debug, Default)]
+pub struct WebCookies {
+    /// The result for each domain, in the order they were requested.
+    pub domains: Vec<DomainCookies>,
+    /// The remember-login cookies for `login.steampowered.com`, if they were requested using 
+    /// [`LoginSessionBuilder::remember_login_cookies`](crate::login_session::LoginSessionBuilder::remember_login_cookies).
+    pub remember_login: Vec<String>,
+}
+
+impl WebCookies {
+    /// Gets the cookies of every domain they were issued for.
+    pub fn cookies(&self) -> Vec<String> {
+        self.domains
+            .iter()
+            .filter_map(|domain_cookies| domain_cookies.result.as_ref().ok())
+            .flatten()
+            .chain(&self.remember_login)
+            .cloned()
+            .collect()
+    }
+    
+    /// Gets the domains cookies were issued for.
+    pub fn succeeded(&self) -> Vec<CookieDomain> {
+        self.domains
+            .iter()
+            .filter(|domain_cookies| domain_cookies.result.is_ok())
+            .map(|domain_cookies| domain_cookies.domain)
+            .collect()
+    }
+    
+    /// Gets the domains cookies could not be issued for, with why.
+    pub fn failures(&self) -> impl Iterator<Item = (CookieDomain, &DomainCookieError)> {
+        self.domains
+            .iter()
+            .filter_map(|domain_cookies| {
+                let error = domain_cookies.result.as_ref().err()?;
+                
+                Some((domain_cookies.domain, error))
+            })
+    }
+    
+    /// Whether cookies were issued for every domain.
+    pub fn is_complete(&self) -> bool {
+        self.failures().next().is_none()
+    }
+    
+    /// Gets the cookies of every domain they were issued for as a [`CookieSet`].
+    pub fn cookie_set(&self) -> CookieSet {
+        CookieSet::parse(&self.cookies())
+    }
+}
+
+impl fmt::Display for WebCookies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failures = self.failures()
+            .map(|(domain, error)| format!("{}: {error}", domain.hostname()))
+            .collect::<Vec<_>>();
+        
+        if failures.is_empty() {
+            write!(f, "cookies were issued for every domain")
+        } else {
+            write!(f, "{}", failures.join(", "))
+        }
+    }
+}
+
+/// Formats a cookie for `domain` in the form returned by `get_web_cookies`.
+pub(crate) fn format_cookie(name: &str, value: &str, domain: &str) -> String {
+    format!("{name}={value}; Path=/; Secure; HttpOnly; SameSite=None; Domain={domain}")
+}
+
+/// Gets the time the `steamLoginSecure` cookie in `cookies` expires. The cookie's value embeds 
+/// the access token (as `steamid||token`), so this is the expiration time of that token. If 
+/// cookies for multiple domains are present, the earliest expiration is returned.
+pub fn web_cookies_expiry<S>(cookies: &[S]) -> Option<DateTime>
+where
+    S: AsRef<str>,
+{
+    cookies
+        .iter()
+        .filter_map(|cookie_str| {
+            let cookie = Cookie::parse(cookie_str.as_ref()).ok()?;
+            
+            if cookie.name() != "steamLoginSecure" {
+                return None;
+            }
+            
+            login_secure_expiry(cookie.value())
+        })
+        .min()
+}
+
+/// Gets the time the access token embedded in the value of a `steamLoginSecure` cookie expires.
+fn login_secure_expiry(value: &str) -> Option<DateTime> {
+    let (value, _) = form_urlencoded::parse(value.as_bytes()).next()?;
+    let (_steamid, token) = value.split_once("||")?;
+    let decoded = JwtPayload::from_str(token).ok()?;
+    
+    DateTime::from_timestamp(decoded.exp as i64, 0)
+}
+
+/// Checks whether `cookies` still hold a valid session on `steamcommunity.com`. This requests 
+/// `https://steamcommunity.com/my`, which redirects to the visitor's profile when signed in and to 
+/// the login page otherwise, so it can be used to decide whether a new login is needed.
+pub async fn verify_web_session<S>(
+    client: &reqwest::Client,
+    cookies: &[S],
+) -> Result<bool, reqwest::Error>
+where
+    S: AsRef<str>,
+{
+    let community = CookieDomain::Community.hostname();
+    let cookie_header = cookies
+        .iter()
+        .filter_map(|cookie_str| Cookie::parse(cookie_str.as_ref()).ok())
+        .filter(|cookie| cookie.domain().map_or(true, |domain| domain == community))
+        .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+        .collect::<Vec<_>>()
+        .join("; ");
+    
+    if cookie_header.is_empty() {
+        return Ok(false);
+    }
+    
+    let response = client.get(format!("https://{community}/my"))
+        .header(COOKIE, cookie_header)
+        .send()
+        .await?;
+    let path = response.url().path();
+    let is_profile = path.starts_with("/id/") || path.starts_with("/profiles/");
+    
+    Ok(response.status().is_success() && is_profile)
+}
+
+/// Formats `cookies` in the Netscape cookie-jar (`cookies.txt`) format used by curl, wget, and 
+/// most browser automation tools. Cookies are expected in the form returned by 
+/// `get_web_cookies`. Cookies that cannot be parsed are skipped. Cookies without a `Domain` 
+/// attribute are written for `steamcommunity.com`.
+pub fn to_netscape_cookie_jar<S>(cookies: &[S]) -> String
+where
+    S: AsRef<str>,
+{
+    let mut output = String::from("# Netscape HTTP Cookie File\n");
+    
+    for cookie_str in cookies {
+        let cookie = match Cookie::parse(cookie_str.as_ref()) {
+            Ok(cookie) => cookie,
+            Err(_) => continue,
+        };
+        let (domain, include_subdomains) = match cookie.domain() {
+            Some(domain) => (format!(".{domain}"), "TRUE"),
+            None => (CookieDomain::Community.hostname().to_string(), "FALSE"),
+        };
+        let prefix = if cookie.http_only().unwrap_or(false) {
+            "#HttpOnly_"
+        } else {
+            ""
+        };
+        let secure = if cookie.secure().unwrap_or(false) {
+            "TRUE"
+        } else {
+            "FALSE"
+        };
+        let expires = cookie.expires_datetime()
+            .map(|datetime| datetime.unix_timestamp())
+            .unwrap_or(0);
+        
+        output.push_str(&format!(
+            "{prefix}{domain}\t{include_subdomains}\t{}\t{secure}\t{expires}\t{}\t{}\n",
+            cookie.path().unwrap_or("/"),
+            cookie.name(),
+            cookie.value(),
+        ));
+    }
+    
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn gets_web_cookies_expiry() {
+        let jwt = "eyAidHlwIjogIkpXVCIsICJhbGciOiAiRWREU0EiIH0.eyAiaXNzIjogInN0ZWFtIiwgInN1YiI6ICI3NjUwMDAwMDAwMDAwMDAwMCIsICJhdWQiOiBbICJ3ZWIiLCAicmVuZXciLCAiZGVyaXZlIiBdLCAiZXhwIjogMTcyMjQwMTE4OCwgIm5iZiI6IDE2OTUzNDY1NjAsICJpYXQiOiAxNzAzOTg2NTYwLCAianRpIjogIjBERDVfMjNBQkNFNDBfMjk2OUYiLCAib2F0IjogMTcwMzk4NjU2MCwgInBlciI6IDEsICJpcF9zdWJqZWN0IjogIjEyNy4wLjAuMSIsICJpcF9jb25maXJtZXIiOiAiMTI3LjAuMC4xIiB9.-fsYDOMqkVFveAAbvSCcED5NLpCbacbY6Mq9N1fev56QCh9f6PNaksqASI2dJORZFPLhZj37kK1UwfX53QYVDF";
+        let cookies = [
+            format!("steamLoginSecure=76500000000000000%7C%7C{jwt}; Path=/; Secure; HttpOnly; SameSite=None; Domain=steamcommunity.com"),
+            String::from("sessionid=123"),
+        ];
+        let expiry = web_cookies_expiry(&cookies).unwrap();
+        
+        assert_eq!(expiry.timestamp(), 1722401188);
+    }
+    
+    #[test]
+    fn formats_netscape_cookie_jar() {
+        let cookies = [
+            "steamLoginSecure=abc; Path=/; Secure; HttpOnly; SameSite=None; Domain=steamcommunity.com",
+            "sessionid=123",
+        ];
+        let output = to_netscape_cookie_jar(&cookies);
+        let mut lines = output.lines();
+        
+        assert_eq!(lines.next(), Some("# Netscape HTTP Cookie File"));
+        assert_eq!(lines.next(), Some("#HttpOnly_.steamcommunity.com\tTRUE\t/\tTRUE\t0\tsteamLoginSecure\tabc"));
+        assert_eq!(lines.next(), Some("steamcommunity.com\tFALSE\t/\tFALSE\t0\tsessionid\t123"));
+        assert_eq!(lines.next(), None);
+    }
+    
+    #[test]
+    fn keeps_cookies_of_domains_that_succeeded() {
+        let web_cookies = WebCookies {
+            domains: vec![
+                DomainCookies {
+                    domain: CookieDomain::Community,
+                    result: Ok(vec![String::from("steamLoginSecure=abc; Domain=steamcommunity.com")]),
+                },
+                DomainCookies {
+                    domain: CookieDomain::Store,
+                    result: Err(DomainCookieError::Status(reqwest::StatusCode::FORBIDDEN)),
+                },
+            ],
+            remember_login: Vec::new(),
+        };
+        
+        assert!(!web_cookies.is_complete());
+        assert_eq!(web_cookies.succeeded(), vec![CookieDomain::Community]);
+        assert_eq!(web_cookies.cookies().len(), 1);
+        assert_eq!(web_cookies.to_string(), "store.steampowered.com: Request was rejected with status 403 Forbidden");
+    }
+}
@@ -0,0 +1,239 @@
+use super::{CookieDomain, format_cookie, login_secure_expiry};
+use crate::types::DateTime;
+use std::fmt;
+use cookie::Cookie;
+
+/// A web cookie for a Steam website. The value is redacted from `Debug` output.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct WebCookie {
+    /// The name of the cookie.
+    pub name: String,
+    /// The value of the cookie.
+    pub value: String,
+    /// The hostname the cookie is for.
+    pub domain: String,
+    /// When the cookie expires, if known. For `steamLoginSecure`, this is when the access token
+    /// it holds expires.
+    pub expires_at: Option<DateTime>,
+}
+
+impl WebCookie {
+    /// Parses a cookie in the form returned by `get_web_cookies`. Cookies without a `Domain`
+    /// attribute are for `steamcommunity.com`.
+    pub fn parse(cookie_str: &str) -> Option<Self> {
+        let cookie = Cookie::parse(cookie_str).ok()?;
+        let domain = cookie.domain()
+            .unwrap_or(CookieDomain::Community.hostname())
+            .to_owned();
+        let expires_at = if cookie.name() == "steamLoginSecure" {
+            login_secure_expiry(cookie.value())
+        } else {
+            cookie.expires_datetime()
+                .and_then(|datetime| DateTime::from_timestamp(datetime.unix_timestamp(), 0))
+        };
+        
+        Some(Self {
+            name: cookie.name().to_owned(),
+            value: cookie.value().to_owned(),
+            domain,
+            expires_at,
+        })
+    }
+    
+    /// Checks whether the cookie has expired at `now`. Cookies without a known expiry never do.
+    pub fn is_expired_at(&self, now: DateTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+impl fmt::Debug for WebCookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebCookie")
+            .field("name", &self.name)
+            .field("value", &"[REDACTED]")
+            .field("domain", &self.domain)
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// Formats the cookie in the form returned by `get_web_cookies`.
+impl fmt::Display for WebCookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_cookie(&self.name, &self.value, &self.domain))
+    }
+}
+
+/// A set of web cookies for the Steam websites, holding at most one cookie of each name per
+/// domain.
+///
+/// # Examples
+/// ```
+/// use steam_session::cookies::{CookieDomain, CookieSet};
+///
+/// let cookies = CookieSet::parse(&[
+///     "sessionid=abc; Path=/; Secure; HttpOnly; SameSite=None; Domain=steamcommunity.com",
+///     "sessionid=def; Path=/; Secure; HttpOnly; SameSite=None; Domain=store.steampowered.com",
+/// ]);
+///
+/// assert_eq!(cookies.get(CookieDomain::Store, "sessionid").unwrap().value, "def");
+/// assert_eq!(cookies.header_value(CookieDomain::Community), "sessionid=abc");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(transparent))]
+pub struct CookieSet {
+    cookies: Vec<WebCookie>,
+}
+
+impl CookieSet {
+    /// Creates an empty [`CookieSet`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Parses `cookies` in the form returned by `get_web_cookies`. Cookies that cannot be parsed
+    /// are skipped.
+    pub fn parse<S>(cookies: &[S]) -> Self
+    where
+        S: AsRef<str>,
+    {
+        cookies
+            .iter()
+            .filter_map(|cookie_str| WebCookie::parse(cookie_str.as_ref()))
+            .collect()
+    }
+    
+    /// Adds `cookie`, returning the cookie of the same name and domain it replaced.
+    pub fn insert(&mut self, cookie: WebCookie) -> Option<WebCookie> {
+        match self.cookies
+            .iter_mut()
+            .find(|existing| existing.name == cookie.name && existing.domain == cookie.domain)
+        {
+            Some(existing) => Some(std::mem::replace(existing, cookie)),
+            None => {
+                self.cookies.push(cookie);
+                None
+            },
+        }
+    }
+    
+    /// Gets the cookie named `name` for `domain`.
+    pub fn get(&self, domain: CookieDomain, name: &str) -> Option<&WebCookie> {
+        self.get_by_hostname(domain.hostname(), name)
+    }
+    
+    /// Gets the cookie named `name` for `hostname`, e.g. `login.steampowered.com`.
+    pub fn get_by_hostname(&self, hostname: &str, name: &str) -> Option<&WebCookie> {
+        self.cookies
+            .iter()
+            .find(|cookie| cookie.domain == hostname && cookie.name == name)
+    }
+    
+    /// Gets the cookies for `domain`.
+    pub fn for_domain(&self, domain: CookieDomain) -> impl Iterator<Item = &WebCookie> {
+        self.cookies
+            .iter()
+            .filter(move |cookie| cookie.domain == domain.hostname())
+    }
+    
+    /// Gets the value of the `Cookie` header to send with requests to `domain`, e.g.
+    /// `"steamLoginSecure=...; sessionid=..."`.
+    pub fn header_value(&self, domain: CookieDomain) -> String {
+        self.for_domain(domain)
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+    
+    /// Gets the time the first cookie with a known expiry expires.
+    pub fn expires_at(&self) -> Option<DateTime> {
+        self.cookies
+            .iter()
+            .filter_map(|cookie| cookie.expires_at)
+            .min()
+    }
+    
+    /// Gets the cookies in the form returned by `get_web_cookies`.
+    pub fn to_strings(&self) -> Vec<String> {
+        self.cookies
+            .iter()
+            .map(WebCookie::to_string)
+            .collect()
+    }
+    
+    /// Iterates over the cookies.
+    pub fn iter(&self) -> std::slice::Iter<'_, WebCookie> {
+        self.cookies.iter()
+    }
+    
+    /// Gets the number of cookies.
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+    
+    /// Checks whether there are no cookies.
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+impl FromIterator<WebCookie> for CookieSet {
+    fn from_iter<I: IntoIterator<Item = WebCookie>>(iter: I) -> Self {
+        let mut cookies = Self::new();
+        
+        for cookie in iter {
+            cookies.insert(cookie);
+        }
+        
+        cookies
+    }
+}
+
+impl IntoIterator for CookieSet {
+    type Item = WebCookie;
+    type IntoIter = std::vec::IntoIter<WebCookie>;
+    
+    fn into_iter(self) -> Self::IntoIter {
+        self.cookies.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CookieSet {
+    type Item = &'a WebCookie;
+    type IntoIter = std::slice::Iter<'a, WebCookie>;
+    
+    fn into_iter(self) -> Self::IntoIter {
+        self.cookies.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn replaces_cookies_of_the_same_name_and_domain() {
+        let mut cookies = CookieSet::parse(&[
+            "sessionid=abc; Path=/; Secure; HttpOnly; SameSite=None; Domain=steamcommunity.com",
+            "sessionid=def; Path=/; Secure; HttpOnly; SameSite=None; Domain=store.steampowered.com",
+            "not a cookie",
+        ]);
+        let replaced = cookies.insert(WebCookie {
+            name: "sessionid".into(),
+            value: "ghi".into(),
+            domain: "steamcommunity.com".into(),
+            expires_at: None,
+        });
+        
+        assert_eq!(replaced.unwrap().value, "abc");
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies.get(CookieDomain::Community, "sessionid").unwrap().value, "ghi");
+        assert!(cookies.get(CookieDomain::Help, "sessionid").is_none());
+        assert_eq!(
+            cookies.to_strings()[0],
+            "sessionid=ghi; Path=/; Secure; HttpOnly; SameSite=None; Domain=steamcommunity.com",
+        );
+    }
+}
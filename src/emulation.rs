@@ -0,0 +1,163 @@
+//! # Client emulation
+//! 
+//! A [`ClientPreset`] sets the `User-Agent`, `Origin`, and `Accept-Language` headers sent with
+//! HTTP and websocket requests so that they match those of an official client. By default the
+//! preset matching the session's platform type is used, so a
+//! [`EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp`] session looks like the mobile
+//! app and a [`EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient`] session looks like
+//! the Steam client.
+//! 
+//! ```
+//! use steam_session::emulation::ClientPreset;
+//! use steam_session::enums::{EAuthTokenPlatformType, ELanguage};
+//! 
+//! let preset = ClientPreset::for_platform(EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser);
+//! let headers = preset.http_headers(ELanguage::English);
+//! 
+//! assert_eq!(headers["origin"], "https://steamcommunity.com");
+//! ```
+
+use crate::enums::{EAuthTokenPlatformType, ELanguage};
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT, ORIGIN, ACCEPT_LANGUAGE, COOKIE};
+
+/// The user agent of the Steam client's embedded browser.
+pub const STEAM_CLIENT_BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows; U; Windows NT 10.0; en-US; Valve Steam Client/default/1665786434; ) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/85.0.4183.121 Safari/537.36";
+/// The user agent of the Steam mobile app's HTTP client.
+pub const MOBILE_APP_USER_AGENT: &str = "okhttp/3.12.12";
+/// The version of the Steam mobile app sent in its `mobileClientVersion` cookie.
+pub const MOBILE_CLIENT_VERSION: &str = "777777 3.0.0";
+/// The user agent of a mainstream desktop browser.
+pub const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+/// Which client requests are made to look like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientPreset {
+    /// The Steam desktop client.
+    SteamClient,
+    /// The Steam mobile app.
+    MobileApp,
+    /// A desktop browser on `steamcommunity.com`.
+    Browser,
+}
+
+impl ClientPreset {
+    /// Gets the preset matching `platform_type`. Unknown platform types look like a browser.
+    pub fn for_platform(platform_type: EAuthTokenPlatformType) -> Self {
+        match platform_type {
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient => Self::SteamClient,
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp => Self::MobileApp,
+            _ => Self::Browser,
+        }
+    }
+    
+    /// Gets the user agent sent with HTTP requests.
+    pub fn user_agent(&self) -> &'static str {
+        match self {
+            Self::SteamClient => STEAM_CLIENT_BROWSER_USER_AGENT,
+            Self::MobileApp => MOBILE_APP_USER_AGENT,
+            Self::Browser => BROWSER_USER_AGENT,
+        }
+    }
+    
+    /// Gets the user agent sent with the websocket upgrade request.
+    pub fn websocket_user_agent(&self) -> &'static str {
+        match self {
+            // the client connects to CMs itself rather than from its embedded browser
+            Self::SteamClient => crate::transports::websocket::STEAM_CLIENT_USER_AGENT,
+            Self::MobileApp => MOBILE_APP_USER_AGENT,
+            Self::Browser => BROWSER_USER_AGENT,
+        }
+    }
+    
+    /// Gets the `Origin` requests are sent from, if the client sends one.
+    pub fn origin(&self) -> Option<&'static str> {
+        match self {
+            Self::SteamClient => Some("https://steamloopback.host"),
+            Self::MobileApp => None,
+            Self::Browser => Some("https://steamcommunity.com"),
+        }
+    }
+    
+    /// Gets the cookies the client sends with every request, such as the `mobileClient` and 
+    /// `mobileClientVersion` cookies of the mobile app, which the mobile confirmations endpoints 
+    /// expect.
+    pub fn cookies(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::MobileApp => &[
+                ("mobileClient", "android"),
+                ("mobileClientVersion", MOBILE_CLIENT_VERSION),
+            ],
+            Self::SteamClient |
+            Self::Browser => &[],
+        }
+    }
+    
+    /// Gets the headers sent with WebAPI requests, which are the HTTP headers along with a 
+    /// `Cookie` header holding the client's `cookies`. These should not be set as the default 
+    /// headers of a client with a cookie store.
+    pub fn api_headers(&self, language: ELanguage) -> HeaderMap {
+        let mut headers = self.http_headers(language);
+        let cookie = self.cookies()
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            if !cookie.is_empty() {
+                headers.insert(COOKIE, cookie);
+            }
+        }
+        
+        headers
+    }
+    
+    /// Gets the headers sent by default with HTTP requests, with `Accept-Language` set for
+    /// `language`.
+    pub fn http_headers(&self, language: ELanguage) -> HeaderMap {
+        self.headers(self.user_agent(), language)
+    }
+    
+    /// Gets the headers sent with the websocket upgrade request, with `Accept-Language` set for
+    /// `language`.
+    pub fn websocket_headers(&self, language: ELanguage) -> HeaderMap {
+        self.headers(self.websocket_user_agent(), language)
+    }
+    
+    fn headers(&self, user_agent: &'static str, language: ELanguage) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        
+        headers.insert(USER_AGENT, HeaderValue::from_static(user_agent));
+        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static(language.language_tag()));
+        
+        if let Some(origin) = self.origin() {
+            headers.insert(ORIGIN, HeaderValue::from_static(origin));
+        }
+        
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn matches_platform_type() {
+        let preset = ClientPreset::for_platform(EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp);
+        let headers = preset.http_headers(ELanguage::German);
+        
+        assert_eq!(preset, ClientPreset::MobileApp);
+        assert_eq!(headers[USER_AGENT], MOBILE_APP_USER_AGENT);
+        assert_eq!(headers[ACCEPT_LANGUAGE], ELanguage::German.language_tag());
+        assert!(!headers.contains_key(ORIGIN));
+    }
+    
+    #[test]
+    fn sends_mobile_cookies_with_api_requests() {
+        let headers = ClientPreset::MobileApp.api_headers(ELanguage::English);
+        
+        assert_eq!(headers[COOKIE], "mobileClient=android; mobileClientVersion=777777 3.0.0");
+        assert!(!ClientPreset::Browser.api_headers(ELanguage::English).contains_key(COOKIE));
+    }
+}
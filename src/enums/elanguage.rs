@@ -0,0 +1,107 @@
+/// A language supported by Steam.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ELanguage {
+    #[default]
+    English = 0,
+    German = 1,
+    French = 2,
+    Italian = 3,
+    Korean = 4,
+    Spanish = 5,
+    SimplifiedChinese = 6,
+    TraditionalChinese = 7,
+    Russian = 8,
+    Thai = 9,
+    Japanese = 10,
+    Portuguese = 11,
+    Polish = 12,
+    Danish = 13,
+    Dutch = 14,
+    Finnish = 15,
+    Norwegian = 16,
+    Swedish = 17,
+    Hungarian = 18,
+    Czech = 19,
+    Romanian = 20,
+    Turkish = 21,
+    Brazilian = 22,
+    Bulgarian = 23,
+    Greek = 24,
+    Ukrainian = 25,
+    LatinAmericanSpanish = 26,
+    Vietnamese = 27,
+    Indonesian = 28,
+}
+
+impl ELanguage {
+    /// Gets the name Steam uses for this language in client and web requests, e.g. `english`.
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            Self::English => "english",
+            Self::German => "german",
+            Self::French => "french",
+            Self::Italian => "italian",
+            Self::Korean => "koreana",
+            Self::Spanish => "spanish",
+            Self::SimplifiedChinese => "schinese",
+            Self::TraditionalChinese => "tchinese",
+            Self::Russian => "russian",
+            Self::Thai => "thai",
+            Self::Japanese => "japanese",
+            Self::Portuguese => "portuguese",
+            Self::Polish => "polish",
+            Self::Danish => "danish",
+            Self::Dutch => "dutch",
+            Self::Finnish => "finnish",
+            Self::Norwegian => "norwegian",
+            Self::Swedish => "swedish",
+            Self::Hungarian => "hungarian",
+            Self::Czech => "czech",
+            Self::Romanian => "romanian",
+            Self::Turkish => "turkish",
+            Self::Brazilian => "brazilian",
+            Self::Bulgarian => "bulgarian",
+            Self::Greek => "greek",
+            Self::Ukrainian => "ukrainian",
+            Self::LatinAmericanSpanish => "latam",
+            Self::Vietnamese => "vietnamese",
+            Self::Indonesian => "indonesian",
+        }
+    }
+    
+    /// Gets the IETF language tag for this language, for use in `Accept-Language` headers.
+    pub fn language_tag(&self) -> &'static str {
+        match self {
+            Self::English => "en-US",
+            Self::German => "de-DE",
+            Self::French => "fr-FR",
+            Self::Italian => "it-IT",
+            Self::Korean => "ko-KR",
+            Self::Spanish => "es-ES",
+            Self::SimplifiedChinese => "zh-CN",
+            Self::TraditionalChinese => "zh-TW",
+            Self::Russian => "ru-RU",
+            Self::Thai => "th-TH",
+            Self::Japanese => "ja-JP",
+            Self::Portuguese => "pt-PT",
+            Self::Polish => "pl-PL",
+            Self::Danish => "da-DK",
+            Self::Dutch => "nl-NL",
+            Self::Finnish => "fi-FI",
+            Self::Norwegian => "no-NO",
+            Self::Swedish => "sv-SE",
+            Self::Hungarian => "hu-HU",
+            Self::Czech => "cs-CZ",
+            Self::Romanian => "ro-RO",
+            Self::Turkish => "tr-TR",
+            Self::Brazilian => "pt-BR",
+            Self::Bulgarian => "bg-BG",
+            Self::Greek => "el-GR",
+            Self::Ukrainian => "uk-UA",
+            Self::LatinAmericanSpanish => "es-419",
+            Self::Vietnamese => "vi-VN",
+            Self::Indonesian => "id-ID",
+        }
+    }
+}
@@ -1,10 +1,17 @@
 mod eos_type;
 mod eresult;
 mod emsg;
+mod elanguage;
 
 pub use eos_type::EOSType;
 pub use emsg::EMsg;
 pub use eresult::EResult;
+pub use elanguage::ELanguage;
+
+/// Serde helpers for [`EAuthTokenPlatformType`], which is generated from protobufs. Use with 
+/// `#[serde(with = "steam_session::enums::platform_type_serde")]`.
+#[cfg(feature = "serialize")]
+pub use crate::serializers::platform_type as platform_type_serde;
 
 pub use crate::proto::enums::ESessionPersistence;
 pub use crate::proto::steammessages_auth_steamclient::{
@@ -12,4 +19,5 @@ pub use crate::proto::steammessages_auth_steamclient::{
     EAuthSessionGuardType,
     EAuthSessionSecurityHistory,
     ETokenRenewalType,
+    EAuthTokenRevokeAction,
 };
\ No newline at end of file
@@ -0,0 +1,65 @@
+//! # Error kinds
+//! 
+//! Each module has its own error type, and errors from lower layers are kept as the source of 
+//! the errors wrapping them. Every error type has a `kind` method returning an [`ErrorKind`] for 
+//! handling errors programmatically without matching on each variant.
+//! 
+//! ```no_run
+//! # async fn example() -> Result<(), steam_session::LoginSessionError> {
+//! use steam_session::ErrorKind;
+//! use steam_session::login_session::connect_webapi;
+//! 
+//! match connect_webapi().await {
+//!     Ok(session) => {},
+//!     Err(error) if error.kind() == ErrorKind::Timeout => {
+//!         // try again
+//!     },
+//!     Err(error) => return Err(error),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+/// A broad category of error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The proxy is misconfigured or could not be connected through.
+    Proxy,
+    /// A network error, including the connection being closed.
+    Connection,
+    /// A request or connection timed out.
+    Timeout,
+    /// Steam responded with a result other than OK.
+    Steam,
+    /// A token is malformed, of the wrong kind, or issued for a different account or platform.
+    Token,
+    /// A response could not be decoded.
+    Decode,
+    /// A method was called when the session was not in a state to handle it.
+    State,
+    /// The configuration or input is invalid.
+    InvalidInput,
+}
+
+/// Gets the kind of a `reqwest` error.
+pub(crate) fn reqwest_kind(error: &reqwest::Error) -> ErrorKind {
+    if error.is_timeout() {
+        ErrorKind::Timeout
+    } else if error.is_decode() {
+        ErrorKind::Decode
+    } else if error.is_builder() {
+        ErrorKind::InvalidInput
+    } else {
+        ErrorKind::Connection
+    }
+}
+
+/// Gets the kind of an IO error.
+pub(crate) fn io_kind(error: &std::io::Error) -> ErrorKind {
+    if error.kind() == std::io::ErrorKind::TimedOut {
+        ErrorKind::Timeout
+    } else {
+        ErrorKind::Connection
+    }
+}
@@ -0,0 +1,68 @@
+use super::{GuardCodeProvider, GuardContext};
+use crate::enums::EAuthSessionGuardType;
+use async_trait::async_trait;
+use tokio::sync::{mpsc, oneshot};
+
+/// A request for a code, received from a [`ChannelGuardCodeProvider`].
+#[derive(Debug)]
+pub struct GuardCodeRequest {
+    /// The kind of code needed.
+    pub guard: EAuthSessionGuardType,
+    /// Details of the login.
+    pub context: GuardContext,
+    reply: oneshot::Sender<Option<String>>,
+}
+
+impl GuardCodeRequest {
+    /// Responds with `code`. Dropping the request without responding provides no code.
+    pub fn respond(self, code: Option<String>) {
+        // an error means the login is no longer waiting
+        let _ = self.reply.send(code);
+    }
+}
+
+/// Sends requests for codes over a channel, for GUIs and other programs that get codes from 
+/// somewhere other than the login's task.
+/// 
+/// ```no_run
+/// # async fn example() {
+/// use steam_session::guard::ChannelGuardCodeProvider;
+/// 
+/// let (provider, mut requests) = ChannelGuardCodeProvider::new(1);
+/// 
+/// tokio::spawn(async move {
+///     while let Some(request) = requests.recv().await {
+///         // ask the user for a code
+///         request.respond(Some("ABC12".into()));
+///     }
+/// });
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChannelGuardCodeProvider {
+    requests: mpsc::Sender<GuardCodeRequest>,
+}
+
+impl ChannelGuardCodeProvider {
+    /// Creates a new [`ChannelGuardCodeProvider`] and the receiver its requests are sent to.
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<GuardCodeRequest>) {
+        let (requests, rx) = mpsc::channel(capacity);
+        
+        (Self { requests }, rx)
+    }
+}
+
+#[async_trait]
+impl GuardCodeProvider for ChannelGuardCodeProvider {
+    async fn provide(&self, guard: EAuthSessionGuardType, ctx: GuardContext) -> Option<String> {
+        let (reply, rx) = oneshot::channel();
+        
+        self.requests.send(GuardCodeRequest {
+            guard,
+            context: ctx,
+            reply,
+        }).await.ok()?;
+        
+        rx.await.ok().flatten()
+    }
+}
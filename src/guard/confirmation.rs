@@ -0,0 +1,78 @@
+use crate::helpers::{DecodeError, decode_base64};
+use std::fmt;
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// The tag a confirmation key is generated for. Each request to the mobile confirmations
+/// endpoints needs a key generated with the tag matching the operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(rename_all = "lowercase"))]
+pub enum ConfirmationTag {
+    /// `conf`, for listing confirmations.
+    Conf,
+    /// `details`, for getting the details of a confirmation.
+    Details,
+    /// `allow`, for accepting confirmations.
+    Allow,
+    /// `cancel`, for cancelling confirmations.
+    Cancel,
+}
+
+impl ConfirmationTag {
+    /// Gets the value used in the key and sent in requests.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Conf => "conf",
+            Self::Details => "details",
+            Self::Allow => "allow",
+            Self::Cancel => "cancel",
+        }
+    }
+}
+
+impl fmt::Display for ConfirmationTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Generates the confirmation key for `identity_secret` and `tag` at `timestamp`, in seconds
+/// since the Unix epoch. The key is sent as `k` along with the same `timestamp` as `t` and
+/// `tag` as `tag`.
+pub fn generate_confirmation_key(
+    identity_secret: &str,
+    timestamp: i64,
+    tag: ConfirmationTag,
+) -> Result<String, DecodeError> {
+    let key = decode_base64(identity_secret.trim_end_matches('='))?;
+    let mut mac = HmacSha1::new_from_slice(&key)?;
+    
+    mac.update(&(timestamp as u64).to_be_bytes());
+    mac.update(tag.as_str().as_bytes());
+    
+    Ok(general_purpose::STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn generates_confirmation_keys() {
+        let identity_secret = "zvIayp3JPvtvX/QGHqsqKBk/44s=";
+        let expected = [
+            (ConfirmationTag::Conf, "LBtgFsHteCGGKobchsBbVVoPJZQ="),
+            (ConfirmationTag::Details, "LG8g1hNgE+YU3ZY6DKqn4SlllbA="),
+            (ConfirmationTag::Allow, "DSMRgAzyenNec2MkJThlZGdReiA="),
+            (ConfirmationTag::Cancel, "Z9Px/iRPMQbkQDlQ1VH7eqnUUI4="),
+        ];
+        
+        for (tag, key) in expected {
+            assert_eq!(generate_confirmation_key(identity_secret, 1_700_000_000, tag).unwrap(), key);
+        }
+    }
+}
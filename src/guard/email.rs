@@ -0,0 +1,140 @@
+use super::{GuardCodeProvider, GuardContext};
+use crate::enums::EAuthSessionGuardType;
+use crate::types::DateTime;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use async_trait::async_trait;
+
+type FetchFuture = Pin<Box<dyn Future<Output = Option<String>> + Send>>;
+type FetchFn = dyn Fn(EmailCodeRequest) -> FetchFuture + Send + Sync;
+
+/// A request to fetch an emailed Steam Guard code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailCodeRequest {
+    /// The account name, if the login was started with credentials.
+    pub account_name: Option<String>,
+    /// The domain of the address the code was sent to, e.g. `gmail.com`. Steam does not reveal
+    /// the rest of the address.
+    pub domain: Option<String>,
+    /// When the code must be provided by, the same for every attempt. The fetch is abandoned at 
+    /// this time.
+    pub deadline: DateTime,
+    /// The attempt, starting at 1. Later attempts mean the previously fetched code was rejected,
+    /// so the fetcher should wait for a newer email.
+    pub attempt: u32,
+}
+
+/// Fetches emailed Steam Guard codes with an async callback, e.g. one that polls a mailbox over
+/// IMAP, for unattended logins to accounts with email-based Steam Guard.
+///
+/// Set with [`LoginSessionBuilder::email_code_fetcher`](crate::LoginSessionBuilder::email_code_fetcher),
+/// or use as a [`GuardCodeProvider`].
+///
+/// ```no_run
+/// use steam_session::guard::EmailCodeFetcher;
+///
+/// let fetcher = EmailCodeFetcher::new(|request| async move {
+///     // search the mailbox for a Steam Guard email received for `request.account_name`
+///     Some(String::from("ABC12"))
+/// });
+/// ```
+#[derive(Clone)]
+pub struct EmailCodeFetcher {
+    fetch: Arc<FetchFn>,
+}
+
+impl fmt::Debug for EmailCodeFetcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmailCodeFetcher").finish_non_exhaustive()
+    }
+}
+
+impl EmailCodeFetcher {
+    /// Creates a new [`EmailCodeFetcher`] from `fetch`, which returns the code or `None` if it
+    /// could not be found.
+    pub fn new<F, Fut>(fetch: F) -> Self
+    where
+        F: Fn(EmailCodeRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + Send + 'static,
+    {
+        Self {
+            fetch: Arc::new(move |request| -> FetchFuture { Box::pin(fetch(request)) }),
+        }
+    }
+}
+
+#[async_trait]
+impl GuardCodeProvider for EmailCodeFetcher {
+    async fn provide(&self, guard: EAuthSessionGuardType, ctx: GuardContext) -> Option<String> {
+        if guard != EAuthSessionGuardType::k_EAuthSessionGuardType_EmailCode {
+            return None;
+        }
+        
+        let timeout = ctx.remaining;
+        let request = EmailCodeRequest {
+            account_name: ctx.account_name,
+            domain: ctx.detail,
+            deadline: ctx.expires_at,
+            attempt: ctx.attempt,
+        };
+        
+        match tokio::time::timeout(timeout, (self.fetch)(request)).await {
+            Ok(code) => code,
+            Err(_) => {
+                debug!("Fetching the Steam Guard email timed out");
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    
+    fn context(remaining: Duration) -> GuardContext {
+        GuardContext {
+            account_name: Some(String::from("account")),
+            steamid: None,
+            detail: Some(String::from("gmail.com")),
+            attempt: 1,
+            // The session's clock may be far from the system clock
+            expires_at: DateTime::default(),
+            remaining,
+        }
+    }
+    
+    fn delayed_fetcher(delay: Duration) -> EmailCodeFetcher {
+        EmailCodeFetcher::new(move |_request| async move {
+            tokio::time::sleep(delay).await;
+            Some(String::from("ABC12"))
+        })
+    }
+    
+    #[tokio::test]
+    async fn waits_for_the_remaining_time() {
+        let code = delayed_fetcher(Duration::from_millis(10))
+            .provide(
+                EAuthSessionGuardType::k_EAuthSessionGuardType_EmailCode,
+                context(Duration::from_millis(200)),
+            )
+            .await;
+        
+        assert_eq!(code.as_deref(), Some("ABC12"));
+    }
+    
+    #[tokio::test]
+    async fn abandons_the_fetch_when_the_login_times_out() {
+        let code = delayed_fetcher(Duration::from_secs(10))
+            .provide(
+                EAuthSessionGuardType::k_EAuthSessionGuardType_EmailCode,
+                context(Duration::from_millis(200)),
+            )
+            .await;
+        
+        assert_eq!(code, None);
+    }
+}
@@ -0,0 +1,76 @@
+//! # Steam Guard codes
+//! 
+//! When a login requires a Steam Guard code, a [`LoginSession`](crate::LoginSession) built with a
+//! [`GuardCodeProvider`] asks it for one and submits it, so the login completes without calling
+//! `submit_steam_guard_code` manually. Codes that Steam rejects are asked for again, up to
+//! [`MAX_ATTEMPTS`] times.
+//! 
+//! [`generate_auth_code`] and [`generate_confirmation_key`] can also be used on their own, e.g. by
+//! trade bots that accept mobile confirmations.
+//! 
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use steam_session::login_session::LoginSession;
+//! use steam_session::transports::web_api::WebApiTransport;
+//! use steam_session::guard::SharedSecretGuardCodeProvider;
+//! use steam_session::enums::EAuthTokenPlatformType;
+//! use std::sync::Arc;
+//! 
+//! let provider = SharedSecretGuardCodeProvider::new("c2hhcmVkIHNlY3JldA==");
+//! let session = LoginSession::builder(WebApiTransport::new(), EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp)
+//!     .guard_code_provider(Arc::new(provider))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod totp;
+mod confirmation;
+mod prompt;
+mod channel;
+mod email;
+
+pub use totp::{SharedSecretGuardCodeProvider, generate_auth_code};
+pub use confirmation::{ConfirmationTag, generate_confirmation_key};
+pub use prompt::StdinGuardCodeProvider;
+pub use channel::{ChannelGuardCodeProvider, GuardCodeRequest};
+pub use email::{EmailCodeFetcher, EmailCodeRequest};
+
+use crate::enums::EAuthSessionGuardType;
+use crate::types::DateTime;
+use std::fmt;
+use std::time::Duration;
+use async_trait::async_trait;
+use steamid_ng::SteamID;
+
+/// The number of times a code is asked for before the login fails.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Provides Steam Guard codes for logins.
+#[async_trait]
+pub trait GuardCodeProvider: fmt::Debug + Send + Sync {
+    /// Provides a code for `guard`, which is either
+    /// [`EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceCode`] or
+    /// [`EAuthSessionGuardType::k_EAuthSessionGuardType_EmailCode`]. Returning `None` leaves the
+    /// code to be submitted with `submit_steam_guard_code`.
+    async fn provide(&self, guard: EAuthSessionGuardType, ctx: GuardContext) -> Option<String>;
+}
+
+/// Details of the login a code is being asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardContext {
+    /// The account name, if the login was started with credentials.
+    pub account_name: Option<String>,
+    /// The SteamID of the account, if known.
+    pub steamid: Option<SteamID>,
+    /// For email codes, the domain of the address the code was sent to, e.g. `gmail.com`.
+    pub detail: Option<String>,
+    /// The attempt, starting at 1. Later attempts mean the previous code was rejected.
+    pub attempt: u32,
+    /// When the code must be provided by. This is the login timeout after the code was first 
+    /// asked for, and stays the same for later attempts.
+    pub expires_at: DateTime,
+    /// How long until `expires_at`, measured with the session's clock when the code was asked 
+    /// for.
+    pub remaining: Duration,
+}
@@ -0,0 +1,53 @@
+use super::{GuardCodeProvider, GuardContext};
+use crate::enums::EAuthSessionGuardType;
+use std::io::{self, BufRead, Write};
+use async_trait::async_trait;
+
+/// Asks for codes by prompting on stderr and reading a line from stdin. An empty line provides
+/// no code.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdinGuardCodeProvider;
+
+impl StdinGuardCodeProvider {
+    /// Creates a new [`StdinGuardCodeProvider`].
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl GuardCodeProvider for StdinGuardCodeProvider {
+    async fn provide(&self, guard: EAuthSessionGuardType, ctx: GuardContext) -> Option<String> {
+        let mut label = match (guard, &ctx.detail) {
+            (EAuthSessionGuardType::k_EAuthSessionGuardType_EmailCode, Some(domain)) => {
+                format!("Steam Guard code sent to your email at {domain}")
+            },
+            (EAuthSessionGuardType::k_EAuthSessionGuardType_EmailCode, None) => {
+                String::from("Steam Guard code sent to your email")
+            },
+            _ => String::from("Steam Guard code from your mobile authenticator"),
+        };
+        
+        if ctx.attempt > 1 {
+            label = format!("That code was incorrect. {label}");
+        }
+        
+        // reading stdin blocks
+        let line = tokio::task::spawn_blocking(move || -> io::Result<String> {
+            let mut line = String::new();
+            
+            eprint!("{label}: ");
+            io::stderr().flush()?;
+            io::stdin().lock().read_line(&mut line)?;
+            
+            Ok(line)
+        }).await.ok()?.ok()?;
+        let code = line.trim();
+        
+        if code.is_empty() {
+            return None;
+        }
+        
+        Some(code.to_string())
+    }
+}
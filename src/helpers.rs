@@ -9,6 +9,7 @@ use serde_json::Value;
 use sha1::{Digest, Sha1};
 use sha2::Sha256;
 use steamid_ng::SteamID;
+use crate::enums::EAuthTokenPlatformType;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -38,6 +39,47 @@ pub enum DecodeError {
     HMACInvalidKeyLength(#[from] hmac::digest::InvalidLength),
 }
 
+impl DecodeError {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::InvalidJWT => crate::ErrorKind::Token,
+            _ => crate::ErrorKind::Decode,
+        }
+    }
+}
+
+/// The kind of a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// An access token.
+    AccessToken,
+    /// A refresh token.
+    RefreshToken,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AccessToken => write!(f, "access token"),
+            Self::RefreshToken => write!(f, "refresh token"),
+        }
+    }
+}
+
+/// Represents an error when a token was issued for a different platform than the one it is being
+/// used with.
+#[derive(Debug, thiserror::Error)]
+#[error("{kind} was issued for {} but session is {}", platform_type_name(.issued), platform_type_name(.expected))]
+pub struct PlatformMismatchError {
+    /// The kind of token.
+    pub kind: TokenKind,
+    /// The platform type the token was issued for.
+    pub issued: EAuthTokenPlatformType,
+    /// The platform type the token was expected to be issued for.
+    pub expected: EAuthTokenPlatformType,
+}
+
 #[derive(Debug, Deserialize)]
 /// Represents a JSON Web Token (JWT) payload.
 pub struct JwtPayload {
@@ -67,6 +109,62 @@ pub struct JwtPayload {
     pub ip_confirmer: String,
 }
 
+impl JwtPayload {
+    /// Checks whether the token contains `audience`. Scoped audiences such as `web:community`
+    /// also match their base audience `web`.
+    pub fn has_audience(&self, audience: &str) -> bool {
+        self.aud
+            .iter()
+            .any(|aud| {
+                aud == audience ||
+                aud.strip_prefix(audience).is_some_and(|rest| rest.starts_with(':'))
+            })
+    }
+    
+    /// Whether this is a refresh token. Refresh tokens contain the `derive` audience.
+    pub fn is_refresh_token(&self) -> bool {
+        self.has_audience("derive")
+    }
+    
+    /// Gets the kind of token.
+    pub fn token_kind(&self) -> TokenKind {
+        if self.is_refresh_token() {
+            TokenKind::RefreshToken
+        } else {
+            TokenKind::AccessToken
+        }
+    }
+    
+    /// Gets the platform type this token was issued for based on its audience.
+    pub fn platform_type(&self) -> EAuthTokenPlatformType {
+        if self.has_audience("client") {
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient
+        } else if self.has_audience("mobile") {
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp
+        } else if self.has_audience("web") {
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser
+        } else {
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_Unknown
+        }
+    }
+    
+    /// Asserts that this token was issued for `platform_type`.
+    pub fn assert_platform(
+        &self,
+        platform_type: EAuthTokenPlatformType,
+    ) -> Result<(), PlatformMismatchError> {
+        if self.has_audience(required_audience(platform_type)) {
+            return Ok(());
+        }
+        
+        Err(PlatformMismatchError {
+            kind: self.token_kind(),
+            issued: self.platform_type(),
+            expected: platform_type,
+        })
+    }
+}
+
 impl FromStr for JwtPayload {
     type Err = DecodeError;
 
@@ -83,6 +181,26 @@ impl TryFrom<&str> for JwtPayload {
     }
 }
 
+/// Gets the audience a token must contain to be used with `platform_type`.
+pub fn required_audience(platform_type: EAuthTokenPlatformType) -> &'static str {
+    match platform_type {
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient => "client",
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp => "mobile",
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser => "web",
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_Unknown => "unknown",
+    }
+}
+
+/// Gets a short display name for `platform_type`, e.g. `MobileApp`.
+pub fn platform_type_name(platform_type: &EAuthTokenPlatformType) -> &'static str {
+    match platform_type {
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient => "SteamClient",
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp => "MobileApp",
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser => "WebBrowser",
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_Unknown => "Unknown",
+    }
+}
+
 /// Converts a value to multipart.
 pub fn value_to_multipart(value: Value) -> reqwest::multipart::Form {
     let mut form = reqwest::multipart::Form::new();
@@ -107,15 +225,20 @@ pub fn value_to_multipart(value: Value) -> reqwest::multipart::Form {
     form
 }
 
-/// Generates a random sessionid.
+/// Generates a random sessionid. This is 12 random bytes encoded as 24 lowercase hexadecimal 
+/// characters, matching the format Steam uses, e.g. `37bf523a24034ec06c60ec61`.
 pub fn generate_sessionid() -> String {
-    // Should look like "37bf523a24034ec06c60ec61"
-    (0..12)
-        .map(|_| {
-            let b = rand::random::<u8>();
+    generate_sessionid_with(&crate::clock::ThreadRandom)
+}
 
-            format!("{b:02x?}")
-        })
+/// Generates a random sessionid using `random`. See [`generate_sessionid`].
+pub fn generate_sessionid_with(random: &dyn crate::clock::RandomSource) -> String {
+    let mut bytes = [0u8; 12];
+    
+    random.fill_bytes(&mut bytes);
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
         .collect()
 }
 
@@ -278,6 +401,28 @@ mod tests {
         assert_eq!(decoded.per, 1);
     }
 
+    #[test]
+    fn test_assert_platform() {
+        let jwt = "eyAidHlwIjogIkpXVCIsICJhbGciOiAiRWREU0EiIH0.eyAiaXNzIjogInN0ZWFtIiwgInN1YiI6ICI3NjUwMDAwMDAwMDAwMDAwMCIsICJhdWQiOiBbICJ3ZWIiLCAicmVuZXciLCAiZGVyaXZlIiBdLCAiZXhwIjogMTcyMjQwMTE4OCwgIm5iZiI6IDE2OTUzNDY1NjAsICJpYXQiOiAxNzAzOTg2NTYwLCAianRpIjogIjBERDVfMjNBQkNFNDBfMjk2OUYiLCAib2F0IjogMTcwMzk4NjU2MCwgInBlciI6IDEsICJpcF9zdWJqZWN0IjogIjEyNy4wLjAuMSIsICJpcF9jb25maXJtZXIiOiAiMTI3LjAuMC4xIiB9.-fsYDOMqkVFveAAbvSCcED5NLpCbacbY6Mq9N1fev56QCh9f6PNaksqASI2dJORZFPLhZj37kK1UwfX53QYVDF";
+        let decoded = decode_jwt(jwt).unwrap();
+        
+        assert!(decoded.is_refresh_token());
+        assert_eq!(decoded.platform_type(), EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser);
+        assert!(decoded.assert_platform(EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser).is_ok());
+        
+        let error = decoded.assert_platform(EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp).unwrap_err();
+        
+        assert_eq!(error.to_string(), "refresh token was issued for WebBrowser but session is MobileApp");
+    }
+
+    #[test]
+    fn test_generate_sessionid() {
+        let sessionid = generate_sessionid();
+        
+        assert_eq!(sessionid.len(), 24);
+        assert!(sessionid.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)));
+    }
+
     #[test]
     fn test_bad_jwt() {
         let jwt = "Yup, this is a bad JWT. It's not even a JWT. It's just a string. It's not even base64 encoded.";
@@ -0,0 +1,64 @@
+//! Base URLs for Steam services. These can be overridden to target partner or mock 
+//! environments.
+
+/// The default base URL for the Steam directory.
+pub const DEFAULT_DIRECTORY_URL: &str = "https://api.steampowered.com";
+/// The default base URL for `login.steampowered.com`.
+pub const DEFAULT_LOGIN_URL: &str = "https://login.steampowered.com";
+/// The default base URL for `api.steampowered.com`.
+pub const DEFAULT_API_URL: &str = "https://api.steampowered.com";
+
+/// Base URLs for Steam services. URLs include the scheme and may include a trailing slash.
+/// 
+/// # Examples
+/// ```
+/// use steam_session::hosts::SteamHosts;
+/// 
+/// let hosts = SteamHosts {
+///     api: String::from("http://localhost:8080"),
+///     ..Default::default()
+/// };
+/// 
+/// assert_eq!(hosts.api_url("ISteamUser/GetPlayerSummaries/v2/"), "http://localhost:8080/ISteamUser/GetPlayerSummaries/v2/");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SteamHosts {
+    /// The Steam directory, used to get the list of CM servers.
+    pub directory: String,
+    /// `login.steampowered.com`, used for web logins.
+    pub login: String,
+    /// `api.steampowered.com`, used for WebAPI requests.
+    pub api: String,
+}
+
+impl Default for SteamHosts {
+    fn default() -> Self {
+        Self {
+            directory: DEFAULT_DIRECTORY_URL.into(),
+            login: DEFAULT_LOGIN_URL.into(),
+            api: DEFAULT_API_URL.into(),
+        }
+    }
+}
+
+impl SteamHosts {
+    /// Gets the URL for `pathname` in the Steam directory.
+    pub fn directory_url(&self, pathname: &str) -> String {
+        join_url(&self.directory, pathname)
+    }
+    
+    /// Gets the URL for `pathname` on `login.steampowered.com`.
+    pub fn login_url(&self, pathname: &str) -> String {
+        join_url(&self.login, pathname)
+    }
+    
+    /// Gets the URL for `pathname` on `api.steampowered.com`.
+    pub fn api_url(&self, pathname: &str) -> String {
+        join_url(&self.api, pathname)
+    }
+}
+
+fn join_url(base: &str, pathname: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), pathname.trim_start_matches('/'))
+}
@@ -0,0 +1,26 @@
+use crate::ErrorKind;
+use crate::login_session::LoginSessionError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}", .0)]
+    LoginSession(#[from] LoginSessionError),
+    #[error("{}", .0)]
+    Qr(#[from] crate::qr::Error),
+    #[error("{}", .0)]
+    Io(#[from] std::io::Error),
+    #[error("The login was not approved in time")]
+    NotApproved,
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::LoginSession(error) => error.kind(),
+            Self::Qr(error) => error.kind(),
+            Self::Io(error) => crate::error::io_kind(error),
+            Self::NotApproved => ErrorKind::Timeout,
+        }
+    }
+}
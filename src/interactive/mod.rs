@@ -0,0 +1,168 @@
+//! # Interactive login
+//! 
+//! Drives a whole login from a terminal: asks for the account name and a masked password, or
+//! shows a QR code to scan when no account name is entered, then asks for Steam Guard codes and
+//! reports confirmations as they are needed. Requires the `interactive` feature.
+//! 
+//! ```no_run
+//! # async fn example() -> Result<(), steam_session::interactive::Error> {
+//! use steam_session::CookieDomain;
+//! 
+//! let mut session = steam_session::interactive::login().await?;
+//! let cookies = session.get_web_cookies(&CookieDomain::DEFAULT).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+
+pub use error::Error;
+
+use crate::login_session::{LoginSession, LoginSessionBuilder, LoginSessionEvent};
+use crate::request::StartLoginSessionWithCredentialsDetails;
+use crate::response::StartSessionResponse;
+use crate::transports::Transport;
+use crate::transports::web_api::WebApiTransport;
+use crate::guard::StdinGuardCodeProvider;
+use crate::enums::{EAuthSessionGuardType, EAuthTokenPlatformType};
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// How long to wait for a login to be approved on another device.
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Logs in as the mobile app over the web API, prompting on the terminal for everything needed.
+pub async fn login() -> Result<LoginSession<WebApiTransport>, Error> {
+    let builder = LoginSession::builder(
+        WebApiTransport::new(),
+        EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp,
+    );
+    
+    login_with(builder).await
+}
+
+/// Logs in with a session built from `builder`, prompting on the terminal for everything needed.
+/// Steam Guard codes are read from stdin, replacing any `guard_code_provider` set on `builder`.
+pub async fn login_with<T>(builder: LoginSessionBuilder<T>) -> Result<LoginSession<T>, Error>
+where
+    T: Transport,
+{
+    let mut session = builder
+        .login_timeout(APPROVAL_TIMEOUT)
+        .guard_code_provider(Arc::new(StdinGuardCodeProvider::new()))
+        .build()?;
+    let events = tokio::spawn(report_events(session.subscribe()));
+    let result = run(&mut session).await;
+    
+    events.abort();
+    result?;
+    
+    if session.get_refresh_token().is_none() {
+        return Err(Error::NotApproved);
+    }
+    
+    Ok(session)
+}
+
+async fn run<T>(session: &mut LoginSession<T>) -> Result<(), Error>
+where
+    T: Transport,
+{
+    let account_name = prompt("Account name (leave empty to log in with a QR code): ").await?;
+    
+    if account_name.is_empty() {
+        if let StartSessionResponse::QrChallenge(challenge_url) = session.start_with_qr().await? {
+            eprintln!("{}", crate::qr::render_unicode(&challenge_url)?);
+            eprintln!("Scan the QR code with the Steam mobile app");
+        }
+        
+        session.poll().await?;
+        return Ok(());
+    }
+    
+    let password = prompt_password("Password: ").await?;
+    let platform_type = session.platform_type();
+    let response = session.start_with_credentials(StartLoginSessionWithCredentialsDetails {
+        account_name,
+        password: password.into(),
+        platform_type,
+        ..Default::default()
+    }).await?;
+    
+    if let StartSessionResponse::ActionRequired(actions) = response {
+        let needs_confirmation = actions
+            .iter()
+            .any(|action| {
+                action.r#type == EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceConfirmation ||
+                action.r#type == EAuthSessionGuardType::k_EAuthSessionGuardType_EmailConfirmation
+            });
+        
+        if needs_confirmation {
+            session.poll().await?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Prints events from the session as they arrive, including new QR codes when Steam rotates the
+/// challenge.
+async fn report_events(mut events: broadcast::Receiver<LoginSessionEvent>) {
+    loop {
+        match events.recv().await {
+            Ok(LoginSessionEvent::AwaitingConfirmation(details)) => {
+                let in_app = details.confirmation_types
+                    .contains(&EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceConfirmation);
+                let expires_at = details.expires_at.format("%H:%M:%S");
+                
+                if in_app {
+                    eprintln!("Approve the login in the Steam mobile app before {expires_at}");
+                } else {
+                    eprintln!("Approve the login from the email Steam sent you before {expires_at}");
+                }
+            },
+            Ok(LoginSessionEvent::QrChallengeChanged(challenge)) => {
+                if let Ok(qr) = crate::qr::render_unicode(&challenge.url) {
+                    eprintln!("{qr}");
+                    eprintln!("The QR code changed; scan the new one with the Steam mobile app");
+                }
+            },
+            Ok(LoginSessionEvent::Authenticated(steamid)) => {
+                eprintln!("Logged in as {}", u64::from(steamid));
+            },
+            Ok(_) => {},
+            Err(broadcast::error::RecvError::Lagged(_)) => {},
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Reads a line from stdin after printing `label`.
+async fn prompt(label: &'static str) -> io::Result<String> {
+    blocking(move || {
+        let mut line = String::new();
+        
+        eprint!("{label}");
+        io::stderr().flush()?;
+        io::stdin().lock().read_line(&mut line)?;
+        
+        Ok(line.trim().to_string())
+    }).await
+}
+
+/// Reads a password from the terminal without echoing it.
+async fn prompt_password(label: &'static str) -> io::Result<String> {
+    blocking(move || rpassword::prompt_password(label)).await
+}
+
+/// Runs `f` on the blocking thread pool.
+async fn blocking<F>(f: F) -> io::Result<String>
+where
+    F: FnOnce() -> io::Result<String> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(io::Error::other)?
+}
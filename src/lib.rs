@@ -1,6 +1,13 @@
 //! Crate for authenticating with the Steam auth server.
+//! 
+//! The types used in the common flows are re-exported from [`prelude`] and from the crate 
+//! root. The modules they are defined in remain available for lower-level use.
+
+#[macro_use]
+mod macros;
 
 pub mod enums;
+pub mod error;
 pub mod net;
 pub mod login_session;
 pub mod transports;
@@ -8,9 +15,51 @@ pub mod authentication_client;
 pub mod login_approver;
 pub mod request;
 pub mod response;
+pub mod cookies;
+pub mod web_api_client;
+pub mod cm_client_session;
+pub mod hosts;
+pub mod prelude;
+pub mod metrics;
+pub mod secret;
+pub mod retry;
+pub mod clock;
+pub mod session_manager;
+pub mod token_store;
+pub mod cache_store;
+pub mod guard;
+pub mod confirmations;
+pub mod account;
+pub mod audit;
+pub mod emulation;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "qr")]
+pub mod qr;
+#[cfg(feature = "interactive")]
+pub mod interactive;
 
 mod types;
 mod serializers;
 mod helpers;
+mod single_flight;
 
+/// The generated protobuf types, for constructing custom requests to send with 
+/// [`WebSocketCMTransport::send_message`].
+#[cfg(feature = "proto")]
 pub use steam_session_proto as proto;
+#[cfg(not(feature = "proto"))]
+pub(crate) use steam_session_proto as proto;
+pub use helpers::{JwtPayload, TokenKind, PlatformMismatchError, DecodeError};
+pub use login_session::{LoginSession, LoginSessionBuilder, LoginSessionError, SessionTokens};
+pub use cm_client_session::{CmClientSession, CmSessionEvent};
+pub use transports::{WebSocketCMTransport, Socks5ProxyConfig};
+pub use transports::web_api::WebApiTransport;
+pub use cookies::CookieDomain;
+pub use hosts::SteamHosts;
+pub use secret::Secret;
+pub use error::ErrorKind;
+pub use retry::{RetryPolicy, PollPolicy};
+pub use session_manager::SessionManager;
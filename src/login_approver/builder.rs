@@ -1,6 +1,9 @@
 use super::LoginApprover;
 use crate::enums::EAuthTokenPlatformType;
 use crate::helpers::DEFAULT_USER_AGENT;
+use crate::secret::Secret;
+use crate::audit::AuditSink;
+use std::sync::Arc;
 
 /// Builder for creating a [`LoginApprover`].
 ///
@@ -16,12 +19,13 @@ use crate::helpers::DEFAULT_USER_AGENT;
 /// ```
 #[derive(Debug)]
 pub struct LoginApproverBuilder {
-    pub access_token: String,
-    pub shared_secret: String,
+    pub access_token: Secret<String>,
+    pub shared_secret: Secret<String>,
     pub platform_type: EAuthTokenPlatformType,
     pub machine_id: Option<Vec<u8>>,
     pub user_agent: &'static str,
     pub client: reqwest::Client,
+    pub audit_sink: Option<Arc<dyn AuditSink>>,
 }
 
 impl LoginApproverBuilder {
@@ -31,24 +35,25 @@ impl LoginApproverBuilder {
         shared_secret: String,
     ) -> Self {
         Self {
-            access_token,
-            shared_secret,
+            access_token: Secret::new(access_token),
+            shared_secret: Secret::new(shared_secret),
             platform_type: EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp,
             machine_id: None,
             user_agent: DEFAULT_USER_AGENT,
             client: Default::default(),
+            audit_sink: None,
         }
     }
     
     /// Sets the access token.
     pub fn access_token(mut self, access_token: String) -> Self {
-        self.access_token = access_token;
+        self.access_token = Secret::new(access_token);
         self
     }
     
     /// Sets the shared secret.
     pub fn shared_secret(mut self, shared_secret: String) -> Self {
-        self.shared_secret = shared_secret;
+        self.shared_secret = Secret::new(shared_secret);
         self
     }
     
@@ -71,6 +76,12 @@ impl LoginApproverBuilder {
         self
     }
     
+    /// Sets the sink told about logins being approved or denied. See [`crate::audit`].
+    pub fn audit_sink(mut self, audit_sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(audit_sink);
+        self
+    }
+    
     /// Builds the [`LoginApprover`]. Returns an error if your `access_token` isn't a well-formed 
     /// JWT, if it's a refresh token rather than an access token, or if it's an access token that 
     /// was not generated using [`EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp`].
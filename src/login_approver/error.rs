@@ -4,12 +4,26 @@ pub enum Error {
     IO(#[from] std::io::Error),
     #[error("Provided token is a refresh token, not an access token")]
     RefreshToken,
-    #[error("Provided token is not valid for MobileApp platform usage")]
-    InvalidToken,
+    #[error("Provided token is not valid for MobileApp platform usage: {}", .0)]
+    InvalidToken(#[from] crate::helpers::PlatformMismatchError),
     #[error("Invalid QR URL")]
     InvalidQRUrl,
     #[error("Decode error: {}", .0)]
     Decode(#[from] crate::helpers::DecodeError),
     #[error("Authentication client error: {}", .0)]
     AuthenticationClient(#[from] crate::authentication_client::Error),
-}
\ No newline at end of file
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::IO(error) => crate::error::io_kind(error),
+            Self::RefreshToken |
+            Self::InvalidToken(_) => crate::ErrorKind::Token,
+            Self::InvalidQRUrl => crate::ErrorKind::InvalidInput,
+            Self::Decode(error) => error.kind(),
+            Self::AuthenticationClient(error) => error.kind(),
+        }
+    }
+}
@@ -5,7 +5,7 @@
 //! ## Examples
 //! ```
 //! use steam_session::login_approver::LoginApprover;
-//! use steam_session::proto::steammessages_auth_steamclient::EAuthTokenPlatformType;
+//! use steam_session::enums::EAuthTokenPlatformType;
 //!
 //! let login_approver = LoginApprover::builder("access_token".to_string(), "shared_secret".to_string())
 //!     .platform_type(EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser)
@@ -22,21 +22,26 @@ use std::str::FromStr;
 pub use error::Error;
 pub use builder::LoginApproverBuilder;
 
+use crate::audit::{AuditSink, AuditEvent, AuditEventKind};
+use crate::cache_store::MemoryCacheStore;
 use crate::authentication_client::{AuthenticationClient, AuthenticationClientConstructorOptions};
-use crate::helpers::{JwtPayload, decode_base64, generate_hmac_signature};
+use crate::enums::EAuthTokenPlatformType;
+use crate::helpers::{JwtPayload, DecodeError, decode_base64, generate_hmac_signature};
 use crate::request::{ApproveAuthSessionRequest, MobileConfirmationRequest};
+use crate::secret::Secret;
 use crate::transports::web_api::WebApiTransport;
 use reqwest::Client;
+use std::sync::Arc;
 use steam_session_proto::steammessages_auth_steamclient::{CAuthentication_UpdateAuthSessionWithMobileConfirmation_Response, CAuthentication_GetAuthSessionInfo_Response};
 use steamid_ng::SteamID;
-use byteorder::{WriteBytesExt, LittleEndian};
 
 /// Can be used to approve a login attempt that was started with a QR code.
 #[derive(Debug)]
 pub struct LoginApprover {
-    shared_secret: String,
-    access_token: String,
+    shared_secret: Secret<String>,
+    access_token: Secret<String>,
     handler: AuthenticationClient<WebApiTransport>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
 }
 
 impl LoginApprover {
@@ -49,14 +54,14 @@ impl LoginApprover {
     
     /// Gets the SteamID.
     pub fn steamid(&self) -> Result<SteamID, Error> {
-        let decoded_token = JwtPayload::from_str(&self.access_token)?;
+        let decoded_token = JwtPayload::from_str(self.access_token.expose_secret())?;
         
         Ok(decoded_token.sub)
     }
     
     /// Gets the access token.
     pub fn get_access_token(&self) -> &String {
-        &self.access_token
+        self.access_token.expose_secret()
     }
     
     /// Sets the access token.
@@ -64,17 +69,9 @@ impl LoginApprover {
         &mut self,
         access_token: String,
     ) -> Result<(), Error> {
-        let decoded = JwtPayload::from_str(&access_token)?;
+        verify_access_token(&access_token)?;
         
-        if !decoded.aud.iter().any(|s| s == "derive") {
-            return Err(Error::RefreshToken);
-        }
-        
-        if !decoded.aud.iter().any(|s| s == "mobile") {
-            return Err(Error::InvalidToken);
-        }
-        
-        self.access_token = access_token;
+        self.access_token = Secret::new(access_token);
         Ok(())
     }
     
@@ -87,7 +84,33 @@ impl LoginApprover {
             .ok_or(Error::InvalidQRUrl)?;
         let response = self.handler.get_auth_session_info(
             decoded_qr.client_id,
-            self.access_token.clone()
+            self.access_token.expose_secret().clone()
+        ).await?;
+        
+        Ok(response)
+    }
+    
+    /// Gets the client IDs of the login sessions for this account that are waiting to be 
+    /// approved, including QR code logins and logins awaiting device confirmation. Use these with 
+    /// [`LoginApprover::get_auth_session_info_for_client`] and 
+    /// [`LoginApprover::approve_auth_session`] to approve logins without scanning a QR code.
+    pub async fn get_auth_sessions(&self) -> Result<Vec<u64>, Error> {
+        let response = self.handler.get_auth_sessions_for_account(
+            self.access_token.expose_secret().clone()
+        ).await?;
+        
+        Ok(response.client_ids)
+    }
+    
+    /// Gets the login session info for `client_id`, as returned by 
+    /// [`LoginApprover::get_auth_sessions`].
+    pub async fn get_auth_session_info_for_client(
+        &self,
+        client_id: u64,
+    ) -> Result<CAuthentication_GetAuthSessionInfo_Response, Error> {
+        let response = self.handler.get_auth_session_info(
+            client_id,
+            self.access_token.expose_secret().clone()
         ).await?;
         
         Ok(response)
@@ -98,18 +121,13 @@ impl LoginApprover {
         &self,
         options: ApproveAuthSessionRequest,
     ) -> Result<CAuthentication_UpdateAuthSessionWithMobileConfirmation_Response, Error> {
-        let mut buffer: Vec<u8> = Vec::new();
-        
-        buffer.write_u16::<LittleEndian>(options.version)?;
-        buffer.write_u64::<LittleEndian>(options.client_id)?;
-        buffer.write_u64::<LittleEndian>(options.steamid)?;
-        
-        let shared_secret = decode_base64(&self.shared_secret)?;
-        let signature = generate_hmac_signature(
-            &shared_secret,
-            &buffer,
+        let signature = mobile_confirmation_signature(
+            self.shared_secret.expose_secret(),
+            options.version,
+            options.client_id,
+            options.steamid,
         )?;
-        let response = self.handler.submit_mobile_confirmation(self.access_token.clone(), MobileConfirmationRequest {
+        let response = self.handler.submit_mobile_confirmation(self.access_token.expose_secret().clone(), MobileConfirmationRequest {
             version: options.version,
             client_id: options.client_id,
             steamid: options.steamid,
@@ -118,6 +136,17 @@ impl LoginApprover {
             persistence: options.persistence,
         }).await?;
         
+        if let Some(audit_sink) = &self.audit_sink {
+            let kind = AuditEventKind::DeviceApproved {
+                client_id: options.client_id,
+                approved: options.approve,
+            };
+            
+            audit_sink.record(&AuditEvent::new(kind, chrono::Utc::now())
+                .steamid(Some(options.steamid))
+                .token(self.access_token.expose_secret()));
+        }
+        
         Ok(response)
     }
 }
@@ -133,21 +162,84 @@ impl TryFrom<LoginApproverBuilder> for LoginApprover {
             transport,
             client: builder.client,
             user_agent: builder.user_agent,
+            language: Default::default(),
+            website_id: None,
+            hosts: Default::default(),
+            device_friendly_name: None,
+            retry_policy: Default::default(),
+            clock: crate::clock::system_clock(),
+            cache_store: Arc::new(MemoryCacheStore::new()),
         });
-        let decoded_access_token = JwtPayload::from_str(&builder.access_token)?;
         
-        if !decoded_access_token.aud.iter().any(|s| s == "derive") {
-            return Err(Error::RefreshToken);
-        }
-        
-        if !decoded_access_token.aud.iter().any(|s| s == "mobile") {
-            return Err(Error::InvalidToken);
-        }
+        verify_access_token(builder.access_token.expose_secret())?;
         
         Ok(Self {
             shared_secret: builder.shared_secret,
             access_token: builder.access_token,
             handler,
+            audit_sink: builder.audit_sink,
         })
     }
-}
\ No newline at end of file
+}
+
+/// Computes the signature sent with `UpdateAuthSessionWithMobileConfirmation` to approve or deny 
+/// the login with `client_id`, for implementing custom approval flows.
+/// 
+/// The signature is an HMAC-SHA256 keyed with the base64-decoded `shared_secret`, over 18 bytes: 
+/// `version` as a little-endian `u16`, then `client_id` and `steamid` as little-endian `u64`s. 
+/// `version` is the version from the QR challenge URL, or from 
+/// `GetAuthSessionInfo` for other logins.
+pub fn mobile_confirmation_signature(
+    shared_secret: &str,
+    version: u16,
+    client_id: u64,
+    steamid: SteamID,
+) -> Result<Vec<u8>, DecodeError> {
+    let mut message = Vec::with_capacity(18);
+    
+    message.extend_from_slice(&version.to_le_bytes());
+    message.extend_from_slice(&client_id.to_le_bytes());
+    message.extend_from_slice(&u64::from(steamid).to_le_bytes());
+    
+    // secrets are usually padded, which the decoder does not accept
+    let shared_secret = Secret::new(decode_base64(shared_secret.trim_end_matches('='))?);
+    
+    generate_hmac_signature(shared_secret.expose_secret(), &message)
+}
+
+/// Verifies that `access_token` is an access token issued for the MobileApp platform.
+fn verify_access_token(access_token: &str) -> Result<(), Error> {
+    let decoded = JwtPayload::from_str(access_token)?;
+    
+    if decoded.is_refresh_token() {
+        return Err(Error::RefreshToken);
+    }
+    
+    decoded.assert_platform(EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp)?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn computes_mobile_confirmation_signature() {
+        let steamid = SteamID::from(76561197960287930);
+        let expected = data_encoding::HEXLOWER
+            .decode(b"280559fb09d3b61cf99dac5ed7bae69c8122dd01f794a1b1662d03be854cb78d")
+            .unwrap();
+        
+        for shared_secret in ["MDEyMzQ1Njc4OWFiY2RlZmdoaWo=", "MDEyMzQ1Njc4OWFiY2RlZmdoaWo"] {
+            let signature = mobile_confirmation_signature(
+                shared_secret,
+                1,
+                2372462679780599330,
+                steamid,
+            ).unwrap();
+            
+            assert_eq!(signature, expected);
+        }
+    }
+}
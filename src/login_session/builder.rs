@@ -1,14 +1,68 @@
-use super::{LoginSessionError, LoginSession};
-use super::helpers::LoginSessionOptions;
-use crate::transports::Transport;
+use super::{LoginSessionError, LoginSession, LoginSessionEvent};
+use super::helpers::{LoginSessionOptions, configure_client};
+use crate::transports::{Transport, Socks5ProxyConfig, HttpPoolConfig};
+use crate::enums::ELanguage;
+use crate::request::WebsiteId;
+use crate::hosts::SteamHosts;
+use crate::retry::{RetryPolicy, PollPolicy};
+use crate::clock::{Clock, RandomSource};
+use crate::guard::{GuardCodeProvider, EmailCodeFetcher};
+use crate::audit::AuditSink;
+use crate::emulation::ClientPreset;
+use crate::cache_store::{CacheStore, MemoryCacheStore};
+use crate::token_store::TokenStore;
+use super::event::EventHook;
+use std::sync::Arc;
+use chrono::Duration;
 use steam_session_proto::steammessages_auth_steamclient::EAuthTokenPlatformType;
 
+/// Builder for creating a [`LoginSession`].
+/// 
+/// # Examples
+/// ```no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use steam_session::login_session::LoginSession;
+/// use steam_session::transports::WebSocketCMTransport;
+/// use steam_session::enums::EAuthTokenPlatformType;
+/// use std::time::Duration;
+/// 
+/// let transport = WebSocketCMTransport::builder()
+///     .connect_timeout(Duration::from_secs(10))
+///     .connect()
+///     .await?;
+/// let session = LoginSession::builder(transport, EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient)
+///     .login_timeout(Duration::from_secs(60))
+///     .device_friendly_name("build-server")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
 pub struct LoginSessionBuilder<T> {
     platform_type: EAuthTokenPlatformType,
     transport: T,
-    client: reqwest::Client,
+    client: Option<reqwest::Client>,
+    proxy: Option<Socks5ProxyConfig>,
     user_agent: Option<&'static str>,
     machine_id: Option<Vec<u8>>,
+    language: ELanguage,
+    website_id: Option<WebsiteId>,
+    hosts: SteamHosts,
+    login_timeout: Option<std::time::Duration>,
+    device_friendly_name: Option<String>,
+    retry_policy: RetryPolicy,
+    poll_policy: PollPolicy,
+    clock: Arc<dyn Clock>,
+    random: Arc<dyn RandomSource>,
+    guard_code_provider: Option<Arc<dyn GuardCodeProvider>>,
+    email_code_provider: Option<Arc<dyn GuardCodeProvider>>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    client_preset: Option<ClientPreset>,
+    http_pool: HttpPoolConfig,
+    cache_store: Arc<dyn CacheStore>,
+    remember_login_cookies: bool,
+    event_hook: Option<EventHook>,
+    token_store: Option<(Arc<dyn TokenStore>, String)>,
 }
 
 impl<T> LoginSessionBuilder<T>
@@ -22,9 +76,28 @@ where
         Self {
             platform_type,
             transport,
-            client: Default::default(),
+            client: None,
+            proxy: None,
             user_agent: None,
             machine_id: None,
+            language: ELanguage::default(),
+            website_id: None,
+            hosts: SteamHosts::default(),
+            login_timeout: None,
+            device_friendly_name: None,
+            retry_policy: RetryPolicy::default(),
+            poll_policy: PollPolicy::default(),
+            clock: crate::clock::system_clock(),
+            random: crate::clock::thread_random(),
+            guard_code_provider: None,
+            email_code_provider: None,
+            audit_sink: None,
+            client_preset: None,
+            http_pool: HttpPoolConfig::default(),
+            cache_store: Arc::new(MemoryCacheStore::new()),
+            remember_login_cookies: false,
+            event_hook: None,
+            token_store: None,
         }
     }
 
@@ -43,18 +116,197 @@ where
         self
     }
 
+    /// Sets the language used for authentication requests and web logins, so that emails and 
+    /// pages sent by Steam are in this language. Defaults to English.
+    pub fn language(mut self, language: ELanguage) -> Self {
+        self.language = language;
+        self
+    }
+    
+    /// Sets the `website_id` sent when starting an auth session. If not set, it is chosen based 
+    /// on the platform type.
+    pub fn website_id(mut self, website_id: WebsiteId) -> Self {
+        self.website_id = Some(website_id);
+        self
+    }
+    
+    /// Sets the hosts web requests are sent to. This does not affect the transport, which must be 
+    /// created with the same hosts.
+    pub fn hosts(mut self, hosts: SteamHosts) -> Self {
+        self.hosts = hosts;
+        self
+    }
+    
+    /// Sets how long to wait for a login attempt to be confirmed when polling before giving up. 
+    /// Defaults to 30 seconds.
+    pub fn login_timeout(mut self, login_timeout: std::time::Duration) -> Self {
+        self.login_timeout = Some(login_timeout);
+        self
+    }
+    
+    /// Sets the device name shown to the user when approving the login and in the list of 
+    /// authorized devices. If not set, a name based on the platform type is used.
+    pub fn device_friendly_name(mut self, device_friendly_name: impl Into<String>) -> Self {
+        self.device_friendly_name = Some(device_friendly_name.into());
+        self
+    }
+    
+    /// Sets how requests that are safe to repeat, such as getting the RSA key for the password 
+    /// and polling, are retried after transient failures. Defaults to [`RetryPolicy::default`]. 
+    /// Use [`RetryPolicy::never`] to disable retries.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+    
+    /// Sets how often a login attempt is polled while waiting for it to be confirmed. Defaults to 
+    /// the interval suggested by Steam, without jitter or backoff.
+    pub fn poll_policy(mut self, poll_policy: PollPolicy) -> Self {
+        self.poll_policy = poll_policy;
+        self
+    }
+    
+    /// Sets the clock used for timeouts, poll intervals, and token ages. Defaults to the system 
+    /// clock. A [`crate::clock::MockClock`] can be used in tests to skip ahead in time.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Sets the source of random bytes used for sessionids. Defaults to the thread-local 
+    /// generator.
+    pub fn random(mut self, random: Arc<dyn RandomSource>) -> Self {
+        self.random = random;
+        self
+    }
+    
+    /// Sets the provider asked for Steam Guard codes when a login requires one. See 
+    /// [`crate::guard`].
+    pub fn guard_code_provider(mut self, guard_code_provider: Arc<dyn GuardCodeProvider>) -> Self {
+        self.guard_code_provider = Some(guard_code_provider);
+        self
+    }
+    
+    /// Sets the callback used to fetch emailed Steam Guard codes. Email codes are fetched with 
+    /// this instead of the `guard_code_provider`, which is still used for mobile authenticator 
+    /// codes.
+    pub fn email_code_fetcher(mut self, email_code_fetcher: EmailCodeFetcher) -> Self {
+        self.email_code_provider = Some(Arc::new(email_code_fetcher));
+        self
+    }
+    
+    /// Sets the sink told about logins and tokens being issued, rotated, or revoked. See 
+    /// [`crate::audit`].
+    pub fn audit_sink(mut self, audit_sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(audit_sink);
+        self
+    }
+    
+    /// Sets which client web requests are made to look like. Defaults to the preset matching the 
+    /// platform type. This is not applied to a `client` that was set.
+    pub fn client_preset(mut self, client_preset: ClientPreset) -> Self {
+        self.client_preset = Some(client_preset);
+        self
+    }
+    
+    /// Sets the connection pool settings of the clients created by the session, including 
+    /// clients created using `build_authenticated_client`. This is not applied to a `client` 
+    /// that was set.
+    pub fn http_pool(mut self, http_pool: HttpPoolConfig) -> Self {
+        self.http_pool = http_pool;
+        self
+    }
+    
+    /// Sets the store RSA keys used to encrypt passwords are cached in. Defaults to a 
+    /// [`MemoryCacheStore`] for this session. A store shared between sessions or processes lets 
+    /// them reuse each other's keys. See [`crate::cache_store`].
+    pub fn cache_store(mut self, cache_store: Arc<dyn CacheStore>) -> Self {
+        self.cache_store = cache_store;
+        self
+    }
+    
+    /// Sets whether web cookies include the remember-login cookies Steam sets for 
+    /// `login.steampowered.com`, such as `steamRefresh_steam` and `steamRememberLogin`. A browser 
+    /// holding them gets new cookies for `checkout.steampowered.com` and the other Steam websites 
+    /// when its cookies expire, without logging in again. They are only issued to 
+    /// [`EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser`] sessions. Defaults to 
+    /// `false`, since they hold the refresh token.
+    pub fn remember_login_cookies(mut self, remember_login_cookies: bool) -> Self {
+        self.remember_login_cookies = remember_login_cookies;
+        self
+    }
+    
+    /// Sets a callback called with each event the session emits, in addition to the events 
+    /// received by subscribers. The callback is called while the session is busy, so it should 
+    /// return quickly.
+    pub fn on_event<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&LoginSessionEvent) + Send + Sync + 'static,
+    {
+        self.event_hook = Some(EventHook::new(hook));
+        self
+    }
+    
+    /// Sets the store the session's tokens are saved to under `account`. Tokens are saved 
+    /// whenever a login completes or a new refresh token is issued, and along with web cookies 
+    /// whenever `get_session_tokens` is called. See [`crate::token_store`].
+    pub fn token_store(mut self, token_store: Arc<dyn TokenStore>, account: impl Into<String>) -> Self {
+        self.token_store = Some((token_store, account.into()));
+        self
+    }
+    
     pub fn client(mut self, client: reqwest::Client) -> Self {
-        self.client = client;
+        self.client = Some(client);
+        self
+    }
+    
+    /// Sets the SOCKS5 proxy for web requests made by the session. If no `client` is set, one 
+    /// will be created using this proxy. Clients created using `build_authenticated_client` will 
+    /// also use this proxy.
+    pub fn proxy(mut self, proxy: Socks5ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
         self
     }
     
     pub fn build(self) -> Result<LoginSession<T>, LoginSessionError> {
+        let client_preset = self.client_preset
+            .unwrap_or_else(|| ClientPreset::for_platform(self.platform_type));
+        let client = match (self.client, &self.proxy) {
+            (Some(client), _) => client,
+            (None, proxy) => configure_client(
+                reqwest::Client::builder(),
+                client_preset,
+                self.language,
+                &self.http_pool,
+                proxy.as_ref(),
+            )?.build()?,
+        };
         let session = LoginSession::new(LoginSessionOptions {
             transport: self.transport,
-            client: self.client,
+            client,
+            proxy: self.proxy,
             platform_type: self.platform_type,
             user_agent: self.user_agent,
             machine_id: self.machine_id,
+            language: self.language,
+            website_id: self.website_id,
+            hosts: self.hosts,
+            login_timeout: self.login_timeout
+                .map(|login_timeout| Duration::from_std(login_timeout).unwrap_or(Duration::MAX)),
+            device_friendly_name: self.device_friendly_name,
+            retry_policy: self.retry_policy,
+            poll_policy: self.poll_policy,
+            clock: self.clock,
+            random: self.random,
+            guard_code_provider: self.guard_code_provider,
+            email_code_provider: self.email_code_provider,
+            audit_sink: self.audit_sink,
+            client_preset,
+            http_pool: self.http_pool,
+            cache_store: self.cache_store,
+            remember_login_cookies: self.remember_login_cookies,
+            event_hook: self.event_hook,
+            token_store: self.token_store,
         })?;
         
         Ok(session)
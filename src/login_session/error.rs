@@ -1,6 +1,8 @@
 use steam_session_proto::steammessages_auth_steamclient::EAuthSessionGuardType;
 
+use super::LoginState;
 use crate::enums::EResult;
+use crate::ErrorKind;
 
 #[derive(Debug, thiserror::Error)]
 pub enum LoginSessionError {
@@ -34,22 +36,87 @@ pub enum LoginSessionError {
     NoAccessToken,
     #[error("Unknown auth session guard type: {:?}", .0)]
     UnknownGuardType(EAuthSessionGuardType),
-    #[error("Token platform type is different from the platform type of this LoginSession instance (required audience \"{}\"", .0)]
-    TokenPlatformDifferent(String),
+    #[error("{}", .0)]
+    TokenPlatformDifferent(#[from] crate::helpers::PlatformMismatchError),
     #[error("Malformed response")]
     MalformedResponse,
     #[error("Received EResult other than OK: {:?}", .0)]
     EResultNotOK(EResult),
     #[error("No cookies were returned in response")]
     NoCookiesInResponse,
+    #[error("finalizelogin was rejected with status {}", .0)]
+    FinalizeLoginStatus(reqwest::StatusCode),
+    #[error("No cookies could be issued: {}", .0)]
+    WebCookies(crate::cookies::WebCookies),
+    #[error("No cookie domains were given")]
+    NoCookieDomains,
     #[error("Receiver error: {}", .0)]
     RecvError(#[from] tokio::sync::oneshot::error::RecvError),
+    #[error("The session task has stopped")]
+    SessionClosed,
+    #[error("Login was aborted")]
+    LoginAborted,
     #[error("SOCKS5 proxy configuration error: {0}")]
-    ProxyConfig(String),
+    ProxyConfig(#[from] crate::transports::Socks5ProxyConfigError),
+    #[error("Token store error: {0}")]
+    TokenStore(#[from] crate::token_store::Error),
+    #[error("The login was not completed: {:?}", .0)]
+    LoginNotCompleted(LoginState),
 }
 
-impl From<crate::transports::Socks5ProxyConfigError> for LoginSessionError {
-    fn from(value: crate::transports::Socks5ProxyConfigError) -> Self {
-        LoginSessionError::ProxyConfig(value.to_string())
+impl LoginSessionError {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Reqwest(error) => crate::error::reqwest_kind(error),
+            Self::Serde(_) |
+            Self::MalformedResponse |
+            Self::NoCookiesInResponse |
+            Self::UnknownGuardType(_) => ErrorKind::Decode,
+            Self::LoginSessionHasNotStarted |
+            Self::LoginCannotUseMethodWithScheme |
+            Self::LoginAttemptSteamGuardNotRequired |
+            Self::SessionClosed |
+            Self::LoginAborted => ErrorKind::State,
+            Self::Decode(error) => error.kind(),
+            Self::ExpectedAccessToken |
+            Self::ExpectedRefreshToken |
+            Self::TokenIsForDifferentAccount |
+            Self::TokenBelongsToOtherAccount |
+            Self::NoRefreshToken |
+            Self::NoAccessToken |
+            Self::TokenPlatformDifferent(_) => ErrorKind::Token,
+            Self::AuthenticationClient(error) => error.kind(),
+            Self::InvalidHeaderValue(_) |
+            Self::NoCookieDomains => ErrorKind::InvalidInput,
+            Self::EResultNotOK(_) |
+            Self::FinalizeLoginStatus(_) => ErrorKind::Steam,
+            Self::WebCookies(web_cookies) => web_cookies
+                .failures()
+                .next()
+                .map(|(_domain, error)| error.kind())
+                .unwrap_or(ErrorKind::Decode),
+            Self::RecvError(_) => ErrorKind::Connection,
+            Self::ProxyConfig(error) => error.kind(),
+            Self::TokenStore(error) => error.kind(),
+            Self::LoginNotCompleted(LoginState::Failed { kind, .. }) => *kind,
+            Self::LoginNotCompleted(_) => ErrorKind::State,
+        }
+    }
+    
+    /// Whether the refresh token was rejected as expired, revoked, or otherwise invalid, so the 
+    /// account needs to be logged in again.
+    pub fn is_refresh_token_rejected(&self) -> bool {
+        let is_rejected_eresult = |eresult: EResult| matches!(
+            eresult,
+            EResult::AccessDenied | EResult::Expired | EResult::Revoked
+        );
+        
+        match self {
+            Self::EResultNotOK(eresult) => is_rejected_eresult(*eresult),
+            Self::AuthenticationClient(error) => error.eresult().is_some_and(is_rejected_eresult),
+            Self::NoAccessToken => false,
+            error => error.kind() == ErrorKind::Token,
+        }
     }
 }
\ No newline at end of file
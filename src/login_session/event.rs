@@ -0,0 +1,122 @@
+use super::{LoginState, LoginReport};
+use crate::enums::EAuthSessionGuardType;
+use crate::types::DateTime;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use steamid_ng::SteamID;
+use tokio::sync::Notify;
+
+pub(super) const EVENT_CHANNEL_CAPACITY: usize = 16;
+
+/// An event emitted by a [`LoginSession`](super::LoginSession) or
+/// [`LoginSessionHandle`](super::LoginSessionHandle).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginSessionEvent {
+    /// A new access token was issued.
+    AccessTokenRefreshed,
+    /// A new refresh token was issued.
+    RefreshTokenRenewed,
+    /// The login is waiting to be approved in the Steam mobile app or from the confirmation
+    /// email. Use this to notify the account owner, and abort the login with a
+    /// [`LoginAbortHandle`] if they decline.
+    AwaitingConfirmation(ConfirmationDetails),
+    /// Steam rotated the QR challenge while polling. The new URL should be rendered in place of 
+    /// the old one, which can no longer be scanned.
+    QrChallengeChanged(QrChallenge),
+    /// The login completed and a refresh token was issued for the account.
+    Authenticated(SteamID),
+    /// The login completed, with a report of how it went.
+    Completed(LoginReport),
+    /// The state of the login changed.
+    StateChanged(LoginState),
+}
+
+/// Details of a login waiting to be approved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmationDetails {
+    /// The account name, if the login was started with credentials.
+    pub account_name: Option<String>,
+    /// The SteamID of the account, if known.
+    pub steamid: Option<SteamID>,
+    /// The ways the login can be approved.
+    pub confirmation_types: Vec<EAuthSessionGuardType>,
+    /// When polling for approval will give up.
+    pub expires_at: DateTime,
+}
+
+/// The challenge of a login started with a QR code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrChallenge {
+    /// The URL to render as a QR code.
+    pub url: String,
+    /// When the challenge stops being useful because polling will give up. Before polling 
+    /// starts, this is when polling would give up if it started when the challenge was issued.
+    pub expires_at: DateTime,
+}
+
+/// Aborts a login waiting to be approved. Polling stops and fails with
+/// [`LoginSessionError::LoginAborted`](super::LoginSessionError::LoginAborted).
+///
+/// Created using [`LoginSession::abort_handle`](super::LoginSession::abort_handle).
+#[derive(Debug, Clone, Default)]
+pub struct LoginAbortHandle {
+    inner: Arc<AbortState>,
+}
+
+#[derive(Debug, Default)]
+struct AbortState {
+    aborted: AtomicBool,
+    notify: Notify,
+}
+
+impl LoginAbortHandle {
+    /// Aborts the login.
+    pub fn abort(&self) {
+        self.inner.aborted.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_one();
+    }
+    
+    /// Checks whether the login was aborted.
+    pub fn is_aborted(&self) -> bool {
+        self.inner.aborted.load(Ordering::SeqCst)
+    }
+    
+    /// Clears the aborted flag for a new login attempt.
+    pub(super) fn reset(&self) {
+        self.inner.aborted.store(false, Ordering::SeqCst);
+    }
+    
+    /// Completes once the login is aborted.
+    pub(super) async fn aborted(&self) {
+        // a stored permit from an earlier attempt may wake this up early
+        while !self.is_aborted() {
+            self.inner.notify.notified().await;
+        }
+    }
+}
+
+/// A callback called with each event a session emits, set with 
+/// [`LoginSessionBuilder::on_event`](super::LoginSessionBuilder::on_event).
+#[derive(Clone)]
+pub(super) struct EventHook(Arc<dyn Fn(&LoginSessionEvent) + Send + Sync>);
+
+impl fmt::Debug for EventHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventHook").finish_non_exhaustive()
+    }
+}
+
+impl EventHook {
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&LoginSessionEvent) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+    
+    /// Calls the callback with `event`.
+    pub fn call(&self, event: &LoginSessionEvent) {
+        (self.0)(event);
+    }
+}
@@ -0,0 +1,167 @@
+use super::{LoginSession, LoginSessionError, LoginSessionEvent, SessionTokens};
+use crate::cookies::CookieDomain;
+use crate::transports::Transport;
+use steamid_ng::SteamID;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+const COMMAND_CHANNEL_CAPACITY: usize = 32;
+
+/// A command sent to the task that owns the session.
+enum Command {
+    GetAccessToken(oneshot::Sender<Option<String>>),
+    GetRefreshToken(oneshot::Sender<Option<String>>),
+    SteamId(oneshot::Sender<Option<SteamID>>),
+    RefreshAccessToken(oneshot::Sender<Result<String, LoginSessionError>>),
+    RenewRefreshToken(oneshot::Sender<Result<bool, LoginSessionError>>),
+    GetWebCookies(Vec<CookieDomain>, oneshot::Sender<Result<Vec<String>, LoginSessionError>>),
+    GetSessionTokens(Vec<CookieDomain>, oneshot::Sender<Result<SessionTokens, LoginSessionError>>),
+}
+
+/// A cheap-to-clone handle to a [`LoginSession`] that is owned by a background task. Commands 
+/// from every clone are handled in order by the task, so concurrent renewals do not race. The 
+/// task stops once every clone has been dropped.
+/// 
+/// Created using [`LoginSession::into_handle`].
+#[derive(Debug, Clone)]
+pub struct LoginSessionHandle {
+    commands: mpsc::Sender<Command>,
+    events: broadcast::Sender<LoginSessionEvent>,
+}
+
+impl LoginSessionHandle {
+    /// Spawns a task owning `session` and returns a handle to it. Events emitted by the session 
+    /// are delivered to subscribers of the handle.
+    pub(super) fn spawn<T>(session: LoginSession<T>) -> Self
+    where
+        T: Transport + 'static,
+    {
+        let (commands, rx) = mpsc::channel(COMMAND_CHANNEL_CAPACITY);
+        let events = session.events.clone();
+        
+        tokio::spawn(run(session, rx));
+        
+        Self {
+            commands,
+            events,
+        }
+    }
+    
+    /// Subscribes to events for this session.
+    pub fn subscribe(&self) -> broadcast::Receiver<LoginSessionEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Gets the access token.
+    pub async fn get_access_token(&self) -> Result<Option<String>, LoginSessionError> {
+        self.send(Command::GetAccessToken).await
+    }
+    
+    /// Gets the refresh token.
+    pub async fn get_refresh_token(&self) -> Result<Option<String>, LoginSessionError> {
+        self.send(Command::GetRefreshToken).await
+    }
+    
+    /// Gets the SteamID.
+    pub async fn steamid(&self) -> Result<Option<SteamID>, LoginSessionError> {
+        self.send(Command::SteamId).await
+    }
+    
+    /// Refreshes the access token and returns it. See [`LoginSession::refresh_access_token`].
+    pub async fn refresh_access_token(&self) -> Result<String, LoginSessionError> {
+        self.send(Command::RefreshAccessToken).await?
+    }
+    
+    /// Renews the refresh token. See [`LoginSession::renew_refresh_token`].
+    pub async fn renew_refresh_token(&self) -> Result<bool, LoginSessionError> {
+        self.send(Command::RenewRefreshToken).await?
+    }
+    
+    /// Gets web cookies for `domains`. See [`LoginSession::get_web_cookies`].
+    pub async fn get_web_cookies(
+        &self,
+        domains: &[CookieDomain],
+    ) -> Result<Vec<String>, LoginSessionError> {
+        let domains = domains.to_vec();
+        
+        self.send(|tx| Command::GetWebCookies(domains, tx)).await?
+    }
+    
+    /// Gets the tokens and web cookies for `domains`. See [`LoginSession::get_session_tokens`].
+    pub async fn get_session_tokens(
+        &self,
+        domains: &[CookieDomain],
+    ) -> Result<SessionTokens, LoginSessionError> {
+        let domains = domains.to_vec();
+        
+        self.send(|tx| Command::GetSessionTokens(domains, tx)).await?
+    }
+    
+    /// Sends a command and waits for the reply.
+    async fn send<R, F>(&self, command: F) -> Result<R, LoginSessionError>
+    where
+        F: FnOnce(oneshot::Sender<R>) -> Command,
+    {
+        let (tx, rx) = oneshot::channel();
+        
+        self.commands.send(command(tx))
+            .await
+            .map_err(|_| LoginSessionError::SessionClosed)?;
+        
+        rx.await.map_err(|_| LoginSessionError::SessionClosed)
+    }
+}
+
+/// Handles commands for `session` until every handle has been dropped.
+async fn run<T>(
+    mut session: LoginSession<T>,
+    mut commands: mpsc::Receiver<Command>,
+)
+where
+    T: Transport,
+{
+    // errors from sending replies mean the receiver is no longer interested
+    while let Some(command) = commands.recv().await {
+        match command {
+            Command::GetAccessToken(tx) => {
+                let _ = tx.send(session.get_access_token().cloned());
+            },
+            Command::GetRefreshToken(tx) => {
+                let _ = tx.send(session.get_refresh_token().cloned());
+            },
+            Command::SteamId(tx) => {
+                let _ = tx.send(session.steamid());
+            },
+            Command::RefreshAccessToken(tx) => {
+                let result = session.refresh_access_token().await
+                    .and_then(|_| session.get_access_token()
+                        .cloned()
+                        .ok_or(LoginSessionError::NoAccessToken));
+                
+                if result.is_ok() {
+                    session.emit(LoginSessionEvent::AccessTokenRefreshed);
+                }
+                
+                let _ = tx.send(result);
+            },
+            Command::RenewRefreshToken(tx) => {
+                let result = session.renew_refresh_token().await;
+                
+                if let Ok(renewed) = result {
+                    session.emit(LoginSessionEvent::AccessTokenRefreshed);
+                    
+                    if renewed {
+                        session.emit(LoginSessionEvent::RefreshTokenRenewed);
+                    }
+                }
+                
+                let _ = tx.send(result);
+            },
+            Command::GetWebCookies(domains, tx) => {
+                let _ = tx.send(session.get_web_cookies(&domains).await);
+            },
+            Command::GetSessionTokens(domains, tx) => {
+                let _ = tx.send(session.get_session_tokens(&domains).await);
+            },
+        }
+    }
+}
@@ -1,33 +1,92 @@
-use super::LoginSessionError;
-use crate::authentication_client::{AuthenticationClient, AuthenticationClientConstructorOptions};
-use crate::helpers::DEFAULT_USER_AGENT;
-use crate::transports::Transport;
-use crate::enums::EAuthTokenPlatformType;
+use crate::transports::{Socks5ProxyConfig, Socks5ProxyConfigError, HttpPoolConfig};
+use crate::enums::{EAuthTokenPlatformType, ELanguage};
+use crate::cookies::CookieDomain;
+use crate::request::WebsiteId;
+use crate::hosts::SteamHosts;
+use crate::retry::{RetryPolicy, PollPolicy};
+use crate::secret::Secret;
+use crate::types::DateTime;
+use crate::clock::{Clock, RandomSource};
+use crate::guard::GuardCodeProvider;
+use crate::audit::AuditSink;
+use crate::emulation::ClientPreset;
+use crate::cache_store::CacheStore;
+use crate::token_store::TokenStore;
+use super::event::EventHook;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct LoginSessionOptions<T> {
     pub transport: T,
     pub client: reqwest::Client,
+    pub proxy: Option<Socks5ProxyConfig>,
     pub platform_type: EAuthTokenPlatformType,
     pub user_agent: Option<&'static str>,
     pub machine_id: Option<Vec<u8>>,
+    pub language: ELanguage,
+    pub website_id: Option<WebsiteId>,
+    pub hosts: SteamHosts,
+    pub login_timeout: Option<chrono::Duration>,
+    pub device_friendly_name: Option<String>,
+    pub retry_policy: RetryPolicy,
+    pub poll_policy: PollPolicy,
+    pub clock: Arc<dyn Clock>,
+    pub random: Arc<dyn RandomSource>,
+    pub guard_code_provider: Option<Arc<dyn GuardCodeProvider>>,
+    pub email_code_provider: Option<Arc<dyn GuardCodeProvider>>,
+    pub audit_sink: Option<Arc<dyn AuditSink>>,
+    pub client_preset: ClientPreset,
+    pub http_pool: HttpPoolConfig,
+    pub cache_store: Arc<dyn CacheStore>,
+    pub remember_login_cookies: bool,
+    pub event_hook: Option<EventHook>,
+    pub token_store: Option<(Arc<dyn TokenStore>, String)>,
 }
 
-pub fn create_handler<T>(
-    transport: T,
-    client: reqwest::Client,
-    platform_type: EAuthTokenPlatformType,
-    machine_id: Option<Vec<u8>>,
-    user_agent: Option<&'static str>,
-) -> Result<AuthenticationClient<T>, LoginSessionError>
-where
-    T: Transport,
-{
-    Ok(AuthenticationClient::new(AuthenticationClientConstructorOptions {
-        platform_type,
-        transport,
-        client,
-        machine_id,
-        user_agent: user_agent.unwrap_or(DEFAULT_USER_AGENT),
-    }))
-}
\ No newline at end of file
+/// Web cookies cached by a session.
+#[derive(Debug, Clone)]
+pub struct CachedWebCookies {
+    pub domains: Vec<CookieDomain>,
+    pub cookies: Secret<Vec<String>>,
+    pub expires_at: Option<DateTime>,
+}
+
+impl CachedWebCookies {
+    /// Checks whether these cookies can be reused for `domains` at `now`, given they should be 
+    /// refreshed `margin` ahead of expiring.
+    pub fn is_fresh_for(
+        &self,
+        domains: &[CookieDomain],
+        now: DateTime,
+        margin: chrono::Duration,
+    ) -> bool {
+        let covers_domains = domains
+            .iter()
+            .all(|domain| self.domains.contains(domain));
+        
+        covers_domains && self.expires_at
+            .map(|expires_at| expires_at - margin > now)
+            .unwrap_or(false)
+    }
+}
+
+/// Applies the headers of `client_preset`, the `http_pool` settings, and `proxy` to `builder`, as 
+/// for every client a session creates.
+pub fn configure_client(
+    builder: reqwest::ClientBuilder,
+    client_preset: ClientPreset,
+    language: ELanguage,
+    http_pool: &HttpPoolConfig,
+    proxy: Option<&Socks5ProxyConfig>,
+) -> Result<reqwest::ClientBuilder, Socks5ProxyConfigError> {
+    let builder = builder.default_headers(client_preset.http_headers(language));
+    let mut builder = http_pool.apply(builder);
+    
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy.reqwest_proxy()?);
+    }
+    
+    Ok(builder)
+}
+
+pub use crate::cookies::format_cookie;
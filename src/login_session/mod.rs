@@ -1,27 +1,54 @@
 mod error;
 mod builder;
 mod helpers;
+mod tokens;
+mod handle;
+mod event;
+mod state;
+mod report;
+mod web_session;
 
 use std::str::FromStr;
 
 pub use error::LoginSessionError;
 pub use builder::LoginSessionBuilder;
+pub use tokens::SessionTokens;
+pub use handle::LoginSessionHandle;
+pub use event::{LoginSessionEvent, ConfirmationDetails, QrChallenge, LoginAbortHandle};
+pub use state::LoginState;
+pub use report::LoginReport;
+pub use web_session::{WebSession, WebSessionSource};
 
-use helpers::LoginSessionOptions;
+use helpers::{LoginSessionOptions, CachedWebCookies};
+use event::{EVENT_CHANNEL_CAPACITY, EventHook};
+use report::LoginTrace;
 
 use crate::enums::EResult;
-use crate::response::{StartSessionResponseValidAction, StartSessionResponse};
+use crate::cookies::{CookieDomain, CookieSet, DomainCookies, DomainCookieError, WebCookies, web_cookies_expiry};
+use crate::response::{StartSessionResponseValidAction, StartSessionResponse, GeneratedAccessToken};
 use crate::request::{
     StartLoginSessionWithCredentialsDetails,
     StartAuthSessionWithCredentialsRequest,
+    GenerateAccessTokenOptions,
 };
 use crate::serializers::from_number_or_string_option;
 use crate::transports::web_api::WebApiTransport;
-use crate::transports::{Transport, WebSocketCMTransport};
+use crate::transports::{Transport, WebSocketCMTransport, Socks5ProxyConfig, HttpPoolConfig};
 use crate::types::DateTime;
-use crate::authentication_client::{AuthenticationClient, Error as AuthenticationClientError};
-use crate::helpers::{JwtPayload, generate_sessionid, create_api_headers, value_to_multipart};
-use crate::enums::{ESessionPersistence, EAuthTokenPlatformType, EAuthSessionGuardType};
+use crate::secret::{Secret, MaskedAccountName};
+use crate::authentication_client::{
+    AuthenticationClient,
+    AuthenticationClientConstructorOptions,
+    Error as AuthenticationClientError,
+};
+use crate::helpers::{JwtPayload, DEFAULT_USER_AGENT, generate_sessionid_with, create_api_headers, value_to_multipart};
+use crate::clock::{Clock, RandomSource};
+use crate::retry::PollPolicy;
+use crate::audit::{AuditSink, AuditEvent, AuditEventKind};
+use crate::token_store::TokenStore;
+use crate::emulation::ClientPreset;
+use crate::guard::{GuardCodeProvider, GuardContext, MAX_ATTEMPTS as MAX_GUARD_CODE_ATTEMPTS};
+use crate::enums::{ESessionPersistence, EAuthTokenPlatformType, EAuthSessionGuardType, ELanguage};
 
 use cookie::Cookie;
 use futures::StreamExt;
@@ -29,28 +56,57 @@ use futures::stream::FuturesOrdered;
 use reqwest::header::SET_COOKIE;
 use serde::Deserialize;
 use serde_json::Value;
-use chrono::{Utc, Duration};
+use chrono::Duration;
+use std::sync::Arc;
 use http::HeaderValue;
 use reqwest::{Client, RequestBuilder};
-use steam_session_proto::steammessages_auth_steamclient::CAuthentication_BeginAuthSessionViaCredentials_Response;
+use steam_session_proto::steammessages_auth_steamclient::{
+    CAuthentication_BeginAuthSessionViaCredentials_Response,
+    EAuthTokenRevokeAction,
+};
 use steamid_ng::SteamID;
 use url::form_urlencoded;
+use tokio::sync::broadcast;
 
 const LOGIN_TIMEOUT_SECONDS: i64 = 30;
+const WEB_COOKIE_REFRESH_MARGIN_MINUTES: i64 = 10;
+/// The names of the cookies Steam uses to remember a login on `login.steampowered.com`.
+const REMEMBER_LOGIN_COOKIES: [&str; 2] = ["steamRefresh_steam", "steamRememberLogin"];
 
 #[derive(Debug)]
 pub struct LoginSession<T> {
     login_timeout: Duration,
     account_name: Option<String>,
-    refresh_token: Option<String>,
-    access_token: Option<String>,
+    refresh_token: Option<Secret<String>>,
+    access_token: Option<Secret<String>>,
     access_token_set_at: Option<DateTime>,
     platform_type: EAuthTokenPlatformType,
     client: Client,
+    proxy: Option<Socks5ProxyConfig>,
     handler: AuthenticationClient<T>,
     steam_guard_code: Option<String>,
     steam_guard_machine_token: Option<Vec<u8>>,
     start_session_response: Option<CAuthentication_BeginAuthSessionViaCredentials_Response>,
+    qr_challenge: Option<QrChallenge>,
+    state: LoginState,
+    trace: LoginTrace,
+    login_report: Option<LoginReport>,
+    sessionid: String,
+    web_cookies: Option<CachedWebCookies>,
+    web_cookie_refresh_margin: Duration,
+    poll_policy: PollPolicy,
+    clock: Arc<dyn Clock>,
+    random: Arc<dyn RandomSource>,
+    events: broadcast::Sender<LoginSessionEvent>,
+    abort: LoginAbortHandle,
+    guard_code_provider: Option<Arc<dyn GuardCodeProvider>>,
+    email_code_provider: Option<Arc<dyn GuardCodeProvider>>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    client_preset: ClientPreset,
+    http_pool: HttpPoolConfig,
+    remember_login_cookies: bool,
+    event_hook: Option<EventHook>,
+    token_store: Option<(Arc<dyn TokenStore>, String)>,
 }
 
 pub async fn connect_ws() -> Result<LoginSession<WebSocketCMTransport>, LoginSessionError> {
@@ -70,20 +126,34 @@ pub async fn connect_webapi() -> Result<LoginSession<WebApiTransport>, LoginSess
         .build()
 }
 
+/// Creates a [`EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp`] session from a 
+/// mobile refresh token over the WebAPI, sending the mobile app's headers. See 
+/// [`LoginSession::from_mobile_refresh_token`].
+pub async fn connect_webapi_mobile(
+    refresh_token: String,
+) -> Result<LoginSession<WebApiTransport>, LoginSessionError> {
+    let transport = WebApiTransport::for_client_preset(ClientPreset::MobileApp, ELanguage::default())?;
+    
+    LoginSession::from_mobile_refresh_token(transport, refresh_token).await
+}
+
+#[deprecated(note = "use `WebSocketCMTransport::builder().proxy(..)` and `LoginSession::builder(..).proxy(..)` instead")]
 pub async fn connect_ws_with_socks5_proxy(
     proxy: &crate::transports::Socks5ProxyConfig,
 ) -> Result<LoginSession<WebSocketCMTransport>, LoginSessionError> {
     let platform_type = EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp;
-    let client = proxy.build_reqwest_client()?;
-    let transport = WebSocketCMTransport::connect_with_socks5_proxy(proxy)
+    let transport = WebSocketCMTransport::builder()
+        .proxy(proxy.clone())
+        .connect()
         .await
         .map_err(AuthenticationClientError::WebSocketCM)?;
 
     LoginSessionBuilder::new(transport, platform_type)
-        .client(client)
+        .proxy(proxy.clone())
         .build()
 }
 
+#[deprecated(note = "use `LoginSession::builder(WebApiTransport::with_custom_client(..), ..).proxy(..)` instead")]
 pub async fn connect_webapi_with_socks5_proxy(
     proxy: &crate::transports::Socks5ProxyConfig,
 ) -> Result<LoginSession<WebApiTransport>, LoginSessionError> {
@@ -93,6 +163,7 @@ pub async fn connect_webapi_with_socks5_proxy(
 
     LoginSessionBuilder::new(transport, platform_type)
         .client(client)
+        .proxy(proxy.clone())
         .build()
 }
 
@@ -108,31 +179,80 @@ where
         LoginSessionBuilder::new(transport, platform_type)
     }
     
+    /// Creates a [`EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp`] session from a 
+    /// stored mobile refresh token and generates an access token with it, without logging in 
+    /// again. The access token is accepted by the endpoints of the mobile app, such as 
+    /// confirmations, and web cookies generated by the session include the mobile app's 
+    /// `mobileClient` cookies. Fails with [`LoginSessionError::TokenPlatformDifferent`] if the 
+    /// refresh token was not issued to the mobile app.
+    pub async fn from_mobile_refresh_token(
+        transport: T,
+        refresh_token: String,
+    ) -> Result<Self, LoginSessionError> {
+        let mut session = Self::builder(transport, EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp)
+            .build()?;
+        
+        session.set_refresh_token(refresh_token)?;
+        session.refresh_access_token().await?;
+        
+        Ok(session)
+    }
+    
     /// Creates a new [`LoginSession`] to use for authentication.
     fn new(
         options: LoginSessionOptions<T>,
     ) -> Result<Self, LoginSessionError> {
         let platform_type = options.platform_type;
-        let handler = helpers::create_handler(
-            options.transport,
-            options.client.clone(),
+        let handler = AuthenticationClient::new(AuthenticationClientConstructorOptions {
             platform_type,
-            options.machine_id,
-            options.user_agent
-        )?;
+            transport: options.transport,
+            client: options.client.clone(),
+            machine_id: options.machine_id,
+            user_agent: options.user_agent.unwrap_or(DEFAULT_USER_AGENT),
+            language: options.language,
+            website_id: options.website_id,
+            hosts: options.hosts,
+            device_friendly_name: options.device_friendly_name,
+            retry_policy: options.retry_policy,
+            clock: Arc::clone(&options.clock),
+            cache_store: options.cache_store,
+        });
+        let login_timeout = options.login_timeout
+            .unwrap_or_else(|| Duration::try_seconds(LOGIN_TIMEOUT_SECONDS).unwrap());
         
         Ok(Self {
-            login_timeout: Duration::try_seconds(LOGIN_TIMEOUT_SECONDS).unwrap(),
+            login_timeout,
             account_name: None,
             refresh_token: None,
             access_token: None,
             access_token_set_at: None,
             platform_type,
             client: options.client,
+            proxy: options.proxy,
             handler,
             steam_guard_code: None,
             steam_guard_machine_token: None,
             start_session_response: None,
+            qr_challenge: None,
+            state: LoginState::Idle,
+            trace: LoginTrace::default(),
+            login_report: None,
+            sessionid: generate_sessionid_with(options.random.as_ref()),
+            web_cookies: None,
+            web_cookie_refresh_margin: Duration::try_minutes(WEB_COOKIE_REFRESH_MARGIN_MINUTES).unwrap(),
+            poll_policy: options.poll_policy,
+            clock: options.clock,
+            random: options.random,
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            abort: LoginAbortHandle::default(),
+            guard_code_provider: options.guard_code_provider,
+            email_code_provider: options.email_code_provider,
+            audit_sink: options.audit_sink,
+            client_preset: options.client_preset,
+            http_pool: options.http_pool,
+            remember_login_cookies: options.remember_login_cookies,
+            event_hook: options.event_hook,
+            token_store: options.token_store,
         })
     }
     
@@ -166,11 +286,16 @@ where
         } = details;
         
         self.steam_guard_code = steam_guard_code;
+        self.account_name = Some(account_name.clone());
+        self.abort.reset();
+        self.start_trace();
+        self.set_state(LoginState::Started);
         
         let encrypted_password = self.handler.encrypt_password(
             account_name.clone(),
-            password.clone(),
-        ).await?;
+            password.expose_secret().clone(),
+        ).await
+            .map_err(|error| self.record_failure(error.into()))?;
         let start_session_response = self.handler.start_session_with_credentials(StartAuthSessionWithCredentialsRequest {
             account_name,
             encrypted_password: encrypted_password.encrypted_password,
@@ -179,18 +304,71 @@ where
             platform_type,
             persistence: persistence.unwrap_or(ESessionPersistence::k_ESessionPersistence_Persistent),
             steam_guard_machine_token: steam_guard_machine_token.clone(),
-        }).await?;
+        }).await
+            .map_err(|error| self.record_failure(error.into()))?;
         
         self.start_session_response = Some(start_session_response);
+        self.qr_challenge = None;
         
-        let response = self.process_start_session_response().await?;
+        let response = self.process_start_session_response().await
+            .map_err(|error| self.record_failure(error))?;
         
         Ok(response)
     }
     
+    /// Starts a new login attempt using a QR code. The returned 
+    /// [`StartSessionResponse::QrChallenge`] contains the challenge URL, which should be rendered 
+    /// as a QR code and scanned with the Steam mobile app. Call `poll` to wait for the login to be 
+    /// approved.
+    pub async fn start_with_qr(&mut self) -> Result<StartSessionResponse, LoginSessionError> {
+        self.start_trace();
+        self.set_state(LoginState::Started);
+        
+        let qr_response = self.handler.start_session_with_qr().await
+            .map_err(|error| self.record_failure(error.into()))?;
+        let mut start_session_response = CAuthentication_BeginAuthSessionViaCredentials_Response::new();
+        
+        start_session_response.set_client_id(qr_response.client_id());
+        start_session_response.set_request_id(qr_response.request_id().to_vec());
+        start_session_response.set_interval(qr_response.interval());
+        start_session_response.allowed_confirmations = qr_response.allowed_confirmations.clone();
+        self.account_name = None;
+        self.steam_guard_code = None;
+        self.abort.reset();
+        self.start_session_response = Some(start_session_response);
+        self.qr_challenge = Some(QrChallenge {
+            url: qr_response.challenge_url().to_string(),
+            expires_at: self.clock.now() + self.login_timeout,
+        });
+        self.set_state(LoginState::WaitingForGuard {
+            guard_types: qr_response.allowed_confirmations
+                .iter()
+                .map(|confirmation| confirmation.confirmation_type())
+                .collect(),
+        });
+        
+        Ok(StartSessionResponse::QrChallenge(qr_response.challenge_url().to_string()))
+    }
+    
+    /// Gets the current challenge of a login started with [`LoginSession::start_with_qr`]. Steam 
+    /// may rotate the challenge while polling, in which case this is updated and 
+    /// [`LoginSessionEvent::QrChallengeChanged`] is emitted.
+    pub fn qr_challenge(&self) -> Option<&QrChallenge> {
+        self.qr_challenge.as_ref()
+    }
+    
+    /// Gets how long the current QR challenge remains useful, e.g. to show a countdown. Returns 
+    /// zero once it has expired, and `None` if the login was not started with a QR code.
+    pub fn qr_challenge_remaining(&self) -> Option<std::time::Duration> {
+        let qr_challenge = self.qr_challenge.as_ref()?;
+        
+        Some((qr_challenge.expires_at - self.clock.now()).to_std().unwrap_or_default())
+    }
+    
     pub fn steamid(&self) -> Option<SteamID> {
-        if let Some(start_session_response) = &self.start_session_response {
-            return Some(SteamID::from(start_session_response.steamid()));
+        // QR sessions don't know the SteamID until they are approved
+        if let Some(steamid) = self.started_steamid() {
+            return Some(SteamID::from(steamid));
         }
         
         let token = if let Some(access_token) = &self.access_token {
@@ -200,11 +378,111 @@ where
         } else {
             None
         }?;
-        let decoded = JwtPayload::from_str(token).ok()?;
+        let decoded = JwtPayload::from_str(token.expose_secret()).ok()?;
         
         Some(decoded.sub)
     }
     
+    /// Gets the state of the current login attempt.
+    pub fn state(&self) -> &LoginState {
+        &self.state
+    }
+    
+    /// Gets the [`LoginReport`] of the last login completed by this session, for tracking how 
+    /// logins are going over time. Sessions authenticated with `set_refresh_token` have no report.
+    pub fn login_report(&self) -> Option<&LoginReport> {
+        self.login_report.as_ref()
+    }
+    
+    /// Starts tracking a new login attempt for its [`LoginReport`].
+    fn start_trace(&mut self) {
+        self.trace = LoginTrace::start(self.clock.now());
+        self.login_report = None;
+    }
+    
+    /// Sets the state of the login, emitting [`LoginSessionEvent::StateChanged`] if it changed.
+    fn set_state(&mut self, state: LoginState) {
+        if self.state != state {
+            self.trace.transition(&self.state, &state, self.clock.now());
+            
+            if let LoginState::Failed { kind, reason } = &state {
+                self.audit(AuditEventKind::LoginFailed {
+                    kind: *kind,
+                    reason: reason.clone(),
+                }, None);
+            }
+            
+            self.state = state.clone();
+            self.emit(LoginSessionEvent::StateChanged(state));
+        }
+    }
+    
+    /// Records that the login failed with `error`, returning it.
+    fn record_failure(&mut self, error: LoginSessionError) -> LoginSessionError {
+        self.set_state(LoginState::Failed {
+            kind: error.kind(),
+            reason: error.to_string(),
+        });
+        error
+    }
+    
+    /// Subscribes to events for this session.
+    pub fn subscribe(&self) -> broadcast::Receiver<LoginSessionEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Gets a handle for aborting a login that is waiting to be approved, e.g. when the account 
+    /// owner declines it out-of-band after being notified by 
+    /// [`LoginSessionEvent::AwaitingConfirmation`].
+    pub fn abort_handle(&self) -> LoginAbortHandle {
+        self.abort.clone()
+    }
+    
+    /// Records an event with the audit sink, if one is set.
+    fn audit(&self, kind: AuditEventKind, token: Option<&str>) {
+        if let Some(audit_sink) = &self.audit_sink {
+            let mut event = AuditEvent::new(kind, self.clock.now())
+                .steamid(self.steamid());
+            
+            if let Some(token) = token {
+                event = event.token(token);
+            }
+            
+            audit_sink.record(&event);
+        }
+    }
+    
+    /// Emits an event to the event hook and subscribers.
+    fn emit(&self, event: LoginSessionEvent) {
+        if let Some(event_hook) = &self.event_hook {
+            event_hook.call(&event);
+        }
+        
+        // an error means there are no subscribers
+        let _ = self.events.send(event);
+    }
+    
+    /// Saves the session's tokens along with `cookies` to the token store, if one is set.
+    async fn save_tokens(&self, cookies: Vec<String>) -> Result<(), LoginSessionError> {
+        let (Some((token_store, account)), Some(steamid)) = (&self.token_store, self.steamid()) else {
+            return Ok(());
+        };
+        let tokens = SessionTokens {
+            steamid,
+            refresh_token: self.refresh_token.clone(),
+            access_token: self.access_token.clone(),
+            cookies,
+        };
+        
+        token_store.save(account, &tokens).await?;
+        Ok(())
+    }
+    
+    /// Gets the HTTP client used for web requests.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+    
     /// Gets the account name.
     pub fn get_account_name(&self) -> Option<&String> {
         self.account_name.as_ref()
@@ -218,13 +496,14 @@ where
     /// [`EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp`], this will also be set after 
     /// calling [`get_web_cookies`] for those platform types.
     pub fn get_access_token(&self) -> Option<&String> {
-        self.access_token.as_ref()
+        self.access_token.as_ref().map(Secret::expose_secret)
     }
     
     /// Sets the access token. Will return an error if:
     /// 
     /// - You set it to a token that isn't well-formed, or
     /// - You set it to a refresh token rather than an access token, or
+    /// - You set it to a token that was issued for a different platform type, or
     /// - You have already called `start_with_credentials` and you set it to a token that doesn't 
     /// belong to the same account, or
     /// - You have already set a refresh token and you set this to a token that doesn't belong to 
@@ -248,18 +527,20 @@ where
         
         let decoded = JwtPayload::from_str(&token)?;
         
-        if decoded.aud.iter().any(|a| a == "derive") {
+        if decoded.is_refresh_token() {
             return Err(LoginSessionError::ExpectedAccessToken);
         }
         
-        if let Some(start_session_response) = &self.start_session_response {
-            if start_session_response.steamid() != u64::from(decoded.sub) {
+        decoded.assert_platform(self.platform_type)?;
+        
+        if let Some(steamid) = self.started_steamid() {
+            if steamid != u64::from(decoded.sub) {
                 return Err(LoginSessionError::TokenIsForDifferentAccount);
             }
         }
         
         if let Some(refresh_token) = &self.refresh_token {
-            let decoded_refresh_token = JwtPayload::from_str(refresh_token)?;
+            let decoded_refresh_token = JwtPayload::from_str(refresh_token.expose_secret())?;
             
             if decoded_refresh_token.sub != decoded.sub {
                 return Err(LoginSessionError::TokenBelongsToOtherAccount);
@@ -267,22 +548,63 @@ where
         }
         
         // Everything checks out
-        self.access_token = Some(token);
-        self.access_token_set_at = Some(Utc::now());
+        self.access_token = Some(Secret::new(token));
+        self.access_token_set_at = Some(self.clock.now());
         
         Ok(())
     }
     
+    /// Gets the platform type the session authenticates as.
+    pub fn platform_type(&self) -> EAuthTokenPlatformType {
+        self.platform_type
+    }
+    
+    /// Gets the language used for authentication requests and web logins.
+    pub fn language(&self) -> ELanguage {
+        self.handler.language()
+    }
+    
+    /// Gets the hosts web requests are sent to.
+    pub fn hosts(&self) -> &crate::hosts::SteamHosts {
+        self.handler.hosts()
+    }
+    
+    /// Gets the store RSA keys used to encrypt passwords are cached in.
+    pub fn cache_store(&self) -> &Arc<dyn crate::cache_store::CacheStore> {
+        self.handler.cache_store()
+    }
+    
+    /// Gets the `sessionid` CSRF token. This is included in the cookies returned by 
+    /// `get_web_cookies` and must be sent as the `sessionid` parameter in POST requests to Steam 
+    /// community endpoints.
+    pub fn get_sessionid(&self) -> &str {
+        &self.sessionid
+    }
+    
+    /// Removes the cached RSA key used to encrypt the password of `account_name`, so the next 
+    /// login fetches a new one.
+    pub async fn invalidate_rsa_key(&self, account_name: &str) {
+        self.handler.invalidate_rsa_key(account_name).await;
+    }
+    
+    /// Generates a new `sessionid` and returns it. Cookies obtained after calling this method will 
+    /// contain the new `sessionid`.
+    pub fn rotate_sessionid(&mut self) -> &str {
+        self.sessionid = generate_sessionid_with(self.random.as_ref());
+        &self.sessionid
+    }
+    
     /// Gets the refresh token. This is populated after authenticatation. You can also assign a 
     /// refresh token calling `set_refresh_token` if you already have one.
     pub fn get_refresh_token(&self) -> Option<&String> {
-        self.access_token.as_ref()
+        self.refresh_token.as_ref().map(Secret::expose_secret)
     }
     
     /// Sets the refresh token. Will return an error if:
     ///
     /// - You set it to a token that isn't well-formed, or
     /// - You set it to an access token rather than a refresh token, or
+    /// - You set it to a token that was issued for a different platform type, or
     /// - You have already called `start_with_credentials` and you set it to a token that doesn't 
     /// belong to the same account, or
     /// - You have already set an `access_token` and you set this to a token that doesn't belong 
@@ -295,29 +617,20 @@ where
         
         let decoded = JwtPayload::from_str(&token)?;
         
-        if !decoded.aud.iter().any(|a| a == "derive") {
+        if !decoded.is_refresh_token() {
             return Err(LoginSessionError::ExpectedRefreshToken);
         }
         
-        let required_audience = match self.platform_type {
-            EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient => "client",
-            EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp => "mobile",
-            EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser => "web",
-            EAuthTokenPlatformType::k_EAuthTokenPlatformType_Unknown => "unknown",
-        };
-        
-        if !decoded.aud.iter().any(|a| a == required_audience) {
-            return Err(LoginSessionError::TokenPlatformDifferent(required_audience.into()));
-        }
+        decoded.assert_platform(self.platform_type)?;
         
-        if let Some(start_session_response) = &self.start_session_response {
-            if start_session_response.steamid() != u64::from(decoded.sub) {
+        if let Some(steamid) = self.started_steamid() {
+            if steamid != u64::from(decoded.sub) {
                 return Err(LoginSessionError::TokenIsForDifferentAccount);
             }
         }
         
         if let Some(access_token) = &self.access_token {
-            let decoded_access_token = JwtPayload::from_str(access_token)?;
+            let decoded_access_token = JwtPayload::from_str(access_token.expose_secret())?;
             
             if decoded_access_token.sub != decoded.sub {
                 return Err(LoginSessionError::TokenBelongsToOtherAccount);
@@ -325,7 +638,8 @@ where
         }
         
         // Everything checks out
-        self.refresh_token = Some(token);
+        self.refresh_token = Some(Secret::new(token));
+        self.set_state(LoginState::Authenticated);
         
         Ok(())
     }
@@ -361,17 +675,44 @@ where
                     };
                     
                     if is_authed {
-                        // We successfully authed already, no action needed
-                        return Ok(StartSessionResponse::Authenticated);
+                        if self.is_authenticated() {
+                            // We successfully authed already, no action needed
+                            return Ok(StartSessionResponse::Authenticated);
+                        }
+                        
+                        // The code was accepted, but another confirmation is still required
+                        continue;
                     }
                     
                     // We need a code from the user
                     let detail = if confirmation.associated_message().is_empty() {
+                        None
+                    } else {
                         Some(confirmation.associated_message().to_string())
+                    };
+                    
+                    let guard_code_provider = if confirmation_type == EAuthSessionGuardType::k_EAuthSessionGuardType_EmailCode {
+                        self.email_code_provider.as_ref().or(self.guard_code_provider.as_ref())
                     } else {
-                        None
+                        self.guard_code_provider.as_ref()
                     };
                     
+                    if let Some(guard_code_provider) = guard_code_provider.cloned() {
+                        let is_authed = self.provide_guard_code(
+                            guard_code_provider.as_ref(),
+                            confirmation_type,
+                            detail.clone(),
+                        ).await?;
+                        
+                        if is_authed {
+                            if self.is_authenticated() {
+                                return Ok(StartSessionResponse::Authenticated);
+                            }
+                            
+                            continue;
+                        }
+                    }
+                    
                     valid_actions.push(StartSessionResponseValidAction {
                         r#type: confirmation_type,
                         detail,
@@ -397,9 +738,49 @@ where
             }
         }
         
+        self.wait_for_guard(valid_actions.iter().map(|action| action.r#type).collect());
+        
         Ok(StartSessionResponse::ActionRequired(valid_actions))
     }
     
+    /// Moves to waiting for one of `guard_types`, notifying subscribers if the login can be 
+    /// confirmed out-of-band.
+    fn wait_for_guard(&mut self, guard_types: Vec<EAuthSessionGuardType>) {
+        let confirmation_types = guard_types
+            .iter()
+            .copied()
+            .filter(|confirmation_type| {
+                *confirmation_type == EAuthSessionGuardType::k_EAuthSessionGuardType_EmailConfirmation ||
+                *confirmation_type == EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceConfirmation
+            })
+            .collect::<Vec<_>>();
+        // subscribers were already notified when a code is accepted partway through a login
+        let already_notified = matches!(
+            &self.state,
+            LoginState::WaitingForGuard { guard_types: waiting_for } if confirmation_types
+                .iter()
+                .all(|confirmation_type| waiting_for.contains(confirmation_type))
+        );
+        
+        self.set_state(LoginState::WaitingForGuard {
+            guard_types,
+        });
+        
+        if !confirmation_types.is_empty() && !already_notified {
+            self.emit(LoginSessionEvent::AwaitingConfirmation(ConfirmationDetails {
+                account_name: self.account_name.clone(),
+                steamid: self.steamid(),
+                confirmation_types,
+                expires_at: self.clock.now() + self.login_timeout,
+            }));
+        }
+    }
+    
+    /// Whether the current login attempt has completed.
+    fn is_authenticated(&self) -> bool {
+        self.state == LoginState::Authenticated
+    }
+    
     /// Attempts steam guard code.
     pub async fn attempt_steam_guard_code(&mut self) -> Result<bool, LoginSessionError> {
         if let Some(steam_guard_code) = &self.steam_guard_code {
@@ -419,6 +800,54 @@ where
         Ok(false)
     }
     
+    /// Asks `guard_code_provider` for a code and submits it, asking again when the code is 
+    /// rejected. Every attempt shares the same deadline, the login timeout after the code was 
+    /// first asked for, and no more codes are asked for once it has passed. Returns true if 
+    /// authenticated.
+    async fn provide_guard_code(
+        &mut self,
+        guard_code_provider: &dyn GuardCodeProvider,
+        guard_type: EAuthSessionGuardType,
+        detail: Option<String>,
+    ) -> Result<bool, LoginSessionError> {
+        let expires_at = self.clock.now() + self.login_timeout;
+        
+        for attempt in 1..=MAX_GUARD_CODE_ATTEMPTS {
+            let remaining = (expires_at - self.clock.now()).to_std().unwrap_or_default();
+            
+            if remaining.is_zero() {
+                debug!("Steam Guard code was not provided in time");
+                return Ok(false);
+            }
+            
+            let context = GuardContext {
+                account_name: self.account_name.clone(),
+                steamid: self.steamid(),
+                detail: detail.clone(),
+                attempt,
+                expires_at,
+                remaining,
+            };
+            let Some(code) = guard_code_provider.provide(guard_type, context).await else {
+                return Ok(false);
+            };
+            
+            match self.submit_steam_guard_code(code).await {
+                Ok(()) => return Ok(true),
+                // the result is wrapped in the transport's error
+                Err(LoginSessionError::AuthenticationClient(error)) if attempt < MAX_GUARD_CODE_ATTEMPTS && matches!(
+                    error.eresult(),
+                    Some(EResult::TwoFactorCodeMismatch | EResult::InvalidLoginAuthCode)
+                ) => {
+                    debug!("Steam Guard code was rejected on attempt {attempt}");
+                },
+                Err(error) => return Err(error),
+            }
+        }
+        
+        Ok(false)
+    }
+    
     /// Attempts email code authentication.
     async fn attempt_email_code_auth(&mut self) -> Result<bool, LoginSessionError> {
         if self.attempt_steam_guard_code().await? {
@@ -440,6 +869,7 @@ where
             ).await?;
             
             if response.result == EResult::OK {
+                self.trace.record_guard_type(EAuthSessionGuardType::k_EAuthSessionGuardType_MachineToken);
                 self.do_poll().await?;
                 return Ok(true);
             }
@@ -459,6 +889,9 @@ where
     /// Note that an incorrect email code will fail with EResult value 
     /// [`EResult::InvalidLoginAuthCode`] (65), and an incorrect TOTP code will fail with EResult 
     /// value [`EResult::TwoFactorCodeMismatch`] (88).
+    /// 
+    /// If Steam still requires a confirmation after accepting the code, the state moves to 
+    /// [`LoginState::WaitingForGuard`] and `poll` should be called to wait for it.
     pub async fn submit_steam_guard_code(
         &mut self,
         auth_code: String,
@@ -488,7 +921,7 @@ where
             EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceCode
         };
         let client_id = start_session_response.client_id();
-        let steamid = start_session_response.steamid();
+        let steamid = SteamID::from(start_session_response.steamid());
         
         self.handler.submit_steam_guard_code(
             client_id,
@@ -496,8 +929,29 @@ where
             auth_code,
             code_type
         ).await?;
-        // should authenticate
-        self.do_poll().await?;
+        self.trace.record_guard_type(code_type);
+        
+        // Some accounts require a confirmation after the code, e.g. an email code followed by 
+        // approving the login in the mobile app
+        if !self.do_poll().await? {
+            let remaining = self.start_session_response.as_ref()
+                .map(|start_session_response| {
+                    start_session_response.allowed_confirmations
+                        .iter()
+                        .map(|confirmation| confirmation.confirmation_type())
+                        .filter(|confirmation_type| {
+                            *confirmation_type == EAuthSessionGuardType::k_EAuthSessionGuardType_EmailConfirmation ||
+                            *confirmation_type == EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceConfirmation
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            
+            if !remaining.is_empty() {
+                debug!("Steam Guard code was accepted, but the login still needs confirmation");
+                self.wait_for_guard(remaining);
+            }
+        }
         
         Ok(())
     }
@@ -508,9 +962,125 @@ where
     /// 
     /// Returns an array of strings. Each string contains a cookie, e.g.
     /// `"steamLoginSecure=blahblahblahblah; Path=/; Secure; HttpOnly; SameSite=None; Domain=steamcommunity.com"`.
+    /// 
+    /// A `steamLoginSecure` and `sessionid` cookie is returned for each domain in `domains`. Use
+    /// [`CookieDomain::DEFAULT`] for `steamcommunity.com`, `store.steampowered.com`, and 
+    /// `help.steampowered.com`. Requesting only the domains you need avoids a round-trip per 
+    /// domain, which can be slow through a proxy.
+    /// 
+    /// Cookies are returned for the domains they could be issued for, even if some failed. Use 
+    /// `get_web_cookies_by_domain` to find out which failed and why. Fails with 
+    /// [`LoginSessionError::WebCookies`] if no domain succeeded.
     pub async fn get_web_cookies(
         &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<Vec<String>, LoginSessionError> {
+        let web_cookies = self.get_web_cookies_by_domain(domains).await?;
+        
+        if web_cookies.succeeded().is_empty() {
+            return Err(LoginSessionError::WebCookies(web_cookies));
+        }
+        
+        Ok(web_cookies.cookies())
+    }
+    
+    /// Gets web cookies for `domains` as a [`CookieSet`], for looking cookies up by domain and 
+    /// name or passing them to another HTTP client. See `get_web_cookies`.
+    pub async fn get_web_cookie_set(
+        &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<CookieSet, LoginSessionError> {
+        let cookies = self.get_web_cookies(domains).await?;
+        
+        Ok(CookieSet::parse(&cookies))
+    }
+    
+    /// Gets web cookies for `domains` along with the result of each domain, so that cookies can 
+    /// be used for the domains that succeeded when others failed, e.g. when a `settoken` request 
+    /// is blocked by a CDN. Errors affecting every domain, such as `finalizelogin` failing, are 
+    /// returned as an error.
+    pub async fn get_web_cookies_by_domain(
+        &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<WebCookies, LoginSessionError> {
+        let web_cookies = self.generate_web_cookies(domains).await?;
+        let cookies = web_cookies.cookies();
+        
+        if !cookies.is_empty() {
+            self.web_cookies = Some(CachedWebCookies {
+                domains: web_cookies.succeeded(),
+                expires_at: web_cookies_expiry(&cookies),
+                cookies: Secret::new(cookies),
+            });
+        }
+        
+        Ok(web_cookies)
+    }
+    
+    /// Gets web cookies for `domains`, reusing the cookies from a previous call when possible. 
+    /// Cookies are regenerated when they expire within the refresh margin (10 minutes by 
+    /// default), when they were generated for other domains, or after calling 
+    /// `invalidate_web_cookies`. Long-running programs should use this instead of 
+    /// `get_web_cookies` so their cookies never go stale.
+    pub async fn get_cached_web_cookies(
+        &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<Vec<String>, LoginSessionError> {
+        if let Some(web_cookies) = &self.web_cookies {
+            if web_cookies.is_fresh_for(domains, self.clock.now(), self.web_cookie_refresh_margin) {
+                return Ok(web_cookies.cookies.expose_secret().clone());
+            }
+        }
+        
+        self.get_web_cookies(domains).await
+    }
+    
+    /// Call this when a request made with web cookies is rejected with 401 Unauthorized. Discards 
+    /// the cached cookies, refreshes the access token if it is used as the session cookie, and 
+    /// returns newly generated cookies for `domains`.
+    pub async fn handle_web_unauthorized(
+        &mut self,
+        domains: &[CookieDomain],
     ) -> Result<Vec<String>, LoginSessionError> {
+        self.invalidate_web_cookies();
+        
+        if self.platform_type == EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient ||
+        self.platform_type == EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp {
+            self.refresh_access_token().await?;
+        }
+        
+        self.get_web_cookies(domains).await
+    }
+    
+    /// Checks whether `cookies` still hold a valid session on `steamcommunity.com` using this 
+    /// session's client. See [`crate::cookies::verify_web_session`].
+    pub async fn verify_web_session(
+        &self,
+        cookies: &[String],
+    ) -> Result<bool, LoginSessionError> {
+        Ok(crate::cookies::verify_web_session(&self.client, cookies).await?)
+    }
+    
+    /// Discards the cookies cached by `get_cached_web_cookies`.
+    pub fn invalidate_web_cookies(&mut self) {
+        self.web_cookies = None;
+    }
+    
+    /// Gets the time the most recently generated web cookies expire.
+    pub fn web_cookies_expiry(&self) -> Option<DateTime> {
+        self.web_cookies.as_ref()?.expires_at
+    }
+    
+    /// Sets how long before expiring `get_cached_web_cookies` will regenerate web cookies.
+    pub fn set_web_cookie_refresh_margin(&mut self, margin: Duration) {
+        self.web_cookie_refresh_margin = margin;
+    }
+    
+    /// Generates web cookies for `domains`.
+    async fn generate_web_cookies(
+        &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<WebCookies, LoginSessionError> {
         #[derive(Debug, Deserialize)]
         struct TransferInfo {
             url: String,
@@ -528,11 +1098,10 @@ where
             transfer_info: Option<Vec<TransferInfo>>,
         }
         
-        async fn get_cookies(request: RequestBuilder) -> Option<Vec<String>> {
-            let response = request.send().await.ok()?;
-            let headers = response.headers();
-            let set_cookie = headers.get_all(SET_COOKIE);
-            let cookies = set_cookie
+        /// Gets the cookies set by `response`, with their domain set to the host it came from.
+        fn set_cookies(response: &reqwest::Response) -> Vec<String> {
+            response.headers()
+                .get_all(SET_COOKIE)
                 .into_iter()
                 .flat_map(|header| {
                     let value = header.to_str().ok()?;
@@ -543,24 +1112,35 @@ where
                     
                     let domain = cookie.domain()?;
                     
-                    Some(format!("{}={}; Path=/; Secure; HttpOnly; SameSite=None; Domain={}", cookie.name(), cookie.value(), domain))
+                    Some(helpers::format_cookie(cookie.name(), cookie.value(), domain))
                 })
-                .collect::<Vec<String>>();
+                .collect::<Vec<String>>()
+        }
+        
+        async fn get_cookies(request: RequestBuilder) -> Result<Vec<String>, DomainCookieError> {
+            let response = request.send().await?;
+            let status = response.status();
             
-            if cookies.is_empty() {
-                return None;
+            if status.is_client_error() || status.is_server_error() {
+                return Err(DomainCookieError::Status(status));
             }
             
+            let cookies = set_cookies(&response);
+            
             if !cookies.iter().any(|cookie| cookie.contains("steamLoginSecure=")) {
-                return None;
+                return Err(DomainCookieError::NoLoginCookie);
             }
             
-            Some(cookies)
+            Ok(cookies)
+        }
+        
+        if domains.is_empty() {
+            return Err(LoginSessionError::NoCookieDomains);
         }
         
         let refresh_token = self.refresh_token.as_ref()
             .ok_or_else(|| LoginSessionError::NoRefreshToken)?;
-        let sessionid = generate_sessionid();
+        let sessionid = self.sessionid.clone();
         let steamid = self.steamid()
             .ok_or_else(|| LoginSessionError::NoRefreshToken)?;
         
@@ -575,7 +1155,7 @@ where
             // the access token being a JWT (as Valve may change it at any point).
             if self.access_token.is_none() ||
             self.access_token_set_at
-                .map(|datetime| Utc::now() - datetime > Duration::try_minutes(10).unwrap())
+                .map(|datetime| self.clock.now() - datetime > Duration::try_minutes(10).unwrap())
                 .unwrap_or(false) {
                 self.refresh_access_token().await?;
             }
@@ -584,28 +1164,66 @@ where
                 .ok_or(LoginSessionError::NoAccessToken)?;
             let steamid = self.steamid()
                 .ok_or(LoginSessionError::NoRefreshToken)?;
-            let cookie_value = format!("{}||{}", u64::from(steamid), access_token);
+            let cookie_value = format!("{}||{}", u64::from(steamid), access_token.expose_secret());
             let encoded_cookie_value = form_urlencoded::byte_serialize(cookie_value.as_bytes())
                 .collect::<String>();
             
-            return Ok(vec![
-                format!("steamLoginSecure={}", encoded_cookie_value),
-                format!("sessionid={}", sessionid),
-            ]);
+            let domains = domains
+                .iter()
+                .map(|domain| {
+                    let mut cookies = vec![
+                        helpers::format_cookie("steamLoginSecure", &encoded_cookie_value, domain.hostname()),
+                        helpers::format_cookie("sessionid", &sessionid, domain.hostname()),
+                    ];
+                    
+                    // e.g. mobile confirmations expect the mobile app's cookies
+                    for (name, value) in self.client_preset.cookies() {
+                        cookies.push(helpers::format_cookie(name, value, domain.hostname()));
+                    }
+                    
+                    DomainCookies {
+                        domain: *domain,
+                        result: Ok(cookies),
+                    }
+                })
+                .collect();
+            
+            return Ok(WebCookies {
+                domains,
+            });
         }
         
         let mut headers = create_api_headers()?;
         headers.insert("Origin", HeaderValue::from_str("https://steamcommunity.com")?);
         headers.insert("Referer", HeaderValue::from_str("https://steamcommunity.com/")?);
+        headers.insert("Accept-Language", HeaderValue::from_str(self.handler.language().language_tag())?);
         let form = reqwest::multipart::Form::new()
-            .text("nonce", refresh_token.clone())
+            .text("nonce", refresh_token.expose_secret().clone())
             .text("sessionid", sessionid.clone())
             .text("redir", "https://steamcommunity.com/login/home/?goto=");
-        let response = self.client.post("https://login.steampowered.com/jwt/finalizelogin")
+        let response = self.client.post(self.handler.hosts().login_url("jwt/finalizelogin"))
             .headers(headers)
             .multipart(form)
             .send()
-            .await?
+            .await?;
+        let status = response.status();
+        
+        if status.is_client_error() || status.is_server_error() {
+            return Err(LoginSessionError::FinalizeLoginStatus(status));
+        }
+        
+        // finalizelogin sets the remember-login cookies for login.steampowered.com
+        let remember_login = if self.remember_login_cookies {
+            set_cookies(&response)
+                .into_iter()
+                .filter(|cookie| REMEMBER_LOGIN_COOKIES
+                    .iter()
+                    .any(|name| cookie.starts_with(&format!("{name}="))))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let response = response
             .json::<Response>()
             .await?;
         
@@ -615,53 +1233,131 @@ where
             }
         }
         
-        let mut transfers = response.transfer_info
-            .ok_or(LoginSessionError::MalformedResponse)?
-            .into_iter()
-            .map(|transfer_info| {
-                let form = value_to_multipart(transfer_info.params)
-                    .text("steamID", u64::from(steamid).to_string());
-                let request = self.client.post(&transfer_info.url).multipart(form);
+        let language_tag = self.handler.language().language_tag();
+        let mut transfer_infos = response.transfer_info
+            .ok_or(LoginSessionError::MalformedResponse)?;
+        let mut transfers = domains
+            .iter()
+            .map(|domain| {
+                // only request cookies for the domains that were asked for
+                let position = transfer_infos
+                    .iter()
+                    .position(|transfer_info| {
+                        url::Url::parse(&transfer_info.url)
+                            .ok()
+                            .and_then(|url| url.host_str().and_then(CookieDomain::from_hostname))
+                            .is_some_and(|transfer_domain| transfer_domain == *domain)
+                    });
+                let request = position.map(|position| {
+                    let transfer_info = transfer_infos.swap_remove(position);
+                    let form = value_to_multipart(transfer_info.params)
+                        .text("steamID", u64::from(steamid).to_string());
+                    
+                    debug!("POST {}", transfer_info.url);
+                    self.client.post(&transfer_info.url)
+                        .header("Accept-Language", language_tag)
+                        .multipart(form)
+                });
+                let domain = *domain;
                 
-                // send a request that will return cookies if it contains cookies
-                log::debug!("POST {}", transfer_info.url);
-                get_cookies(request)
+                async move {
+                    let result = match request {
+                        // send a request that will return cookies if it contains cookies
+                        Some(request) => get_cookies(request).await,
+                        None => Err(DomainCookieError::NoTransferInfo),
+                    };
+                    
+                    DomainCookies {
+                        domain,
+                        result,
+                    }
+                }
             })
             .collect::<FuturesOrdered<_>>();
-        let mut cookies = Vec::new();
+        let mut web_cookies = WebCookies {
+            remember_login,
+            ..WebCookies::default()
+        };
         
-        while let Some(transfer) = transfers.next().await {
-            if let Some(mut domain_cookies) = transfer {
-                cookies.append(&mut domain_cookies);
+        while let Some(mut domain_cookies) = transfers.next().await {
+            if let Ok(cookies) = &mut domain_cookies.result {
+                cookies.retain(|cookie| !cookie.starts_with("sessionid="));
+                cookies.push(helpers::format_cookie("sessionid", &sessionid, domain_cookies.domain.hostname()));
             }
+            
+            web_cookies.domains.push(domain_cookies);
         }
         
-        if cookies.is_empty() {
-            return Err(LoginSessionError::NoCookiesInResponse);
-        }
+        Ok(web_cookies)
+    }
+    
+    /// Gets the tokens for this session along with web cookies for `domains`.
+    pub async fn get_session_tokens(
+        &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<SessionTokens, LoginSessionError> {
+        let cookies = self.get_web_cookies(domains).await?;
+        let steamid = self.steamid()
+            .ok_or(LoginSessionError::NoRefreshToken)?;
         
-        let mut cookies = cookies
-            .into_iter()
-            .filter(|cookie| !cookie.contains("sessionid="))
-            .collect::<Vec<_>>();
+        self.save_tokens(cookies.clone()).await?;
         
-        cookies.push(format!("sessionid={sessionid}"));
+        Ok(SessionTokens {
+            steamid,
+            refresh_token: self.refresh_token.clone(),
+            access_token: self.access_token.clone(),
+            cookies,
+        })
+    }
+    
+    /// Builds a `reqwest::Client` preloaded with web cookies for `domains`. If this session was 
+    /// created with a proxy, the client will use the same proxy.
+    pub async fn build_authenticated_client(
+        &mut self,
+        domains: &[CookieDomain],
+    ) -> Result<Client, LoginSessionError> {
+        let tokens = self.get_session_tokens(domains).await?;
+        let builder = helpers::configure_client(
+            tokens.client_builder(),
+            self.client_preset,
+            self.language(),
+            &self.http_pool,
+            self.proxy.as_ref(),
+        )?;
         
-        Ok(cookies)
+        Ok(builder.build()?)
+    }
+    
+    /// Moves this session into a background task and returns a cheap-to-clone handle to it, so 
+    /// multiple parts of an application can share the session.
+    pub fn into_handle(self) -> LoginSessionHandle
+    where
+        T: 'static,
+    {
+        LoginSessionHandle::spawn(self)
     }
     
     /// Refreshes the access token. As long as a `refresh_token` is set, you can call this method 
     /// to obtain a new access token. 
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "login.refresh_access_token",
+        skip_all,
+        fields(steamid = ?self.steamid().map(u64::from)),
+    ))]
     pub async fn refresh_access_token(&mut self) -> Result<(), LoginSessionError> {
         let refresh_token = self.refresh_token.as_ref()
             .ok_or_else(|| LoginSessionError::NoRefreshToken)?;
         let access_token = self.handler.generate_access_token_for_app(
-            refresh_token.clone(),
+            refresh_token.expose_secret().clone(),
             false,
         ).await?;
         let access_token = access_token.access_token().to_string();
         
         self.set_access_token(access_token)?;
+        self.audit(
+            AuditEventKind::TokenIssued(crate::TokenKind::AccessToken),
+            self.access_token.as_ref().map(|access_token| access_token.expose_secret().as_str()),
+        );
         
         Ok(())
     }
@@ -674,43 +1370,141 @@ where
     /// accessed using the {@link refreshToken} property), or false if no new refresh token was 
     /// issued. Regardless of the return value, the {@link accessToken} property is always 
     /// updated with a fresh access token (unless there was an error).
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "login.renew_refresh_token",
+        skip_all,
+        fields(steamid = ?self.steamid().map(u64::from)),
+    ))]
     pub async fn renew_refresh_token(&mut self) -> Result<bool, LoginSessionError> {
+        let generated = self.generate_access_token(GenerateAccessTokenOptions::renew()).await?;
+        
+        Ok(generated.is_renewed())
+    }
+    
+    /// Generates an access token with `options` and stores it, along with the refresh token if 
+    /// Steam issued a new one. Returns the details decoded from the access token.
+    pub async fn generate_access_token(
+        &mut self,
+        options: GenerateAccessTokenOptions,
+    ) -> Result<GeneratedAccessToken, LoginSessionError> {
         let refresh_token = self.refresh_token.as_ref()
             .ok_or_else(|| LoginSessionError::NoRefreshToken)?;
-        let response = self.handler.generate_access_token_for_app(refresh_token.clone(), true)
-            .await?;
-        let access_token = response.access_token();
-        let refresh_token = response.refresh_token();
+        let generated = self.handler.generate_access_token_with_options(
+            refresh_token.expose_secret().clone(),
+            options,
+        ).await?;
+        
+        self.set_access_token(generated.access_token.expose_secret().clone())?;
+        self.audit(
+            AuditEventKind::TokenIssued(crate::TokenKind::AccessToken),
+            Some(generated.access_token.expose_secret()),
+        );
+        
+        if let Some(refresh_token) = &generated.refresh_token {
+            self.set_refresh_token(refresh_token.expose_secret().clone())?;
+            self.audit(AuditEventKind::TokenRotated, Some(refresh_token.expose_secret()));
+            self.save_tokens(Vec::new()).await?;
+        }
         
-        self.set_access_token(access_token.to_owned())?;
-        self.set_refresh_token(refresh_token.to_owned())?;
+        Ok(generated)
+    }
+    
+    /// Revokes the refresh token, logging the session out permanently. An access token is 
+    /// generated first if the session does not have one. The tokens and cached cookies are 
+    /// cleared on success.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "login.revoke_refresh_token",
+        skip_all,
+        fields(steamid = ?self.steamid().map(u64::from)),
+    ))]
+    pub async fn revoke_refresh_token(&mut self) -> Result<(), LoginSessionError> {
+        if self.access_token.is_none() {
+            self.refresh_access_token().await?;
+        }
         
-        Ok(!access_token.is_empty())
+        let refresh_token = self.refresh_token.as_ref()
+            .ok_or_else(|| LoginSessionError::NoRefreshToken)?;
+        let access_token = self.access_token.as_ref()
+            .map(|access_token| access_token.expose_secret().clone());
+        
+        self.handler.revoke_token(
+            refresh_token.expose_secret().clone(),
+            EAuthTokenRevokeAction::k_EAuthTokenRevokePermanent,
+            access_token,
+        ).await?;
+        self.audit(
+            AuditEventKind::TokenRevoked,
+            self.refresh_token.as_ref().map(|refresh_token| refresh_token.expose_secret().as_str()),
+        );
+        self.refresh_token = None;
+        self.access_token = None;
+        self.access_token_set_at = None;
+        self.web_cookies = None;
+        
+        Ok(())
     }
     
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "login.poll", skip_all))]
     pub async fn poll(&mut self) -> Result<(), LoginSessionError> {
-        let polling_started_time = Utc::now();
+        let polling_started_time = self.clock.now();
         let poll_interval = self.start_session_response.as_ref()
             .ok_or(LoginSessionError::LoginSessionHasNotStarted)?
             .interval();
+        let mut attempts: u32 = 0;
+        
+        // the challenge is only useful for as long as we keep polling
+        if let Some(qr_challenge) = self.qr_challenge.as_mut() {
+            qr_challenge.expires_at = polling_started_time + self.login_timeout;
+        }
+        
+        self.set_state(LoginState::Polling);
         
         loop {
-            let total_polling_time = Utc::now() - polling_started_time;
+            if self.abort.is_aborted() {
+                debug!("Login aborted after {attempts} polls");
+                return Err(self.record_failure(LoginSessionError::LoginAborted));
+            }
+            
+            let total_polling_time = self.clock.now() - polling_started_time;
             
             if total_polling_time >= self.login_timeout {
+                debug!("Login timed out after {attempts} polls");
+                self.set_state(LoginState::Failed {
+                    kind: crate::ErrorKind::Timeout,
+                    reason: "the login was not confirmed in time".into(),
+                });
                 return Ok(());
             }
             
-            if self.do_poll().await? {
+            attempts += 1;
+            
+            let is_complete = self.do_poll().await
+                .map_err(|error| self.record_failure(error))?;
+            
+            if is_complete {
+                debug!(
+                    "Login completed for {} after {attempts} polls",
+                    MaskedAccountName(self.account_name.as_deref().unwrap_or_default())
+                );
+                crate::metrics::login_polls(attempts);
                 return Ok(());
             }
             
-            // poll again
-            async_std::task::sleep(std::time::Duration::from_secs(poll_interval as u64)).await;
+            // poll again, unless aborted while waiting
+            let pending_for = (self.clock.now() - polling_started_time).to_std().unwrap_or_default();
+            let delay = self.poll_policy.delay(
+                std::time::Duration::from_secs(poll_interval as u64),
+                pending_for,
+                self.random.as_ref(),
+            );
+            let sleep = self.clock.sleep(delay);
+            
+            futures::future::select(sleep, Box::pin(self.abort.aborted())).await;
         }
     }
     
     /// Performs a poll. Returns true if complete.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "login.poll_iteration", skip_all))]
     async fn do_poll(&mut self) -> Result<bool, LoginSessionError> {
         let start_session_response = self.start_session_response.as_ref()
             .ok_or(LoginSessionError::LoginSessionHasNotStarted)?;
@@ -721,18 +1515,42 @@ where
             request_id.into(),
         ).await?;
         
+        self.trace.record_poll();
+        
         if response.had_remote_interaction() {
+            self.trace.record_confirmation();
+        }
+        
+        // QR challenges expire, so Steam issues a new client ID and challenge while pending
+        if response.refresh_token().is_empty() && response.new_client_id() != 0 {
+            if let Some(start_session_response) = self.start_session_response.as_mut() {
+                start_session_response.set_client_id(response.new_client_id());
+            }
             
+            if !response.new_challenge_url().is_empty() {
+                if let Some(qr_challenge) = self.qr_challenge.as_mut() {
+                    debug!("QR challenge rotated");
+                    qr_challenge.url = response.new_challenge_url().to_owned();
+                    
+                    let qr_challenge = qr_challenge.clone();
+                    
+                    self.emit(LoginSessionEvent::QrChallengeChanged(qr_challenge));
+                }
+            }
         }
         
         if !response.refresh_token().is_empty() {
             let client_id = response.new_client_id();
             
+            if !response.account_name().is_empty() {
+                self.account_name = Some(response.account_name().to_owned());
+            }
+            
             if let Some(start_session_response) = self.start_session_response.as_mut() {
                 start_session_response.set_client_id(client_id);
             }
             
-            self.access_token = Some(response.access_token().to_owned());
+            self.access_token = Some(Secret::new(response.access_token().to_owned()));
             self.set_access_token(response.access_token().to_owned())?;
             self.set_refresh_token(response.refresh_token().to_owned())?;
             
@@ -751,12 +1569,37 @@ where
                 self.refresh_access_token().await?;
             }
             
+            self.audit(AuditEventKind::LoginSucceeded, Some(response.refresh_token()));
+            self.save_tokens(Vec::new()).await?;
+            
+            if let Some(steamid) = self.steamid() {
+                self.emit(LoginSessionEvent::Authenticated(steamid));
+            }
+            
+            let cm_endpoint = self.handler.transport().cm_endpoint();
+            let proxy = self.proxy.as_ref()
+                .map(|proxy| format!("{}:{}", proxy.host(), proxy.port()));
+            
+            if let Some(report) = self.trace.finish(self.clock.now(), cm_endpoint, proxy) {
+                crate::metrics::login_phases(&report);
+                self.login_report = Some(report.clone());
+                self.emit(LoginSessionEvent::Completed(report));
+            }
+            
             return Ok(true);
         }
         
         Ok(false)
     }
     
+    /// Gets the SteamID from the start session response, if known.
+    fn started_steamid(&self) -> Option<u64> {
+        self.start_session_response
+            .as_ref()
+            .map(|start_session_response| start_session_response.steamid())
+            .filter(|steamid| *steamid != 0)
+    }
+    
     fn verify_started(&self, must_have_steamid: bool) -> Result<(), LoginSessionError> {
         if self.start_session_response.is_none() {
             return Err(LoginSessionError::LoginSessionHasNotStarted);
@@ -769,3 +1612,160 @@ where
         Ok(())
     }
 }
+
+impl LoginSession<WebSocketCMTransport> {
+    /// Moves the session to a new CM server connected through `proxy`, or directly if `None`, 
+    /// so it can continue from a fresh IP when its current proxy gets flagged. A login in 
+    /// progress carries on over the new connection, and the old connection is closed. Web 
+    /// requests made by the session use a new client with the same proxy, replacing any client 
+    /// set when building the session.
+    pub async fn reconnect_with_proxy(
+        &mut self,
+        proxy: Option<Socks5ProxyConfig>,
+    ) -> Result<(), LoginSessionError> {
+        let client = helpers::configure_client(
+            Client::builder(),
+            self.client_preset,
+            self.language(),
+            &self.http_pool,
+            proxy.as_ref(),
+        )?.build()?;
+        let transport = self.handler.transport()
+            .reconnect_with_proxy(proxy.clone())
+            .await
+            .map_err(AuthenticationClientError::WebSocketCM)?;
+        let old = self.handler.set_transport(transport);
+        
+        // the old connection stays open until it is closed
+        if let Err(error) = old.close().await {
+            debug!("Failed to close CM connection to {}: {error}", old.endpoint());
+        }
+        
+        self.handler.set_client(client.clone());
+        self.client = client;
+        self.proxy = proxy;
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::hosts::SteamHosts;
+    use steam_session_proto::steammessages_auth_steamclient::CAuthentication_AllowedConfirmation;
+    use std::sync::Mutex;
+    use async_trait::async_trait;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    
+    /// Serves responses that reject every request with `eresult`.
+    async fn serve_eresult(eresult: EResult) -> SteamHosts {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hosts = SteamHosts {
+            api: format!("http://{}", listener.local_addr().unwrap()),
+            ..Default::default()
+        };
+        
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                
+                // reads the whole request so the connection is not reset before the response
+                loop {
+                    let n = match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => n,
+                    };
+                    
+                    request.extend_from_slice(&buf[..n]);
+                    
+                    if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                        let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                        let content_length = head
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .and_then(|value| value.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        
+                        if request.len() >= end + 4 + content_length {
+                            break;
+                        }
+                    }
+                }
+                
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nx-eresult: {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    eresult as i32,
+                );
+                
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        
+        hosts
+    }
+    
+    /// Provides codes, advancing the clock each time as if the user took a while to enter them.
+    #[derive(Debug)]
+    struct SlowGuardCodeProvider {
+        clock: Arc<MockClock>,
+        delay: std::time::Duration,
+        contexts: Mutex<Vec<GuardContext>>,
+    }
+    
+    #[async_trait]
+    impl GuardCodeProvider for SlowGuardCodeProvider {
+        async fn provide(&self, _guard: EAuthSessionGuardType, ctx: GuardContext) -> Option<String> {
+            self.contexts.lock().unwrap().push(ctx);
+            self.clock.advance(self.delay);
+            Some(String::from("ABCDE"))
+        }
+    }
+    
+    #[tokio::test]
+    async fn keeps_the_guard_code_deadline_between_attempts() {
+        let started_at = DateTime::from_timestamp(1700000000, 0).unwrap();
+        let clock = Arc::new(MockClock::new(started_at));
+        let hosts = serve_eresult(EResult::TwoFactorCodeMismatch).await;
+        let mut session = LoginSession::builder(
+            WebApiTransport::new().with_hosts(hosts),
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp,
+        )
+            .clock(Arc::clone(&clock) as Arc<dyn Clock>)
+            .login_timeout(std::time::Duration::from_secs(15))
+            .build()
+            .unwrap();
+        let mut start_session_response = CAuthentication_BeginAuthSessionViaCredentials_Response::new();
+        let mut allowed_confirmation = CAuthentication_AllowedConfirmation::new();
+        
+        allowed_confirmation.set_confirmation_type(EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceCode);
+        start_session_response.set_client_id(1);
+        start_session_response.set_steamid(76561197960287930);
+        start_session_response.allowed_confirmations.push(allowed_confirmation);
+        session.start_session_response = Some(start_session_response);
+        
+        let provider = SlowGuardCodeProvider {
+            clock: Arc::clone(&clock),
+            delay: std::time::Duration::from_secs(10),
+            contexts: Mutex::new(Vec::new()),
+        };
+        let is_authed = session.provide_guard_code(
+            &provider,
+            EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceCode,
+            None,
+        ).await.unwrap();
+        let contexts = provider.contexts.into_inner().unwrap();
+        let expires_at = started_at + Duration::try_seconds(15).unwrap();
+        
+        assert!(!is_authed);
+        // the deadline passed while the second code was being entered, so no third is asked for
+        assert_eq!(contexts.len(), 2);
+        assert_eq!(contexts[0].expires_at, expires_at);
+        assert_eq!(contexts[0].remaining, std::time::Duration::from_secs(15));
+        assert_eq!(contexts[1].expires_at, expires_at);
+        assert_eq!(contexts[1].remaining, std::time::Duration::from_secs(5));
+    }
+}
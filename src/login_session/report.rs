@@ -0,0 +1,171 @@
+use super::LoginState;
+use crate::enums::EAuthSessionGuardType;
+use crate::types::DateTime;
+use std::time::Duration;
+
+/// How a completed login went, from [`LoginSession::login_report`](super::LoginSession::login_report)
+/// or [`LoginSessionEvent::Completed`](super::LoginSessionEvent::Completed). Record these to track
+/// the health of logins over time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginReport {
+    /// When the login was started.
+    pub started_at: DateTime,
+    /// When the login completed.
+    pub completed_at: DateTime,
+    /// How long starting the session took, including encrypting the password.
+    pub starting: Duration,
+    /// How long the login waited for a Steam Guard code or confirmation.
+    pub waiting_for_guard: Duration,
+    /// How long was spent polling for the login to be confirmed.
+    pub polling: Duration,
+    /// The number of times the status of the login was polled.
+    pub polls: u32,
+    /// The endpoint of the CM server the login went through, if any.
+    pub cm_endpoint: Option<String>,
+    /// The proxy the login went through, as `host:port`.
+    pub proxy: Option<String>,
+    /// The Steam Guard types used to complete the login, in the order they were used. This is
+    /// [`EAuthSessionGuardType::k_EAuthSessionGuardType_None`] if none were needed.
+    pub guard_types: Vec<EAuthSessionGuardType>,
+}
+
+impl LoginReport {
+    /// Gets how long the login took in total.
+    pub fn total(&self) -> Duration {
+        (self.completed_at - self.started_at).to_std().unwrap_or_default()
+    }
+}
+
+/// Tracks a login in progress for its [`LoginReport`].
+#[derive(Debug, Clone, Default)]
+pub(super) struct LoginTrace {
+    started_at: Option<DateTime>,
+    phase_started_at: Option<DateTime>,
+    starting: Duration,
+    waiting_for_guard: Duration,
+    polling: Duration,
+    polls: u32,
+    awaiting_confirmation: Vec<EAuthSessionGuardType>,
+    guard_types: Vec<EAuthSessionGuardType>,
+}
+
+impl LoginTrace {
+    /// Starts tracking a login started at `now`.
+    pub(super) fn start(now: DateTime) -> Self {
+        Self {
+            started_at: Some(now),
+            phase_started_at: Some(now),
+            ..Self::default()
+        }
+    }
+    
+    /// Moves from `from` to `to` at `now`, adding the time spent in `from` to its phase.
+    pub(super) fn transition(&mut self, from: &LoginState, to: &LoginState, now: DateTime) {
+        let Some(phase_started_at) = self.phase_started_at.replace(now) else {
+            return;
+        };
+        let elapsed = (now - phase_started_at).to_std().unwrap_or_default();
+        
+        match from {
+            LoginState::Started => self.starting += elapsed,
+            LoginState::WaitingForGuard { .. } => self.waiting_for_guard += elapsed,
+            LoginState::Polling => self.polling += elapsed,
+            _ => {},
+        }
+        
+        if let LoginState::WaitingForGuard { guard_types } = to {
+            self.awaiting_confirmation = guard_types
+                .iter()
+                .copied()
+                .filter(|guard_type| {
+                    *guard_type == EAuthSessionGuardType::k_EAuthSessionGuardType_EmailConfirmation ||
+                    *guard_type == EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceConfirmation
+                })
+                .collect();
+        }
+    }
+    
+    /// Records a poll of the login's status.
+    pub(super) fn record_poll(&mut self) {
+        if self.started_at.is_some() {
+            self.polls += 1;
+        }
+    }
+    
+    /// Records that `guard_type` was used.
+    pub(super) fn record_guard_type(&mut self, guard_type: EAuthSessionGuardType) {
+        if self.started_at.is_some() && !self.guard_types.contains(&guard_type) {
+            self.guard_types.push(guard_type);
+        }
+    }
+    
+    /// Records that the login was confirmed out-of-band, using one of the confirmations it was
+    /// waiting for.
+    pub(super) fn record_confirmation(&mut self) {
+        for guard_type in std::mem::take(&mut self.awaiting_confirmation) {
+            self.record_guard_type(guard_type);
+        }
+    }
+    
+    /// Finishes tracking the login, which completed at `now`. Returns `None` if no login was
+    /// being tracked.
+    pub(super) fn finish(
+        &mut self,
+        now: DateTime,
+        cm_endpoint: Option<String>,
+        proxy: Option<String>,
+    ) -> Option<LoginReport> {
+        let trace = std::mem::take(self);
+        let started_at = trace.started_at?;
+        let guard_types = if trace.guard_types.is_empty() {
+            vec![EAuthSessionGuardType::k_EAuthSessionGuardType_None]
+        } else {
+            trace.guard_types
+        };
+        
+        Some(LoginReport {
+            started_at,
+            completed_at: now,
+            starting: trace.starting,
+            waiting_for_guard: trace.waiting_for_guard,
+            polling: trace.polling,
+            polls: trace.polls,
+            cm_endpoint,
+            proxy,
+            guard_types,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    
+    #[test]
+    fn adds_time_to_phases() {
+        let at = |seconds| Utc.timestamp_opt(1_700_000_000 + seconds, 0).unwrap();
+        let waiting = LoginState::WaitingForGuard {
+            guard_types: vec![EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceConfirmation],
+        };
+        let mut trace = LoginTrace::start(at(0));
+        
+        trace.transition(&LoginState::Idle, &LoginState::Started, at(0));
+        trace.transition(&LoginState::Started, &waiting, at(2));
+        trace.transition(&waiting, &LoginState::Polling, at(5));
+        trace.record_poll();
+        trace.record_poll();
+        trace.record_confirmation();
+        trace.transition(&LoginState::Polling, &LoginState::Authenticated, at(15));
+        
+        let report = trace.finish(at(15), None, Some("127.0.0.1:1080".into())).unwrap();
+        
+        assert_eq!(report.starting, Duration::from_secs(2));
+        assert_eq!(report.waiting_for_guard, Duration::from_secs(3));
+        assert_eq!(report.polling, Duration::from_secs(10));
+        assert_eq!(report.total(), Duration::from_secs(15));
+        assert_eq!(report.polls, 2);
+        assert_eq!(report.guard_types, vec![EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceConfirmation]);
+        assert!(trace.finish(at(16), None, None).is_none());
+    }
+}
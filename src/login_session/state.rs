@@ -0,0 +1,38 @@
+use crate::enums::EAuthSessionGuardType;
+use crate::ErrorKind;
+
+/// The state of a login attempt, from [`LoginSession::state`](super::LoginSession::state).
+///
+/// Changes are also emitted as
+/// [`LoginSessionEvent::StateChanged`](super::LoginSessionEvent::StateChanged).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LoginState {
+    /// No login has been started.
+    #[default]
+    Idle,
+    /// A login was started and is being processed.
+    Started,
+    /// The login needs a Steam Guard code or confirmation of one of `guard_types`.
+    WaitingForGuard {
+        /// The ways the login can be completed.
+        guard_types: Vec<EAuthSessionGuardType>,
+    },
+    /// Polling for the login to be confirmed.
+    Polling,
+    /// The session holds a refresh token.
+    Authenticated,
+    /// The login failed, timed out, or was aborted. A new login can be started.
+    Failed {
+        /// The kind of failure.
+        kind: ErrorKind,
+        /// A description of the failure.
+        reason: String,
+    },
+}
+
+impl LoginState {
+    /// Whether the login can no longer progress without starting a new one.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Authenticated | Self::Failed { .. })
+    }
+}
@@ -0,0 +1,125 @@
+use crate::cookies::{CookieDomain, CookieSet, to_netscape_cookie_jar, web_cookies_expiry};
+use crate::secret::Secret;
+use crate::types::DateTime;
+use std::fmt;
+use cookie::Cookie;
+use reqwest::cookie::Jar;
+use steamid_ng::SteamID;
+use url::Url;
+
+/// The tokens and web cookies for an authenticated session. The tokens and cookies are redacted 
+/// from `Debug` output.
+#[derive(Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionTokens {
+    /// The SteamID of the account.
+    #[cfg_attr(feature = "serialize", serde(with = "crate::serializers::steamid_as_u64"))]
+    pub steamid: SteamID,
+    /// The refresh token.
+    pub refresh_token: Option<Secret<String>>,
+    /// The access token.
+    pub access_token: Option<Secret<String>>,
+    /// The web cookies, in the form returned by `get_web_cookies`.
+    pub cookies: Vec<String>,
+}
+
+impl fmt::Debug for SessionTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionTokens")
+            .field("steamid", &self.steamid)
+            .field("refresh_token", &self.refresh_token)
+            .field("access_token", &self.access_token)
+            .field("cookies", &format_args!("[REDACTED; {}]", self.cookies.len()))
+            .finish()
+    }
+}
+
+impl SessionTokens {
+    /// Adds the web cookies to `jar`. Each cookie is added for the domain in its `Domain`
+    /// attribute, falling back to `steamcommunity.com` for cookies without one.
+    pub fn apply_to_cookie_jar(&self, jar: &Jar) {
+        for cookie_str in &self.cookies {
+            if let Some(url) = cookie_url(cookie_str) {
+                jar.add_cookie_str(cookie_str, &url);
+            }
+        }
+    }
+
+    /// Adds the web cookies to `store`, in the same way as [`Self::apply_to_cookie_jar`]. Unlike
+    /// a [`Jar`], the store can be inspected and persisted. Requires the `cookie-store` feature.
+    #[cfg(feature = "cookie-store")]
+    pub fn apply_to_cookie_store(&self, store: &reqwest_cookie_store::CookieStoreMutex) {
+        let mut store = store.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        for cookie_str in &self.cookies {
+            if let Some(url) = cookie_url(cookie_str) {
+                if let Err(error) = store.parse(cookie_str, &url) {
+                    debug!("Skipping cookie that could not be stored: {error}");
+                }
+            }
+        }
+    }
+
+    /// Gets the time the web cookies expire, based on the access token embedded in the 
+    /// `steamLoginSecure` cookie.
+    pub fn web_cookies_expiry(&self) -> Option<DateTime> {
+        web_cookies_expiry(&self.cookies)
+    }
+
+    /// Gets the web cookies as a [`CookieSet`].
+    pub fn cookie_set(&self) -> CookieSet {
+        CookieSet::parse(&self.cookies)
+    }
+
+    /// Formats the web cookies in the Netscape cookie-jar (`cookies.txt`) format.
+    pub fn to_netscape_cookie_jar(&self) -> String {
+        to_netscape_cookie_jar(&self.cookies)
+    }
+
+    /// Creates a `reqwest::ClientBuilder` with a cookie jar preloaded with the web cookies.
+    pub fn client_builder(&self) -> reqwest::ClientBuilder {
+        let jar = Jar::default();
+
+        self.apply_to_cookie_jar(&jar);
+
+        reqwest::Client::builder()
+            .cookie_provider(std::sync::Arc::new(jar))
+    }
+}
+
+/// Gets the URL `cookie_str` is set for, from its `Domain` attribute or `steamcommunity.com` for
+/// cookies without one.
+fn cookie_url(cookie_str: &str) -> Option<Url> {
+    let domain = Cookie::parse(cookie_str)
+        .ok()
+        .and_then(|cookie| cookie.domain().map(String::from))
+        .unwrap_or_else(|| CookieDomain::Community.hostname().into());
+
+    Url::parse(&format!("https://{domain}/")).ok()
+}
+
+#[cfg(all(test, feature = "cookie-store"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_cookies_to_cookie_store() {
+        let tokens = SessionTokens {
+            steamid: SteamID::from(76561197960287930),
+            refresh_token: None,
+            access_token: None,
+            cookies: vec![
+                String::from("sessionid=abc; Domain=store.steampowered.com; Path=/"),
+                String::from("steamLoginSecure=token"),
+            ],
+        };
+        let store = reqwest_cookie_store::CookieStoreMutex::default();
+
+        tokens.apply_to_cookie_store(&store);
+
+        let store = store.lock().unwrap();
+
+        assert!(store.get("store.steampowered.com", "/", "sessionid").is_some());
+        assert!(store.get("steamcommunity.com", "/", "steamLoginSecure").is_some());
+    }
+}
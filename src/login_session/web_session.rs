@@ -0,0 +1,177 @@
+use super::{LoginSession, LoginSessionError, SessionTokens};
+use crate::cookies::{CookieDomain, CookieSet, web_cookies_expiry};
+use crate::enums::EAuthSessionGuardType;
+use crate::request::StartLoginSessionWithCredentialsDetails;
+use crate::response::StartSessionResponse;
+use crate::token_store::TokenStore;
+use crate::transports::Transport;
+
+/// Where the cookies returned by [`LoginSession::ensure_web_session`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebSessionSource {
+    /// The stored cookies were still valid.
+    Stored,
+    /// New cookies were generated from the refresh token.
+    Refreshed,
+    /// The refresh token was missing or rejected, so the account was logged in again.
+    LoggedIn,
+}
+
+/// A web session returned by [`LoginSession::ensure_web_session`].
+#[derive(Debug, Clone)]
+pub struct WebSession {
+    /// The tokens and cookies of the session, as saved to the token store.
+    pub tokens: SessionTokens,
+    /// Where the cookies came from.
+    pub source: WebSessionSource,
+}
+
+impl<T> LoginSession<T>
+where
+    T: Transport,
+{
+    /// Makes sure there are valid web cookies for `domains`, doing whatever is needed to get
+    /// them:
+    ///
+    /// 1. The tokens stored for `account` in `store` are loaded. If their cookies cover `domains`,
+    ///    are not about to expire, and are accepted by `steamcommunity.com`, they are returned.
+    /// 2. Otherwise new cookies are generated from the refresh token, using the stored refresh
+    ///    token if the session does not have one.
+    /// 3. If there is no refresh token or Steam rejects it, the account is logged in again
+    ///    with `credentials`, using the session's guard code providers for any codes needed and
+    ///    polling for confirmations.
+    ///
+    /// The tokens and cookies are saved to `store` whenever they change. Fails with
+    /// [`LoginSessionError::NoRefreshToken`] if a login is needed and no `credentials` were
+    /// given, or [`LoginSessionError::LoginNotCompleted`] if the login could not be completed
+    /// without help, e.g. when a code is needed and no provider is set.
+    pub async fn ensure_web_session(
+        &mut self,
+        store: &dyn TokenStore,
+        account: &str,
+        domains: &[CookieDomain],
+        credentials: Option<&StartLoginSessionWithCredentialsDetails>,
+    ) -> Result<WebSession, LoginSessionError> {
+        if domains.is_empty() {
+            return Err(LoginSessionError::NoCookieDomains);
+        }
+        
+        if let Some(stored) = store.load(account).await? {
+            if self.refresh_token.is_none() {
+                if let Some(refresh_token) = &stored.refresh_token {
+                    // a token this session can't use is as good as none
+                    if let Err(error) = self.set_refresh_token(refresh_token.expose_secret().clone()) {
+                        debug!("Stored refresh token cannot be used: {error}");
+                    }
+                }
+            }
+            
+            if self.stored_cookies_valid(&stored, domains).await? {
+                return Ok(WebSession {
+                    tokens: stored,
+                    source: WebSessionSource::Stored,
+                });
+            }
+        }
+        
+        if self.refresh_token.is_some() {
+            match self.get_web_cookies(domains).await {
+                Ok(cookies) => {
+                    return self.save_web_session(store, account, cookies, WebSessionSource::Refreshed).await;
+                },
+                Err(error) if error.is_refresh_token_rejected() => {
+                    debug!("Refresh token was rejected, logging in again: {error}");
+                    self.refresh_token = None;
+                    self.access_token = None;
+                    self.access_token_set_at = None;
+                    self.web_cookies = None;
+                },
+                Err(error) => return Err(error),
+            }
+        }
+        
+        let credentials = credentials
+            .ok_or(LoginSessionError::NoRefreshToken)?;
+        
+        self.login_with_credentials(credentials.clone()).await?;
+        
+        let cookies = self.get_web_cookies(domains).await?;
+        
+        self.save_web_session(store, account, cookies, WebSessionSource::LoggedIn).await
+    }
+    
+    /// Checks whether the cookies in `stored` can be used for `domains`.
+    async fn stored_cookies_valid(
+        &self,
+        stored: &SessionTokens,
+        domains: &[CookieDomain],
+    ) -> Result<bool, LoginSessionError> {
+        let cookie_set = CookieSet::parse(&stored.cookies);
+        let covers_domains = domains
+            .iter()
+            .all(|domain| cookie_set.get(*domain, "steamLoginSecure").is_some());
+        let is_fresh = web_cookies_expiry(&stored.cookies)
+            .is_some_and(|expires_at| expires_at - self.web_cookie_refresh_margin > self.clock.now());
+        
+        if !covers_domains || !is_fresh {
+            return Ok(false);
+        }
+        
+        // only cookies for steamcommunity.com can be checked
+        if domains.contains(&CookieDomain::Community) {
+            return self.verify_web_session(&stored.cookies).await;
+        }
+        
+        Ok(true)
+    }
+    
+    /// Logs in with `details`, polling for the login to be confirmed if needed.
+    async fn login_with_credentials(
+        &mut self,
+        details: StartLoginSessionWithCredentialsDetails,
+    ) -> Result<(), LoginSessionError> {
+        if let StartSessionResponse::ActionRequired(actions) = self.start_with_credentials(details).await? {
+            let can_be_confirmed = actions
+                .iter()
+                .any(|action| {
+                    action.r#type == EAuthSessionGuardType::k_EAuthSessionGuardType_EmailConfirmation ||
+                    action.r#type == EAuthSessionGuardType::k_EAuthSessionGuardType_DeviceConfirmation
+                });
+            
+            if can_be_confirmed {
+                self.poll().await?;
+            }
+        }
+        
+        if !self.is_authenticated() {
+            return Err(LoginSessionError::LoginNotCompleted(self.state.clone()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Saves the session's tokens along with `cookies` to `store`.
+    async fn save_web_session(
+        &self,
+        store: &dyn TokenStore,
+        account: &str,
+        cookies: Vec<String>,
+        source: WebSessionSource,
+    ) -> Result<WebSession, LoginSessionError> {
+        let steamid = self.steamid()
+            .ok_or(LoginSessionError::NoRefreshToken)?;
+        let tokens = SessionTokens {
+            steamid,
+            refresh_token: self.refresh_token.clone(),
+            access_token: self.access_token.clone(),
+            cookies,
+        };
+        
+        store.save(account, &tokens).await?;
+        
+        Ok(WebSession {
+            tokens,
+            source,
+        })
+    }
+}
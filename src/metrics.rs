@@ -0,0 +1,86 @@
+//! Metrics emitted through the `metrics` crate when the `metrics` feature is enabled. Without the 
+//! feature these functions do nothing.
+//! 
+//! | Name | Type | Labels |
+//! |------|------|--------|
+//! | `steam_session_cm_connects_total` | counter | |
+//! | `steam_session_cm_connect_failures_total` | counter | |
+//! | `steam_session_cm_reconnects_total` | counter | |
+//! | `steam_session_request_duration_seconds` | histogram | `transport`, `method` |
+//! | `steam_session_request_failures_total` | counter | `transport`, `method` |
+//! | `steam_session_login_polls` | histogram | |
+//! | `steam_session_login_phase_duration_seconds` | histogram | `phase` |
+//! | `steam_session_active_cm_sessions` | gauge | |
+
+use std::time::Duration;
+
+/// Records the result of connecting to a CM server.
+pub(crate) fn cm_connect<T, E>(_result: &Result<T, E>) {
+    #[cfg(feature = "metrics")]
+    match _result {
+        Ok(_) => metrics::counter!("steam_session_cm_connects_total").increment(1),
+        Err(_) => metrics::counter!("steam_session_cm_connect_failures_total").increment(1),
+    };
+}
+
+/// Records a reconnect to a CM server.
+pub(crate) fn cm_reconnect() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("steam_session_cm_reconnects_total").increment(1);
+}
+
+/// Records the duration and result of an API request.
+pub(crate) fn request<T, E>(
+    _transport: &'static str,
+    _method: &'static str,
+    _elapsed: Duration,
+    _result: &Result<T, E>,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::histogram!(
+            "steam_session_request_duration_seconds",
+            "transport" => _transport,
+            "method" => _method,
+        ).record(_elapsed.as_secs_f64());
+        
+        if _result.is_err() {
+            metrics::counter!(
+                "steam_session_request_failures_total",
+                "transport" => _transport,
+                "method" => _method,
+            ).increment(1);
+        }
+    };
+}
+
+/// Records the number of polls it took for a login to complete.
+pub(crate) fn login_polls(_polls: u32) {
+    #[cfg(feature = "metrics")]
+    metrics::histogram!("steam_session_login_polls").record(_polls as f64);
+}
+
+/// Records how long each phase of a completed login took.
+pub(crate) fn login_phases(_report: &crate::login_session::LoginReport) {
+    #[cfg(feature = "metrics")]
+    {
+        let phases = [
+            ("starting", _report.starting),
+            ("waiting_for_guard", _report.waiting_for_guard),
+            ("polling", _report.polling),
+        ];
+        
+        for (phase, elapsed) in phases {
+            metrics::histogram!(
+                "steam_session_login_phase_duration_seconds",
+                "phase" => phase,
+            ).record(elapsed.as_secs_f64());
+        }
+    };
+}
+
+/// Records a CM session starting or stopping.
+pub(crate) fn active_cm_sessions(_delta: f64) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!("steam_session_active_cm_sessions").increment(_delta);
+}
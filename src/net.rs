@@ -3,32 +3,70 @@ use crate::proto::steammessages_clientserver_login::CMsgClientHello;
 use crate::proto::custom::CAuthentication_BeginAuthSessionViaCredentials_Request_BinaryGuardData;
 use crate::proto::steammessages_auth_steamclient::{
     CAuthentication_BeginAuthSessionViaCredentials_Response,
+    CAuthentication_BeginAuthSessionViaQR_Request,
+    CAuthentication_BeginAuthSessionViaQR_Response,
     CAuthentication_UpdateAuthSessionWithSteamGuardCode_Request,
     CAuthentication_UpdateAuthSessionWithSteamGuardCode_Response,
     CAuthentication_UpdateAuthSessionWithMobileConfirmation_Response,
     CAuthentication_UpdateAuthSessionWithMobileConfirmation_Request,
     CAuthentication_GetAuthSessionInfo_Request,
     CAuthentication_GetAuthSessionInfo_Response,
+    CAuthentication_GetAuthSessionsForAccount_Request,
+    CAuthentication_GetAuthSessionsForAccount_Response,
     CAuthentication_GetPasswordRSAPublicKey_Request,
     CAuthentication_GetPasswordRSAPublicKey_Response,
     CAuthentication_PollAuthSessionStatus_Request,
     CAuthentication_PollAuthSessionStatus_Response,
     CAuthentication_AccessToken_GenerateForApp_Request,
     CAuthentication_AccessToken_GenerateForApp_Response,
+    CAuthentication_Token_Revoke_Request,
+    CAuthentication_Token_Revoke_Response,
+    CAuthentication_RefreshToken_Revoke_Request,
+    CAuthentication_RefreshToken_Revoke_Response,
 };
 use std::io::Read;
 
+/// A service method request. Implement this with [`api_request!`](crate::api_request).
 pub trait ApiRequest: Sized + protobuf::Message + protobuf::MessageFull {
     const KIND: EMsg;
     const INTERFACE: &'static str;
     const METHOD: &'static str;
     const VERSION: u32;
+    /// The job target name, e.g. `Authentication.PollAuthSessionStatus#1`.
     const NAME: &'static str;
+    /// Whether the request must be sent with an access token.
+    const REQUIRES_ACCESS_TOKEN: bool = false;
+    /// The priority of the request when sent over a busy websocket connection.
+    const PRIORITY: MessagePriority = MessagePriority::Normal;
     type Response: ApiResponse;
 }
 
+/// The priority of an outgoing CM message. When writes to the socket are backed up, such as 
+/// when a proxy link is saturated, messages waiting to be written go out in order of priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum MessagePriority {
+    /// Requests that are sent repeatedly and can wait, such as polling for a login.
+    Bulk = 0,
+    /// Most requests.
+    #[default]
+    Normal = 1,
+    /// Requests that should not wait behind others, such as heartbeats and Steam Guard codes.
+    Urgent = 2,
+}
+
 pub trait ApiResponse: Sized {
     fn parse_from_reader(reader: &mut dyn Read) -> protobuf::Result<Self>;
+    
+    /// Encodes the response with sensitive fields cleared, for wire logging.
+    fn redacted_payload(&self) -> Option<Vec<u8>> {
+        None
+    }
+    
+    /// Whether the response contains fields unknown to its definition, checked when parsing 
+    /// strictly.
+    fn has_unknown_fields(&self) -> bool {
+        false
+    }
 }
 
 impl ApiResponse for () {
@@ -37,41 +75,88 @@ impl ApiResponse for () {
     }
 }
 
-macro_rules! api_method {
-    (($interface:literal, $method:literal, $version:expr) => $req:path, $res:path) => {
-        impl ApiRequest for $req {
-            const KIND: EMsg = EMsg::ServiceMethodCallFromClientNonAuthed;
-            const INTERFACE: &'static str = $interface;
-            const METHOD: &'static str = $method;
-            const VERSION: u32 = $version;
-            const NAME: &'static str = concat!($interface, ".", $method, "#", $version);
-            type Response = $res;
-        }
+/// Implements [`ApiRequest`] for a request type, and [`ApiResponse`] for its response type if 
+/// one is given. Both must be protobuf messages generated by `rust-protobuf`. Add 
+/// `requires_access_token` after the method if the request must be sent with an access token, 
+/// and `priority = ...` with a [`MessagePriority`] variant to send it with a priority other than 
+/// [`MessagePriority::Normal`].
+/// 
+/// # Examples
+/// ```ignore
+/// use steam_session::api_request;
+/// use steam_session::net::{ApiRequest, ApiResponse};
+/// 
+/// // A method without a response
+/// api_request!(("Player", "ClientNotify", 1) => CPlayer_ClientNotify_Request);
+/// // A method with a response that requires an access token
+/// api_request!(("Player", "GetNickname", 1), requires_access_token => CPlayer_GetNickname_Request, CPlayer_GetNickname_Response);
+/// // A method that can wait behind other requests
+/// api_request!(("Player", "GetGameBadgeLevels", 1), priority = Bulk => CPlayer_GetGameBadgeLevels_Request, CPlayer_GetGameBadgeLevels_Response);
+/// ```
+#[macro_export]
+macro_rules! api_request {
+    (($interface:literal, $method:literal, $version:expr) => $req:path $(, $res:path)?) => {
+        $crate::api_request!(@impl ($interface, $method, $version), false, Normal => $req $(, $res)?);
+    };
+    (($interface:literal, $method:literal, $version:expr), requires_access_token => $req:path $(, $res:path)?) => {
+        $crate::api_request!(@impl ($interface, $method, $version), true, Normal => $req $(, $res)?);
+    };
+    (($interface:literal, $method:literal, $version:expr), priority = $priority:ident => $req:path $(, $res:path)?) => {
+        $crate::api_request!(@impl ($interface, $method, $version), false, $priority => $req $(, $res)?);
+    };
+    (($interface:literal, $method:literal, $version:expr), requires_access_token, priority = $priority:ident => $req:path $(, $res:path)?) => {
+        $crate::api_request!(@impl ($interface, $method, $version), true, $priority => $req $(, $res)?);
+    };
+    (@impl ($interface:literal, $method:literal, $version:expr), $requires_access_token:literal, $priority:ident => $req:path, $res:path) => {
+        $crate::api_request!(@request ($interface, $method, $version), $requires_access_token, $priority => $req, $res);
         
-        impl ApiResponse for $res {
-            fn parse_from_reader(reader: &mut dyn Read) -> protobuf::Result<Self> {
-                <Self as protobuf::Message>::parse_from_reader(reader)
+        impl $crate::net::ApiResponse for $res {
+            fn parse_from_reader(reader: &mut dyn ::std::io::Read) -> ::protobuf::Result<Self> {
+                <Self as ::protobuf::Message>::parse_from_reader(reader)
+            }
+            
+            fn redacted_payload(&self) -> Option<Vec<u8>> {
+                Some($crate::net::redacted_payload(self))
+            }
+            
+            fn has_unknown_fields(&self) -> bool {
+                <Self as ::protobuf::Message>::special_fields(self)
+                    .unknown_fields()
+                    .iter()
+                    .next()
+                    .is_some()
             }
         }
     };
-    (($interface:literal, $method:literal, $version:expr) => $req:path) => {
-        impl ApiRequest for $req {
-            const KIND: EMsg = EMsg::ServiceMethodCallFromClientNonAuthed;
+    (@impl ($interface:literal, $method:literal, $version:expr), $requires_access_token:literal, $priority:ident => $req:path) => {
+        $crate::api_request!(@request ($interface, $method, $version), $requires_access_token, $priority => $req, ());
+    };
+    (@request ($interface:literal, $method:literal, $version:expr), $requires_access_token:literal, $priority:ident => $req:path, $res:ty) => {
+        impl $crate::net::ApiRequest for $req {
+            const KIND: $crate::enums::EMsg = $crate::enums::EMsg::ServiceMethodCallFromClientNonAuthed;
             const INTERFACE: &'static str = $interface;
             const METHOD: &'static str = $method;
             const VERSION: u32 = $version;
             const NAME: &'static str = concat!($interface, ".", $method, "#", $version);
-            type Response = ();
+            const REQUIRES_ACCESS_TOKEN: bool = $requires_access_token;
+            const PRIORITY: $crate::net::MessagePriority = $crate::net::MessagePriority::$priority;
+            type Response = $res;
         }
     };
 }
 
-api_method!(("Client", "Hello", 1) => CMsgClientHello);
-api_method!(("Authentication", "GenerateAccessTokenForApp", 1) => CAuthentication_AccessToken_GenerateForApp_Request, CAuthentication_AccessToken_GenerateForApp_Response);
-api_method!(("Authentication", "BeginAuthSessionViaCredentials", 1) => CAuthentication_BeginAuthSessionViaCredentials_Request_BinaryGuardData, CAuthentication_BeginAuthSessionViaCredentials_Response);
-api_method!(("Authentication", "UpdateAuthSessionWithSteamGuardCode", 1) => CAuthentication_UpdateAuthSessionWithSteamGuardCode_Request, CAuthentication_UpdateAuthSessionWithSteamGuardCode_Response);
-api_method!(("Authentication", "UpdateAuthSessionWithMobileConfirmation", 1) => CAuthentication_UpdateAuthSessionWithMobileConfirmation_Request, CAuthentication_UpdateAuthSessionWithMobileConfirmation_Response);
-api_method!(("Authentication", "GetAuthSessionInfo", 1) => CAuthentication_GetAuthSessionInfo_Request, CAuthentication_GetAuthSessionInfo_Response);
-api_method!(("Authentication", "GetPasswordRSAPublicKey", 1) => CAuthentication_GetPasswordRSAPublicKey_Request, CAuthentication_GetPasswordRSAPublicKey_Response);
-api_method!(("Authentication", "PollAuthSessionStatus", 1) => CAuthentication_PollAuthSessionStatus_Request, CAuthentication_PollAuthSessionStatus_Response);
+#[doc(hidden)]
+pub use crate::transports::websocket::wire_log::redacted_payload;
 
+api_request!(("Client", "Hello", 1) => CMsgClientHello);
+api_request!(("Authentication", "GenerateAccessTokenForApp", 1) => CAuthentication_AccessToken_GenerateForApp_Request, CAuthentication_AccessToken_GenerateForApp_Response);
+api_request!(("Authentication", "BeginAuthSessionViaCredentials", 1) => CAuthentication_BeginAuthSessionViaCredentials_Request_BinaryGuardData, CAuthentication_BeginAuthSessionViaCredentials_Response);
+api_request!(("Authentication", "BeginAuthSessionViaQR", 1) => CAuthentication_BeginAuthSessionViaQR_Request, CAuthentication_BeginAuthSessionViaQR_Response);
+api_request!(("Authentication", "UpdateAuthSessionWithSteamGuardCode", 1), priority = Urgent => CAuthentication_UpdateAuthSessionWithSteamGuardCode_Request, CAuthentication_UpdateAuthSessionWithSteamGuardCode_Response);
+api_request!(("Authentication", "UpdateAuthSessionWithMobileConfirmation", 1), requires_access_token, priority = Urgent => CAuthentication_UpdateAuthSessionWithMobileConfirmation_Request, CAuthentication_UpdateAuthSessionWithMobileConfirmation_Response);
+api_request!(("Authentication", "GetAuthSessionInfo", 1), requires_access_token => CAuthentication_GetAuthSessionInfo_Request, CAuthentication_GetAuthSessionInfo_Response);
+api_request!(("Authentication", "GetAuthSessionsForAccount", 1), requires_access_token => CAuthentication_GetAuthSessionsForAccount_Request, CAuthentication_GetAuthSessionsForAccount_Response);
+api_request!(("Authentication", "GetPasswordRSAPublicKey", 1) => CAuthentication_GetPasswordRSAPublicKey_Request, CAuthentication_GetPasswordRSAPublicKey_Response);
+api_request!(("Authentication", "PollAuthSessionStatus", 1), priority = Bulk => CAuthentication_PollAuthSessionStatus_Request, CAuthentication_PollAuthSessionStatus_Response);
+api_request!(("Authentication", "RevokeToken", 1), requires_access_token => CAuthentication_Token_Revoke_Request, CAuthentication_Token_Revoke_Response);
+api_request!(("Authentication", "RevokeRefreshToken", 1), requires_access_token => CAuthentication_RefreshToken_Revoke_Request, CAuthentication_RefreshToken_Revoke_Response);
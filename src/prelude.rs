@@ -0,0 +1,39 @@
+//! Re-exports of the types used in the common flows, so they can be brought into scope with a 
+//! single import.
+//! 
+//! ```
+//! use steam_session::prelude::*;
+//! ```
+
+pub use crate::login_session::{
+    LoginSession,
+    LoginSessionBuilder,
+    LoginSessionError,
+    SessionTokens,
+    LoginSessionHandle,
+    LoginSessionEvent,
+    LoginAbortHandle,
+    connect_ws,
+    connect_webapi,
+};
+pub use crate::login_approver::LoginApprover;
+pub use crate::cm_client_session::{
+    CmClientSession,
+    CmSessionEvent,
+    LogOnDetails,
+    LogOffReason,
+    ReloginPolicy,
+};
+pub use crate::web_api_client::WebApiClient;
+pub use crate::transports::{Transport, WebSocketCMTransport, Socks5ProxyConfig};
+pub use crate::transports::web_api::WebApiTransport;
+pub use crate::cookies::CookieDomain;
+pub use crate::hosts::SteamHosts;
+pub use crate::secret::Secret;
+pub use crate::retry::RetryPolicy;
+pub use crate::session_manager::SessionManager;
+pub use crate::token_store::{TokenStore, FileTokenStore};
+pub use crate::request::{StartLoginSessionWithCredentialsDetails, WebsiteId};
+pub use crate::response::StartSessionResponse;
+pub use crate::enums::{EAuthTokenPlatformType, EAuthSessionGuardType, ELanguage, EResult};
+pub use steamid_ng::SteamID;
@@ -0,0 +1,20 @@
+use crate::ErrorKind;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("QR code: {}", .0)]
+    Qr(#[from] qrcode::types::QrError),
+    #[error("Image: {}", .0)]
+    Image(#[from] image::ImageError),
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            // the data does not fit in a QR code
+            Self::Qr(_) => ErrorKind::InvalidInput,
+            Self::Image(_) => ErrorKind::Decode,
+        }
+    }
+}
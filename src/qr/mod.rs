@@ -0,0 +1,89 @@
+//! # QR codes
+//! 
+//! Renders the challenge URL from [`LoginSession::start_with_qr`](crate::LoginSession::start_with_qr)
+//! as a QR code to be scanned with the Steam mobile app. Requires the `qr` feature.
+//! 
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use steam_session::login_session::connect_webapi;
+//! use steam_session::response::StartSessionResponse;
+//! 
+//! let mut session = connect_webapi().await?;
+//! 
+//! if let StartSessionResponse::QrChallenge(challenge_url) = session.start_with_qr().await? {
+//!     println!("{}", steam_session::qr::render_unicode(&challenge_url)?);
+//! }
+//! 
+//! session.poll().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+
+pub use error::Error;
+
+use std::io::Cursor;
+use image::{ImageFormat, Luma};
+use qrcode::QrCode;
+use qrcode::render::{svg, unicode::Dense1x2};
+
+/// The minimum width and height of rendered PNG and SVG images, in pixels.
+pub const DEFAULT_IMAGE_SIZE: u32 = 256;
+
+/// Renders `url` as a QR code made of Unicode half blocks, two rows of modules per line, for
+/// printing to a terminal. The code is drawn light-on-dark so it scans on dark terminal themes.
+pub fn render_unicode(url: &str) -> Result<String, Error> {
+    let code = QrCode::new(url.as_bytes())?;
+    let rendered = code.render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build();
+    
+    Ok(rendered)
+}
+
+/// Renders `url` as a QR code in PNG format, at least `size` pixels wide and tall.
+pub fn render_png(url: &str, size: u32) -> Result<Vec<u8>, Error> {
+    let code = QrCode::new(url.as_bytes())?;
+    let image = code.render::<Luma<u8>>()
+        .min_dimensions(size, size)
+        .build();
+    let mut bytes = Vec::new();
+    
+    image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+    
+    Ok(bytes)
+}
+
+/// Renders `url` as a QR code in SVG format, at least `size` pixels wide and tall.
+pub fn render_svg(url: &str, size: u32) -> Result<String, Error> {
+    let code = QrCode::new(url.as_bytes())?;
+    let rendered = code.render::<svg::Color<'_>>()
+        .min_dimensions(size, size)
+        .build();
+    
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const URL: &str = "https://s.team/q/1/2372462679780599330";
+    
+    #[test]
+    fn renders_png() {
+        let png = render_png(URL, DEFAULT_IMAGE_SIZE).unwrap();
+        
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+    
+    #[test]
+    fn renders_svg() {
+        let svg = render_svg(URL, DEFAULT_IMAGE_SIZE).unwrap();
+        
+        assert!(svg.contains("<svg"));
+    }
+}
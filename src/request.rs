@@ -1,9 +1,11 @@
-use crate::enums::{ESessionPersistence, EAuthTokenPlatformType};
+use crate::enums::{ESessionPersistence, EAuthTokenPlatformType, ETokenRenewalType};
+use crate::secret::Secret;
+use steamid_ng::SteamID;
 
 #[derive(Debug, Clone)]
 pub struct StartLoginSessionWithCredentialsDetails {
     pub account_name: String,
-    pub password: String,
+    pub password: Secret<String>,
     pub platform_type: EAuthTokenPlatformType,
     pub persistence: Option<ESessionPersistence>,
     pub steam_guard_machine_token: Option<Vec<u8>>,
@@ -16,7 +18,7 @@ impl Default for StartLoginSessionWithCredentialsDetails {
     fn default() -> Self {
         Self {
             account_name: String::new(),
-            password: String::new(),
+            password: Secret::default(),
             platform_type: EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp,
             persistence: None,
             steam_guard_machine_token: None,
@@ -30,7 +32,7 @@ impl Default for StartLoginSessionWithCredentialsDetails {
 #[derive(Debug, Clone)]
 pub struct StartAuthSessionWithCredentialsRequest {
     pub account_name: String,
-    pub encrypted_password: String,
+    pub encrypted_password: Secret<String>,
     pub encryption_timestamp: u64,
     pub remember_login: bool,
     pub platform_type: EAuthTokenPlatformType,
@@ -42,7 +44,7 @@ pub struct StartAuthSessionWithCredentialsRequest {
 pub struct MobileConfirmationRequest {
     pub version: u16,
     pub client_id: u64,
-    pub steamid: u64,
+    pub steamid: SteamID,
     pub signature: Vec<u8>,
     pub confirm: bool,
     pub persistence: ESessionPersistence,
@@ -52,7 +54,114 @@ pub struct MobileConfirmationRequest {
 pub struct ApproveAuthSessionRequest {
     pub version: u16,
     pub client_id: u64,
-    pub steamid: u64,
+    pub steamid: SteamID,
     pub approve: bool,
     pub persistence: ESessionPersistence,
-}
\ No newline at end of file
+}
+
+/// Options for generating an access token from a refresh token with `GenerateAccessTokenForApp`.
+#[derive(Debug, Clone, Default)]
+pub struct GenerateAccessTokenOptions {
+    /// Whether Steam may also issue a new refresh token, which invalidates the previous one. 
+    /// Defaults to [`ETokenRenewalType::k_ETokenRenewalType_None`].
+    pub renewal_type: ETokenRenewalType,
+    /// The SteamID sent with the request. Defaults to the subject of the refresh token.
+    pub steamid: Option<SteamID>,
+}
+
+impl GenerateAccessTokenOptions {
+    /// Options allowing Steam to issue a new refresh token.
+    pub fn renew() -> Self {
+        Self {
+            renewal_type: ETokenRenewalType::k_ETokenRenewalType_Allow,
+            ..Default::default()
+        }
+    }
+}
+
+/// The `website_id` sent when starting an auth session. Some flows require a specific value. By 
+/// default the value is chosen based on the platform type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(into = "String", try_from = "String"))]
+pub enum WebsiteId {
+    /// `Community`
+    Community,
+    /// `Store`
+    Store,
+    /// `Mobile`
+    Mobile,
+    /// `Partner`
+    Partner,
+    /// `Client`
+    Client,
+    /// `Unknown`
+    Unknown,
+    /// Any other value. Use [`WebsiteId::from_str`](std::str::FromStr::from_str) to create a 
+    /// validated value.
+    Custom(String),
+}
+
+impl WebsiteId {
+    /// Gets the value sent in requests.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Community => "Community",
+            Self::Store => "Store",
+            Self::Mobile => "Mobile",
+            Self::Partner => "Partner",
+            Self::Client => "Client",
+            Self::Unknown => "Unknown",
+            Self::Custom(value) => value,
+        }
+    }
+}
+
+impl std::fmt::Display for WebsiteId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for WebsiteId {
+    type Err = InvalidWebsiteIdError;
+    
+    /// Parses a `website_id`. Known values are returned as presets. Any other value must be 
+    /// non-empty and contain only ASCII letters, digits, and underscores.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "Community" => Ok(Self::Community),
+            "Store" => Ok(Self::Store),
+            "Mobile" => Ok(Self::Mobile),
+            "Partner" => Ok(Self::Partner),
+            "Client" => Ok(Self::Client),
+            "Unknown" => Ok(Self::Unknown),
+            value if !value.is_empty() && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_') => Ok(Self::Custom(value.into())),
+            value => Err(InvalidWebsiteIdError(value.into())),
+        }
+    }
+}
+
+impl From<WebsiteId> for String {
+    fn from(website_id: WebsiteId) -> Self {
+        match website_id {
+            WebsiteId::Custom(value) => value,
+            website_id => website_id.as_str().into(),
+        }
+    }
+}
+
+impl TryFrom<String> for WebsiteId {
+    type Error = InvalidWebsiteIdError;
+    
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// A `website_id` was empty or contained invalid characters.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Invalid website_id: {:?}", .0)]
+pub struct InvalidWebsiteIdError(pub String);
@@ -1,4 +1,9 @@
-use steam_session_proto::steammessages_auth_steamclient::EAuthSessionGuardType;
+use crate::helpers::{DecodeError, JwtPayload};
+use crate::secret::Secret;
+use crate::types::DateTime;
+use std::str::FromStr;
+use steamid_ng::SteamID;
+use steam_session_proto::steammessages_auth_steamclient::{EAuthSessionGuardType, CAuthentication_AccessToken_GenerateForApp_Response};
 
 #[derive(Debug, Clone)]
 pub struct StartSessionResponseValidAction {
@@ -47,4 +52,50 @@ impl StartSessionResponse {
             _ => false,
         }
     }
-}
\ No newline at end of file
+}
+
+/// An access token generated from a refresh token, along with the details decoded from it.
+#[derive(Debug, Clone)]
+pub struct GeneratedAccessToken {
+    /// The access token.
+    pub access_token: Secret<String>,
+    /// The new refresh token, if Steam issued one. The previous refresh token is no longer valid 
+    /// when this is set.
+    pub refresh_token: Option<Secret<String>>,
+    /// The SteamID the access token was issued for.
+    pub steamid: SteamID,
+    /// The audience of the access token, e.g. `web:community`.
+    pub audience: Vec<String>,
+    /// When the access token was issued.
+    pub issued_at: DateTime,
+    /// When the access token expires.
+    pub expires_at: DateTime,
+}
+
+impl GeneratedAccessToken {
+    /// Whether a new refresh token was issued.
+    pub fn is_renewed(&self) -> bool {
+        self.refresh_token.is_some()
+    }
+}
+
+impl TryFrom<CAuthentication_AccessToken_GenerateForApp_Response> for GeneratedAccessToken {
+    type Error = DecodeError;
+    
+    fn try_from(mut response: CAuthentication_AccessToken_GenerateForApp_Response) -> Result<Self, Self::Error> {
+        let access_token = response.take_access_token();
+        let refresh_token = Some(response.take_refresh_token())
+            .filter(|refresh_token| !refresh_token.is_empty())
+            .map(Secret::new);
+        let decoded = JwtPayload::from_str(&access_token)?;
+        
+        Ok(Self {
+            steamid: decoded.sub,
+            audience: decoded.aud,
+            issued_at: DateTime::from_timestamp(decoded.iat as i64, 0).unwrap_or_default(),
+            expires_at: DateTime::from_timestamp(decoded.exp as i64, 0).unwrap_or_default(),
+            access_token: Secret::new(access_token),
+            refresh_token,
+        })
+    }
+}
@@ -0,0 +1,298 @@
+//! # Retrying requests
+//! 
+//! A [`RetryPolicy`] controls how individual service calls are retried after transient failures, 
+//! such as timeouts, dropped connections, or Steam reporting that it is busy. Only calls that are 
+//! safe to repeat are retried: getting the RSA key for a password, polling a login attempt, 
+//! getting auth session info, and generating an access token without renewing the refresh token.
+//! 
+//! A [`PollPolicy`] controls the delay between polls while waiting for a login attempt to be 
+//! confirmed. Steam's suggested interval is always honored; jitter and backoff only add to it.
+
+use crate::enums::EResult;
+use crate::ErrorKind;
+use crate::clock::RandomSource;
+use std::time::Duration;
+
+/// How requests are retried after transient failures.
+/// 
+/// # Examples
+/// ```
+/// use steam_session::retry::RetryPolicy;
+/// use std::time::Duration;
+/// 
+/// let policy = RetryPolicy::default()
+///     .max_attempts(5)
+///     .initial_backoff(Duration::from_millis(250));
+/// 
+/// assert_eq!(policy.backoff(3), Some(Duration::from_secs(1)));
+/// assert_eq!(policy.backoff(5), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first.
+    pub max_attempts: u32,
+    /// The delay before the first retry. This doubles for each subsequent retry.
+    pub initial_backoff: Duration,
+    /// The maximum delay between attempts.
+    pub max_backoff: Duration,
+    /// Results from Steam that are retried.
+    pub retryable_eresults: Vec<EResult>,
+    /// Whether connection errors and timeouts are retried.
+    pub retry_transport_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            retryable_eresults: vec![
+                EResult::Busy,
+                EResult::Timeout,
+                EResult::ServiceUnavailable,
+                EResult::TryAnotherCM,
+            ],
+            retry_transport_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+    
+    /// Sets the maximum number of attempts, including the first.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+    
+    /// Sets the delay before the first retry.
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+    
+    /// Sets the maximum delay between attempts.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+    
+    /// Sets the results from Steam that are retried.
+    pub fn retryable_eresults(mut self, retryable_eresults: Vec<EResult>) -> Self {
+        self.retryable_eresults = retryable_eresults;
+        self
+    }
+    
+    /// Sets whether connection errors and timeouts are retried.
+    pub fn retry_transport_errors(mut self, retry_transport_errors: bool) -> Self {
+        self.retry_transport_errors = retry_transport_errors;
+        self
+    }
+    
+    /// Gets the delay before the next attempt after `attempts` attempts have failed, or `None` if 
+    /// no more attempts should be made.
+    pub fn backoff(&self, attempts: u32) -> Option<Duration> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        let backoff = self.initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff);
+        
+        Some(backoff.min(self.max_backoff))
+    }
+    
+    /// Whether a failure of `kind`, with `eresult` if Steam gave one, is retried.
+    pub fn is_retryable(&self, kind: ErrorKind, eresult: Option<EResult>) -> bool {
+        match eresult {
+            Some(eresult) => self.retryable_eresults.contains(&eresult),
+            None => {
+                self.retry_transport_errors &&
+                matches!(kind, ErrorKind::Connection | ErrorKind::Timeout)
+            },
+        }
+    }
+}
+
+/// How often a login attempt is polled while waiting for it to be confirmed.
+/// 
+/// By default, the interval suggested by Steam is used as is. Large numbers of sessions polling 
+/// from behind the same proxy can add jitter to spread their polls out, and back off when a login 
+/// is left pending for a long time.
+/// 
+/// # Examples
+/// ```
+/// use steam_session::retry::PollPolicy;
+/// use std::time::Duration;
+/// 
+/// let policy = PollPolicy::default()
+///     .jitter(Duration::from_millis(500))
+///     .backoff_after(Duration::from_secs(60))
+///     .max_interval(Duration::from_secs(20));
+/// 
+/// assert_eq!(policy.interval(Duration::from_secs(5), Duration::from_secs(30)), Duration::from_secs(5));
+/// assert_eq!(policy.interval(Duration::from_secs(5), Duration::from_secs(60)), Duration::from_secs(10));
+/// assert_eq!(policy.interval(Duration::from_secs(5), Duration::from_secs(600)), Duration::from_secs(20));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollPolicy {
+    /// The maximum random delay added to each interval.
+    pub jitter: Duration,
+    /// How long a login can be pending before the interval starts doubling. The interval doubles 
+    /// again each time this much more time passes. `None` disables backoff.
+    pub backoff_after: Option<Duration>,
+    /// The maximum interval reached by backing off. Intervals suggested by Steam that are longer 
+    /// than this are still honored.
+    pub max_interval: Duration,
+}
+
+impl Default for PollPolicy {
+    fn default() -> Self {
+        Self {
+            jitter: Duration::ZERO,
+            backoff_after: None,
+            max_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl PollPolicy {
+    /// Sets the maximum random delay added to each interval.
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+    
+    /// Sets how long a login can be pending before backing off.
+    pub fn backoff_after(mut self, backoff_after: Duration) -> Self {
+        self.backoff_after = Some(backoff_after);
+        self
+    }
+    
+    /// Sets the maximum interval reached by backing off.
+    pub fn max_interval(mut self, max_interval: Duration) -> Self {
+        self.max_interval = max_interval;
+        self
+    }
+    
+    /// Gets the interval before the next poll, without jitter, when Steam suggested 
+    /// `server_interval` and the login has been pending for `pending_for`.
+    pub fn interval(&self, server_interval: Duration, pending_for: Duration) -> Duration {
+        let periods = match self.backoff_after {
+            Some(backoff_after) if !backoff_after.is_zero() => {
+                (pending_for.as_nanos() / backoff_after.as_nanos()).min(u32::MAX as u128) as u32
+            },
+            _ => 0,
+        };
+        
+        if periods == 0 {
+            return server_interval;
+        }
+        
+        let interval = server_interval
+            .checked_mul(2u32.saturating_pow(periods))
+            .unwrap_or(self.max_interval)
+            .min(self.max_interval);
+        
+        interval.max(server_interval)
+    }
+    
+    /// Gets a random delay of up to `jitter` to add to an interval.
+    pub fn random_jitter(&self, random: &dyn RandomSource) -> Duration {
+        if self.jitter.is_zero() {
+            return Duration::ZERO;
+        }
+        
+        let max_nanos = u64::try_from(self.jitter.as_nanos()).unwrap_or(u64::MAX);
+        let nanos = u64::from_le_bytes(random.bytes::<8>()) % max_nanos.saturating_add(1);
+        
+        Duration::from_nanos(nanos)
+    }
+    
+    /// Gets the delay before the next poll, including jitter.
+    pub fn delay(
+        &self,
+        server_interval: Duration,
+        pending_for: Duration,
+        random: &dyn RandomSource,
+    ) -> Duration {
+        self.interval(server_interval, pending_for) + self.random_jitter(random)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn backs_off_exponentially_up_to_max() {
+        let policy = RetryPolicy::default()
+            .max_attempts(10)
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(5));
+        
+        assert_eq!(policy.backoff(1), Some(Duration::from_secs(1)));
+        assert_eq!(policy.backoff(2), Some(Duration::from_secs(2)));
+        assert_eq!(policy.backoff(3), Some(Duration::from_secs(4)));
+        assert_eq!(policy.backoff(4), Some(Duration::from_secs(5)));
+        assert_eq!(policy.backoff(10), None);
+    }
+    
+    #[test]
+    fn never_retries() {
+        assert_eq!(RetryPolicy::never().backoff(1), None);
+    }
+    
+    #[test]
+    fn retries_transient_failures() {
+        let policy = RetryPolicy::default();
+        
+        assert!(policy.is_retryable(ErrorKind::Steam, Some(EResult::Busy)));
+        assert!(!policy.is_retryable(ErrorKind::Steam, Some(EResult::InvalidPassword)));
+        assert!(policy.is_retryable(ErrorKind::Timeout, None));
+        assert!(!policy.is_retryable(ErrorKind::Decode, None));
+    }
+    
+    #[test]
+    fn polls_at_server_interval_by_default() {
+        let policy = PollPolicy::default();
+        let random = crate::clock::SeededRandom::new(1);
+        
+        assert_eq!(policy.delay(Duration::from_secs(5), Duration::from_secs(3600), &random), Duration::from_secs(5));
+    }
+    
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = PollPolicy::default().jitter(Duration::from_millis(250));
+        let random = crate::clock::SeededRandom::new(7);
+        
+        for _ in 0..100 {
+            let delay = policy.delay(Duration::from_secs(5), Duration::ZERO, &random);
+            
+            assert!(delay >= Duration::from_secs(5));
+            assert!(delay <= Duration::from_millis(5250));
+        }
+    }
+    
+    #[test]
+    fn backs_off_while_pending_but_honors_server_interval() {
+        let policy = PollPolicy::default()
+            .backoff_after(Duration::from_secs(60))
+            .max_interval(Duration::from_secs(10));
+        
+        assert_eq!(policy.interval(Duration::from_secs(5), Duration::from_secs(59)), Duration::from_secs(5));
+        assert_eq!(policy.interval(Duration::from_secs(5), Duration::from_secs(60)), Duration::from_secs(10));
+        assert_eq!(policy.interval(Duration::from_secs(5), Duration::from_secs(6000)), Duration::from_secs(10));
+        assert_eq!(policy.interval(Duration::from_secs(15), Duration::from_secs(6000)), Duration::from_secs(15));
+    }
+}
@@ -0,0 +1,160 @@
+//! A wrapper for sensitive values such as passwords and tokens. With the `zeroize` feature 
+//! (enabled by default) wrapped values are cleared from memory when dropped.
+//! 
+//! [`MaskedToken`] and [`MaskedAccountName`] display tokens and account names in log output so 
+//! that entries can be correlated without the values themselves being logged.
+
+use std::fmt;
+
+/// A value that can be held in a [`Secret`]. With the `zeroize` feature this requires 
+/// [`zeroize::Zeroize`] so the value can be cleared when dropped.
+#[cfg(feature = "zeroize")]
+pub trait SecretValue: zeroize::Zeroize {}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize> SecretValue for T {}
+
+/// A value that can be held in a [`Secret`].
+#[cfg(not(feature = "zeroize"))]
+pub trait SecretValue {}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> SecretValue for T {}
+
+/// Wraps a sensitive value such as a password or token so that it is never printed. `Debug` and 
+/// `Display` output `[REDACTED]`. Use [`Secret::expose_secret`] to access the value.
+/// 
+/// # Examples
+/// ```
+/// use steam_session::Secret;
+/// 
+/// let password = Secret::from("hunter2");
+/// 
+/// assert_eq!(format!("{password:?}"), "[REDACTED]");
+/// assert_eq!(password.expose_secret(), "hunter2");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serialize", feature = "config"), derive(serde::Deserialize))]
+#[cfg_attr(any(feature = "serialize", feature = "config"), serde(transparent))]
+pub struct Secret<T: SecretValue>(T);
+
+impl<T: SecretValue> Secret<T> {
+    /// Wraps `value`.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+    
+    /// Gets a reference to the value.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+    
+    /// Consumes the wrapper, returning the value.
+    pub fn into_inner(mut self) -> T
+    where
+        T: Default,
+    {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Secret<String> {
+    /// Gets a short fingerprint of the token, for correlating it across logs and audit events. 
+    /// See [`crate::audit::token_fingerprint`]. Only use this for tokens; the fingerprint of a 
+    /// low-entropy value such as a password can be reversed by brute force.
+    pub fn fingerprint(&self) -> String {
+        crate::audit::token_fingerprint(&self.0)
+    }
+    
+    /// Gets a wrapper that displays the token by its fingerprint.
+    pub fn masked(&self) -> MaskedToken<'_> {
+        MaskedToken(&self.0)
+    }
+}
+
+impl<T: SecretValue> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret<String> {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl<T: SecretValue> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl<T: SecretValue> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+/// Displays a token by its fingerprint, e.g. `token:ba7816bf8f01cfea`.
+/// 
+/// # Examples
+/// ```
+/// use steam_session::secret::MaskedToken;
+/// 
+/// assert_eq!(MaskedToken("abc").to_string(), "token:ba7816bf8f01cfea");
+/// ```
+#[derive(Clone, Copy)]
+pub struct MaskedToken<'a>(pub &'a str);
+
+impl fmt::Display for MaskedToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "token:{}", crate::audit::token_fingerprint(self.0))
+    }
+}
+
+impl fmt::Debug for MaskedToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Displays an account name with all but its first and last characters masked, e.g. `j*****e`. 
+/// Names of two characters or fewer are masked entirely.
+/// 
+/// # Examples
+/// ```
+/// use steam_session::secret::MaskedAccountName;
+/// 
+/// assert_eq!(MaskedAccountName("johndoe").to_string(), "j*****e");
+/// assert_eq!(MaskedAccountName("jd").to_string(), "**");
+/// ```
+#[derive(Clone, Copy)]
+pub struct MaskedAccountName<'a>(pub &'a str);
+
+impl fmt::Display for MaskedAccountName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chars = self.0.chars().collect::<Vec<_>>();
+        
+        match chars.as_slice() {
+            [first, middle @ .., last] if !middle.is_empty() => {
+                write!(f, "{first}{}{last}", "*".repeat(middle.len()))
+            },
+            _ => f.write_str(&"*".repeat(chars.len())),
+        }
+    }
+}
+
+impl fmt::Debug for MaskedAccountName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: SecretValue> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
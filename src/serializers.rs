@@ -42,4 +42,52 @@ where
     }?;
     
     T::try_from(number).map_err(|_| de::Error::custom("failed to convert from primitive"))
-}
\ No newline at end of file
+}
+/// Serializes an [`EAuthTokenPlatformType`](crate::enums::EAuthTokenPlatformType) as its 
+/// numeric value. For use with `#[serde(with = "...")]`.
+#[cfg(feature = "serialize")]
+pub mod platform_type {
+    use crate::enums::EAuthTokenPlatformType;
+    use protobuf::Enum;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de;
+    
+    pub fn serialize<S>(platform_type: &EAuthTokenPlatformType, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i32(platform_type.value())
+    }
+    
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<EAuthTokenPlatformType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = i32::deserialize(deserializer)?;
+        
+        EAuthTokenPlatformType::from_i32(value)
+            .ok_or_else(|| de::Error::custom(format!("unknown platform type: {value}")))
+    }
+}
+
+/// Serializes a [`SteamID`](steamid_ng::SteamID) as a 64-bit integer. For use with 
+/// `#[serde(with = "...")]`.
+#[cfg(feature = "serialize")]
+pub mod steamid_as_u64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use steamid_ng::SteamID;
+    
+    pub fn serialize<S>(steamid: &SteamID, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(u64::from(*steamid))
+    }
+    
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<SteamID, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(SteamID::from(u64::deserialize(deserializer)?))
+    }
+}
@@ -0,0 +1,337 @@
+use crate::secret::Secret;
+use crate::transports::{Socks5ProxyConfig, Socks5ProxyConfigError};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use serde::Deserialize;
+
+/// The length of a decoded shared secret.
+const SHARED_SECRET_LENGTH: usize = 20;
+
+/// An account managed by a [`SessionManager`](super::SessionManager).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedAccount {
+    /// The account name used to log in.
+    pub account_name: String,
+    /// The password of the account.
+    pub password: Secret<String>,
+    /// The shared secret of the account's mobile authenticator, if it has one, used to generate
+    /// Steam Guard codes.
+    pub shared_secret: Option<Secret<String>>,
+    /// The proxy the account logs in through, if any.
+    pub proxy: Option<Socks5ProxyConfig>,
+}
+
+/// The format of an accounts file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountsFormat {
+    /// One account per line as `login:password`, optionally followed by `:shared_secret` and
+    /// `:proxy`. The proxy may be written as `host:port`, `host:port:username:password`, or a
+    /// `socks5://` URL. An empty shared secret can be given to set only a proxy.
+    ///
+    /// Passwords containing `:` cannot be told apart from the fields after them, so they are not
+    /// supported in this format. Lines that fail to parse because of this are reported with
+    /// [`AccountParseErrorReason::Ambiguous`]. Use [`AccountsFormat::Csv`] or
+    /// [`AccountsFormat::Json`] for such passwords.
+    Colon,
+    /// One account per line as `login,password,shared_secret,proxy`, with the last two columns
+    /// optional. A header line starting with `login` is skipped.
+    Csv,
+    /// An array of objects with `login`, `password`, and optional `shared_secret` and `proxy`
+    /// fields.
+    Json,
+}
+
+impl AccountsFormat {
+    /// Gets the format of the file at `path` from its extension. Files with no recognized
+    /// extension are assumed to be [`AccountsFormat::Colon`].
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Self::Json,
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Self::Csv,
+            _ => Self::Colon,
+        }
+    }
+}
+
+/// An entry in an accounts file that could not be loaded.
+#[derive(Debug, thiserror::Error)]
+#[error("Line {}: {}", .line, .reason)]
+pub struct AccountParseError {
+    /// The line of the entry, starting at 1. For JSON files this is the position of the entry in
+    /// the array.
+    pub line: usize,
+    /// Why the entry could not be loaded.
+    pub reason: AccountParseErrorReason,
+}
+
+/// Why an entry in an accounts file could not be loaded.
+#[derive(Debug, thiserror::Error)]
+pub enum AccountParseErrorReason {
+    #[error("Missing {}", .0)]
+    MissingField(&'static str),
+    #[error("Too many fields")]
+    TooManyFields,
+    #[error("Account names may only contain letters, digits, and underscores")]
+    InvalidAccountName,
+    #[error("Shared secret is not a valid base64-encoded {SHARED_SECRET_LENGTH}-byte secret")]
+    InvalidSharedSecret,
+    #[error("Invalid proxy: {}", .0)]
+    InvalidProxy(#[from] Socks5ProxyConfigError),
+    #[error("Account {} is listed more than once", .0)]
+    DuplicateAccount(String),
+    #[error("Invalid entry: {}", .0)]
+    InvalidEntry(String),
+    #[error("{} (passwords containing ':' are not supported in this format)", .0)]
+    Ambiguous(Box<AccountParseErrorReason>),
+}
+
+/// The result of [`SessionManager::load_accounts`](super::SessionManager::load_accounts).
+#[derive(Debug, Default)]
+pub struct LoadAccountsReport {
+    /// The number of accounts loaded.
+    pub loaded: usize,
+    /// The entries that could not be loaded.
+    pub errors: Vec<AccountParseError>,
+}
+
+impl LoadAccountsReport {
+    /// Whether every entry was loaded.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// An entry in a JSON accounts file.
+#[derive(Deserialize)]
+struct JsonAccount {
+    #[serde(alias = "account_name", alias = "username")]
+    login: Option<String>,
+    password: Option<String>,
+    #[serde(default)]
+    shared_secret: Option<String>,
+    #[serde(default)]
+    proxy: Option<String>,
+}
+
+impl fmt::Debug for JsonAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonAccount")
+            .field("login", &self.login)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parses the accounts in `text`. Entries that are invalid are returned as errors rather than
+/// failing the whole file. Blank lines and lines starting with `#` are skipped.
+///
+/// # Examples
+/// ```
+/// use steam_session::session_manager::{parse_accounts, AccountsFormat};
+///
+/// let text = "alice:hunter2\nbob:swordfish:MTIzNDU2Nzg5MDEyMzQ1Njc4OTA=:127.0.0.1:1080\nmallory\n";
+/// let (accounts, errors) = parse_accounts(text, AccountsFormat::Colon).unwrap();
+///
+/// assert_eq!(accounts.len(), 2);
+/// assert_eq!(errors[0].line, 3);
+/// ```
+pub fn parse_accounts(
+    text: &str,
+    format: AccountsFormat,
+) -> Result<(Vec<ManagedAccount>, Vec<AccountParseError>), serde_json::Error> {
+    let entries = match format {
+        AccountsFormat::Json => serde_json::from_str::<Vec<serde_json::Value>>(text)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| (index + 1, parse_json_entry(value)))
+            .collect::<Vec<_>>(),
+        AccountsFormat::Colon | AccountsFormat::Csv => text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .filter(|(line_number, line)| {
+                // skip a header
+                !(format == AccountsFormat::Csv && *line_number == 1 && line.starts_with("login"))
+            })
+            .map(|(line_number, line)| (line_number, parse_line(line, format)))
+            .collect::<Vec<_>>(),
+    };
+    let mut seen = HashSet::new();
+    let mut accounts = Vec::new();
+    let mut errors = Vec::new();
+    
+    for (line, entry) in entries {
+        let result = entry.and_then(|account| {
+            if seen.insert(account.account_name.to_lowercase()) {
+                Ok(account)
+            } else {
+                Err(AccountParseErrorReason::DuplicateAccount(account.account_name))
+            }
+        });
+        
+        match result {
+            Ok(account) => accounts.push(account),
+            Err(reason) => errors.push(AccountParseError { line, reason }),
+        }
+    }
+    
+    Ok((accounts, errors))
+}
+
+fn parse_line(line: &str, format: AccountsFormat) -> Result<ManagedAccount, AccountParseErrorReason> {
+    let fields = if format == AccountsFormat::Csv {
+        let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+        
+        if fields.len() > 4 {
+            return Err(AccountParseErrorReason::TooManyFields);
+        }
+        
+        fields
+    } else {
+        // the proxy takes the rest of the line, since it may contain colons
+        line.splitn(4, ':').collect::<Vec<_>>()
+    };
+    let field = |index: usize| fields
+        .get(index)
+        .copied()
+        .filter(|field| !field.is_empty());
+    
+    validate(field(0), field(1), field(2), field(3)).map_err(|reason| match reason {
+        // a password containing colons is split into the fields after it
+        AccountParseErrorReason::InvalidSharedSecret |
+        AccountParseErrorReason::InvalidProxy(_) if format == AccountsFormat::Colon => {
+            AccountParseErrorReason::Ambiguous(Box::new(reason))
+        },
+        reason => reason,
+    })
+}
+
+fn parse_json_entry(value: serde_json::Value) -> Result<ManagedAccount, AccountParseErrorReason> {
+    let entry = serde_json::from_value::<JsonAccount>(value)
+        .map_err(|error| AccountParseErrorReason::InvalidEntry(error.to_string()))?;
+    
+    validate(
+        entry.login.as_deref(),
+        entry.password.as_deref(),
+        entry.shared_secret.as_deref().filter(|field| !field.is_empty()),
+        entry.proxy.as_deref().filter(|field| !field.is_empty()),
+    )
+}
+
+fn validate(
+    login: Option<&str>,
+    password: Option<&str>,
+    shared_secret: Option<&str>,
+    proxy: Option<&str>,
+) -> Result<ManagedAccount, AccountParseErrorReason> {
+    let account_name = login.ok_or(AccountParseErrorReason::MissingField("login"))?;
+    let password = password.ok_or(AccountParseErrorReason::MissingField("password"))?;
+    
+    if !account_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(AccountParseErrorReason::InvalidAccountName);
+    }
+    
+    if let Some(shared_secret) = shared_secret {
+        crate::helpers::decode_base64(shared_secret.trim_end_matches('='))
+            .ok()
+            .filter(|shared_secret| shared_secret.len() == SHARED_SECRET_LENGTH)
+            .ok_or(AccountParseErrorReason::InvalidSharedSecret)?;
+    }
+    
+    let proxy = proxy
+        .map(parse_proxy)
+        .transpose()?;
+    
+    Ok(ManagedAccount {
+        account_name: account_name.to_owned(),
+        password: Secret::new(password.to_owned()),
+        shared_secret: shared_secret.map(|shared_secret| Secret::new(shared_secret.to_owned())),
+        proxy,
+    })
+}
+
+/// Parses a proxy, also accepting the `host:port:username:password` form.
+fn parse_proxy(proxy: &str) -> Result<Socks5ProxyConfig, Socks5ProxyConfigError> {
+    if !proxy.contains("://") {
+        if let [host, port, username, password] = proxy.splitn(4, ':').collect::<Vec<_>>()[..] {
+            if port.parse::<u16>().is_ok() {
+                // the credentials are set as is rather than put in a URL, where characters such
+                // as `@` and `/` would need to be percent-encoded
+                return Ok(format!("{host}:{port}")
+                    .parse::<Socks5ProxyConfig>()?
+                    .with_credentials(username, password));
+            }
+        }
+    }
+    
+    proxy.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_bot_farm_formats() {
+        let text = "\
+# accounts
+alice:hunter2
+bob:swordfish:MTIzNDU2Nzg5MDEyMzQ1Njc4OTA=:127.0.0.1:1080:user:pass
+carol:password::socks5://127.0.0.1:9050
+dave
+alice:again
+eve:password:not base64!
+";
+        let (accounts, errors) = parse_accounts(text, AccountsFormat::Colon).unwrap();
+        
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[1].proxy.as_ref().unwrap().credentials().0, Some("user"));
+        assert!(accounts[2].shared_secret.is_none());
+        assert_eq!(accounts[2].proxy.as_ref().unwrap().port(), 9050);
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], AccountParseError { line: 5, reason: AccountParseErrorReason::MissingField("password") }));
+        assert!(matches!(errors[1].reason, AccountParseErrorReason::DuplicateAccount(_)));
+        assert!(matches!(
+            &errors[2].reason,
+            AccountParseErrorReason::Ambiguous(reason) if matches!(**reason, AccountParseErrorReason::InvalidSharedSecret),
+        ));
+    }
+    
+    #[test]
+    fn rejects_passwords_with_colons() {
+        let (accounts, errors) = parse_accounts("alice:pass:word\n", AccountsFormat::Colon).unwrap();
+        
+        assert!(accounts.is_empty());
+        assert!(matches!(errors[0].reason, AccountParseErrorReason::Ambiguous(_)));
+        
+        // other formats can hold them
+        let (accounts, errors) = parse_accounts("alice,pass:word\n", AccountsFormat::Csv).unwrap();
+        
+        assert!(errors.is_empty());
+        assert_eq!(accounts[0].password.expose_secret(), "pass:word");
+    }
+    
+    #[test]
+    fn keeps_proxy_credentials_as_is() {
+        let (accounts, errors) = parse_accounts(
+            "alice:hunter2::127.0.0.1:1080:user:p@ss/w#rd:1\n",
+            AccountsFormat::Colon,
+        ).unwrap();
+        let proxy = accounts[0].proxy.as_ref().unwrap();
+        
+        assert!(errors.is_empty());
+        assert_eq!(proxy.host(), "127.0.0.1");
+        assert_eq!(proxy.port(), 1080);
+        assert_eq!(proxy.credentials(), (Some("user"), Some("p@ss/w#rd:1")));
+    }
+    
+    #[test]
+    fn parses_json() {
+        let text = r#"[{"login": "alice", "password": "hunter2"}, {"login": "bob"}]"#;
+        let (accounts, errors) = parse_accounts(text, AccountsFormat::Json).unwrap();
+        
+        assert_eq!(accounts[0].account_name, "alice");
+        assert_eq!(errors[0].line, 2);
+    }
+}
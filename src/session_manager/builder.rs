@@ -0,0 +1,120 @@
+use super::{SessionManager, SessionEventSink};
+use super::scheduler::{LoginLimits, LoginScheduler};
+use super::state::default_account_retry_policy;
+use crate::clock::Clock;
+use crate::retry::RetryPolicy;
+use crate::token_store::TokenStore;
+use crate::transports::websocket::WebSocketCMTransportBuilder;
+use std::sync::Arc;
+
+/// The default number of connections opened at once by [`SessionManager::preconnect`].
+const DEFAULT_CONNECT_CONCURRENCY: usize = 16;
+
+/// Builder for creating a [`SessionManager`].
+/// 
+/// # Examples
+/// ```
+/// use steam_session::session_manager::SessionManager;
+/// use steam_session::transports::WebSocketCMTransport;
+/// use std::time::Duration;
+/// 
+/// let manager = SessionManager::builder()
+///     .transport_builder(WebSocketCMTransport::builder().connect_timeout(Duration::from_secs(10)))
+///     .connect_concurrency(32)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionManagerBuilder {
+    transport_builder: WebSocketCMTransportBuilder,
+    connect_concurrency: usize,
+    login_limits: LoginLimits,
+    account_retry_policy: RetryPolicy,
+    clock: Arc<dyn Clock>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    event_sink: Option<Arc<dyn SessionEventSink>>,
+}
+
+impl Default for SessionManagerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionManagerBuilder {
+    /// Creates a new [`SessionManagerBuilder`].
+    pub fn new() -> Self {
+        Self {
+            transport_builder: WebSocketCMTransportBuilder::new(),
+            connect_concurrency: DEFAULT_CONNECT_CONCURRENCY,
+            login_limits: LoginLimits::default(),
+            account_retry_policy: default_account_retry_policy(),
+            clock: crate::clock::system_clock(),
+            token_store: None,
+            event_sink: None,
+        }
+    }
+    
+    /// Sets the builder used to connect transports to CM servers.
+    pub fn transport_builder(mut self, transport_builder: WebSocketCMTransportBuilder) -> Self {
+        self.transport_builder = transport_builder;
+        self
+    }
+    
+    /// Sets how many connections are opened at once when preconnecting. Defaults to 16. A value 
+    /// of 0 is treated as 1.
+    pub fn connect_concurrency(mut self, connect_concurrency: usize) -> Self {
+        self.connect_concurrency = connect_concurrency.max(1);
+        self
+    }
+    
+    /// Sets the limits logins are scheduled with. Defaults to [`LoginLimits::default`].
+    pub fn login_limits(mut self, login_limits: LoginLimits) -> Self {
+        self.login_limits = login_limits;
+        self
+    }
+    
+    /// Sets how failed logins of managed accounts are backed off. Defaults to backing off from a 
+    /// minute, doubling up to an hour. Only the backoff settings of the policy are used.
+    pub fn account_retry_policy(mut self, account_retry_policy: RetryPolicy) -> Self {
+        self.account_retry_policy = account_retry_policy;
+        self
+    }
+    
+    /// Sets the clock used for account states. Defaults to the system clock.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Sets the store the tokens of managed accounts are kept in. It is flushed when the manager 
+    /// is shut down.
+    pub fn token_store(mut self, token_store: Arc<dyn TokenStore>) -> Self {
+        self.token_store = Some(token_store);
+        self
+    }
+    
+    /// Sets the sink told when managed accounts log in, need a Steam Guard code, are revoked, or 
+    /// are throttled.
+    pub fn event_sink(mut self, event_sink: Arc<dyn SessionEventSink>) -> Self {
+        self.event_sink = Some(event_sink);
+        self
+    }
+    
+    /// Builds the [`SessionManager`].
+    pub fn build(self) -> SessionManager {
+        SessionManager {
+            transport_builder: self.transport_builder,
+            connect_concurrency: self.connect_concurrency,
+            pool: Default::default(),
+            accounts: Default::default(),
+            scheduler: LoginScheduler::new(self.login_limits),
+            account_retry_policy: self.account_retry_policy,
+            clock: self.clock,
+            token_store: self.token_store,
+            shutdown: tokio::sync::watch::channel(false).0,
+            in_flight: tokio::sync::watch::channel(0).0,
+            changes: tokio::sync::broadcast::channel(super::CHANGE_CHANNEL_CAPACITY).0,
+            event_sink: self.event_sink,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::ErrorKind;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Websocket CM: {}", .0)]
+    WebSocketCM(#[from] crate::transports::websocket::Error),
+    #[error("IO error: {}", .0)]
+    Io(#[from] std::io::Error),
+    #[error("Invalid accounts file: {}", .0)]
+    AccountsFile(#[from] serde_json::Error),
+    #[error("Token store: {}", .0)]
+    TokenStore(#[from] crate::token_store::Error),
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::WebSocketCM(error) => error.kind(),
+            Self::Io(error) => crate::error::io_kind(error),
+            Self::AccountsFile(_) => ErrorKind::InvalidInput,
+            Self::TokenStore(error) => error.kind(),
+        }
+    }
+}
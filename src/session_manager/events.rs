@@ -0,0 +1,165 @@
+use super::{AccountState, AccountStatus};
+use crate::types::DateTime;
+use std::fmt;
+use std::time::Duration;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+/// The number of events a [`WebhookSink`] buffers while earlier events are being posted.
+const WEBHOOK_QUEUE_CAPACITY: usize = 1024;
+/// How long a webhook request can take before it is abandoned.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Receives lifecycle events of the accounts of a [`SessionManager`](super::SessionManager).
+/// Implementations should return quickly, e.g. by forwarding events to a channel, since they are
+/// called inline.
+///
+/// This is implemented for [`mpsc::Sender`], so events can be sent to a bus consumed elsewhere.
+/// Events are dropped while the channel is full.
+pub trait SessionEventSink: fmt::Debug + Send + Sync {
+    /// Sends `event`.
+    fn send(&self, event: &SessionEvent);
+}
+
+/// A lifecycle event of a managed account. Serializes to JSON as an object with the account
+/// name, the time, an `event` field naming the kind, and the fields of the kind, e.g.
+/// `{"account_name":"alice","time":"2024-01-01T00:00:00Z","event":"guard_required"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionEvent {
+    /// The name of the account.
+    pub account_name: String,
+    /// When the event happened.
+    pub time: DateTime,
+    /// What happened.
+    #[serde(flatten)]
+    pub kind: SessionEventKind,
+}
+
+/// What happened in a [`SessionEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SessionEventKind {
+    /// The account logged in.
+    Authenticated {
+        /// When the refresh token expires, if known.
+        expiry: Option<DateTime>,
+    },
+    /// A login is waiting for a Steam Guard code or confirmation.
+    GuardRequired,
+    /// The account's token was revoked or rejected.
+    Revoked {
+        /// Why, if known.
+        reason: Option<String>,
+    },
+    /// Logging in failed and is backing off.
+    Throttled {
+        /// When the account can be logged in again.
+        until: DateTime,
+        /// Why the login failed.
+        reason: Option<String>,
+    },
+}
+
+impl SessionEvent {
+    /// Gets the event for `account_name` moving to the state in `status`, if it is one that
+    /// events are sent for.
+    pub(super) fn from_status(account_name: &str, status: &AccountStatus) -> Option<Self> {
+        let kind = match &status.state {
+            AccountState::Active { expiry } => SessionEventKind::Authenticated {
+                expiry: *expiry,
+            },
+            AccountState::Guarded => SessionEventKind::GuardRequired,
+            AccountState::Revoked => SessionEventKind::Revoked {
+                reason: status.last_error.clone(),
+            },
+            AccountState::Throttled { until } => SessionEventKind::Throttled {
+                until: *until,
+                reason: status.last_error.clone(),
+            },
+            AccountState::NeedsLogin => return None,
+        };
+        
+        Some(Self {
+            account_name: account_name.to_owned(),
+            time: status.updated_at,
+            kind,
+        })
+    }
+}
+
+impl SessionEventSink for mpsc::Sender<SessionEvent> {
+    fn send(&self, event: &SessionEvent) {
+        if let Err(error) = self.try_send(event.clone()) {
+            warn!("Dropped session event for {}: {error}", event.account_name);
+        }
+    }
+}
+
+/// Posts events as JSON to an HTTP webhook. Events are posted one at a time in the order they
+/// were sent by a background task; failed posts are logged and not retried.
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    queue: mpsc::Sender<SessionEvent>,
+}
+
+impl WebhookSink {
+    /// Creates a sink posting to `url`. This must be called from within a Tokio runtime.
+    pub fn new(url: reqwest::Url) -> Self {
+        Self::with_client(reqwest::Client::new(), url)
+    }
+    
+    /// Creates a sink posting to `url` using `client`, which can set default headers such as
+    /// an `Authorization` header expected by the webhook. This must be called from within a
+    /// Tokio runtime.
+    pub fn with_client(client: reqwest::Client, url: reqwest::Url) -> Self {
+        let (queue, mut events) = mpsc::channel::<SessionEvent>(WEBHOOK_QUEUE_CAPACITY);
+        
+        // the task stops once every sender is dropped
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let result = client
+                    .post(url.clone())
+                    .json(&event)
+                    .timeout(WEBHOOK_TIMEOUT)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status);
+                
+                if let Err(error) = result {
+                    warn!("Failed to post session event for {} to webhook: {error}", event.account_name);
+                }
+            }
+        });
+        
+        Self {
+            queue,
+        }
+    }
+}
+
+impl SessionEventSink for WebhookSink {
+    fn send(&self, event: &SessionEvent) {
+        SessionEventSink::send(&self.queue, event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    
+    #[test]
+    fn serializes_flat_json() {
+        let event = SessionEvent {
+            account_name: "alice".into(),
+            time: chrono::Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            kind: SessionEventKind::GuardRequired,
+        };
+        
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"account_name":"alice","time":"2024-01-01T00:00:00Z","event":"guard_required"}"#,
+        );
+    }
+}
@@ -0,0 +1,916 @@
+//! # Session manager
+//! 
+//! Manages the CM connections used by many sessions at once. Connecting hundreds of sockets at 
+//! the start of a mass login causes a latency spike, so connections can be opened ahead of time 
+//! with [`SessionManager::preconnect`] and handed out as they are needed.
+//! 
+//! Accounts to manage can be loaded from the batch files commonly used to store them with 
+//! [`SessionManager::load_accounts`]. Logging in many accounts at once is staggered and limited 
+//! according to the manager's [`LoginLimits`], so that Steam does not ban the IPs they log in 
+//! from. The [`AccountState`] of each account is tracked as logins succeed and fail, and can be 
+//! read for all accounts at once with [`SessionManager::snapshot`].
+//! 
+//! Accounts can be added, removed, and moved to other proxies while the manager is running, 
+//! either directly or by reloading the accounts file with [`SessionManager::reload_accounts`] or 
+//! [`SessionManager::watch_accounts`]. Sessions learn about changes to their account from 
+//! [`SessionManager::subscribe_changes`], so unrelated sessions are left running.
+//! 
+//! An account can hold a session for each [`SessionPlatform`] at once, e.g. a web browser 
+//! session alongside a mobile app session. Each platform logs in as its own [`SessionDevice`] 
+//! and keeps its tokens under its own key in the token store, so the sessions do not replace 
+//! each other. Build their sessions with [`SessionManager::session_builder`].
+//! 
+//! Orchestration systems can be told when accounts log in, need a Steam Guard code, are 
+//! revoked, or are throttled by setting a [`SessionEventSink`], such as a [`WebhookSink`] or an 
+//! `mpsc` channel, with [`SessionManagerBuilder::event_sink`].
+//! 
+//! Before the process exits, [`SessionManager::shutdown`] cancels logins that have not started, 
+//! waits for the rest to finish, and closes pooled connections.
+//! 
+//! ```no_run
+//! # async fn example() -> Result<(), steam_session::session_manager::Error> {
+//! use steam_session::session_manager::SessionManager;
+//! 
+//! let manager = SessionManager::builder().build();
+//! 
+//! manager.preconnect(100).await?;
+//! 
+//! // Uses a warm connection if one is available
+//! let transport = manager.transport().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+mod builder;
+mod accounts;
+mod scheduler;
+mod state;
+mod reload;
+mod events;
+mod platforms;
+
+pub use error::Error;
+pub use builder::SessionManagerBuilder;
+pub use accounts::{
+    ManagedAccount,
+    AccountsFormat,
+    AccountParseError,
+    AccountParseErrorReason,
+    LoadAccountsReport,
+    parse_accounts,
+};
+pub use scheduler::{LoginLimits, LoginPermit};
+pub use state::{AccountState, AccountStatus, AccountsSnapshot};
+pub use reload::{AccountChange, ReloadReport};
+pub use events::{SessionEventSink, SessionEvent, SessionEventKind, WebhookSink};
+pub use platforms::{SessionPlatform, SessionDevice};
+
+/// What happened during [`SessionManager::shutdown`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Whether every login in flight finished before the deadline.
+    pub drained: bool,
+    /// The number of logins still running when the deadline passed.
+    pub abandoned_logins: usize,
+    /// The number of pooled connections closed.
+    pub transports_closed: usize,
+}
+
+use scheduler::LoginScheduler;
+use crate::transports::{WebSocketCMTransport, Socks5ProxyConfig};
+use crate::transports::websocket::WebSocketCMTransportBuilder;
+use crate::clock::Clock;
+use crate::enums::EResult;
+use crate::retry::RetryPolicy;
+use crate::types::DateTime;
+use crate::token_store::TokenStore;
+use crate::transports::Transport;
+use crate::login_session::{LoginSessionBuilder, SessionTokens};
+use crate::ErrorKind;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::future::Future;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use futures::StreamExt;
+use futures::future::Either;
+use tokio::sync::{broadcast, watch};
+
+/// The capacity of the channel account changes are sent on.
+const CHANGE_CHANNEL_CAPACITY: usize = 64;
+
+/// Manages CM connections for many sessions.
+#[derive(Debug)]
+pub struct SessionManager {
+    transport_builder: WebSocketCMTransportBuilder,
+    connect_concurrency: usize,
+    pool: Mutex<VecDeque<WebSocketCMTransport>>,
+    accounts: Mutex<BTreeMap<String, AccountEntry>>,
+    scheduler: LoginScheduler,
+    account_retry_policy: RetryPolicy,
+    clock: Arc<dyn Clock>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    shutdown: watch::Sender<bool>,
+    in_flight: watch::Sender<usize>,
+    changes: broadcast::Sender<AccountChange>,
+    event_sink: Option<Arc<dyn SessionEventSink>>,
+}
+
+/// A managed account and its status.
+#[derive(Debug)]
+struct AccountEntry {
+    account: ManagedAccount,
+    status: AccountStatus,
+    devices: BTreeMap<SessionPlatform, SessionDevice>,
+    /// Whether [`SessionManager::login_due`] is logging in the account.
+    claimed: bool,
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl SessionManager {
+    /// Creates a new [`SessionManagerBuilder`].
+    pub fn builder() -> SessionManagerBuilder {
+        SessionManagerBuilder::new()
+    }
+    
+    /// Opens connections until `n` warm connections are in the pool. Connections are opened 
+    /// concurrently, up to the configured `connect_concurrency` at a time. Connections that fail 
+    /// are skipped; an error is only returned if every connection failed.
+    /// 
+    /// Returns the number of warm connections in the pool.
+    pub async fn preconnect(&self, n: usize) -> Result<usize, Error> {
+        let missing = n.saturating_sub(self.warm_count());
+        
+        if missing == 0 {
+            return Ok(self.warm_count());
+        }
+        
+        let mut connecting = futures::stream::iter(0..missing)
+            .map(|_| self.transport_builder.clone().connect())
+            .buffer_unordered(self.connect_concurrency);
+        let mut connected = 0;
+        let mut last_error = None;
+        
+        while let Some(result) = connecting.next().await {
+            match result {
+                Ok(transport) => {
+                    connected += 1;
+                    self.lock_pool().push_back(transport);
+                },
+                Err(error) => {
+                    warn!("Error preconnecting to CM server: {error}");
+                    last_error = Some(error);
+                },
+            }
+        }
+        
+        debug!("Preconnected {connected} of {missing} CM connections");
+        
+        match last_error {
+            Some(error) if connected == 0 => Err(error.into()),
+            _ => Ok(self.warm_count()),
+        }
+    }
+    
+    /// Takes a warm connection from the pool, or connects a new one if the pool is empty.
+    pub async fn transport(&self) -> Result<WebSocketCMTransport, Error> {
+        if let Some(transport) = self.take_warm() {
+            return Ok(transport);
+        }
+        
+        let transport = self.transport_builder.clone().connect().await?;
+        
+        Ok(transport)
+    }
+    
+    /// Takes a warm connection from the pool, if one is available. Connections that became 
+    /// unhealthy while waiting in the pool are discarded.
+    pub fn take_warm(&self) -> Option<WebSocketCMTransport> {
+        let mut pool = self.lock_pool();
+        
+        while let Some(transport) = pool.pop_front() {
+            if transport.is_healthy() {
+                return Some(transport);
+            }
+        }
+        
+        None
+    }
+    
+    /// Gets the number of healthy connections in the pool. Unhealthy connections are removed.
+    pub fn warm_count(&self) -> usize {
+        let mut pool = self.lock_pool();
+        
+        pool.retain(|transport| transport.is_healthy());
+        pool.len()
+    }
+    
+    /// Loads the accounts in the file at `path`, in the format given by its extension (see 
+    /// [`AccountsFormat::from_path`]). Accounts already managed are replaced by entries with 
+    /// the same account name, keeping their state.
+    /// 
+    /// Invalid entries are skipped and returned in the report with their line numbers, so one 
+    /// malformed line does not prevent the rest of the file from loading. An error is only 
+    /// returned if the file cannot be read or, for JSON files, is not an array.
+    pub async fn load_accounts(&self, path: impl AsRef<Path>) -> Result<LoadAccountsReport, Error> {
+        let path = path.as_ref();
+        let text = tokio::fs::read_to_string(path).await?;
+        let (accounts, errors) = parse_accounts(&text, AccountsFormat::from_path(path))?;
+        let loaded = accounts.len();
+        
+        for account in accounts {
+            self.upsert_account(account);
+        }
+        
+        debug!("Loaded {loaded} accounts from {} ({} invalid)", path.display(), errors.len());
+        
+        Ok(LoadAccountsReport {
+            loaded,
+            errors,
+        })
+    }
+    
+    /// Loads the accounts in the file at `path` like [`SessionManager::load_accounts`], then 
+    /// removes managed accounts that are no longer in the file. Accounts whose entries did not 
+    /// change keep running untouched. Each change is sent to 
+    /// [`SessionManager::subscribe_changes`].
+    pub async fn reload_accounts(&self, path: impl AsRef<Path>) -> Result<ReloadReport, Error> {
+        let path = path.as_ref();
+        let text = tokio::fs::read_to_string(path).await?;
+        let (accounts, errors) = parse_accounts(&text, AccountsFormat::from_path(path))?;
+        let mut report = ReloadReport::default();
+        
+        if errors.is_empty() {
+            let keys = accounts
+                .iter()
+                .map(|account| account_key(&account.account_name))
+                .collect::<std::collections::HashSet<_>>();
+            let removed = self.lock_accounts()
+                .iter()
+                .filter(|(key, _)| !keys.contains(*key))
+                .map(|(_, entry)| entry.account.account_name.clone())
+                .collect::<Vec<_>>();
+            
+            for account_name in removed {
+                if let Some(account) = self.remove_account(&account_name) {
+                    report.removed.push(account.account_name);
+                }
+            }
+        }
+        
+        for account in accounts {
+            let account_name = account.account_name.clone();
+            
+            match self.upsert_account(account) {
+                None => report.added.push(account_name),
+                Some(changes) if !changes.is_empty() => report.updated.push(account_name),
+                Some(_) => {},
+            }
+        }
+        
+        report.errors = errors;
+        debug!(
+            "Reloaded accounts from {}: {} added, {} removed, {} updated, {} invalid",
+            path.display(),
+            report.added.len(),
+            report.removed.len(),
+            report.updated.len(),
+            report.errors.len()
+        );
+        
+        Ok(report)
+    }
+    
+    /// Reloads the accounts file at `path` with [`SessionManager::reload_accounts`] whenever its 
+    /// modification time changes, checking every `interval`. The task stops once the manager is 
+    /// shut down or dropped.
+    pub fn watch_accounts(
+        self: &Arc<Self>,
+        path: impl Into<PathBuf>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::downgrade(self);
+        let path = path.into();
+        
+        tokio::spawn(async move {
+            let mut last_modified = modified(&path).await;
+            
+            loop {
+                tokio::time::sleep(interval).await;
+                
+                let Some(manager) = Weak::upgrade(&manager) else {
+                    break;
+                };
+                
+                if manager.is_shutting_down() {
+                    break;
+                }
+                
+                let current = modified(&path).await;
+                
+                if current.is_none() || current == last_modified {
+                    continue;
+                }
+                
+                last_modified = current;
+                
+                if let Err(error) = manager.reload_accounts(&path).await {
+                    warn!("Failed to reload accounts from {}: {error}", path.display());
+                }
+            }
+        })
+    }
+    
+    /// Subscribes to changes to the managed accounts. Changes made before subscribing are not 
+    /// received.
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<AccountChange> {
+        self.changes.subscribe()
+    }
+    
+    /// Adds `account`, or updates it if an account with the same name is managed, keeping its 
+    /// state. Account names are matched ignoring case. Returns whether the account was added.
+    pub fn add_account(&self, account: ManagedAccount) -> bool {
+        self.upsert_account(account).is_none()
+    }
+    
+    /// Stops managing `account_name`, returning the account if it was managed.
+    pub fn remove_account(&self, account_name: &str) -> Option<ManagedAccount> {
+        let entry = self.lock_accounts().remove(&account_key(account_name))?;
+        
+        self.send_change(AccountChange::Removed(entry.account.account_name.clone()));
+        Some(entry.account)
+    }
+    
+    /// Moves `account_name` to `proxy`, or to a direct connection if `proxy` is `None`. Returns 
+    /// `false` if the account is not managed.
+    pub fn set_proxy(&self, account_name: &str, proxy: Option<Socks5ProxyConfig>) -> bool {
+        let mut account = match self.account(account_name) {
+            Some(account) => account,
+            None => return false,
+        };
+        
+        account.proxy = proxy;
+        self.upsert_account(account);
+        true
+    }
+    
+    /// Gets the accounts being managed, ordered by account name ignoring case.
+    pub fn accounts(&self) -> Vec<ManagedAccount> {
+        self.lock_accounts()
+            .values()
+            .map(|entry| entry.account.clone())
+            .collect()
+    }
+    
+    /// Gets the managed account named `account_name`.
+    pub fn account(&self, account_name: &str) -> Option<ManagedAccount> {
+        self.lock_accounts()
+            .get(&account_key(account_name))
+            .map(|entry| entry.account.clone())
+    }
+    
+    /// Gets the status of the managed account named `account_name`.
+    pub fn account_status(&self, account_name: &str) -> Option<AccountStatus> {
+        self.lock_accounts()
+            .get(&account_key(account_name))
+            .map(|entry| entry.status.clone())
+    }
+    
+    /// Gets the accounts that should be logged in now: those needing a login, those whose 
+    /// throttle has passed, and those whose session has expired. Accounts already being logged 
+    /// in by [`SessionManager::login_due`] are left out.
+    pub fn due_accounts(&self) -> Vec<ManagedAccount> {
+        let now = self.clock.now();
+        
+        self.lock_accounts()
+            .values()
+            .filter(|entry| !entry.claimed && entry.status.is_due(now))
+            .map(|entry| entry.account.clone())
+            .collect()
+    }
+    
+    /// Gets the status of every managed account, with the number of accounts in each state.
+    pub fn snapshot(&self) -> AccountsSnapshot {
+        let accounts = self.lock_accounts()
+            .values()
+            .map(|entry| (entry.account.account_name.clone(), entry.status.clone()))
+            .collect();
+        
+        AccountsSnapshot::new(self.clock.now(), accounts)
+    }
+    
+    /// Sets the state of `account_name`. Moving to [`AccountState::Active`] clears its failures. 
+    /// Returns `false` if the account is not managed.
+    pub fn set_account_state(&self, account_name: &str, state: AccountState) -> bool {
+        let now = self.clock.now();
+        let status = {
+            let mut accounts = self.lock_accounts();
+            let Some(entry) = accounts.get_mut(&account_key(account_name)) else {
+                return false;
+            };
+            
+            entry.status.set_state(state, now);
+            entry.status.clone()
+        };
+        
+        self.send_event(account_name, &status);
+        true
+    }
+    
+    /// Marks `account_name` as logged in, with a refresh token expiring at `expiry`.
+    pub fn mark_active(&self, account_name: &str, expiry: Option<DateTime>) -> bool {
+        self.set_account_state(account_name, AccountState::Active { expiry })
+    }
+    
+    /// Marks `account_name` as waiting for a Steam Guard code or confirmation.
+    pub fn mark_guarded(&self, account_name: &str) -> bool {
+        self.set_account_state(account_name, AccountState::Guarded)
+    }
+    
+    /// Marks `account_name` as having its token revoked.
+    pub fn mark_revoked(&self, account_name: &str) -> bool {
+        self.set_account_state(account_name, AccountState::Revoked)
+    }
+    
+    /// Records a failed login of `account_name`. Token errors mark the account as revoked; other 
+    /// failures throttle it according to the account retry policy set with 
+    /// [`SessionManagerBuilder::account_retry_policy`]. Returns the new state, or `None` if the 
+    /// account is not managed.
+    pub fn record_login_failure(
+        &self,
+        account_name: &str,
+        kind: ErrorKind,
+        eresult: Option<EResult>,
+        reason: impl Into<String>,
+    ) -> Option<AccountState> {
+        let now = self.clock.now();
+        let reason = reason.into();
+        let status = {
+            let mut accounts = self.lock_accounts();
+            let status = &mut accounts.get_mut(&account_key(account_name))?.status;
+            
+            status.record_failure(kind, eresult, reason, &self.account_retry_policy, now);
+            status.clone()
+        };
+        
+        debug!("Login of {account_name} failed {} times, now {:?}", status.failures, status.state);
+        self.send_event(account_name, &status);
+        Some(status.state)
+    }
+    
+    /// Gets the limits logins are scheduled with.
+    pub fn login_limits(&self) -> &LoginLimits {
+        self.scheduler.limits()
+    }
+    
+    /// Waits until a login through `proxy` is allowed to start under the manager's 
+    /// [`LoginLimits`]. Hold the permit for as long as the login runs.
+    pub async fn acquire_login_slot(&self, proxy: Option<&Socks5ProxyConfig>) -> LoginPermit {
+        self.scheduler.acquire(proxy).await
+    }
+    
+    /// Logs in `accounts` using `login`, staggered and limited according to the manager's 
+    /// [`LoginLimits`]. Returns the result of each login with its account name, in the order the 
+    /// logins were scheduled: the order given, or shuffled if [`LoginLimits::shuffle`] is set. 
+    /// This is not necessarily the order the logins finished in. Logins that had not started 
+    /// when the manager was shut down are cancelled and left out of the results.
+    /// 
+    /// # Examples
+    /// ```no_run
+    /// # async fn example(manager: steam_session::session_manager::SessionManager) {
+    /// let results = manager.run_logins(manager.accounts(), |account| async move {
+    ///     // start a LoginSession for the account...
+    ///     account.account_name.len()
+    /// }).await;
+    /// # }
+    /// ```
+    pub async fn run_logins<F, Fut, T>(&self, accounts: Vec<ManagedAccount>, login: F) -> Vec<(String, T)>
+    where
+        F: Fn(ManagedAccount) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let logins = self.scheduler
+            .order(accounts)
+            .into_iter()
+            .map(|account| {
+                let login = &login;
+                
+                async move {
+                    let _in_flight = InFlightLogin::new(&self.in_flight);
+                    let _permit = self.acquire_login_slot_unless_shutdown(account.proxy.as_ref()).await?;
+                    let account_name = account.account_name.clone();
+                    
+                    Some((account_name, login(account).await))
+                }
+            });
+        
+        futures::future::join_all(logins)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+    
+    /// Logs in every managed account using `login`. See [`SessionManager::run_logins`].
+    pub async fn login_all<F, Fut, T>(&self, login: F) -> Vec<(String, T)>
+    where
+        F: Fn(ManagedAccount) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        self.run_logins(self.accounts(), login).await
+    }
+    
+    /// Logs in the accounts from [`SessionManager::due_accounts`] using `login`. See 
+    /// [`SessionManager::run_logins`].
+    /// 
+    /// The accounts are claimed until their logins finish, so overlapping calls do not log in 
+    /// the same account twice.
+    pub async fn login_due<F, Fut, T>(&self, login: F) -> Vec<(String, T)>
+    where
+        F: Fn(ManagedAccount) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let claim = self.claim_due_accounts();
+        let accounts = claim.accounts.clone();
+        
+        self.run_logins(accounts, login).await
+    }
+    
+    /// Gets the token store set with [`SessionManagerBuilder::token_store`].
+    pub fn token_store(&self) -> Option<&Arc<dyn TokenStore>> {
+        self.token_store.as_ref()
+    }
+    
+    /// Creates a builder for a session of `account_name` on `platform`, using `transport`. The 
+    /// session logs in as the account's device for the platform, through the account's proxy. 
+    /// Returns `None` if the account is not managed.
+    pub fn session_builder<T>(
+        &self,
+        account_name: &str,
+        platform: SessionPlatform,
+        transport: T,
+    ) -> Option<LoginSessionBuilder<T>>
+    where
+        T: Transport,
+    {
+        let (account, device) = {
+            let accounts = self.lock_accounts();
+            let entry = accounts.get(&account_key(account_name))?;
+            
+            (entry.account.clone(), Self::entry_device(entry, platform))
+        };
+        let mut builder = device.apply(LoginSessionBuilder::new(transport, platform.platform_type()));
+        
+        if let Some(proxy) = account.proxy {
+            builder = builder.proxy(proxy);
+        }
+        
+        Some(builder)
+    }
+    
+    /// Gets the device `account_name` logs in as on `platform`. Returns `None` if the account is 
+    /// not managed.
+    pub fn device(&self, account_name: &str, platform: SessionPlatform) -> Option<SessionDevice> {
+        self.lock_accounts()
+            .get(&account_key(account_name))
+            .map(|entry| Self::entry_device(entry, platform))
+    }
+    
+    /// Sets the device `account_name` logs in as on `platform`, leaving its other platforms 
+    /// unchanged. Returns `false` if the account is not managed.
+    pub fn set_device(&self, account_name: &str, platform: SessionPlatform, device: SessionDevice) -> bool {
+        match self.lock_accounts().get_mut(&account_key(account_name)) {
+            Some(entry) => {
+                entry.devices.insert(platform, device);
+                true
+            },
+            None => false,
+        }
+    }
+    
+    /// Loads the tokens of `account_name`'s session on `platform` from the token store. Returns 
+    /// `None` if no tokens are stored or no token store is set.
+    pub async fn load_tokens(
+        &self,
+        account_name: &str,
+        platform: SessionPlatform,
+    ) -> Result<Option<SessionTokens>, Error> {
+        let Some(token_store) = &self.token_store else {
+            return Ok(None);
+        };
+        
+        Ok(token_store.load(&platform.token_key(account_name)).await?)
+    }
+    
+    /// Saves the tokens of `account_name`'s session on `platform` to the token store, leaving the 
+    /// tokens of its other platforms unchanged. Does nothing if no token store is set.
+    pub async fn save_tokens(
+        &self,
+        account_name: &str,
+        platform: SessionPlatform,
+        tokens: &SessionTokens,
+    ) -> Result<(), Error> {
+        if let Some(token_store) = &self.token_store {
+            token_store.save(&platform.token_key(account_name), tokens).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Whether [`SessionManager::shutdown`] has been called.
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+    
+    /// Shuts the manager down within `deadline`. Logins waiting to start are cancelled and no new 
+    /// logins are started. Logins already running are given until the deadline to finish, 
+    /// after which pooled connections are drained of requests in flight for whatever time 
+    /// remains and closed, and the token store is flushed. Connections taken from the pool are 
+    /// owned by their sessions and are not closed.
+    /// 
+    /// Fails only if the token store cannot be flushed.
+    pub async fn shutdown(&self, deadline: Duration) -> Result<ShutdownReport, Error> {
+        let started = Instant::now();
+        
+        self.shutdown.send_replace(true);
+        
+        let mut in_flight = self.in_flight.subscribe();
+        let drained = tokio::time::timeout(deadline, in_flight.wait_for(|in_flight| *in_flight == 0))
+            .await
+            .is_ok();
+        let abandoned_logins = *self.in_flight.borrow();
+        let remaining = deadline.saturating_sub(started.elapsed());
+        let transports = std::mem::take(&mut *self.lock_pool());
+        let transports_closed = transports.len();
+        
+        futures::future::join_all(transports.iter().map(|transport| async move {
+            transport.drain(remaining).await;
+            
+            if let Err(error) = transport.close().await {
+                debug!("Error closing CM connection: {error}");
+            }
+        })).await;
+        
+        if let Some(token_store) = &self.token_store {
+            token_store.flush().await?;
+        }
+        
+        debug!("Shut down with {abandoned_logins} logins abandoned and {transports_closed} connections closed");
+        
+        Ok(ShutdownReport {
+            drained,
+            abandoned_logins,
+            transports_closed,
+        })
+    }
+    
+    /// Claims the accounts from [`SessionManager::due_accounts`] until the returned claim is 
+    /// dropped.
+    fn claim_due_accounts(&self) -> DueClaim<'_> {
+        let now = self.clock.now();
+        let accounts = self.lock_accounts()
+            .values_mut()
+            .filter(|entry| !entry.claimed && entry.status.is_due(now))
+            .map(|entry| {
+                entry.claimed = true;
+                entry.account.clone()
+            })
+            .collect();
+        
+        DueClaim {
+            manager: self,
+            accounts,
+        }
+    }
+    
+    /// Waits for a login slot, giving up if the manager is shut down first.
+    async fn acquire_login_slot_unless_shutdown(&self, proxy: Option<&Socks5ProxyConfig>) -> Option<LoginPermit> {
+        let mut shutdown = self.shutdown.subscribe();
+        let acquire = std::pin::pin!(self.acquire_login_slot(proxy));
+        let cancelled = std::pin::pin!(shutdown.wait_for(|shutdown| *shutdown));
+        
+        match futures::future::select(acquire, cancelled).await {
+            Either::Left((permit, _)) => Some(permit),
+            Either::Right(_) => None,
+        }
+    }
+    
+    /// Adds or updates `account`, sending the changes. Returns the changes made to an existing 
+    /// account, or `None` if it was added.
+    fn upsert_account(&self, account: ManagedAccount) -> Option<Vec<AccountChange>> {
+        let now = self.clock.now();
+        let account_name = account.account_name.clone();
+        let key = account_key(&account_name);
+        let changes = {
+            let mut accounts = self.lock_accounts();
+            
+            match accounts.get_mut(&key) {
+                Some(entry) => {
+                    let changes = reload::diff_account(&entry.account, &account);
+                    
+                    entry.account = account;
+                    Some(changes)
+                },
+                None => {
+                    accounts.insert(key, AccountEntry {
+                        account,
+                        status: AccountStatus::new(now),
+                        devices: BTreeMap::new(),
+                        claimed: false,
+                    });
+                    None
+                },
+            }
+        };
+        
+        match &changes {
+            Some(changes) => {
+                for change in changes {
+                    self.send_change(change.clone());
+                }
+            },
+            None => self.send_change(AccountChange::Added(account_name)),
+        }
+        
+        changes
+    }
+    
+    /// Gets the device of `entry` on `platform`, or the default for the platform if none was set.
+    fn entry_device(entry: &AccountEntry, platform: SessionPlatform) -> SessionDevice {
+        entry.devices
+            .get(&platform)
+            .cloned()
+            .unwrap_or_else(|| SessionDevice::for_platform(platform))
+    }
+    
+    fn send_change(&self, change: AccountChange) {
+        // there may be no subscribers
+        let _ = self.changes.send(change);
+    }
+    
+    /// Sends the event for `account_name` moving to the state in `status` to the event sink.
+    fn send_event(&self, account_name: &str, status: &AccountStatus) {
+        let Some(event_sink) = &self.event_sink else {
+            return;
+        };
+        
+        if let Some(event) = SessionEvent::from_status(account_name, status) {
+            event_sink.send(&event);
+        }
+    }
+    
+    fn lock_accounts(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, AccountEntry>> {
+        self.accounts.lock().unwrap_or_else(|error| error.into_inner())
+    }
+    
+    fn lock_pool(&self) -> std::sync::MutexGuard<'_, VecDeque<WebSocketCMTransport>> {
+        self.pool.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// Gets the key `account_name` is managed under. Steam account names are not case-sensitive.
+fn account_key(account_name: &str) -> String {
+    account_name.to_lowercase()
+}
+
+/// Gets the modification time of the file at `path`.
+async fn modified(path: &Path) -> Option<std::time::SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// Counts a login as in flight until it is dropped.
+struct InFlightLogin<'a>(&'a watch::Sender<usize>);
+
+impl<'a> InFlightLogin<'a> {
+    fn new(in_flight: &'a watch::Sender<usize>) -> Self {
+        in_flight.send_modify(|in_flight| *in_flight += 1);
+        Self(in_flight)
+    }
+}
+
+impl Drop for InFlightLogin<'_> {
+    fn drop(&mut self) {
+        self.0.send_modify(|in_flight| *in_flight -= 1);
+    }
+}
+
+/// Accounts claimed by [`SessionManager::login_due`], released when dropped.
+struct DueClaim<'a> {
+    manager: &'a SessionManager,
+    accounts: Vec<ManagedAccount>,
+}
+
+impl Drop for DueClaim<'_> {
+    fn drop(&mut self) {
+        let mut accounts = self.manager.lock_accounts();
+        
+        for account in &self.accounts {
+            // the account may have been removed during the login
+            if let Some(entry) = accounts.get_mut(&account_key(&account.account_name)) {
+                entry.claimed = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::Secret;
+    
+    fn accounts_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("steam-session-accounts-{}-{name}.txt", std::process::id()))
+    }
+    
+    fn account(account_name: &str) -> ManagedAccount {
+        ManagedAccount {
+            account_name: account_name.into(),
+            password: Secret::new("hunter2".into()),
+            shared_secret: None,
+            proxy: None,
+        }
+    }
+    
+    fn drain_changes(changes: &mut broadcast::Receiver<AccountChange>) -> Vec<AccountChange> {
+        std::iter::from_fn(|| changes.try_recv().ok()).collect()
+    }
+    
+    #[tokio::test]
+    async fn reloads_changed_accounts() {
+        let path = accounts_path("changed");
+        let manager = SessionManager::default();
+        
+        tokio::fs::write(&path, "alice:hunter2\nbob:swordfish\ncarol:letmein\n").await.unwrap();
+        manager.load_accounts(&path).await.unwrap();
+        
+        let mut changes = manager.subscribe_changes();
+        
+        tokio::fs::write(&path, "Alice:hunter3\nbob:swordfish::127.0.0.1:1080\ndave:trustno1\n").await.unwrap();
+        
+        let report = manager.reload_accounts(&path).await.unwrap();
+        
+        tokio::fs::remove_file(&path).await.unwrap();
+        
+        assert_eq!(report.added, ["dave"]);
+        assert_eq!(report.removed, ["carol"]);
+        assert_eq!(report.updated, ["Alice", "bob"]);
+        assert_eq!(manager.accounts().len(), 3);
+        assert_eq!(manager.account("alice").unwrap().password.expose_secret(), "hunter3");
+        assert!(manager.account("bob").unwrap().proxy.is_some());
+        
+        let changes = drain_changes(&mut changes);
+        
+        assert_eq!(changes.len(), 4);
+        assert_eq!(changes[0], AccountChange::Removed("carol".into()));
+        assert_eq!(changes[1], AccountChange::CredentialsChanged("Alice".into()));
+        assert!(matches!(
+            &changes[2],
+            AccountChange::ProxyChanged { account_name, proxy: Some(_) } if account_name == "bob",
+        ));
+        assert_eq!(changes[3], AccountChange::Added("dave".into()));
+    }
+    
+    #[tokio::test]
+    async fn keeps_accounts_when_the_file_has_errors() {
+        let path = accounts_path("errors");
+        let manager = SessionManager::default();
+        
+        tokio::fs::write(&path, "alice:hunter2\nbob:swordfish\n").await.unwrap();
+        manager.load_accounts(&path).await.unwrap();
+        tokio::fs::write(&path, "alice:hunter2\nmallory\n").await.unwrap();
+        
+        let report = manager.reload_accounts(&path).await.unwrap();
+        
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.removed.is_empty());
+        assert_eq!(manager.accounts().len(), 2);
+        
+        tokio::fs::remove_file(&path).await.unwrap();
+        
+        assert!(manager.reload_accounts(&path).await.is_err());
+        assert_eq!(manager.accounts().len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn logs_in_due_accounts_once_when_calls_overlap() {
+        let manager = SessionManager::default();
+        
+        manager.add_account(account("alice"));
+        
+        let login = |account: ManagedAccount| async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            account.account_name
+        };
+        let (first, second) = tokio::join!(manager.login_due(login), manager.login_due(login));
+        
+        assert_eq!(first.len() + second.len(), 1);
+        // the claim is released once the login finishes
+        assert_eq!(manager.due_accounts().len(), 1);
+    }
+}
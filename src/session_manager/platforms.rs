@@ -0,0 +1,117 @@
+use crate::enums::EAuthTokenPlatformType;
+use crate::emulation::ClientPreset;
+use crate::login_session::LoginSessionBuilder;
+use crate::transports::Transport;
+
+/// The platform a session of a managed account is for. An account can hold a session for each
+/// platform at once, e.g. a web browser session for cookies alongside a mobile app session for
+/// confirmations, each with its own tokens and device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SessionPlatform {
+    /// A web browser session.
+    WebBrowser,
+    /// A Steam mobile app session.
+    MobileApp,
+    /// A Steam desktop client session.
+    SteamClient,
+}
+
+impl SessionPlatform {
+    /// Every platform.
+    pub const ALL: [Self; 3] = [Self::WebBrowser, Self::MobileApp, Self::SteamClient];
+    
+    /// Gets the platform type sessions for this platform log in with.
+    pub fn platform_type(&self) -> EAuthTokenPlatformType {
+        match self {
+            Self::WebBrowser => EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser,
+            Self::MobileApp => EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp,
+            Self::SteamClient => EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient,
+        }
+    }
+    
+    /// Gets the platform for `platform_type`. Returns `None` for unknown platform types.
+    pub fn from_platform_type(platform_type: EAuthTokenPlatformType) -> Option<Self> {
+        match platform_type {
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser => Some(Self::WebBrowser),
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_MobileApp => Some(Self::MobileApp),
+            EAuthTokenPlatformType::k_EAuthTokenPlatformType_SteamClient => Some(Self::SteamClient),
+            _ => None,
+        }
+    }
+    
+    /// Gets a short name for the platform, e.g. `"web"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WebBrowser => "web",
+            Self::MobileApp => "mobile",
+            Self::SteamClient => "client",
+        }
+    }
+    
+    /// Gets the key the tokens of `account_name`'s session for this platform are stored under,
+    /// e.g. `"account:mobile"`.
+    pub fn token_key(&self, account_name: &str) -> String {
+        format!("{account_name}:{}", self.as_str())
+    }
+}
+
+/// The device a session logs in as. Sessions for different platforms of the same account are
+/// given separate devices, so they appear as separate logins in the account's authorized devices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionDevice {
+    /// The name of the device, or `None` to use the default for the platform.
+    pub device_friendly_name: Option<String>,
+    /// The machine ID sent by Steam client sessions, or `None` to derive one from the account
+    /// name.
+    pub machine_id: Option<Vec<u8>>,
+    /// The client requests are made to look like.
+    pub client_preset: ClientPreset,
+}
+
+impl SessionDevice {
+    /// Gets the default device for `platform`, which looks like the official client for it.
+    pub fn for_platform(platform: SessionPlatform) -> Self {
+        Self {
+            device_friendly_name: None,
+            machine_id: None,
+            client_preset: ClientPreset::for_platform(platform.platform_type()),
+        }
+    }
+    
+    /// Sets the device details on `builder`.
+    pub fn apply<T>(&self, builder: LoginSessionBuilder<T>) -> LoginSessionBuilder<T>
+    where
+        T: Transport,
+    {
+        let mut builder = builder.client_preset(self.client_preset);
+        
+        if let Some(device_friendly_name) = &self.device_friendly_name {
+            builder = builder.device_friendly_name(device_friendly_name);
+        }
+        
+        if let Some(machine_id) = &self.machine_id {
+            builder = builder.machine_id(machine_id.clone());
+        }
+        
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn stores_platforms_under_separate_keys() {
+        let keys = SessionPlatform::ALL
+            .iter()
+            .map(|platform| platform.token_key("account"))
+            .collect::<Vec<_>>();
+        
+        assert_eq!(keys, ["account:web", "account:mobile", "account:client"]);
+        
+        for platform in SessionPlatform::ALL {
+            assert_eq!(SessionPlatform::from_platform_type(platform.platform_type()), Some(platform));
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use super::{AccountParseError, ManagedAccount};
+use crate::transports::Socks5ProxyConfig;
+
+/// A change to the accounts of a running [`SessionManager`](super::SessionManager), from
+/// [`SessionManager::subscribe_changes`](super::SessionManager::subscribe_changes). Sessions
+/// only need to react to changes to their own account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountChange {
+    /// An account was added.
+    Added(String),
+    /// An account was removed. Its session should be logged off.
+    Removed(String),
+    /// The password or shared secret of an account changed.
+    CredentialsChanged(String),
+    /// The proxy of an account changed. Its session should reconnect through the new proxy.
+    ProxyChanged {
+        /// The name of the account.
+        account_name: String,
+        /// The new proxy, or `None` to connect directly.
+        proxy: Option<Socks5ProxyConfig>,
+    },
+}
+
+/// The result of [`SessionManager::reload_accounts`](super::SessionManager::reload_accounts).
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    /// The accounts that were added.
+    pub added: Vec<String>,
+    /// The accounts that were removed.
+    pub removed: Vec<String>,
+    /// The accounts whose credentials or proxy changed.
+    pub updated: Vec<String>,
+    /// The entries that could not be loaded. When there are any, no accounts are removed, since
+    /// an invalid entry may belong to an account that is still wanted.
+    pub errors: Vec<AccountParseError>,
+}
+
+impl ReloadReport {
+    /// Whether the reload changed nothing.
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+/// Gets the changes needed to turn `current` into `new`.
+pub(super) fn diff_account(current: &ManagedAccount, new: &ManagedAccount) -> Vec<AccountChange> {
+    let mut changes = Vec::new();
+    
+    if current.password != new.password || current.shared_secret != new.shared_secret {
+        changes.push(AccountChange::CredentialsChanged(new.account_name.clone()));
+    }
+    
+    if current.proxy != new.proxy {
+        changes.push(AccountChange::ProxyChanged {
+            account_name: new.account_name.clone(),
+            proxy: new.proxy.clone(),
+        });
+    }
+    
+    changes
+}
@@ -0,0 +1,195 @@
+use super::ManagedAccount;
+use crate::transports::Socks5ProxyConfig;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use dashmap::DashMap;
+use rand::seq::SliceRandom;
+use tokio::sync::{Semaphore, OwnedSemaphorePermit};
+use tokio::time::Instant;
+
+/// Limits on how logins started through a [`SessionManager`](super::SessionManager) are
+/// scheduled. Starting hundreds of logins at once from the same IP gets it banned by Steam, so
+/// by default at most 8 logins run at once, at most 2 of them through the same proxy, and at
+/// most 30 are started per minute.
+///
+/// # Examples
+/// ```
+/// use steam_session::session_manager::{SessionManager, LoginLimits};
+///
+/// let manager = SessionManager::builder()
+///     .login_limits(LoginLimits::default().max_concurrent(16).logins_per_minute(60))
+///     .build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginLimits {
+    /// The maximum number of logins running at once.
+    pub max_concurrent: usize,
+    /// The maximum number of logins running at once through the same proxy. Logins without a
+    /// proxy share one limit.
+    pub max_concurrent_per_proxy: usize,
+    /// The maximum number of logins started per minute. `None` starts logins as soon as the
+    /// concurrency limits allow.
+    pub logins_per_minute: Option<u32>,
+    /// Whether accounts are logged in in a random order rather than the order given, so that
+    /// accounts sharing a proxy are spread out.
+    pub shuffle: bool,
+}
+
+impl Default for LoginLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 8,
+            max_concurrent_per_proxy: 2,
+            logins_per_minute: Some(30),
+            shuffle: true,
+        }
+    }
+}
+
+impl LoginLimits {
+    /// Sets the maximum number of logins running at once. A value of 0 is treated as 1.
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+    
+    /// Sets the maximum number of logins running at once through the same proxy. A value of 0
+    /// is treated as 1.
+    pub fn max_concurrent_per_proxy(mut self, max_concurrent_per_proxy: usize) -> Self {
+        self.max_concurrent_per_proxy = max_concurrent_per_proxy.max(1);
+        self
+    }
+    
+    /// Sets the maximum number of logins started per minute. A value of 0 is treated as 1.
+    pub fn logins_per_minute(mut self, logins_per_minute: u32) -> Self {
+        self.logins_per_minute = Some(logins_per_minute.max(1));
+        self
+    }
+    
+    /// Removes the limit on the number of logins started per minute.
+    pub fn unlimited_rate(mut self) -> Self {
+        self.logins_per_minute = None;
+        self
+    }
+    
+    /// Sets whether accounts are logged in in a random order.
+    pub fn shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+    
+    /// Gets the time between logins being started.
+    fn interval(&self) -> Option<Duration> {
+        self.logins_per_minute
+            .map(|logins_per_minute| Duration::from_secs(60) / logins_per_minute.max(1))
+    }
+}
+
+/// Permission to run a login, from
+/// [`SessionManager::acquire_login_slot`](super::SessionManager::acquire_login_slot). The slot is
+/// released when this is dropped.
+#[derive(Debug)]
+pub struct LoginPermit {
+    _global: OwnedSemaphorePermit,
+    _proxy: OwnedSemaphorePermit,
+}
+
+/// Hands out login slots according to [`LoginLimits`].
+#[derive(Debug)]
+pub(super) struct LoginScheduler {
+    limits: LoginLimits,
+    global: Arc<Semaphore>,
+    proxies: DashMap<String, Arc<Semaphore>>,
+    next_start: Mutex<Option<Instant>>,
+}
+
+impl LoginScheduler {
+    pub(super) fn new(limits: LoginLimits) -> Self {
+        Self {
+            global: Arc::new(Semaphore::new(limits.max_concurrent)),
+            limits,
+            proxies: DashMap::new(),
+            next_start: Mutex::new(None),
+        }
+    }
+    
+    pub(super) fn limits(&self) -> &LoginLimits {
+        &self.limits
+    }
+    
+    /// Waits for a login through `proxy` to be allowed to start.
+    pub(super) async fn acquire(&self, proxy: Option<&Socks5ProxyConfig>) -> LoginPermit {
+        let proxy_semaphore = self.proxy_semaphore(proxy);
+        // the semaphores are never closed
+        let proxy = proxy_semaphore.acquire_owned().await.expect("semaphore closed");
+        let global = Arc::clone(&self.global).acquire_owned().await.expect("semaphore closed");
+        
+        if let Some(start_at) = self.reserve_start() {
+            tokio::time::sleep_until(start_at).await;
+        }
+        
+        LoginPermit {
+            _global: global,
+            _proxy: proxy,
+        }
+    }
+    
+    /// Orders `accounts` for logging in.
+    pub(super) fn order(&self, mut accounts: Vec<ManagedAccount>) -> Vec<ManagedAccount> {
+        if self.limits.shuffle {
+            accounts.shuffle(&mut rand::thread_rng());
+        }
+        
+        accounts
+    }
+    
+    fn proxy_semaphore(&self, proxy: Option<&Socks5ProxyConfig>) -> Arc<Semaphore> {
+        let key = proxy
+            .map(|proxy| format!("{}:{}", proxy.host(), proxy.port()))
+            .unwrap_or_default();
+        
+        self.proxies
+            .entry(key)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limits.max_concurrent_per_proxy)))
+            .clone()
+    }
+    
+    /// Reserves the next time a login can start, if starts are rate limited.
+    fn reserve_start(&self) -> Option<Instant> {
+        let interval = self.limits.interval()?;
+        let mut next_start = self.next_start
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let start_at = next_start.map_or(now, |next_start| next_start.max(now));
+        
+        *next_start = Some(start_at + interval);
+        Some(start_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn staggers_logins() {
+        let scheduler = LoginScheduler::new(LoginLimits::default().logins_per_minute(60));
+        let first = scheduler.reserve_start().unwrap();
+        let second = scheduler.reserve_start().unwrap();
+        let third = scheduler.reserve_start().unwrap();
+        
+        assert_eq!(second - first, Duration::from_secs(1));
+        assert_eq!(third - second, Duration::from_secs(1));
+    }
+    
+    #[tokio::test]
+    async fn limits_logins_per_proxy() {
+        let scheduler = LoginScheduler::new(LoginLimits::default().max_concurrent_per_proxy(1).unlimited_rate());
+        let proxy = Socks5ProxyConfig::new("127.0.0.1", 1080);
+        let _permit = scheduler.acquire(Some(&proxy)).await;
+        
+        assert!(tokio::time::timeout(Duration::from_millis(10), scheduler.acquire(Some(&proxy))).await.is_err());
+        assert!(tokio::time::timeout(Duration::from_millis(10), scheduler.acquire(None)).await.is_ok());
+    }
+}
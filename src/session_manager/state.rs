@@ -0,0 +1,209 @@
+use crate::enums::EResult;
+use crate::retry::RetryPolicy;
+use crate::types::DateTime;
+use crate::ErrorKind;
+use std::time::Duration;
+
+/// The lifecycle state of an account managed by a [`SessionManager`](super::SessionManager).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccountState {
+    /// The account has no valid session and should be logged in.
+    NeedsLogin,
+    /// A login is waiting for a Steam Guard code or confirmation.
+    Guarded,
+    /// The account has a session, valid until `expiry` if it is known.
+    Active {
+        /// When the refresh token expires.
+        expiry: Option<DateTime>,
+    },
+    /// Logging in failed and is not retried until `until`.
+    Throttled {
+        /// When the account can be logged in again.
+        until: DateTime,
+    },
+    /// The account's token was revoked or rejected. It is not logged in again until it is marked
+    /// as needing a login.
+    Revoked,
+}
+
+/// The state of a managed account along with the failures leading up to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountStatus {
+    /// The current state.
+    pub state: AccountState,
+    /// The number of logins that failed in a row.
+    pub failures: u32,
+    /// A description of the last failure, if the last login failed.
+    pub last_error: Option<String>,
+    /// When the state last changed.
+    pub updated_at: DateTime,
+}
+
+impl AccountStatus {
+    pub(super) fn new(now: DateTime) -> Self {
+        Self {
+            state: AccountState::NeedsLogin,
+            failures: 0,
+            last_error: None,
+            updated_at: now,
+        }
+    }
+    
+    /// Whether the account should be logged in at `now`: it needs a login, its throttle has
+    /// passed, or its session has expired.
+    pub fn is_due(&self, now: DateTime) -> bool {
+        match &self.state {
+            AccountState::NeedsLogin => true,
+            AccountState::Throttled { until } => *until <= now,
+            AccountState::Active { expiry } => expiry.is_some_and(|expiry| expiry <= now),
+            AccountState::Guarded |
+            AccountState::Revoked => false,
+        }
+    }
+    
+    pub(super) fn set_state(&mut self, state: AccountState, now: DateTime) {
+        if matches!(state, AccountState::Active { .. }) {
+            self.failures = 0;
+            self.last_error = None;
+        }
+        
+        self.state = state;
+        self.updated_at = now;
+    }
+    
+    /// Records a failed login, moving the account to [`AccountState::Revoked`] if its token was
+    /// rejected and to [`AccountState::Throttled`] otherwise. Failures back off according to
+    /// `retry_policy`, continuing at its maximum backoff once its attempts are used up. Steam
+    /// throttling the login backs off for the maximum straight away.
+    pub(super) fn record_failure(
+        &mut self,
+        kind: ErrorKind,
+        eresult: Option<EResult>,
+        reason: String,
+        retry_policy: &RetryPolicy,
+        now: DateTime,
+    ) {
+        self.failures = self.failures.saturating_add(1);
+        self.last_error = Some(reason);
+        
+        let state = if kind == ErrorKind::Token {
+            AccountState::Revoked
+        } else {
+            let backoff = match eresult {
+                Some(EResult::RateLimitExceeded | EResult::AccountLoginDeniedThrottle) => {
+                    retry_policy.max_backoff
+                },
+                _ => retry_policy.backoff(self.failures).unwrap_or(retry_policy.max_backoff),
+            };
+            
+            let until = chrono::Duration::from_std(backoff)
+                .ok()
+                .and_then(|backoff| now.checked_add_signed(backoff))
+                .unwrap_or(DateTime::MAX_UTC);
+            
+            AccountState::Throttled {
+                until,
+            }
+        };
+        
+        self.state = state;
+        self.updated_at = now;
+    }
+}
+
+/// The states of every managed account at a point in time, from
+/// [`SessionManager::snapshot`](super::SessionManager::snapshot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountsSnapshot {
+    /// When the snapshot was taken.
+    pub at: DateTime,
+    /// The number of accounts needing a login, including throttled accounts whose throttle has
+    /// passed and active accounts whose session has expired.
+    pub due: usize,
+    /// The number of accounts in [`AccountState::NeedsLogin`].
+    pub needs_login: usize,
+    /// The number of accounts in [`AccountState::Guarded`].
+    pub guarded: usize,
+    /// The number of accounts in [`AccountState::Active`].
+    pub active: usize,
+    /// The number of accounts in [`AccountState::Throttled`].
+    pub throttled: usize,
+    /// The number of accounts in [`AccountState::Revoked`].
+    pub revoked: usize,
+    /// The status of each account, by account name.
+    pub accounts: Vec<(String, AccountStatus)>,
+}
+
+impl AccountsSnapshot {
+    pub(super) fn new(at: DateTime, accounts: Vec<(String, AccountStatus)>) -> Self {
+        let mut snapshot = Self {
+            at,
+            due: 0,
+            needs_login: 0,
+            guarded: 0,
+            active: 0,
+            throttled: 0,
+            revoked: 0,
+            accounts: Vec::new(),
+        };
+        
+        for (_, status) in &accounts {
+            if status.is_due(at) {
+                snapshot.due += 1;
+            }
+            
+            match status.state {
+                AccountState::NeedsLogin => snapshot.needs_login += 1,
+                AccountState::Guarded => snapshot.guarded += 1,
+                AccountState::Active { .. } => snapshot.active += 1,
+                AccountState::Throttled { .. } => snapshot.throttled += 1,
+                AccountState::Revoked => snapshot.revoked += 1,
+            }
+        }
+        
+        snapshot.accounts = accounts;
+        snapshot
+    }
+}
+
+/// The default policy for retrying failed logins of managed accounts: backing off from a minute up
+/// to an hour.
+pub(super) fn default_account_retry_policy() -> RetryPolicy {
+    RetryPolicy::default()
+        .max_attempts(8)
+        .initial_backoff(Duration::from_secs(60))
+        .max_backoff(Duration::from_secs(60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    
+    #[test]
+    fn backs_off_failed_logins() {
+        let now = Utc::now();
+        let retry_policy = default_account_retry_policy();
+        let mut status = AccountStatus::new(now);
+        
+        status.record_failure(ErrorKind::Connection, None, "timed out".into(), &retry_policy, now);
+        
+        assert_eq!(status.state, AccountState::Throttled { until: now + chrono::Duration::try_minutes(1).unwrap() });
+        assert!(!status.is_due(now));
+        
+        status.record_failure(ErrorKind::Steam, Some(EResult::RateLimitExceeded), "rate limited".into(), &retry_policy, now);
+        
+        assert_eq!(status.state, AccountState::Throttled { until: now + chrono::Duration::try_hours(1).unwrap() });
+        
+        status.set_state(AccountState::Active { expiry: None }, now);
+        
+        assert_eq!(status.failures, 0);
+        
+        status.record_failure(ErrorKind::Token, None, "token revoked".into(), &retry_policy, now);
+        
+        assert_eq!(status.state, AccountState::Revoked);
+    }
+}
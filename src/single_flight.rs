@@ -0,0 +1,73 @@
+//! Coalescing of identical concurrent requests.
+
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+use dashmap::DashMap;
+use tokio::sync::OnceCell;
+
+/// Runs at most one call per key at a time. Callers with the same key while a call is in flight 
+/// wait for it and share its result instead of making their own.
+#[derive(Debug)]
+pub(crate) struct SingleFlight<K, V>
+where
+    K: Eq + Hash,
+{
+    calls: DashMap<K, Arc<OnceCell<V>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V>
+where
+    K: Eq + Hash,
+{
+    fn default() -> Self {
+        Self {
+            calls: DashMap::new(),
+        }
+    }
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    /// Runs `f` for `key`, or waits for the call already in flight for `key`.
+    pub async fn run<F, Fut>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let call = Arc::clone(&*self.calls.entry(key.clone()).or_default());
+        let value = call.get_or_init(f).await.clone();
+        
+        // Later calls should make a new request rather than reuse this result
+        self.calls.remove_if(&key, |_, current| Arc::ptr_eq(current, &call));
+        drop(call);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    
+    #[tokio::test]
+    async fn coalesces_concurrent_calls() {
+        let single_flight = SingleFlight::<&str, u32>::default();
+        let calls = AtomicU32::new(0);
+        let call = || async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            calls.fetch_add(1, Ordering::SeqCst) + 1
+        };
+        let (a, b) = tokio::join!(
+            single_flight.run("key", call),
+            single_flight.run("key", call),
+        );
+        
+        assert_eq!((a, b), (1, 1));
+        assert_eq!(single_flight.run("key", call).await, 2);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::ErrorKind;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("IO error: {}", .0)]
+    Io(#[from] std::io::Error),
+    #[error("Serde error: {}", .0)]
+    Serde(#[from] serde_json::Error),
+    #[cfg(feature = "redis")]
+    #[error("Redis error: {}", .0)]
+    Redis(#[from] redis::RedisError),
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {}", .0)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("The tokens for {} were saved by someone else since they were loaded", .account)]
+    Conflict {
+        account: String,
+    },
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io(error) => crate::error::io_kind(error),
+            Self::Serde(_) => ErrorKind::Decode,
+            #[cfg(feature = "redis")]
+            Self::Redis(error) if error.is_timeout() => ErrorKind::Timeout,
+            #[cfg(feature = "redis")]
+            Self::Redis(_) => ErrorKind::Connection,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(rusqlite::Error::FromSqlConversionFailure(..)) => ErrorKind::Decode,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(_) => ErrorKind::Connection,
+            Self::Conflict { .. } => ErrorKind::State,
+        }
+    }
+}
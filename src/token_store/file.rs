@@ -0,0 +1,133 @@
+use super::{TokenStore, StoredTokens, Error};
+use crate::login_session::SessionTokens;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+/// A token store that keeps tokens in a JSON file, keyed by account name. Writes go to a
+/// temporary file which then replaces the store, so an interrupted write never corrupts it. On
+/// Unix the file is only readable by its owner.
+#[derive(Debug)]
+pub struct FileTokenStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileTokenStore {
+    /// Creates a new [`FileTokenStore`] at `path`. The file is created when tokens are first
+    /// saved.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+    
+    /// Gets the path to the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    /// Gets the names of the accounts with stored tokens.
+    pub async fn accounts(&self) -> Result<Vec<String>, Error> {
+        let _guard = self.lock.lock().await;
+        
+        Ok(self.read().await?.into_keys().collect())
+    }
+    
+    async fn read(&self) -> Result<BTreeMap<String, StoredTokens>, Error> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(error) => Err(error.into()),
+        }
+    }
+    
+    async fn write(&self, tokens: &BTreeMap<String, StoredTokens>) -> Result<(), Error> {
+        let bytes = serde_json::to_vec_pretty(tokens)?;
+        let mut tmp_path = self.path.clone().into_os_string();
+        
+        tmp_path.push(".tmp");
+        
+        let tmp_path = PathBuf::from(tmp_path);
+        let mut options = tokio::fs::OpenOptions::new();
+        
+        options.write(true).create(true).truncate(true);
+        
+        #[cfg(unix)]
+        options.mode(0o600);
+        
+        let mut file = options.open(&tmp_path).await?;
+        
+        tokio::io::AsyncWriteExt::write_all(&mut file, &bytes).await?;
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TokenStore for FileTokenStore {
+    async fn load(&self, account: &str) -> Result<Option<SessionTokens>, Error> {
+        let _guard = self.lock.lock().await;
+        let mut tokens = self.read().await?;
+        
+        Ok(tokens.remove(account).map(SessionTokens::from))
+    }
+    
+    async fn save(&self, account: &str, tokens: &SessionTokens) -> Result<(), Error> {
+        let _guard = self.lock.lock().await;
+        let mut stored = self.read().await?;
+        
+        stored.insert(account.to_owned(), tokens.into());
+        self.write(&stored).await
+    }
+    
+    async fn remove(&self, account: &str) -> Result<(), Error> {
+        let _guard = self.lock.lock().await;
+        let mut stored = self.read().await?;
+        
+        if stored.remove(account).is_some() {
+            self.write(&stored).await?;
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steamid_ng::SteamID;
+    
+    #[tokio::test]
+    async fn saves_and_loads_tokens() {
+        let path = std::env::temp_dir().join(format!("steam-session-tokens-{}.json", std::process::id()));
+        let store = FileTokenStore::new(&path);
+        let tokens = SessionTokens {
+            steamid: SteamID::from(76561197960287930),
+            refresh_token: Some("refresh".into()),
+            access_token: None,
+            cookies: vec!["sessionid=abc".into()],
+        };
+        
+        store.save("account", &tokens).await.unwrap();
+        
+        let loaded = store.load("account").await.unwrap().unwrap();
+        
+        assert_eq!(loaded.steamid, tokens.steamid);
+        assert_eq!(loaded.refresh_token.unwrap().expose_secret(), "refresh");
+        assert_eq!(loaded.cookies, tokens.cookies);
+        assert!(store.load("other").await.unwrap().is_none());
+        
+        store.remove("account").await.unwrap();
+        
+        assert!(store.load("account").await.unwrap().is_none());
+        
+        let _ = std::fs::remove_file(&path);
+    }
+}
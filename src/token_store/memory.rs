@@ -0,0 +1,34 @@
+use super::{TokenStore, StoredTokens, Error};
+use crate::login_session::SessionTokens;
+use async_trait::async_trait;
+use dashmap::DashMap;
+
+/// A token store that keeps tokens in memory. Useful for tests and short-lived programs.
+#[derive(Debug, Default)]
+pub struct MemoryTokenStore {
+    tokens: DashMap<String, StoredTokens>,
+}
+
+impl MemoryTokenStore {
+    /// Creates a new empty [`MemoryTokenStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TokenStore for MemoryTokenStore {
+    async fn load(&self, account: &str) -> Result<Option<SessionTokens>, Error> {
+        Ok(self.tokens.get(account).map(|tokens| tokens.clone().into()))
+    }
+    
+    async fn save(&self, account: &str, tokens: &SessionTokens) -> Result<(), Error> {
+        self.tokens.insert(account.to_owned(), tokens.into());
+        Ok(())
+    }
+    
+    async fn remove(&self, account: &str) -> Result<(), Error> {
+        self.tokens.remove(account);
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+//! # Token stores
+//! 
+//! Persists [`SessionTokens`] between runs, keyed by account name, so a refresh token only needs
+//! to be obtained once.
+//! 
+//! ```no_run
+//! # async fn example(tokens: steam_session::SessionTokens) -> Result<(), steam_session::token_store::Error> {
+//! use steam_session::token_store::{TokenStore, FileTokenStore};
+//! 
+//! let store = FileTokenStore::new("tokens.json");
+//! 
+//! store.save("account", &tokens).await?;
+//! 
+//! let tokens = store.load("account").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+mod file;
+mod memory;
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use error::Error;
+pub use file::FileTokenStore;
+pub use memory::MemoryTokenStore;
+#[cfg(feature = "redis")]
+pub use self::redis::RedisTokenStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::{SqliteTokenStore, LoginRecord};
+
+use crate::login_session::SessionTokens;
+use crate::secret::Secret;
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use steamid_ng::SteamID;
+
+/// Storage for session tokens.
+#[async_trait]
+pub trait TokenStore: fmt::Debug + Send + Sync {
+    /// Loads the tokens for `account`. Returns `None` if there are no tokens stored.
+    async fn load(&self, account: &str) -> Result<Option<SessionTokens>, Error>;
+    
+    /// Saves the tokens for `account`, replacing any stored tokens.
+    async fn save(&self, account: &str, tokens: &SessionTokens) -> Result<(), Error>;
+    
+    /// Removes the tokens for `account`.
+    async fn remove(&self, account: &str) -> Result<(), Error>;
+    
+    /// Writes any buffered changes to the underlying storage. Stores that write changes as they 
+    /// are made do nothing.
+    async fn flush(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The form tokens are stored in.
+#[derive(Clone, Serialize, Deserialize)]
+struct StoredTokens {
+    steamid: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    access_token: Option<String>,
+    #[serde(default)]
+    cookies: Vec<String>,
+}
+
+impl fmt::Debug for StoredTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoredTokens")
+            .field("steamid", &self.steamid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<&SessionTokens> for StoredTokens {
+    fn from(tokens: &SessionTokens) -> Self {
+        Self {
+            steamid: u64::from(tokens.steamid),
+            refresh_token: tokens.refresh_token
+                .as_ref()
+                .map(|token| token.expose_secret().clone()),
+            access_token: tokens.access_token
+                .as_ref()
+                .map(|token| token.expose_secret().clone()),
+            cookies: tokens.cookies.clone(),
+        }
+    }
+}
+
+impl From<StoredTokens> for SessionTokens {
+    fn from(tokens: StoredTokens) -> Self {
+        Self {
+            steamid: SteamID::from(tokens.steamid),
+            refresh_token: tokens.refresh_token.map(Secret::new),
+            access_token: tokens.access_token.map(Secret::new),
+            cookies: tokens.cookies,
+        }
+    }
+}
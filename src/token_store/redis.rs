@@ -0,0 +1,148 @@
+use super::{TokenStore, StoredTokens, Error};
+use crate::login_session::SessionTokens;
+use async_trait::async_trait;
+use redis::{AsyncCommands, Script};
+use redis::aio::ConnectionManager;
+
+/// Sets the tokens if the stored version matches, returning the new version or -1 if it does not.
+const SAVE_IF_VERSION_SCRIPT: &str = r"
+local version = redis.call('HGET', KEYS[1], 'version') or '0'
+if version ~= ARGV[1] then
+    return -1
+end
+local next_version = tonumber(version) + 1
+redis.call('HSET', KEYS[1], 'version', next_version, 'tokens', ARGV[2])
+return next_version
+";
+
+/// A token store that keeps tokens in Redis, so worker processes connected to the same server
+/// share them. Keys are prefixed with `steam-session:` unless set with
+/// [`RedisTokenStore::with_prefix`].
+///
+/// Each save increments a version stored alongside the tokens. Workers that renew tokens should
+/// load them with [`RedisTokenStore::load_versioned`] and save them with
+/// [`RedisTokenStore::save_if_version`], which fails with [`Error::Conflict`] if another worker
+/// saved tokens in between, rather than overwriting a newer refresh token with an older one.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use steam_session::token_store::{RedisTokenStore, Error};
+///
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let store = RedisTokenStore::new(client).await?;
+///
+/// if let Some((tokens, version)) = store.load_versioned("account").await? {
+///     // renew the tokens...
+///     match store.save_if_version("account", &tokens, version).await {
+///         Ok(_) => {},
+///         // another worker renewed them first, use theirs
+///         Err(Error::Conflict { .. }) => {},
+///         Err(error) => return Err(error.into()),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RedisTokenStore {
+    connection: ConnectionManager,
+    prefix: String,
+    save_if_version_script: Script,
+}
+
+impl std::fmt::Debug for RedisTokenStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisTokenStore")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisTokenStore {
+    /// Connects to the Redis server of `client`. The connection is re-established automatically
+    /// if it is lost.
+    pub async fn new(client: redis::Client) -> Result<Self, Error> {
+        Ok(Self {
+            connection: ConnectionManager::new(client).await?,
+            prefix: "steam-session:".into(),
+            save_if_version_script: Script::new(SAVE_IF_VERSION_SCRIPT),
+        })
+    }
+    
+    /// Sets the prefix added to keys.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+    
+    /// Loads the tokens for `account` along with their version. Returns `None` if there are no
+    /// tokens stored.
+    pub async fn load_versioned(&self, account: &str) -> Result<Option<(SessionTokens, u64)>, Error> {
+        let mut connection = self.connection.clone();
+        let (version, tokens): (Option<u64>, Option<String>) = connection
+            .hget(self.key(account), &["version", "tokens"])
+            .await?;
+        let Some(tokens) = tokens else {
+            return Ok(None);
+        };
+        let tokens: StoredTokens = serde_json::from_str(&tokens)?;
+        
+        Ok(Some((tokens.into(), version.unwrap_or_default())))
+    }
+    
+    /// Saves the tokens for `account` if the stored version is still `version`, returning the new
+    /// version. Use a `version` of 0 to save only if no tokens are stored. Fails with
+    /// [`Error::Conflict`] if the tokens were saved by someone else since they were loaded.
+    pub async fn save_if_version(
+        &self,
+        account: &str,
+        tokens: &SessionTokens,
+        version: u64,
+    ) -> Result<u64, Error> {
+        let mut connection = self.connection.clone();
+        let tokens = serde_json::to_string(&StoredTokens::from(tokens))?;
+        let new_version: i64 = self.save_if_version_script
+            .key(self.key(account))
+            .arg(version)
+            .arg(tokens)
+            .invoke_async(&mut connection)
+            .await?;
+        
+        u64::try_from(new_version).map_err(|_| Error::Conflict {
+            account: account.to_owned(),
+        })
+    }
+    
+    fn key(&self, account: &str) -> String {
+        format!("{}tokens:{account}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl TokenStore for RedisTokenStore {
+    async fn load(&self, account: &str) -> Result<Option<SessionTokens>, Error> {
+        Ok(self.load_versioned(account).await?.map(|(tokens, _)| tokens))
+    }
+    
+    async fn save(&self, account: &str, tokens: &SessionTokens) -> Result<(), Error> {
+        let mut connection = self.connection.clone();
+        let key = self.key(account);
+        let tokens = serde_json::to_string(&StoredTokens::from(tokens))?;
+        
+        // still bump the version so versioned saves started before this one conflict
+        let () = redis::pipe()
+            .atomic()
+            .hincr(&key, "version", 1)
+            .hset(&key, "tokens", tokens)
+            .query_async(&mut connection)
+            .await?;
+        Ok(())
+    }
+    
+    async fn remove(&self, account: &str) -> Result<(), Error> {
+        let mut connection = self.connection.clone();
+        
+        connection.del::<_, ()>(self.key(account)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+use super::{TokenStore, StoredTokens, Error};
+use crate::login_session::SessionTokens;
+use crate::types::DateTime;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use async_trait::async_trait;
+use rusqlite::{Connection, OptionalExtension, params};
+
+/// Migrations applied in order to bring a database up to date. The number applied is kept in
+/// `PRAGMA user_version`, so new migrations must only ever be appended.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE accounts (
+        account TEXT PRIMARY KEY NOT NULL,
+        steamid INTEGER,
+        refresh_token TEXT,
+        access_token TEXT,
+        cookies TEXT NOT NULL DEFAULT '[]',
+        guard_data TEXT,
+        device_id TEXT
+    );
+    CREATE TABLE login_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        account TEXT NOT NULL,
+        time TEXT NOT NULL,
+        succeeded INTEGER NOT NULL,
+        reason TEXT
+    );
+    CREATE INDEX login_history_account ON login_history (account, time);",
+];
+
+/// A login attempt recorded with [`SqliteTokenStore::record_login`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginRecord {
+    /// When the login was attempted.
+    pub time: DateTime,
+    /// Whether the login succeeded.
+    pub succeeded: bool,
+    /// Why the login failed, if it did.
+    pub reason: Option<String>,
+}
+
+/// A token store that keeps tokens in an SQLite database, along with the Steam Guard data,
+/// device ID, and login history of each account. The schema is migrated when the database is
+/// opened, so databases created by older versions can be opened by newer ones.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), steam_session::token_store::Error> {
+/// use steam_session::token_store::SqliteTokenStore;
+///
+/// let store = SqliteTokenStore::open("accounts.db").await?;
+///
+/// if let Some(guard_data) = store.guard_data("account").await? {
+///     // pass to LoginSession::set_steam_guard_machine_token
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqliteTokenStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteTokenStore {
+    /// Opens the database at `path`, creating it if it does not exist.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        
+        Self::from_connection(move || Connection::open(path)).await
+    }
+    
+    /// Opens a database in memory. Useful for tests.
+    pub async fn open_in_memory() -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory).await
+    }
+    
+    async fn from_connection<F>(open: F) -> Result<Self, Error>
+    where
+        F: FnOnce() -> rusqlite::Result<Connection> + Send + 'static,
+    {
+        let connection = tokio::task::spawn_blocking(move || {
+            let mut connection = open()?;
+            
+            migrate(&mut connection)?;
+            Ok::<_, Error>(connection)
+        })
+            .await
+            .map_err(std::io::Error::other)??;
+        
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+    
+    /// Gets the names of the accounts in the store.
+    pub async fn accounts(&self) -> Result<Vec<String>, Error> {
+        self.run(|connection| {
+            let mut statement = connection.prepare("SELECT account FROM accounts ORDER BY account")?;
+            let accounts = statement
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            
+            Ok(accounts)
+        }).await
+    }
+    
+    /// Gets the Steam Guard machine token of `account`.
+    pub async fn guard_data(&self, account: &str) -> Result<Option<String>, Error> {
+        self.get_column(account, "guard_data").await
+    }
+    
+    /// Sets the Steam Guard machine token of `account`.
+    pub async fn save_guard_data(&self, account: &str, guard_data: &str) -> Result<(), Error> {
+        self.set_column(account, "guard_data", guard_data).await
+    }
+    
+    /// Gets the device ID of `account`'s mobile authenticator.
+    pub async fn device_id(&self, account: &str) -> Result<Option<String>, Error> {
+        self.get_column(account, "device_id").await
+    }
+    
+    /// Sets the device ID of `account`'s mobile authenticator.
+    pub async fn save_device_id(&self, account: &str, device_id: &str) -> Result<(), Error> {
+        self.set_column(account, "device_id", device_id).await
+    }
+    
+    /// Records a login attempt for `account`.
+    pub async fn record_login(&self, account: &str, record: &LoginRecord) -> Result<(), Error> {
+        let account = account.to_owned();
+        let record = record.clone();
+        
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT INTO login_history (account, time, succeeded, reason) VALUES (?1, ?2, ?3, ?4)",
+                params![account, record.time, record.succeeded, record.reason],
+            )?;
+            Ok(())
+        }).await
+    }
+    
+    /// Gets up to `limit` of the most recent login attempts for `account`, newest first.
+    pub async fn login_history(&self, account: &str, limit: usize) -> Result<Vec<LoginRecord>, Error> {
+        let account = account.to_owned();
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        
+        self.run(move |connection| {
+            let mut statement = connection.prepare(
+                "SELECT time, succeeded, reason FROM login_history WHERE account = ?1 ORDER BY time DESC, id DESC LIMIT ?2",
+            )?;
+            let history = statement
+                .query_map(params![account, limit], |row| Ok(LoginRecord {
+                    time: row.get(0)?,
+                    succeeded: row.get(1)?,
+                    reason: row.get(2)?,
+                }))?
+                .collect::<Result<Vec<_>, _>>()?;
+            
+            Ok(history)
+        }).await
+    }
+    
+    async fn get_column(&self, account: &str, column: &'static str) -> Result<Option<String>, Error> {
+        let account = account.to_owned();
+        
+        self.run(move |connection| {
+            let value = connection
+                .query_row(
+                    &format!("SELECT {column} FROM accounts WHERE account = ?1"),
+                    [account],
+                    |row| row.get::<_, Option<String>>(0),
+                )
+                .optional()?;
+            
+            Ok(value.flatten())
+        }).await
+    }
+    
+    async fn set_column(&self, account: &str, column: &'static str, value: &str) -> Result<(), Error> {
+        let account = account.to_owned();
+        let value = value.to_owned();
+        
+        self.run(move |connection| {
+            connection.execute(
+                &format!("INSERT INTO accounts (account, {column}) VALUES (?1, ?2) ON CONFLICT (account) DO UPDATE SET {column} = excluded.{column}"),
+                params![account, value],
+            )?;
+            Ok(())
+        }).await
+    }
+    
+    /// Runs `f` with the connection on the blocking thread pool.
+    async fn run<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, Error> + Send + 'static,
+    {
+        let connection = Arc::clone(&self.connection);
+        
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            
+            f(&mut connection)
+        })
+            .await
+            .map_err(std::io::Error::other)?
+    }
+}
+
+#[async_trait]
+impl TokenStore for SqliteTokenStore {
+    async fn load(&self, account: &str) -> Result<Option<SessionTokens>, Error> {
+        let account = account.to_owned();
+        
+        self.run(move |connection| {
+            let row = connection
+                .query_row(
+                    "SELECT steamid, refresh_token, access_token, cookies FROM accounts WHERE account = ?1 AND steamid IS NOT NULL",
+                    [account],
+                    |row| Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, String>(3)?,
+                    )),
+                )
+                .optional()?;
+            let Some((steamid, refresh_token, access_token, cookies)) = row else {
+                return Ok(None);
+            };
+            let tokens = StoredTokens {
+                steamid: steamid as u64,
+                refresh_token,
+                access_token,
+                cookies: serde_json::from_str(&cookies)?,
+            };
+            
+            Ok(Some(tokens.into()))
+        }).await
+    }
+    
+    async fn save(&self, account: &str, tokens: &SessionTokens) -> Result<(), Error> {
+        let account = account.to_owned();
+        let tokens = StoredTokens::from(tokens);
+        let cookies = serde_json::to_string(&tokens.cookies)?;
+        
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT INTO accounts (account, steamid, refresh_token, access_token, cookies) VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT (account) DO UPDATE SET
+                    steamid = excluded.steamid,
+                    refresh_token = excluded.refresh_token,
+                    access_token = excluded.access_token,
+                    cookies = excluded.cookies",
+                params![account, tokens.steamid as i64, tokens.refresh_token, tokens.access_token, cookies],
+            )?;
+            Ok(())
+        }).await
+    }
+    
+    async fn remove(&self, account: &str) -> Result<(), Error> {
+        let account = account.to_owned();
+        
+        // the guard data, device ID, and login history are kept
+        self.run(move |connection| {
+            connection.execute(
+                "UPDATE accounts SET steamid = NULL, refresh_token = NULL, access_token = NULL, cookies = '[]' WHERE account = ?1",
+                [account],
+            )?;
+            Ok(())
+        }).await
+    }
+}
+
+/// Applies the migrations that have not been applied to `connection`.
+fn migrate(connection: &mut Connection) -> Result<(), Error> {
+    let applied: usize = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+        let transaction = connection.transaction()?;
+        
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", version + 1)?;
+        transaction.commit()?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steamid_ng::SteamID;
+    
+    #[tokio::test]
+    async fn keeps_guard_data_when_tokens_are_removed() {
+        let store = SqliteTokenStore::open_in_memory().await.unwrap();
+        let tokens = SessionTokens {
+            steamid: SteamID::from(76561197960287930),
+            refresh_token: Some("refresh".into()),
+            access_token: None,
+            cookies: vec!["sessionid=abc".into()],
+        };
+        
+        store.save_guard_data("account", "guard").await.unwrap();
+        store.save("account", &tokens).await.unwrap();
+        
+        let loaded = store.load("account").await.unwrap().unwrap();
+        
+        assert_eq!(loaded.steamid, tokens.steamid);
+        assert_eq!(loaded.cookies, tokens.cookies);
+        
+        store.remove("account").await.unwrap();
+        
+        assert!(store.load("account").await.unwrap().is_none());
+        assert_eq!(store.guard_data("account").await.unwrap().as_deref(), Some("guard"));
+        assert_eq!(store.accounts().await.unwrap(), vec!["account".to_owned()]);
+    }
+}
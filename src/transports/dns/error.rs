@@ -0,0 +1,27 @@
+use crate::ErrorKind;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("DNS-over-HTTPS request: {}", .0)]
+    Http(#[from] reqwest::Error),
+    #[error("DNS query for {host} failed with status {status}")]
+    Status {
+        /// The hostname that was queried.
+        host: String,
+        /// The DNS response code.
+        status: u32,
+    },
+    #[error("DNS query for {} returned no addresses", .0)]
+    NoAddresses(String),
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Http(error) => crate::error::reqwest_kind(error),
+            Self::Status { .. } |
+            Self::NoAddresses(_) => ErrorKind::Connection,
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! # DNS-over-HTTPS
+//! 
+//! Resolves CM and login hostnames over HTTPS, for networks where local DNS is blocked or
+//! poisoned but traffic should not go through a proxy. Connections are still made to the
+//! hostname, so TLS verification and SNI are unaffected.
+//! 
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use steam_session::transports::WebSocketCMTransport;
+//! use steam_session::transports::dns::DohResolver;
+//! use steam_session::WebApiTransport;
+//! 
+//! let resolver = DohResolver::cloudflare();
+//! let transport = WebSocketCMTransport::builder()
+//!     .dns_resolver(resolver.clone())
+//!     .connect()
+//!     .await?;
+//! let web_api = WebApiTransport::with_custom_client(resolver.client_builder().build()?);
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+
+pub use error::Error;
+
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use reqwest::Client;
+use reqwest::header::ACCEPT;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::Deserialize;
+
+/// The JSON API of Cloudflare's resolver. An IP address is used so that reaching the resolver
+/// does not itself need DNS.
+pub const CLOUDFLARE_URL: &str = "https://1.1.1.1/dns-query";
+/// The JSON API of Google's resolver.
+pub const GOOGLE_URL: &str = "https://8.8.8.8/resolve";
+
+const RECORD_TYPE_A: u16 = 1;
+const RECORD_TYPE_AAAA: u16 = 28;
+
+/// Resolves hostnames with a DNS-over-HTTPS resolver that speaks the JSON API
+/// (`application/dns-json`), such as Cloudflare's or Google's.
+#[derive(Debug, Clone)]
+pub struct DohResolver {
+    url: String,
+    client: Client,
+}
+
+impl Default for DohResolver {
+    fn default() -> Self {
+        Self::cloudflare()
+    }
+}
+
+impl DohResolver {
+    /// Creates a new [`DohResolver`] that queries the resolver at `url`. The URL should use an IP
+    /// address rather than a hostname, otherwise reaching the resolver goes through local DNS.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_client(url, Client::new())
+    }
+    
+    /// Creates a new [`DohResolver`] that queries the resolver at `url` using `client`.
+    pub fn with_client(url: impl Into<String>, client: Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+    
+    /// Creates a new [`DohResolver`] using Cloudflare's resolver.
+    pub fn cloudflare() -> Self {
+        Self::new(CLOUDFLARE_URL)
+    }
+    
+    /// Creates a new [`DohResolver`] using Google's resolver.
+    pub fn google() -> Self {
+        Self::new(GOOGLE_URL)
+    }
+    
+    /// Gets the URL of the resolver.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+    
+    /// Resolves `host` to its IPv4 and IPv6 addresses, IPv4 first and without duplicates. If one
+    /// of the queries fails, the addresses from the other are returned. IP address literals are
+    /// returned as is.
+    pub async fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, Error> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+        
+        let (v4, v6) = futures::join!(
+            self.query(host, RECORD_TYPE_A),
+            self.query(host, RECORD_TYPE_AAAA),
+        );
+        let mut addresses = match (v4, v6) {
+            (Ok(mut v4), Ok(v6)) => {
+                v4.extend(v6);
+                v4
+            },
+            (Ok(addresses), Err(error)) |
+            (Err(error), Ok(addresses)) => {
+                debug!("DNS-over-HTTPS query for {host} failed: {error}");
+                addresses
+            },
+            (Err(error), Err(_)) => return Err(error),
+        };
+        
+        // keeps the order given by the resolver
+        let mut seen = HashSet::new();
+        
+        addresses.retain(|address| seen.insert(*address));
+        
+        if addresses.is_empty() {
+            return Err(Error::NoAddresses(host.into()));
+        }
+        
+        Ok(addresses)
+    }
+    
+    /// Creates a [`reqwest::ClientBuilder`] that resolves hostnames with this resolver, e.g. for
+    /// [`WebApiTransport::with_custom_client`](crate::WebApiTransport::with_custom_client).
+    pub fn client_builder(&self) -> reqwest::ClientBuilder {
+        Client::builder().dns_resolver(Arc::new(self.clone()))
+    }
+    
+    async fn query(&self, host: &str, record_type: u16) -> Result<Vec<IpAddr>, Error> {
+        let record_type_str = record_type.to_string();
+        let response = self.client
+            .get(&self.url)
+            .query(&[("name", host), ("type", record_type_str.as_str())])
+            .header(ACCEPT, "application/dns-json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<DohResponse>()
+            .await?;
+        
+        if response.status != 0 {
+            return Err(Error::Status {
+                host: host.into(),
+                status: response.status,
+            });
+        }
+        
+        // CNAME records are followed by the resolver and included in the answer, skip them
+        let addresses = response.answer
+            .into_iter()
+            .filter(|answer| answer.r#type == record_type)
+            .filter_map(|answer| answer.data.parse::<IpAddr>().ok())
+            .collect();
+        
+        Ok(addresses)
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        
+        Box::pin(async move {
+            let addresses = DohResolver::resolve(&resolver, name.as_str()).await?;
+            // reqwest replaces the port with the one from the URL
+            let addrs: Addrs = Box::new(addresses
+                .into_iter()
+                .map(|ip| SocketAddr::new(ip, 0)));
+            
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(addrs)
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    r#type: u16,
+    data: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    
+    /// Serves DNS-over-HTTPS responses, answering queries for A records with `v4` and queries
+    /// for AAAA records with `v6`, or a server error for `None`.
+    async fn serve_dns(v4: Option<&'static str>, v6: Option<&'static str>) -> DohResolver {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/dns-query", listener.local_addr().unwrap());
+        
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                
+                let request = String::from_utf8_lossy(&request);
+                let answer = if request.contains(&format!("type={RECORD_TYPE_AAAA}")) {
+                    v6.map(|data| (RECORD_TYPE_AAAA, data))
+                } else {
+                    v4.map(|data| (RECORD_TYPE_A, data))
+                };
+                let response = match answer {
+                    Some((record_type, data)) => {
+                        let answer = data
+                            .split(',')
+                            .map(|data| format!(r#"{{ "type": {record_type}, "data": "{data}" }}"#))
+                            .collect::<Vec<_>>()
+                            .join(",");
+                        let body = format!(r#"{{ "Status": 0, "Answer": [{answer}] }}"#);
+                        
+                        format!(
+                            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                            body.len(),
+                        )
+                    },
+                    None => String::from("HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"),
+                };
+                
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        
+        DohResolver::new(url)
+    }
+    
+    #[tokio::test]
+    async fn resolves_addresses_without_duplicates() {
+        let resolver = serve_dns(Some("10.0.0.1,10.0.0.2,10.0.0.1"), Some("::1")).await;
+        let addresses = resolver.resolve("api.steampowered.com").await.unwrap();
+        
+        assert_eq!(addresses, [
+            "10.0.0.1".parse::<IpAddr>().unwrap(),
+            "10.0.0.2".parse().unwrap(),
+            "::1".parse().unwrap(),
+        ]);
+    }
+    
+    #[tokio::test]
+    async fn falls_back_to_the_query_that_succeeded() {
+        let resolver = serve_dns(Some("10.0.0.1"), None).await;
+        
+        assert_eq!(resolver.resolve("api.steampowered.com").await.unwrap(), ["10.0.0.1".parse::<IpAddr>().unwrap()]);
+        
+        let resolver = serve_dns(None, Some("::1")).await;
+        
+        assert_eq!(resolver.resolve("api.steampowered.com").await.unwrap(), ["::1".parse::<IpAddr>().unwrap()]);
+        
+        let resolver = serve_dns(None, None).await;
+        
+        assert!(matches!(resolver.resolve("api.steampowered.com").await, Err(Error::Http(_))));
+    }
+    
+    #[tokio::test]
+    async fn returns_ip_addresses_as_is() {
+        let resolver = DohResolver::new("http://127.0.0.1:9/dns-query");
+        
+        assert_eq!(resolver.resolve("[::1]").await.unwrap(), ["::1".parse::<IpAddr>().unwrap()]);
+    }
+    
+    #[test]
+    fn parses_json_response() {
+        let json = r#"{
+            "Status": 0,
+            "Answer": [
+                { "name": "api.steampowered.com", "type": 5, "TTL": 60, "data": "cdn.example." },
+                { "name": "cdn.example", "type": 1, "TTL": 60, "data": "23.52.74.146" }
+            ]
+        }"#;
+        let response = serde_json::from_str::<DohResponse>(json).unwrap();
+        
+        assert_eq!(response.status, 0);
+        assert_eq!(response.answer.len(), 2);
+        assert_eq!(response.answer[1].r#type, RECORD_TYPE_A);
+    }
+}
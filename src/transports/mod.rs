@@ -1,9 +1,12 @@
+pub mod dns;
 pub mod proxy;
 pub mod web_api;
 pub mod websocket;
+mod pool;
 
 pub use proxy::{Socks5ProxyConfig, Socks5ProxyConfigError};
 pub use websocket::WebSocketCMTransport;
+pub use pool::HttpPoolConfig;
 
 use crate::authentication_client::Error as AuthenticationClientError;
 use crate::net::ApiRequest;
@@ -19,4 +22,9 @@ pub trait Transport: Sync + Send {
     where
         Msg: ApiRequest,
         <Msg as ApiRequest>::Response: Send;
+    
+    /// Gets the endpoint of the CM server requests are sent to, if they go through one.
+    fn cm_endpoint(&self) -> Option<String> {
+        None
+    }
 }
\ No newline at end of file
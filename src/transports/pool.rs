@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+/// Connection pool settings for the HTTP clients created by a session, used for the web login
+/// flow and authenticated clients.
+///
+/// Connections are negotiated as HTTP/2 when the server supports it, so requests to the same
+/// host, such as `finalizelogin` followed by polling or setting tokens, share one connection
+/// rather than opening a new one each time. Keeping idle connections open avoids repeating the
+/// TCP, proxy, and TLS handshakes, which is slow through a proxy.
+///
+/// # Examples
+/// ```
+/// use steam_session::transports::HttpPoolConfig;
+/// use std::time::Duration;
+///
+/// let pool = HttpPoolConfig::default()
+///     .pool_idle_timeout(Duration::from_secs(300))
+///     .http2_keep_alive_interval(Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpPoolConfig {
+    /// The maximum number of idle connections kept open per host.
+    pub pool_max_idle_per_host: usize,
+    /// How long idle connections are kept open. `None` keeps them open indefinitely.
+    pub pool_idle_timeout: Option<Duration>,
+    /// The interval of TCP keepalive probes. `None` disables them.
+    pub tcp_keepalive: Option<Duration>,
+    /// The interval of HTTP/2 pings sent to keep connections alive. `None` disables them.
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// Whether HTTP/2 flow control windows are sized adaptively.
+    pub http2_adaptive_window: bool,
+}
+
+impl Default for HttpPoolConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: 4,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            http2_keep_alive_interval: None,
+            http2_adaptive_window: true,
+        }
+    }
+}
+
+impl HttpPoolConfig {
+    /// Sets the maximum number of idle connections kept open per host.
+    pub fn pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool_max_idle_per_host = pool_max_idle_per_host;
+        self
+    }
+    
+    /// Sets how long idle connections are kept open.
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+    
+    /// Sets the interval of TCP keepalive probes.
+    pub fn tcp_keepalive(mut self, tcp_keepalive: Duration) -> Self {
+        self.tcp_keepalive = Some(tcp_keepalive);
+        self
+    }
+    
+    /// Sets the interval of HTTP/2 pings sent to keep connections alive.
+    pub fn http2_keep_alive_interval(mut self, http2_keep_alive_interval: Duration) -> Self {
+        self.http2_keep_alive_interval = Some(http2_keep_alive_interval);
+        self
+    }
+    
+    /// Sets whether HTTP/2 flow control windows are sized adaptively.
+    pub fn http2_adaptive_window(mut self, http2_adaptive_window: bool) -> Self {
+        self.http2_adaptive_window = http2_adaptive_window;
+        self
+    }
+    
+    /// Applies the settings to `builder`.
+    pub fn apply(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        builder
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .http2_keep_alive_interval(self.http2_keep_alive_interval)
+            .http2_keep_alive_while_idle(self.http2_keep_alive_interval.is_some())
+            .http2_adaptive_window(self.http2_adaptive_window)
+    }
+}
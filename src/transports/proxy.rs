@@ -4,16 +4,18 @@ mod config {
     use std::fmt;
     use std::str::FromStr;
 
+    use crate::secret::Secret;
     use reqwest::Client;
     use url::Url;
 
     /// SOCKS5 proxy configuration.
     #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
     pub struct Socks5ProxyConfig {
         host: String,
         port: u16,
         username: Option<String>,
-        password: Option<String>,
+        password: Option<Secret<String>>,
         /// `true` означает, что DNS будет резолвиться на стороне прокси (`socks5h`).
         remote_dns: bool,
     }
@@ -43,7 +45,7 @@ mod config {
             password: impl Into<String>,
         ) -> Self {
             self.username = Some(username.into());
-            self.password = Some(password.into());
+            self.password = Some(Secret::new(password.into()));
             self
         }
 
@@ -61,7 +63,7 @@ mod config {
         pub fn credentials(&self) -> (Option<&str>, Option<&str>) {
             (
                 self.username.as_deref().filter(|value| !value.is_empty()),
-                self.password.as_deref(),
+                self.password.as_ref().map(|password| password.expose_secret().as_str()),
             )
         }
 
@@ -83,7 +85,7 @@ mod config {
             }
 
             if let Some(password) = &self.password {
-                if url.set_password(Some(password)).is_err() {
+                if url.set_password(Some(password.expose_secret())).is_err() {
                     return Err(Socks5ProxyConfigError::InvalidPassword);
                 }
             }
@@ -91,14 +93,17 @@ mod config {
             Ok(url)
         }
 
-        /// Creates `reqwest::Client` configured with this SOCKS5 proxy.
-        pub fn build_reqwest_client(&self) -> Result<Client, Socks5ProxyConfigError> {
+        /// Creates `reqwest::Proxy` for this SOCKS5 proxy.
+        pub fn reqwest_proxy(&self) -> Result<reqwest::Proxy, Socks5ProxyConfigError> {
             let url = self.proxy_url()?;
-            let proxy =
-                reqwest::Proxy::all(url.as_str()).map_err(Socks5ProxyConfigError::Reqwest)?;
 
+            reqwest::Proxy::all(url.as_str()).map_err(Socks5ProxyConfigError::Reqwest)
+        }
+
+        /// Creates `reqwest::Client` configured with this SOCKS5 proxy.
+        pub fn build_reqwest_client(&self) -> Result<Client, Socks5ProxyConfigError> {
             Client::builder()
-                .proxy(proxy)
+                .proxy(self.reqwest_proxy()?)
                 .build()
                 .map_err(Socks5ProxyConfigError::Reqwest)
         }
@@ -134,7 +139,7 @@ mod config {
                 "" => None,
                 value => Some(value.to_string()),
             };
-            let password = url.password().map(|value| value.to_string());
+            let password = url.password().map(|value| Secret::new(value.to_string()));
             let remote_dns = scheme == "socks5h";
 
             Ok(Socks5ProxyConfig {
@@ -173,7 +178,16 @@ mod config {
         InvalidUsername,
         #[error("Invalid password for SOCKS5 proxy")]
         InvalidPassword,
+        #[error("SOCKS5 proxy auth requires both username and password")]
+        IncompleteCredentials,
         #[error("Failed to build HTTP client with SOCKS5 proxy: {0}")]
         Reqwest(#[from] reqwest::Error),
     }
+
+    impl Socks5ProxyConfigError {
+        /// Gets the kind of error.
+        pub fn kind(&self) -> crate::ErrorKind {
+            crate::ErrorKind::Proxy
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use crate::enums::EResult;
+use crate::ErrorKind;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -14,4 +15,30 @@ pub enum Error {
     UnknownEResult(i32),
     #[error("Received EResult other than OK: {:?}", .0)]
     EResultNotOK(EResult),
-}
\ No newline at end of file
+    #[error("Request was not authorized: {}", .0)]
+    Unauthorized(reqwest::StatusCode),
+}
+
+impl Error {
+    /// Whether the access token sent with the request was rejected as invalid or expired.
+    pub fn is_access_token_rejected(&self) -> bool {
+        matches!(
+            self,
+            Self::Unauthorized(_) |
+            Self::EResultNotOK(EResult::AccessDenied | EResult::Expired),
+        )
+    }
+    
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Http(error) => crate::error::reqwest_kind(error),
+            Self::Decode(error) => error.kind(),
+            Self::Proto(_) |
+            Self::UnknownEResult(_) => ErrorKind::Decode,
+            Self::InvalidHeaderValue(_) => ErrorKind::InvalidInput,
+            Self::EResultNotOK(_) => ErrorKind::Steam,
+            Self::Unauthorized(_) => ErrorKind::Token,
+        }
+    }
+}
@@ -1,15 +1,22 @@
-use super::{Error, WebApiTransport};
+use super::Error;
 use crate::enums::EResult;
 use crate::net::{ApiRequest, ApiResponse};
 use crate::helpers::{encode_base64, create_api_headers};
+use crate::hosts::SteamHosts;
 use std::ops::Deref;
 use reqwest::StatusCode;
 use bytes::{BytesMut, Buf};
 use lazy_static::lazy_static;
 
 /// Gets a response.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    name = "webapi.request",
+    skip_all,
+    fields(method = <Msg as ApiRequest>::NAME),
+))]
 pub async fn get_response<Msg>(
     client: &reqwest::Client,
+    hosts: &SteamHosts,
     msg: Msg,
     access_token: Option<String>,
 ) -> Result<Msg::Response, Error>
@@ -24,7 +31,7 @@ where
         Msg::VERSION,
     );
     let headers = create_api_headers()?;
-    let url = WebApiTransport::get_url(&pathname);
+    let url = hosts.api_url(&pathname);
     let encoded_message = encode_base64(msg.write_to_bytes()?);
     let request = if is_get_request(&pathname) {
         let mut query = vec![("input_protobuf_encoded", encoded_message.as_str())];
@@ -33,16 +40,21 @@ where
             query.push(("access_token", access_token.as_str()));
         }
 
-        log::debug!("GET {}", url);
+        debug!("GET {}", url);
         client.get(&url)
             .query(&query)
     } else {
         let form = reqwest::multipart::Form::new()
             .text("input_protobuf_encoded", encoded_message);
 
-        log::debug!("POST {}", url);
-        client.post(&url)
-            .multipart(form)
+        let mut request = client.post(&url);
+        
+        if let Some(access_token) = &access_token {
+            request = request.query(&[("access_token", access_token.as_str())]);
+        }
+        
+        debug!("POST {}", url);
+        request.multipart(form)
     };
     let response = request
         .headers(headers)
@@ -77,8 +89,8 @@ fn check_response_for_errors(response: &reqwest::Response) -> Result<(), Error>
         }
     }
 
-    if response.status() != StatusCode::OK {
-
+    if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        return Err(Error::Unauthorized(response.status()));
     }
 
     Ok(())
@@ -1,19 +1,35 @@
 mod error;
 mod helpers;
+mod tokens;
 
 pub use error::Error;
 
 use crate::authentication_client::Error as AuthenticationClientError;
 use crate::transports::Transport;
 use crate::net::ApiRequest;
+use crate::hosts::SteamHosts;
+use crate::emulation::ClientPreset;
+use crate::enums::ELanguage;
+use crate::clock::Clock;
+use tokens::AccessTokens;
+use std::sync::Arc;
 use async_trait::async_trait;
 use tokio::sync::oneshot;
 
-const HOSTNAME: &str = "api.steampowered.com";
-
 /// Web API transport.
-#[derive(Debug, Default)]
-pub struct WebApiTransport(reqwest::Client);
+#[derive(Debug)]
+pub struct WebApiTransport {
+    client: reqwest::Client,
+    hosts: SteamHosts,
+    access_tokens: Option<Arc<AccessTokens>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for WebApiTransport {
+    fn default() -> Self {
+        Self::with_custom_client(reqwest::Client::default())
+    }
+}
 
 #[async_trait]
 impl Transport for WebApiTransport {
@@ -28,12 +44,20 @@ impl Transport for WebApiTransport {
     {
         let (tx, rx) = oneshot::channel();
         
-        let client = self.0.clone();
+        let client = self.client.clone();
+        let hosts = self.hosts.clone();
+        let clock = Arc::clone(&self.clock);
+        let access_tokens = self.access_tokens.clone()
+            .filter(|_| access_token.is_none() && <Msg as ApiRequest>::REQUIRES_ACCESS_TOKEN);
         tokio::spawn(async move {
-            let result = helpers::get_response(&client, msg, access_token)
-                .await
+            let started = std::time::Instant::now();
+            let result = match access_tokens {
+                Some(access_tokens) => send_with_access_tokens(&client, &hosts, &*clock, msg, &access_tokens).await,
+                None => helpers::get_response(&client, &hosts, msg, access_token).await,
+            }
                 .map_err(AuthenticationClientError::WebAPI);
             
+            crate::metrics::request("webapi", <Msg as ApiRequest>::NAME, started.elapsed(), &result);
             tx.send(result)
         });
         
@@ -47,12 +71,86 @@ impl WebApiTransport {
         Self::default()
     }
 
+    /// Creates a new [`WebApiTransport`] whose requests look like they come from the client of 
+    /// `client_preset`. For [`ClientPreset::MobileApp`] this sends the mobile app's user agent and 
+    /// `mobileClient` cookies.
+    pub fn for_client_preset(
+        client_preset: ClientPreset,
+        language: ELanguage,
+    ) -> Result<Self, reqwest::Error> {
+        let client = reqwest::Client::builder()
+            .default_headers(client_preset.api_headers(language))
+            .build()?;
+        
+        Ok(Self::with_custom_client(client))
+    }
+    
     pub fn with_custom_client(client: reqwest::Client) -> Self {
-        Self(client)
+        Self {
+            client,
+            hosts: SteamHosts::default(),
+            access_tokens: None,
+            clock: crate::clock::system_clock(),
+        }
+    }
+    
+    /// Attaches access tokens generated from `refresh_token` to requests that require one and 
+    /// were not given one, such as revoking tokens. A new access token is generated when the 
+    /// current one is about to expire or is rejected.
+    pub fn with_refresh_token(mut self, refresh_token: String) -> Self {
+        self.access_tokens = Some(Arc::new(AccessTokens::new(refresh_token)));
+        self
     }
+    
+    /// Sets the access token attached to requests, to use before a new one is generated. Has no 
+    /// effect unless a refresh token was set with [`Self::with_refresh_token`].
+    pub async fn set_access_token(&self, access_token: String) {
+        if let Some(access_tokens) = &self.access_tokens {
+            access_tokens.set_access_token(access_token).await;
+        }
+    }
+    
+    /// Sets the clock used to check whether the access token is about to expire.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Sets the hosts requests are sent to.
+    pub fn with_hosts(mut self, hosts: SteamHosts) -> Self {
+        self.hosts = hosts;
+        self
+    }
+    
+    /// Gets the hosts requests are sent to.
+    pub fn hosts(&self) -> &SteamHosts {
+        &self.hosts
+    }
+}
 
-    /// Gets the URL.
-    fn get_url(pathname: &str) -> String {
-        format!("https://{HOSTNAME}/{pathname}")
+/// Sends `msg` with an access token, generating a new token and sending again once if the 
+/// current one is rejected.
+async fn send_with_access_tokens<Msg>(
+    client: &reqwest::Client,
+    hosts: &SteamHosts,
+    clock: &dyn Clock,
+    msg: Msg,
+    access_tokens: &AccessTokens,
+) -> Result<Msg::Response, Error>
+where
+    Msg: ApiRequest,
+    <Msg as ApiRequest>::Response: Send,
+{
+    let access_token = access_tokens.get(client, hosts, clock, false).await?;
+    
+    match helpers::get_response(client, hosts, msg.clone(), Some(access_token)).await {
+        Err(error) if error.is_access_token_rejected() => {
+            debug!("Access token rejected for {}; generating a new one", <Msg as ApiRequest>::NAME);
+            
+            let access_token = access_tokens.get(client, hosts, clock, true).await?;
+            
+            helpers::get_response(client, hosts, msg, Some(access_token)).await
+        },
+        result => result,
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,200 @@
+use super::{Error, helpers};
+use crate::helpers::JwtPayload;
+use crate::hosts::SteamHosts;
+use crate::secret::Secret;
+use crate::clock::Clock;
+use crate::types::DateTime;
+use crate::proto::steammessages_auth_steamclient::{
+    CAuthentication_AccessToken_GenerateForApp_Request,
+    ETokenRenewalType,
+};
+use std::str::FromStr;
+use tokio::sync::Mutex;
+
+/// Access tokens are renewed this many seconds before they expire.
+const ACCESS_TOKEN_EXPIRY_MARGIN_SECONDS: u64 = 60;
+
+/// Access tokens attached to requests that require one, generated from a refresh token.
+#[derive(Debug)]
+pub(super) struct AccessTokens {
+    refresh_token: Secret<String>,
+    access_token: Mutex<Option<Secret<String>>>,
+}
+
+impl AccessTokens {
+    pub fn new(refresh_token: String) -> Self {
+        Self {
+            refresh_token: Secret::new(refresh_token),
+            access_token: Mutex::new(None),
+        }
+    }
+    
+    /// Sets the access token, e.g. one issued alongside the refresh token.
+    pub async fn set_access_token(&self, access_token: String) {
+        *self.access_token.lock().await = Some(Secret::new(access_token));
+    }
+    
+    /// Gets an access token, generating a new one if there is none, it is about to expire, or 
+    /// `renew` is set because Steam rejected it.
+    pub async fn get(
+        &self,
+        client: &reqwest::Client,
+        hosts: &SteamHosts,
+        clock: &dyn Clock,
+        renew: bool,
+    ) -> Result<String, Error> {
+        // Held while generating so that concurrent requests share one new token
+        let mut access_token = self.access_token.lock().await;
+        let now = clock.now();
+        
+        if let Some(access_token) = access_token.as_ref().filter(|token| !renew && !is_expiring(token, now)) {
+            return Ok(access_token.expose_secret().clone());
+        }
+        
+        let decoded = JwtPayload::from_str(self.refresh_token.expose_secret())?;
+        let mut msg = CAuthentication_AccessToken_GenerateForApp_Request::new();
+        
+        msg.set_refresh_token(self.refresh_token.expose_secret().clone());
+        msg.set_steamid(u64::from(decoded.sub));
+        msg.set_renewal_type(ETokenRenewalType::k_ETokenRenewalType_None);
+        
+        debug!("Generating access token for web API requests from {}", self.refresh_token.masked());
+        
+        let response = helpers::get_response(client, hosts, msg, None).await?;
+        let generated = response.access_token().to_owned();
+        
+        *access_token = Some(Secret::new(generated.clone()));
+        Ok(generated)
+    }
+}
+
+/// Whether `access_token` expires within the margin of `now`, or cannot be decoded.
+fn is_expiring(access_token: &Secret<String>, now: DateTime) -> bool {
+    let now = now.timestamp().max(0) as u64;
+    
+    JwtPayload::from_str(access_token.expose_secret())
+        .map(|decoded| decoded.exp <= now + ACCESS_TOKEN_EXPIRY_MARGIN_SECONDS)
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::proto::steammessages_auth_steamclient::CAuthentication_AccessToken_GenerateForApp_Response;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use protobuf::Message;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    
+    /// Expires at 1722401188.
+    const TOKEN: &str = "eyAidHlwIjogIkpXVCIsICJhbGciOiAiRWREU0EiIH0.eyAiaXNzIjogInN0ZWFtIiwgInN1YiI6ICI3NjUwMDAwMDAwMDAwMDAwMCIsICJhdWQiOiBbICJ3ZWIiLCAicmVuZXciLCAiZGVyaXZlIiBdLCAiZXhwIjogMTcyMjQwMTE4OCwgIm5iZiI6IDE2OTUzNDY1NjAsICJpYXQiOiAxNzAzOTg2NTYwLCAianRpIjogIjBERDVfMjNBQkNFNDBfMjk2OUYiLCAib2F0IjogMTcwMzk4NjU2MCwgInBlciI6IDEsICJpcF9zdWJqZWN0IjogIjEyNy4wLjAuMSIsICJpcF9jb25maXJtZXIiOiAiMTI3LjAuMC4xIiB9.-fsYDOMqkVFveAAbvSCcED5NLpCbacbY6Mq9N1fev56QCh9f6PNaksqASI2dJORZFPLhZj37kK1UwfX53QYVDF";
+    const EXPIRES_AT: i64 = 1722401188;
+    
+    fn at(timestamp: i64) -> DateTime {
+        DateTime::from_timestamp(timestamp, 0).unwrap()
+    }
+    
+    async fn read_request(stream: &mut TcpStream) {
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            
+            if n == 0 {
+                return;
+            }
+            
+            request.extend_from_slice(&buf[..n]);
+            
+            if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                let content_length = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|value| value.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                
+                if request.len() >= end + 4 + content_length {
+                    return;
+                }
+            }
+        }
+    }
+    
+    /// Serves GenerateAccessTokenForApp requests with `access_token`, counting the requests.
+    async fn serve_access_token(access_token: &str) -> (SteamHosts, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hosts = SteamHosts {
+            api: format!("http://{}", listener.local_addr().unwrap()),
+            ..Default::default()
+        };
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        let mut response = CAuthentication_AccessToken_GenerateForApp_Response::new();
+        
+        response.set_access_token(access_token.into());
+        
+        let body = response.write_to_bytes().unwrap();
+        
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                read_request(&mut stream).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len(),
+                );
+                
+                stream.write_all(head.as_bytes()).await.unwrap();
+                stream.write_all(&body).await.unwrap();
+            }
+        });
+        
+        (hosts, requests)
+    }
+    
+    #[test]
+    fn checks_expiry_with_the_given_time() {
+        let token = Secret::new(String::from(TOKEN));
+        
+        assert!(!is_expiring(&token, at(EXPIRES_AT - 3600)));
+        assert!(is_expiring(&token, at(EXPIRES_AT - 30)));
+        assert!(is_expiring(&token, at(EXPIRES_AT + 3600)));
+        assert!(is_expiring(&Secret::new(String::from("not a token")), at(EXPIRES_AT - 3600)));
+    }
+    
+    #[tokio::test]
+    async fn renews_access_tokens_about_to_expire() {
+        let (hosts, requests) = serve_access_token("renewed").await;
+        let client = reqwest::Client::new();
+        let clock = MockClock::new(at(EXPIRES_AT - 3600));
+        let tokens = AccessTokens::new(String::from(TOKEN));
+        
+        tokens.set_access_token(String::from(TOKEN)).await;
+        
+        assert_eq!(tokens.get(&client, &hosts, &clock, false).await.unwrap(), TOKEN);
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+        
+        clock.set(at(EXPIRES_AT - 30));
+        
+        assert_eq!(tokens.get(&client, &hosts, &clock, false).await.unwrap(), "renewed");
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+    
+    #[tokio::test]
+    async fn renews_rejected_access_tokens() {
+        let (hosts, requests) = serve_access_token("renewed").await;
+        let client = reqwest::Client::new();
+        let clock = MockClock::new(at(EXPIRES_AT - 3600));
+        let tokens = AccessTokens::new(String::from(TOKEN));
+        
+        tokens.set_access_token(String::from(TOKEN)).await;
+        
+        assert_eq!(tokens.get(&client, &hosts, &clock, true).await.unwrap(), "renewed");
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
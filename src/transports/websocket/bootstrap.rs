@@ -0,0 +1,59 @@
+//! The CM list embedded in the crate, used when the Steam directory cannot be reached on a cold 
+//! start. The list is signed so a tampered copy is not connected to. It is also the fixture for 
+//! the CM list parsing tests.
+//! 
+//! To update the list, replace `bootstrap/cmlist.vdf` with a fresh response from 
+//! `ISteamDirectory/GetCMListForConnect` (`format=vdf`) and sign it with the key matching 
+//! `bootstrap/bootstrap.pub.pem`:
+//! 
+//! ```text
+//! openssl dgst -sha256 -sign bootstrap.key.pem -out cmlist.vdf.sig cmlist.vdf
+//! ```
+
+use super::cm_list_cache::{Error, parse_cm_list};
+use super::cm_server::CmServer;
+use rsa::RsaPublicKey;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::signature::Verifier;
+use sha2::Sha256;
+
+pub(super) const CM_LIST: &str = include_str!("bootstrap/cmlist.vdf");
+const SIGNATURE: &[u8] = include_bytes!("bootstrap/cmlist.vdf.sig");
+const PUBLIC_KEY: &str = include_str!("bootstrap/bootstrap.pub.pem");
+
+/// Verifies and parses the embedded CM list.
+pub(super) fn cm_list() -> Result<Vec<CmServer>, Error> {
+    verify(CM_LIST.as_bytes(), SIGNATURE, PUBLIC_KEY)?;
+    parse_cm_list(CM_LIST)
+}
+
+/// Verifies the RSA PKCS#1 v1.5 SHA-256 `signature` of `message` with the PEM-encoded 
+/// `public_key`.
+fn verify(message: &[u8], signature: &[u8], public_key: &str) -> Result<(), Error> {
+    let public_key = RsaPublicKey::from_public_key_pem(public_key)
+        .map_err(|_| Error::BootstrapVerification)?;
+    let signature = Signature::try_from(signature)
+        .map_err(|_| Error::BootstrapVerification)?;
+    
+    VerifyingKey::<Sha256>::new(public_key)
+        .verify(message, &signature)
+        .map_err(|_| Error::BootstrapVerification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn verifies_embedded_list() {
+        assert!(!cm_list().unwrap().is_empty());
+    }
+    
+    #[test]
+    fn rejects_tampered_list() {
+        let tampered = CM_LIST.replace("ext1-ord1", "ext1-evil");
+        
+        assert!(verify(tampered.as_bytes(), SIGNATURE, PUBLIC_KEY).is_err());
+    }
+}
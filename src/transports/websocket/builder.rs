@@ -0,0 +1,276 @@
+use super::{Error, IpPreference, ParseMode, WebSocketCMTransport, WireLogging, helpers, cm_list_for_directory};
+use crate::hosts::SteamHosts;
+use crate::transports::Socks5ProxyConfig;
+use crate::transports::dns::DohResolver;
+use crate::clock::RandomSource;
+use crate::emulation::ClientPreset;
+use crate::enums::ELanguage;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::Connector;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::http::{HeaderMap, HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::http::header::USER_AGENT;
+
+/// The number of CM servers tried when connecting, unless set with 
+/// [`WebSocketCMTransportBuilder::max_connect_attempts`].
+pub const DEFAULT_CONNECT_ATTEMPTS: usize = 3;
+
+/// The user agent sent by the Steam client's HTTP requests, which is sent with the websocket 
+/// upgrade request unless the headers are changed.
+pub const STEAM_CLIENT_USER_AGENT: &str = "Valve/Steam HTTP Client 1.0";
+
+/// Gets the headers sent with the websocket upgrade request by default, matching those of the 
+/// Steam client.
+pub fn default_upgrade_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    
+    headers.insert(USER_AGENT, HeaderValue::from_static(STEAM_CLIENT_USER_AGENT));
+    headers
+}
+
+/// Builder for connecting a [`WebSocketCMTransport`].
+/// 
+/// # Examples
+/// ```no_run
+/// # async fn example() -> Result<(), steam_session::transports::websocket::Error> {
+/// use steam_session::transports::WebSocketCMTransport;
+/// use std::time::Duration;
+/// 
+/// let transport = WebSocketCMTransport::builder()
+///     .connect_timeout(Duration::from_secs(10))
+///     .connect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebSocketCMTransportBuilder {
+    pub(super) proxy: Option<Socks5ProxyConfig>,
+    pub(super) dns_resolver: Option<DohResolver>,
+    pub(super) cm_list_client: Option<reqwest::Client>,
+    pub(super) max_connect_attempts: usize,
+    pub(super) ip_preference: IpPreference,
+    pub(super) websocket_config: Option<WebSocketConfig>,
+    pub(super) tls_connector: Option<TlsConnector>,
+    pub(super) hosts: SteamHosts,
+    pub(super) connect_timeout: Option<Duration>,
+    pub(super) wire_logging: WireLogging,
+    pub(super) parse_mode: ParseMode,
+    pub(super) flush_interval: Option<Duration>,
+    pub(super) ping_interval: Option<Duration>,
+    pub(super) latency_threshold: Option<Duration>,
+    pub(super) random: Option<Arc<dyn RandomSource>>,
+    pub(super) headers: HeaderMap,
+}
+
+impl Default for WebSocketCMTransportBuilder {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            dns_resolver: None,
+            cm_list_client: None,
+            max_connect_attempts: DEFAULT_CONNECT_ATTEMPTS,
+            ip_preference: IpPreference::default(),
+            websocket_config: None,
+            tls_connector: None,
+            hosts: SteamHosts::default(),
+            connect_timeout: None,
+            wire_logging: WireLogging::default(),
+            parse_mode: ParseMode::default(),
+            flush_interval: None,
+            ping_interval: None,
+            latency_threshold: None,
+            random: None,
+            headers: default_upgrade_headers(),
+        }
+    }
+}
+
+impl WebSocketCMTransportBuilder {
+    /// Creates a new [`WebSocketCMTransportBuilder`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Sets the SOCKS5 proxy to connect through. This is also used to get the CM server list.
+    pub fn proxy(mut self, proxy: Socks5ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+    
+    /// Sets the number of different CM servers to try before giving up. If every attempt fails, 
+    /// [`Error::ConnectFailed`] lists each server tried, the stage it failed at, and how long it 
+    /// took. Defaults to [`DEFAULT_CONNECT_ATTEMPTS`].
+    pub fn max_connect_attempts(mut self, max_connect_attempts: usize) -> Self {
+        self.max_connect_attempts = max_connect_attempts.max(1);
+        self
+    }
+    
+    /// Sets the HTTP client used to get the CM server list, independent of the proxy used to 
+    /// connect to the CM server. By default the list is fetched through the proxy if one is set, 
+    /// and directly otherwise. The list is shared by every transport using the same directory.
+    pub fn cm_list_client(mut self, client: reqwest::Client) -> Self {
+        self.cm_list_client = Some(client);
+        self
+    }
+    
+    /// Resolves the Steam directory and CM server hostnames over DNS-over-HTTPS with `resolver` 
+    /// rather than local DNS, for networks where DNS is blocked or poisoned. The connection is 
+    /// still made to the hostname, so TLS is verified as usual. Ignored when a proxy is set, as 
+    /// the proxy resolves hostnames instead.
+    pub fn dns_resolver(mut self, resolver: DohResolver) -> Self {
+        self.dns_resolver = Some(resolver);
+        self
+    }
+    
+    /// Sets which IP versions are used, and in what order, when connecting to a CM server 
+    /// directly. Defaults to [`IpPreference::System`]. Ignored when a proxy is set.
+    pub fn ip_preference(mut self, ip_preference: IpPreference) -> Self {
+        self.ip_preference = ip_preference;
+        self
+    }
+    
+    /// Sets the websocket configuration, such as write buffer sizes and the maximum frame and 
+    /// message sizes, for tuning high-throughput deployments. Uses tungstenite's defaults if not 
+    /// set.
+    pub fn websocket_config(mut self, websocket_config: WebSocketConfig) -> Self {
+        self.websocket_config = Some(websocket_config);
+        self
+    }
+    
+    /// Sets the TLS connector used to connect to the CM server, e.g. a `native-tls` connector 
+    /// with custom root certificates or session resumption. A default connector is created for 
+    /// each connection if not set.
+    pub fn tls_connector(mut self, tls_connector: Connector) -> Self {
+        self.tls_connector = Some(TlsConnector(tls_connector));
+        self
+    }
+    
+    /// Sets the hosts. The CM server is picked from the list given by the Steam directory in 
+    /// `hosts`.
+    pub fn hosts(mut self, hosts: SteamHosts) -> Self {
+        self.hosts = hosts;
+        self
+    }
+    
+    /// Sets the timeout for each attempt to connect to a CM server. Attempts that time out are 
+    /// reported in [`Error::ConnectFailed`] like any other failed attempt, and the next server is 
+    /// tried. Getting the CM server list has its own timeout. No timeout is applied if not set.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+    
+    /// Sets how much of the traffic to and from the CM server is logged. Defaults to 
+    /// [`WireLogging::Off`].
+    pub fn wire_logging(mut self, wire_logging: WireLogging) -> Self {
+        self.wire_logging = wire_logging;
+        self
+    }
+    
+    /// Sets how messages and responses that cannot be parsed are handled. Defaults to 
+    /// [`ParseMode::Lenient`].
+    pub fn parse_mode(mut self, parse_mode: ParseMode) -> Self {
+        self.parse_mode = parse_mode;
+        self
+    }
+    
+    /// Batches outgoing messages, writing them to the socket at most once every 
+    /// `flush_interval` rather than once per message. Each message is still sent in its own 
+    /// frame, but bursts such as many sessions polling at once are written together, reducing 
+    /// syscalls and proxy overhead at the cost of up to `flush_interval` added latency. Messages 
+    /// are written immediately if not set.
+    pub fn flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = Some(flush_interval.max(Duration::from_millis(1)));
+        self
+    }
+    
+    /// Sends a websocket ping every `ping_interval` to measure the round-trip time, which is 
+    /// available from [`WebSocketCMTransport::latency`]. Pings are not sent if not set.
+    pub fn ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.ping_interval = Some(ping_interval.max(Duration::from_millis(1)));
+        self
+    }
+    
+    /// Emits [`super::TransportEvent::HighLatency`] when the round-trip time of a ping exceeds 
+    /// `latency_threshold`, which can be used to decide when to switch CM servers or proxies. 
+    /// Requires [`Self::ping_interval`] to be set.
+    pub fn latency_threshold(mut self, latency_threshold: Duration) -> Self {
+        self.latency_threshold = Some(latency_threshold);
+        self
+    }
+    
+    /// Sets the source of random bytes used for the websocket key and job IDs. Defaults to the 
+    /// thread-local generator.
+    pub fn random(mut self, random: Arc<dyn RandomSource>) -> Self {
+        self.random = Some(random);
+        self
+    }
+    
+    /// Sets the headers sent with the websocket upgrade request, replacing the defaults from 
+    /// [`default_upgrade_headers`]. The headers required for the upgrade, such as `Host` and 
+    /// `Sec-WebSocket-Key`, are always set and cannot be overridden.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+    
+    /// Sets the headers sent with the websocket upgrade request to those of `client_preset`, 
+    /// with `Accept-Language` set for `language`.
+    pub fn client_preset(mut self, client_preset: ClientPreset, language: ELanguage) -> Self {
+        self.headers = client_preset.websocket_headers(language);
+        self
+    }
+    
+    /// Adds a header sent with the websocket upgrade request, replacing any existing value.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+    
+    /// Connects to a CM server.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "cm.connect",
+        skip_all,
+        fields(directory = %self.hosts.directory, proxy = self.proxy.is_some()),
+    ))]
+    pub async fn connect(self) -> Result<WebSocketCMTransport, Error> {
+        let result = self.connect_inner().await;
+        
+        crate::metrics::cm_connect(&result);
+        result
+    }
+    
+    async fn connect_inner(self) -> Result<WebSocketCMTransport, Error> {
+        let cm_list = cm_list_for_directory(&self.hosts.directory);
+        let random = self.random.clone().unwrap_or_else(crate::clock::thread_random);
+        let mut transport = helpers::connect_to_cm(&cm_list, &self, random).await?;
+        
+        transport.hosts = self.hosts;
+        transport.connect_timeout = self.connect_timeout;
+        
+        if let Some(flush_interval) = self.flush_interval {
+            transport.start_flushing(flush_interval);
+        }
+        
+        if let Some(ping_interval) = self.ping_interval {
+            transport.start_pinging(ping_interval, self.latency_threshold);
+        }
+        
+        transport.say_hello().await?;
+        
+        Ok(transport)
+    }
+}
+
+/// Wraps a [`Connector`], which does not implement `Debug`.
+#[derive(Clone)]
+pub(super) struct TlsConnector(pub(super) Connector);
+
+impl fmt::Debug for TlsConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConnector").finish_non_exhaustive()
+    }
+}
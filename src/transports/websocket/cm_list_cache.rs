@@ -1,15 +1,26 @@
+use super::bootstrap;
 use super::cm_server::CmServer;
+use crate::hosts::DEFAULT_DIRECTORY_URL;
+use crate::retry::RetryPolicy;
+use crate::cache_store::CacheStore;
 use std::ops::{Deref, DerefMut};
 use std::fmt;
 use std::collections::HashMap;
+use std::sync::Arc;
 use chrono::{Duration, Utc};
 use rand::seq::SliceRandom;
-use serde::Deserialize;
+use serde::{Serialize, Deserialize};
 use reqwest::Client;
 use reqwest::header::{HeaderMap, HeaderValue, InvalidHeaderValue};
 use reqwest::header::{USER_AGENT, ACCEPT_CHARSET, ACCEPT};
 use lazy_static::lazy_static;
 
+/// How often fetching the list is retried while the embedded bootstrap list is in use.
+const BOOTSTRAP_RETRY_SECONDS: i64 = 30;
+/// How long fetching the list can take before it is abandoned, unless set with 
+/// [`CmListCache::set_fetch_timeout`].
+pub const DEFAULT_FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 lazy_static! {
     pub static ref DEFAULT_CLIENT: Client = Client::new();
 }
@@ -30,6 +41,88 @@ pub enum Error {
     CmServerListResponseMessage(String),
     #[error("Error parsing VDF body: {}", .0)]
     VdfParse(#[from] Box<keyvalues_serde::error::Error>),
+    #[error("Embedded bootstrap CM list failed signature verification")]
+    BootstrapVerification,
+    #[error("The Steam directory did not respond within {:?}", .0)]
+    DirectoryTimeout(std::time::Duration),
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::NoCmServer |
+            Self::ReqwestResponseNotOk(_) |
+            Self::CmServerListResponseMessage(_) => crate::ErrorKind::Connection,
+            Self::Reqwest(error) => crate::error::reqwest_kind(error),
+            Self::InvalidHeaderValue(_) => crate::ErrorKind::InvalidInput,
+            Self::NoCmServerList |
+            Self::VdfParse(_) |
+            Self::BootstrapVerification => crate::ErrorKind::Decode,
+            Self::DirectoryTimeout(_) => crate::ErrorKind::Timeout,
+        }
+    }
+    
+    /// Whether fetching the list again may succeed.
+    fn is_retryable(&self, retry_policy: &RetryPolicy) -> bool {
+        match self {
+            // the directory rejected the request itself
+            Self::ReqwestResponseNotOk(response) if response.status().is_client_error() => false,
+            Self::ReqwestResponseNotOk(_) => true,
+            error => retry_policy.is_retryable(error.kind(), None),
+        }
+    }
+}
+
+/// How the request for a CM list was routed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CmListRoute {
+    /// Directly, using the default client.
+    Direct,
+    /// Through the SOCKS5 proxy with this address. Credentials are not included.
+    Proxy(String),
+    /// Directly, with hostnames resolved by the DNS-over-HTTPS resolver at this URL.
+    DnsOverHttps(String),
+    /// Using a client supplied by the caller.
+    Client,
+    /// Not fetched; the list was fetched by another process and loaded from the cache store.
+    CacheStore,
+    /// Not fetched; the directory could not be reached and the list embedded in the crate was 
+    /// used instead.
+    Bootstrap,
+}
+
+impl fmt::Display for CmListRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Direct => write!(f, "direct"),
+            Self::Proxy(proxy) => write!(f, "proxy {proxy}"),
+            Self::DnsOverHttps(resolver) => write!(f, "direct with DNS-over-HTTPS via {resolver}"),
+            Self::Client => write!(f, "custom client"),
+            Self::CacheStore => write!(f, "cache store"),
+            Self::Bootstrap => write!(f, "embedded bootstrap list"),
+        }
+    }
+}
+
+/// The last time a CM list was refreshed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CmListRefresh {
+    /// When the list was fetched.
+    pub at: chrono::DateTime<Utc>,
+    /// How the request was routed.
+    pub route: CmListRoute,
+    /// The URL the list was fetched from.
+    pub url: String,
+    /// The number of servers in the list.
+    pub servers: usize,
+}
+
+/// The form lists are kept in a [`CacheStore`] in.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredCmList {
+    fetched_at: chrono::DateTime<Utc>,
+    servers: Vec<CmServer>,
 }
 
 /// A container for a list of cached [`CmServer`].
@@ -38,6 +131,12 @@ pub struct CmListCache {
     inner: Vec<CmServer>,
     expiry_duration: Duration,
     last_cached: Option<chrono::DateTime<Utc>>,
+    last_refresh: Option<CmListRefresh>,
+    use_bootstrap: bool,
+    directory: String,
+    fetch_timeout: std::time::Duration,
+    retry_policy: RetryPolicy,
+    cache_store: Option<Arc<dyn CacheStore>>,
 }
 
 impl Default for CmListCache {
@@ -49,13 +148,69 @@ impl Default for CmListCache {
 impl CmListCache {
     /// Creates a new [`CmListCache`]`.
     pub fn new() -> Self {
+        Self::with_directory(DEFAULT_DIRECTORY_URL)
+    }
+    
+    /// Creates a new [`CmListCache`] that gets servers from the Steam directory at `directory`.
+    pub fn with_directory(directory: &str) -> Self {
         Self {
             inner: Vec::new(),
             expiry_duration: Duration::try_minutes(5).unwrap(),
             last_cached: None,
+            last_refresh: None,
+            use_bootstrap: directory == DEFAULT_DIRECTORY_URL,
+            directory: directory.into(),
+            fetch_timeout: DEFAULT_FETCH_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
+            cache_store: None,
         }
     }
     
+    /// Gets when and how the list was last refreshed, or `None` if it has not been fetched yet. 
+    /// Useful for telling whether the directory saw requests from a proxy or from this host when 
+    /// debugging IP-based rate limits.
+    pub fn last_refresh(&self) -> Option<&CmListRefresh> {
+        self.last_refresh.as_ref()
+    }
+    
+    /// Sets whether the CM list embedded in the crate is used when the directory cannot be 
+    /// reached and no list has been fetched yet. Enabled by default for the default directory 
+    /// only. While the embedded list is in use, fetching is retried every 30 seconds.
+    pub fn set_use_bootstrap(&mut self, use_bootstrap: bool) {
+        self.use_bootstrap = use_bootstrap;
+    }
+    
+    /// Sets how long each request for the list can take before it is abandoned and fails with 
+    /// [`Error::DirectoryTimeout`]. Defaults to 10 seconds.
+    pub fn set_fetch_timeout(&mut self, fetch_timeout: std::time::Duration) {
+        self.fetch_timeout = fetch_timeout;
+    }
+    
+    /// Sets how requests for the list are retried after timeouts, connection errors, and server 
+    /// errors. Defaults to [`RetryPolicy::default`].
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+    
+    /// Sets a store lists are shared through. When the list is outdated, a list fetched by another 
+    /// process is loaded from the store before the directory is asked for one, and lists fetched 
+    /// from the directory are saved to it. Failing to read or write the store is not an error.
+    pub fn set_cache_store(&mut self, cache_store: Arc<dyn CacheStore>) {
+        self.cache_store = Some(cache_store);
+    }
+    
+    /// Checks whether the list in use is the embedded bootstrap list.
+    pub fn is_bootstrapped(&self) -> bool {
+        self.last_refresh
+            .as_ref()
+            .is_some_and(|refresh| refresh.route == CmListRoute::Bootstrap)
+    }
+    
+    /// Gets the base URL of the Steam directory servers are fetched from.
+    pub fn directory(&self) -> &str {
+        &self.directory
+    }
+    
     pub fn pick_random_websocket_server(&self) -> Option<CmServer> {
         self.pick_random(&|cm_server| {
             cm_server.r#type == "websockets" &&
@@ -63,6 +218,23 @@ impl CmListCache {
         })
     }
     
+    /// Picks up to `count` different websocket servers at random.
+    pub fn pick_random_websocket_servers(&self, count: usize) -> Vec<CmServer> {
+        let mut servers = self.inner
+            .iter()
+            .filter(|cm_server| {
+                cm_server.r#type == "websockets" &&
+                cm_server.realm == "steamglobal"
+            })
+            .collect::<Vec<_>>();
+        
+        servers.truncate(20);
+        servers
+            .choose_multiple(&mut rand::thread_rng(), count)
+            .map(|server| (*server).clone())
+            .collect()
+    }
+    
     pub fn pick_random(&self, filter: &dyn Fn(&&CmServer) -> bool) -> Option<CmServer> {
         let mut servers = self.inner
             .iter()
@@ -78,14 +250,30 @@ impl CmListCache {
     
     /// Updates the list of servers, if they are oudated.
     pub async fn update(&mut self) -> Result<(), Error> {
-        self.update_with_client(&DEFAULT_CLIENT).await
+        self.update_via(&DEFAULT_CLIENT, CmListRoute::Direct).await
     }
 
+    /// Updates the list of servers using `client`, if they are outdated. Callers sharing a cache 
+    /// through its mutex (such as [`super::DEFAULT_CM_LIST`]) wait for a single update rather 
+    /// than each fetching the list.
     pub async fn update_with_client(&mut self, client: &Client) -> Result<(), Error> {
+        self.update_via(client, CmListRoute::Client).await
+    }
+    
+    /// Updates the list of servers using `client`, if they are outdated, recording `route` as 
+    /// how the request was made.
+    pub async fn update_via(&mut self, client: &Client, route: CmListRoute) -> Result<(), Error> {
         let now = chrono::offset::Utc::now();
+        let is_bootstrapped = self.is_bootstrapped();
+        let expiry_duration = if is_bootstrapped {
+            // get a real list as soon as the directory is reachable
+            Duration::try_seconds(BOOTSTRAP_RETRY_SECONDS).unwrap()
+        } else {
+            self.expiry_duration
+        };
         let is_expired = if let Some(last_cached) = self.last_cached {
             let difference = now - last_cached;
-            difference > self.expiry_duration
+            difference > expiry_duration
         } else {
             true
         };
@@ -93,12 +281,103 @@ impl CmListCache {
         if !is_expired {
             return Ok(());
         }
+        
+        if let Some(stored) = self.load_from_cache_store(now).await {
+            self.inner = stored.servers;
+            self.last_cached = Some(stored.fetched_at);
+            debug!("Loaded CM list for {} from cache store", self.directory);
+            self.last_refresh = Some(CmListRefresh {
+                at: stored.fetched_at,
+                route: CmListRoute::CacheStore,
+                url: cm_list_url(&self.directory),
+                servers: self.inner.len(),
+            });
+            return Ok(());
+        }
 
-        self.inner = get_cm_list(client).await?;
+        let cm_list = match get_cm_list(client, &self.directory, self.fetch_timeout, &self.retry_policy).await {
+            Ok(cm_list) => cm_list,
+            Err(error) if self.use_bootstrap && (self.inner.is_empty() || is_bootstrapped) => {
+                warn!("Failed to get CM list from {}, using embedded list: {error}", self.directory);
+                
+                if !is_bootstrapped {
+                    self.inner = bootstrap::cm_list().map_err(|bootstrap_error| {
+                        warn!("Failed to load embedded CM list: {bootstrap_error}");
+                        error
+                    })?;
+                }
+                
+                self.last_cached = Some(now);
+                self.last_refresh = Some(CmListRefresh {
+                    at: now,
+                    route: CmListRoute::Bootstrap,
+                    url: cm_list_url(&self.directory),
+                    servers: self.inner.len(),
+                });
+                return Ok(());
+            },
+            Err(error) => return Err(error),
+        };
+        
+        self.inner = cm_list;
         self.last_cached = Some(now);
+        debug!("Refreshed CM list from {} ({route})", self.directory);
+        self.last_refresh = Some(CmListRefresh {
+            at: now,
+            route,
+            url: cm_list_url(&self.directory),
+            servers: self.inner.len(),
+        });
+        self.save_to_cache_store(now).await;
         Ok(())
     }
     
+    /// Gets the cache store key for the list of this directory.
+    fn cache_key(&self) -> String {
+        format!("cm_list:{}", self.directory)
+    }
+    
+    /// Loads a list that has not expired from the cache store, if one is set.
+    async fn load_from_cache_store(&self, now: chrono::DateTime<Utc>) -> Option<StoredCmList> {
+        let cache_store = self.cache_store.as_ref()?;
+        let bytes = match cache_store.get(&self.cache_key()).await {
+            Ok(bytes) => bytes?,
+            Err(error) => {
+                warn!("Failed to get CM list from cache store: {error}");
+                return None;
+            },
+        };
+        let stored = serde_json::from_slice::<StoredCmList>(&bytes)
+            .map_err(|error| warn!("Failed to decode CM list from cache store: {error}"))
+            .ok()?;
+        
+        if stored.servers.is_empty() || now - stored.fetched_at > self.expiry_duration {
+            return None;
+        }
+        
+        Some(stored)
+    }
+    
+    /// Saves the list to the cache store, if one is set.
+    async fn save_to_cache_store(&self, fetched_at: chrono::DateTime<Utc>) {
+        let Some(cache_store) = &self.cache_store else {
+            return;
+        };
+        let stored = StoredCmList {
+            fetched_at,
+            servers: self.inner.clone(),
+        };
+        let ttl = self.expiry_duration.to_std().unwrap_or_default();
+        let result = match serde_json::to_vec(&stored) {
+            Ok(bytes) => cache_store.set(&self.cache_key(), &bytes, ttl).await,
+            Err(error) => Err(error.into()),
+        };
+        
+        if let Err(error) = result {
+            warn!("Failed to save CM list to cache store: {error}");
+        }
+    }
+    
     /// Gets a reference to the inner value.
     pub fn get(&self) -> &Vec<CmServer> {
         self.inner.as_ref()
@@ -125,13 +404,36 @@ impl DerefMut for CmListCache {
     }
 }
     
-async fn get_cm_list(client: &Client) -> Result<Vec<CmServer>, Error> {
-    // todo handle errors
-    fetch_cm_list(client).await
+/// Fetches the list, abandoning requests after `fetch_timeout` and retrying transient failures.
+async fn get_cm_list(
+    client: &Client,
+    directory: &str,
+    fetch_timeout: std::time::Duration,
+    retry_policy: &RetryPolicy,
+) -> Result<Vec<CmServer>, Error> {
+    let mut attempts = 0;
+    
+    loop {
+        attempts += 1;
+        
+        let error = match tokio::time::timeout(fetch_timeout, fetch_cm_list(client, directory)).await {
+            Ok(Ok(cm_list)) => return Ok(cm_list),
+            Ok(Err(error)) => error,
+            Err(_) => Error::DirectoryTimeout(fetch_timeout),
+        };
+        
+        match retry_policy.backoff(attempts) {
+            Some(backoff) if error.is_retryable(retry_policy) => {
+                warn!("Failed to get CM list from {directory}, retrying in {backoff:?}: {error}");
+                tokio::time::sleep(backoff).await;
+            },
+            _ => return Err(error),
+        }
+    }
 }
 
-async fn fetch_cm_list(client: &Client) -> Result<Vec<CmServer>, Error> {
-    let url = "https://api.steampowered.com/ISteamDirectory/GetCMListForConnect/v0001/?cellid=0&format=vdf";
+async fn fetch_cm_list(client: &Client, directory: &str) -> Result<Vec<CmServer>, Error> {
+    let url = cm_list_url(directory);
     let mut headers = HeaderMap::new();
 
     headers.append(
@@ -144,12 +446,20 @@ async fn fetch_cm_list(client: &Client) -> Result<Vec<CmServer>, Error> {
     );
     headers.append(ACCEPT, HeaderValue::from_str("text/html,*/*;q=0.9")?);
 
-    let response = client.get(url).headers(headers).send().await?;
+    let response = client.get(&url).headers(headers).send().await?;
     let text = check_response_ok(response).await?.text().await?;
 
     parse_cm_list(&text)
 }
 
+/// Gets the URL of the CM list for `directory`.
+fn cm_list_url(directory: &str) -> String {
+    format!(
+        "{}/ISteamDirectory/GetCMListForConnect/v0001/?cellid=0&format=vdf",
+        directory.trim_end_matches('/'),
+    )
+}
+
 /// Checks if the response is OK.
 async fn check_response_ok(response: reqwest::Response) -> Result<reqwest::Response, Error> {
     match response.status().as_u16() {
@@ -160,7 +470,7 @@ async fn check_response_ok(response: reqwest::Response) -> Result<reqwest::Respo
     }
 }
 
-fn parse_cm_list(text: &str) -> Result<Vec<CmServer>, Error> {
+pub(super) fn parse_cm_list(text: &str) -> Result<Vec<CmServer>, Error> {
     #[derive(Debug, Deserialize)]
     struct CmBody {
         #[serde(default)]
@@ -198,7 +508,7 @@ mod tests {
     
     #[test]
     fn parse_vdf() {
-        let text = include_str!("./fixtures/cmlist.vdf");
+        let text = bootstrap::CM_LIST;
         let serverlist = parse_cm_list(text).unwrap();
         
         assert_eq!(serverlist.first().unwrap().endpoint, "ext1-ord1.steamserver.net:27017");
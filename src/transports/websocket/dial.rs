@@ -0,0 +1,174 @@
+use super::Error;
+use crate::transports::dns::{DohResolver, Error as DnsError};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use futures::StreamExt;
+use futures::future::{self, Either};
+use futures::stream::FuturesUnordered;
+use tokio::net::TcpStream;
+
+/// How long to wait for a connection attempt before starting the next one when dialing with 
+/// [`IpPreference::HappyEyeballs`] (RFC 8305).
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Which IP versions are used, and in what order, when connecting to a CM server directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum IpPreference {
+    /// Addresses are tried one at a time in the order they were resolved.
+    #[default]
+    System,
+    /// IPv4 addresses are tried first, then IPv6 addresses.
+    PreferIpv4,
+    /// IPv6 addresses are tried first, then IPv4 addresses. Use this on hosts where only IPv6 
+    /// egress is stable.
+    PreferIpv6,
+    /// Addresses are tried alternating between IPv6 and IPv4, starting a new attempt every 
+    /// 250ms while earlier ones are still pending, and the first to connect is used (RFC 8305).
+    HappyEyeballs,
+}
+
+impl IpPreference {
+    /// Orders `addresses` by this preference.
+    fn sort(&self, addresses: Vec<SocketAddr>) -> Vec<SocketAddr> {
+        if *self == Self::System {
+            return addresses;
+        }
+        
+        let (v6, v4): (Vec<_>, Vec<_>) = addresses
+            .into_iter()
+            .partition(SocketAddr::is_ipv6);
+        
+        match self {
+            Self::PreferIpv4 => v4.into_iter().chain(v6).collect(),
+            Self::PreferIpv6 => v6.into_iter().chain(v4).collect(),
+            Self::System |
+            Self::HappyEyeballs => {
+                let mut sorted = Vec::with_capacity(v4.len() + v6.len());
+                let mut v4 = v4.into_iter();
+                let mut v6 = v6.into_iter();
+                
+                loop {
+                    match (v6.next(), v4.next()) {
+                        (None, None) => break,
+                        (a, b) => sorted.extend(a.into_iter().chain(b)),
+                    }
+                }
+                
+                sorted
+            },
+        }
+    }
+}
+
+/// Resolves `host`, using `resolver` if given and the system resolver otherwise, and connects to 
+/// it in the order given by `preference`.
+pub(super) async fn connect_direct(
+    host: &str,
+    port: u16,
+    resolver: Option<&DohResolver>,
+    preference: IpPreference,
+) -> Result<TcpStream, Error> {
+    let addresses = match resolver {
+        Some(resolver) => resolver
+            .resolve(host)
+            .await?
+            .into_iter()
+            .map(|ip: IpAddr| SocketAddr::new(ip, port))
+            .collect::<Vec<_>>(),
+        None => tokio::net::lookup_host((host, port)).await?.collect(),
+    };
+    
+    if addresses.is_empty() {
+        return Err(DnsError::NoAddresses(host.into()).into());
+    }
+    
+    let addresses = preference.sort(addresses);
+    let stream = if preference == IpPreference::HappyEyeballs {
+        connect_racing(addresses).await?
+    } else {
+        connect_sequential(addresses).await?
+    };
+    
+    Ok(stream)
+}
+
+/// Connects to the first of `addresses` that accepts a connection, one at a time.
+async fn connect_sequential(addresses: Vec<SocketAddr>) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    
+    for address in addresses {
+        match TcpStream::connect(address).await {
+            Ok(stream) => return Ok(stream),
+            Err(error) => {
+                debug!("Connecting to {address} failed: {error}");
+                last_error = Some(error);
+            },
+        }
+    }
+    
+    Err(last_error.unwrap_or_else(|| io::ErrorKind::AddrNotAvailable.into()))
+}
+
+/// Connects to `addresses`, starting the next attempt every [`CONNECTION_ATTEMPT_DELAY`] or as 
+/// soon as an attempt fails, and returns the first connection made.
+async fn connect_racing(addresses: Vec<SocketAddr>) -> io::Result<TcpStream> {
+    let mut addresses = addresses.into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_error = None;
+    
+    loop {
+        if let Some(address) = addresses.next() {
+            attempts.push(TcpStream::connect(address));
+        }
+        
+        if attempts.is_empty() {
+            break;
+        }
+        
+        let finished = if !addresses.as_slice().is_empty() {
+            let delay = Box::pin(tokio::time::sleep(CONNECTION_ATTEMPT_DELAY));
+            
+            match future::select(attempts.next(), delay).await {
+                Either::Left((finished, _)) => finished,
+                // start the next attempt
+                Either::Right(_) => continue,
+            }
+        } else {
+            attempts.next().await
+        };
+        
+        match finished {
+            Some(Ok(stream)) => return Ok(stream),
+            Some(Err(error)) => {
+                debug!("Connection attempt failed: {error}");
+                last_error = Some(error);
+            },
+            None => {},
+        }
+    }
+    
+    Err(last_error.unwrap_or_else(|| io::ErrorKind::AddrNotAvailable.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn interleaves_addresses_for_happy_eyeballs() {
+        let addresses = vec![
+            "1.1.1.1:443".parse().unwrap(),
+            "2.2.2.2:443".parse().unwrap(),
+            "[::1]:443".parse().unwrap(),
+        ];
+        let sorted = IpPreference::HappyEyeballs.sort(addresses);
+        
+        assert_eq!(sorted, vec![
+            "[::1]:443".parse::<SocketAddr>().unwrap(),
+            "1.1.1.1:443".parse().unwrap(),
+            "2.2.2.2:443".parse().unwrap(),
+        ]);
+    }
+}
@@ -1,11 +1,16 @@
 use super::cm_list_cache;
-use crate::enums::EResult;
+use crate::enums::{EMsg, EResult};
+use crate::ErrorKind;
 use tokio_tungstenite::tungstenite;
+use std::fmt;
+use std::time::Duration;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("{}", .0)]
     CmServer(#[from] cm_list_cache::Error),
+    #[error("{}", .0)]
+    Dns(#[from] crate::transports::dns::Error),
     #[error("IO: {}", .0)]
     OI(#[from] std::io::Error),
     #[error("Invalid URI with websocket: {}", .0)]
@@ -28,8 +33,36 @@ pub enum Error {
     Proto(#[from] protobuf::Error),
     #[error("Wrong service method: expected {}; got {}", .0, .1)]
     DifferentServiceMethod(&'static str, String),
-    #[error("Response timed out")]
-    Timeout,
+    #[error("Timed out: {}", .0)]
+    Timeout(RequestContext),
+    #[error("{source} ({context})")]
+    Request {
+        /// The request that failed.
+        context: RequestContext,
+        /// The error.
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("Failed to parse {} ({length} bytes): {source}", emsg_name(.emsg))]
+    Parse {
+        /// The type of the message, if known.
+        emsg: Option<EMsg>,
+        /// The length of the message body in bytes.
+        length: usize,
+        /// The error.
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("Response to {} contains fields unknown to the protobuf definitions", .0)]
+    UnknownFields(&'static str),
+    #[error("{}", .0)]
+    ConnectFailed(ConnectReport),
+    #[error("Websocket connection was closed")]
+    ConnectionClosed,
+    #[error("Transport is draining and no longer accepts new requests")]
+    Draining,
+    #[error("Websocket connection was lost: {}", .0)]
+    Disconnected(DisconnectReason),
     #[error("Receiver error: {}", .0)]
     RecvError(#[from] tokio::sync::oneshot::error::RecvError),
     #[error("Unknown EMsg: {}", .0)]
@@ -39,7 +72,358 @@ pub enum Error {
     #[error("Received EResult other than OK: {:?}", .0)]
     EResultNotOK(EResult),
     #[error("SOCKS5 proxy configuration error: {0}")]
-    ProxyConfig(String),
+    ProxyConfig(#[from] crate::transports::Socks5ProxyConfigError),
     #[error("SOCKS5 proxy error: {0}")]
     Socks(#[from] tokio_socks::Error),
-}
\ No newline at end of file
+}
+
+impl Error {
+    /// Wraps an error parsing a message of type `emsg` with a body of `length` bytes.
+    pub(crate) fn parse(self, emsg: Option<EMsg>, length: usize) -> Self {
+        Self::Parse {
+            emsg,
+            length,
+            source: Box::new(self),
+        }
+    }
+    
+    /// Attaches the request that was being made when the error occurred.
+    pub fn with_context(self, context: RequestContext) -> Self {
+        Self::Request {
+            context,
+            source: Box::new(self),
+        }
+    }
+    
+    /// Gets the request that was being made when the error occurred, if known.
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::Timeout(context) |
+            Self::Request { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+    
+    /// Whether the error is the transport refusing new requests while draining.
+    pub fn is_draining(&self) -> bool {
+        match self {
+            Self::Draining => true,
+            Self::Request { source, .. } => source.is_draining(),
+            _ => false,
+        }
+    }
+    
+    /// Whether the error is the connection to the CM server closing or being lost.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            Self::ConnectionClosed |
+            Self::Disconnected(_) => true,
+            Self::Request { source, .. } => source.is_disconnected(),
+            _ => false,
+        }
+    }
+    
+    /// Gets the kind of error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::CmServer(error) => error.kind(),
+            Self::Dns(error) => error.kind(),
+            Self::OI(error) => crate::error::io_kind(error),
+            Self::Url(_) |
+            Self::UrlNoHostName |
+            Self::Http(_) => ErrorKind::InvalidInput,
+            Self::Connection(_) |
+            Self::ConnectionClosed |
+            Self::Draining |
+            Self::Disconnected(_) |
+            Self::RecvError(_) => ErrorKind::Connection,
+            Self::ResponseError(_) |
+            Self::ClientLogOnResponseTryAnotherCM(_) |
+            Self::EResultNotOK(_) => ErrorKind::Steam,
+            Self::NoBodyInResponse |
+            Self::UnexpectedNonProtobufMessage(_) |
+            Self::Proto(_) |
+            Self::DifferentServiceMethod(..) |
+            Self::UnknownEMsg(_) |
+            Self::UnknownEResult(_) |
+            Self::Parse { .. } |
+            Self::UnknownFields(_) => ErrorKind::Decode,
+            Self::Timeout(_) => ErrorKind::Timeout,
+            Self::Request { source, .. } => source.kind(),
+            Self::ConnectFailed(report) => report.kind(),
+            Self::ProxyConfig(_) => ErrorKind::Proxy,
+            Self::Socks(tokio_socks::Error::Io(error)) => crate::error::io_kind(error),
+            Self::Socks(error) => SocksFailure::from_error(error).kind(),
+        }
+    }
+    
+    /// Gets what went wrong connecting through the SOCKS5 proxy, if the error came from the 
+    /// proxy. For a failed connection, this is the failure of the last attempt.
+    pub fn socks_failure(&self) -> Option<SocksFailure> {
+        match self {
+            Self::Socks(error) => Some(SocksFailure::from_error(error)),
+            Self::Request { source, .. } => source.socks_failure(),
+            Self::ConnectFailed(report) => report.attempts
+                .last()
+                .and_then(|attempt| attempt.socks_failure),
+            _ => None,
+        }
+    }
+}
+
+/// What went wrong connecting through a SOCKS5 proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SocksFailure {
+    /// The proxy could not be reached or did not respond.
+    ProxyUnreachable,
+    /// The proxy rejected the credentials, or requires credentials that were not given.
+    AuthRejected,
+    /// The proxy's rules do not allow the connection.
+    NotAllowed,
+    /// The proxy could not reach the CM server's host or network.
+    HostUnreachable,
+    /// The CM server refused the proxy's connection.
+    ConnectionRefused,
+    /// The proxy reported a general failure.
+    GeneralFailure,
+    /// The proxy sent an invalid response or does not support the request.
+    Protocol,
+}
+
+impl SocksFailure {
+    /// Classifies `error`.
+    pub fn from_error(error: &tokio_socks::Error) -> Self {
+        use tokio_socks::Error as SocksError;
+        
+        match error {
+            SocksError::Io(_) |
+            SocksError::ProxyServerUnreachable => Self::ProxyUnreachable,
+            SocksError::NoAcceptableAuthMethods |
+            SocksError::PasswordAuthFailure(_) |
+            SocksError::AuthorizationRequired |
+            SocksError::InvalidAuthValues(_) => Self::AuthRejected,
+            SocksError::ConnectionNotAllowedByRuleset => Self::NotAllowed,
+            SocksError::NetworkUnreachable |
+            SocksError::HostUnreachable |
+            SocksError::TtlExpired => Self::HostUnreachable,
+            SocksError::ConnectionRefused => Self::ConnectionRefused,
+            SocksError::GeneralSocksServerFailure |
+            SocksError::UnknownError => Self::GeneralFailure,
+            _ => Self::Protocol,
+        }
+    }
+    
+    /// Gets the kind of error. Failures reaching the CM server are [`ErrorKind::Connection`], 
+    /// since the proxy itself worked; the rest are [`ErrorKind::Proxy`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::HostUnreachable |
+            Self::ConnectionRefused => ErrorKind::Connection,
+            _ => ErrorKind::Proxy,
+        }
+    }
+    
+    /// Gets what a proxy pool should do about the failure.
+    pub fn action(&self) -> ProxyAction {
+        match self {
+            Self::HostUnreachable |
+            Self::ConnectionRefused => ProxyAction::Retry,
+            Self::ProxyUnreachable |
+            Self::GeneralFailure |
+            Self::Protocol => ProxyAction::Rotate,
+            Self::AuthRejected |
+            Self::NotAllowed => ProxyAction::Alert,
+        }
+    }
+}
+
+impl fmt::Display for SocksFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProxyUnreachable => write!(f, "proxy unreachable"),
+            Self::AuthRejected => write!(f, "authentication rejected"),
+            Self::NotAllowed => write!(f, "not allowed by proxy rules"),
+            Self::HostUnreachable => write!(f, "host unreachable"),
+            Self::ConnectionRefused => write!(f, "connection refused"),
+            Self::GeneralFailure => write!(f, "general failure"),
+            Self::Protocol => write!(f, "protocol error"),
+        }
+    }
+}
+
+/// What to do about a proxy after a [`SocksFailure`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProxyAction {
+    /// The proxy works; retry through it, e.g. with another CM server.
+    Retry,
+    /// The proxy is down or misbehaving; move to another proxy.
+    Rotate,
+    /// The proxy is misconfigured, e.g. its credentials are wrong; retrying will not help, so 
+    /// someone should be told.
+    Alert,
+}
+
+fn emsg_name(emsg: &Option<EMsg>) -> String {
+    match emsg {
+        Some(emsg) => format!("{emsg:?}"),
+        None => "message".into(),
+    }
+}
+
+/// The request that was being made when an error occurred.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestContext {
+    /// The name of the request, e.g. `Authentication.PollAuthSessionStatus#1`.
+    pub request: Option<&'static str>,
+    /// The job ID of the request.
+    pub jobid: Option<u64>,
+    /// The endpoint of the CM server.
+    pub endpoint: Option<String>,
+    /// How long the request had been running.
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.request.unwrap_or("unnamed request"))?;
+        
+        if let Some(jobid) = self.jobid {
+            write!(f, " (jobid {jobid})")?;
+        }
+        
+        if let Some(endpoint) = &self.endpoint {
+            write!(f, " to {endpoint}")?;
+        }
+        
+        write!(f, " after {:?}", self.elapsed)
+    }
+}
+
+/// Why the connection was lost, other than being closed normally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// Reading from the socket failed.
+    Error(String),
+    /// The task reading from the socket panicked.
+    Panicked(String),
+}
+
+impl fmt::Display for DisconnectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(message) => write!(f, "{message}"),
+            Self::Panicked(message) => write!(f, "read task panicked: {message}"),
+        }
+    }
+}
+
+/// The stage of connecting to a CM server at which an attempt failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectStage {
+    /// Connecting through the SOCKS5 proxy.
+    Socks,
+    /// Resolving the hostname or opening the TCP connection.
+    Tcp,
+    /// The TLS handshake.
+    Tls,
+    /// The websocket upgrade request.
+    Upgrade,
+}
+
+impl fmt::Display for ConnectStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Socks => write!(f, "SOCKS"),
+            Self::Tcp => write!(f, "TCP"),
+            Self::Tls => write!(f, "TLS"),
+            Self::Upgrade => write!(f, "upgrade"),
+        }
+    }
+}
+
+/// A failed attempt to connect to a CM server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectAttempt {
+    /// The endpoint of the CM server.
+    pub endpoint: String,
+    /// The stage the attempt failed at.
+    pub stage: ConnectStage,
+    /// The kind of error.
+    pub kind: ErrorKind,
+    /// What went wrong with the proxy, if the attempt failed at [`ConnectStage::Socks`].
+    pub socks_failure: Option<SocksFailure>,
+    /// The error message.
+    pub error: String,
+    /// How long the attempt took.
+    pub elapsed: Duration,
+}
+
+/// Every failed attempt to connect to a CM server, for telling proxy problems apart from Steam 
+/// outages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectReport {
+    /// The attempts, in the order they were made.
+    pub attempts: Vec<ConnectAttempt>,
+}
+
+impl ConnectReport {
+    /// Gets the kind of error of the last attempt.
+    pub fn kind(&self) -> ErrorKind {
+        self.attempts
+            .last()
+            .map(|attempt| attempt.kind)
+            .unwrap_or(ErrorKind::Connection)
+    }
+    
+    /// Checks whether every attempt failed at `stage`.
+    pub fn all_failed_at(&self, stage: ConnectStage) -> bool {
+        !self.attempts.is_empty() && self.attempts
+            .iter()
+            .all(|attempt| attempt.stage == stage)
+    }
+}
+
+impl fmt::Display for ConnectReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to connect to a CM server after {} attempts", self.attempts.len())?;
+        
+        for attempt in &self.attempts {
+            write!(
+                f,
+                "; {} at {} after {:?}: {}",
+                attempt.endpoint,
+                attempt.stage,
+                attempt.elapsed,
+                attempt.error,
+            )?;
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn classifies_socks_errors() {
+        let auth = Error::Socks(tokio_socks::Error::PasswordAuthFailure(1));
+        let refused = Error::Socks(tokio_socks::Error::ConnectionRefused);
+        let report = ConnectReport {
+            attempts: vec![ConnectAttempt {
+                endpoint: "cm.example:443".into(),
+                stage: ConnectStage::Socks,
+                kind: refused.kind(),
+                socks_failure: refused.socks_failure(),
+                error: refused.to_string(),
+                elapsed: Duration::from_secs(1),
+            }],
+        };
+        
+        assert_eq!(auth.kind(), ErrorKind::Proxy);
+        assert_eq!(auth.socks_failure().map(|failure| failure.action()), Some(ProxyAction::Alert));
+        assert_eq!(refused.kind(), ErrorKind::Connection);
+        assert_eq!(Error::ConnectFailed(report).socks_failure(), Some(SocksFailure::ConnectionRefused));
+    }
+}
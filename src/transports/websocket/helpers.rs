@@ -1,120 +1,277 @@
-use super::{Error, CmListError, WebSocketCMTransport, CmListCache};
+use super::{Error, ConnectAttempt, ConnectReport, ConnectStage, CmListError, WebSocketCMTransport, WebSocketCMTransportBuilder, CmListCache, RequestContext, WireLogging, ParseMode};
+use super::{dial, wire_log};
+use super::cm_list_cache::{CmListRoute, DEFAULT_CLIENT};
 use super::response::ApiResponseBody;
-use crate::net::ApiRequest;
+use crate::net::{ApiRequest, ApiResponse};
 use crate::authentication_client::Error as AuthenticationClientError;
-use crate::transports::Socks5ProxyConfig;
+use crate::transports::Socks5ProxyConfigError;
+use crate::clock::RandomSource;
 use std::sync::Arc;
+use std::time::Instant;
 use futures::StreamExt;
 use tokio::sync::oneshot;
 use tokio::time::timeout;
+use crate::transports::dns::DohResolver;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite;
 use tokio_tungstenite::tungstenite::http::uri::Uri;
 use tokio_tungstenite::tungstenite::http::request::Request;
-use tokio_tungstenite::{connect_async, client_async_tls_with_config, MaybeTlsStream};
+use tokio_tungstenite::tungstenite::http::HeaderName;
+use tokio_tungstenite::tungstenite::http::header::{HOST, CONNECTION, UPGRADE, SEC_WEBSOCKET_VERSION, SEC_WEBSOCKET_KEY};
+use tokio_tungstenite::{client_async_tls_with_config, MaybeTlsStream, WebSocketStream};
 use tokio_socks::tcp::Socks5Stream;
 
 /// Generate a random key for the `Sec-WebSocket-Key` header.
-fn generate_key() -> String {
+fn generate_key(random: &dyn RandomSource) -> String {
     // a base64-encoded (see Section 4 of [RFC4648]) value that,
     // when decoded, is 16 bytes in length (RFC 6455)
-    let r: [u8; 16] = rand::random();
+    let r: [u8; 16] = random.bytes();
     data_encoding::BASE64.encode(&r)
 }
 
-pub async fn connect_to_cm(cm_list: &Arc<tokio::sync::Mutex<CmListCache>>) -> Result<WebSocketCMTransport, Error> {
-    connect_to_cm_with_socks5_proxy(cm_list, None).await
-}
-
-pub async fn connect_to_cm_with_socks5_proxy(
+/// Connects to a random CM server using the proxy and DNS resolver from `options`, trying up to 
+/// `max_connect_attempts` servers. Each attempt is given `connect_timeout`, so a server that 
+/// hangs is recorded as a failed attempt rather than using up the time for the others.
+pub async fn connect_to_cm(
     cm_list: &Arc<tokio::sync::Mutex<CmListCache>>,
-    proxy: Option<&Socks5ProxyConfig>,
+    options: &WebSocketCMTransportBuilder,
+    random: Arc<dyn RandomSource>,
 ) -> Result<WebSocketCMTransport, Error> {
-    let proxied_client = if let Some(config) = proxy {
-        Some(
-            config
-                .build_reqwest_client()
-                .map_err(|err| Error::ProxyConfig(err.to_string()))?,
-        )
+    let proxy = options.proxy.as_ref();
+    // the resolver is only used for direct connections, the proxy resolves hostnames otherwise
+    let dns_resolver = options.dns_resolver.as_ref().filter(|_| proxy.is_none());
+    let (client, route) = if let Some(client) = &options.cm_list_client {
+        (client.clone(), CmListRoute::Client)
+    } else if let Some(config) = proxy {
+        (config.build_reqwest_client()?, CmListRoute::Proxy(config.to_string()))
+    } else if let Some(resolver) = dns_resolver {
+        let client = resolver.client_builder().build().map_err(CmListError::Reqwest)?;
+        
+        (client, CmListRoute::DnsOverHttps(resolver.url().into()))
     } else {
-        None
+        (DEFAULT_CLIENT.clone(), CmListRoute::Direct)
     };
-
-    let cm_server = {
+    
+    let (cm_servers, cm_list_refresh) = {
         let mut cm_list = cm_list.lock().await;
-
-        if let Some(client) = proxied_client.as_ref() {
-            cm_list.update_with_client(client).await?;
-        } else {
-            cm_list.update().await?;
+        
+        cm_list.update_via(&client, route).await?;
+        // pick random servers
+        (
+            cm_list.pick_random_websocket_servers(options.max_connect_attempts),
+            cm_list.last_refresh().cloned(),
+        )
+    };
+    
+    if cm_servers.is_empty() {
+        return Err(Error::CmServer(CmListError::NoCmServer));
+    }
+    
+    if let Some(proxy_config) = proxy {
+        if let (Some(_), None) | (None, Some(_)) = proxy_config.credentials() {
+            return Err(Socks5ProxyConfigError::IncompleteCredentials.into());
         }
-        // pick a random server
-        cm_list.pick_random_websocket_server()
     }
-    .ok_or(Error::CmServer(CmListError::NoCmServer))?;
-    let connect_addr = format!("wss://{}/cmsocket/", cm_server.endpoint);
-    let uri = connect_addr.parse::<Uri>()?;
-    let authority = uri.authority().ok_or(Error::UrlNoHostName)?.as_str();
+    
+    let mut report = ConnectReport::default();
+    let mut connected = None;
+    
+    for cm_server in cm_servers {
+        let started = Instant::now();
+        
+        match connect_to_server(&cm_server.endpoint, options, dns_resolver, random.as_ref()).await {
+            Ok(ws_stream) => {
+                connected = Some((cm_server, ws_stream));
+                break;
+            },
+            Err((stage, error)) => {
+                debug!("Connecting to CM server {} failed at {stage}: {error}", cm_server.endpoint);
+                report.attempts.push(ConnectAttempt {
+                    endpoint: cm_server.endpoint,
+                    stage,
+                    kind: error.kind(),
+                    socks_failure: error.socks_failure(),
+                    error: error.to_string(),
+                    elapsed: started.elapsed(),
+                });
+            },
+        }
+    }
+    
+    let Some((cm_server, ws_stream)) = connected else {
+        return Err(Error::ConnectFailed(report));
+    };
+    let (ws_write, ws_read) = ws_stream.split();
+    let mut transport = WebSocketCMTransport::new(
+        ws_read,
+        ws_write,
+        options.wire_logging,
+        options.parse_mode,
+    );
+    
+    transport.proxy = proxy.cloned();
+    transport.dns_resolver = options.dns_resolver.clone();
+    transport.cm_list_client = options.cm_list_client.clone();
+    transport.cm_list_refresh = cm_list_refresh;
+    transport.max_connect_attempts = options.max_connect_attempts;
+    transport.ip_preference = options.ip_preference;
+    transport.websocket_config = options.websocket_config;
+    transport.tls_connector = options.tls_connector.clone();
+    transport.random = random;
+    transport.headers = options.headers.clone();
+    transport.endpoint = cm_server.endpoint;
+    
+    Ok(transport)
+}
+
+/// Connects to the CM server at `endpoint`. Errors are returned with the stage they occurred at.
+async fn connect_to_server(
+    endpoint: &str,
+    options: &WebSocketCMTransportBuilder,
+    dns_resolver: Option<&DohResolver>,
+    random: &dyn RandomSource,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, (ConnectStage, Error)> {
+    let upgrade_error = |error: Error| (ConnectStage::Upgrade, error);
+    let connect_addr = format!("wss://{endpoint}/cmsocket/");
+    let uri = connect_addr
+        .parse::<Uri>()
+        .map_err(|error| upgrade_error(error.into()))?;
+    let authority = uri.authority()
+        .ok_or(Error::UrlNoHostName)
+        .map_err(upgrade_error)?
+        .as_str();
     let host = authority
         .find('@')
         .map(|idx| authority.split_at(idx + 1).1)
         .unwrap_or_else(|| authority);
-    let request_uri = uri.clone(); // Clone uri here
-    let request = Request::builder()
-        .header("batch-test", "true")
+    let mut request = Request::builder()
         .header("Host", host)
         .header("Connection", "Upgrade")
         .header("Upgrade", "websocket")
         .header("Sec-WebSocket-Version", "13")
-        .header("Sec-WebSocket-Key", generate_key())
-        .uri(request_uri)
-        .body(())?;
-    // todo use timeout when connecting
-    // let connect_timeout = Duration::seconds(CONNECTION_TIMEOUT_SECONDS);
-    let (ws_stream, _) = if let Some(proxy_config) = proxy {
-        let host = uri.host().ok_or(Error::UrlNoHostName)?;
-        let port = uri.port_u16().unwrap_or(443);
-        let proxy_addr = proxy_config.proxy_addr();
-        let (username, password) = proxy_config.credentials();
-
-        let stream = match (username, password) {
-            (Some(user), Some(pass)) => {
-                Socks5Stream::connect_with_password(proxy_addr, (host, port), user, pass).await?
-            }
-            (Some(_), None) | (None, Some(_)) => {
-                return Err(Error::ProxyConfig(
-                    "SOCKS5 proxy auth requires both username and password".into(),
-                ));
-            }
-            _ => Socks5Stream::connect(proxy_addr, (host, port)).await?,
+        .header("Sec-WebSocket-Key", generate_key(random))
+        .uri(uri.clone())
+        .body(())
+        .map_err(|error| upgrade_error(error.into()))?;
+    
+    for (name, value) in &options.headers {
+        if !is_handshake_header(name) {
+            request.headers_mut().append(name.clone(), value.clone());
         }
-        .into_inner();
-
-        client_async_tls_with_config(request, stream, None, None).await?
+    }
+    
+    let host = uri.host()
+        .ok_or(Error::UrlNoHostName)
+        .map_err(upgrade_error)?;
+    let port = uri.port_u16().unwrap_or(443);
+    let deadline = options.connect_timeout
+        .map(|connect_timeout| tokio::time::Instant::now() + connect_timeout);
+    let timed_out = |stage: ConnectStage| (stage, Error::Timeout(RequestContext {
+        request: Some("connect"),
+        endpoint: Some(endpoint.to_owned()),
+        elapsed: options.connect_timeout.unwrap_or_default(),
+        ..Default::default()
+    }));
+    let (stream_stage, connecting) = if let Some(proxy_config) = &options.proxy {
+        let proxy_addr = proxy_config.proxy_addr();
+        let connecting = async move {
+            let connecting = match proxy_config.credentials() {
+                (Some(user), Some(pass)) => {
+                    Socks5Stream::connect_with_password(proxy_addr, (host, port), user, pass).await
+                },
+                _ => Socks5Stream::connect(proxy_addr, (host, port)).await,
+            };
+            
+            connecting
+                .map(Socks5Stream::into_inner)
+                .map_err(Error::from)
+        };
+        
+        (ConnectStage::Socks, futures::future::Either::Left(connecting))
     } else {
-        connect_async(request).await?
+        let connecting = dial::connect_direct(host, port, dns_resolver, options.ip_preference);
+        
+        (ConnectStage::Tcp, futures::future::Either::Right(connecting))
     };
-    let (ws_write, ws_read) = ws_stream.split();
-    let transport = WebSocketCMTransport::new(ws_read, ws_write);
+    let stream = within(deadline, connecting)
+        .await
+        .ok_or_else(|| timed_out(stream_stage))?
+        .map_err(|error| (stream_stage, error))?;
+    // the request still holds the hostname, so it is used for SNI and certificate checks
+    let handshake = client_async_tls_with_config(
+        request,
+        stream,
+        options.websocket_config,
+        options.tls_connector.clone().map(|connector| connector.0),
+    );
+    let (ws_stream, _) = within(deadline, handshake)
+        .await
+        // TLS and the upgrade happen in one call, so a timeout during either is reported as TLS
+        .ok_or_else(|| timed_out(ConnectStage::Tls))?
+        .map_err(|error| {
+            let stage = match error {
+                tungstenite::Error::Tls(_) => ConnectStage::Tls,
+                _ => ConnectStage::Upgrade,
+            };
+            
+            (stage, error.into())
+        })?;
+    
+    Ok(ws_stream)
+}
 
-    Ok(transport)
+/// Runs `future` until `deadline`, returning `None` if it passes first.
+async fn within<F>(deadline: Option<tokio::time::Instant>, future: F) -> Option<F::Output>
+where
+    F: std::future::Future,
+{
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+/// Whether `name` is set by the websocket handshake itself.
+fn is_handshake_header(name: &HeaderName) -> bool {
+    [HOST, CONNECTION, UPGRADE, SEC_WEBSOCKET_VERSION, SEC_WEBSOCKET_KEY].contains(name)
 }
 
 pub async fn wait_for_response<Msg>(
     rx: oneshot::Receiver<Result<ApiResponseBody, Error>>,
+    wire_logging: WireLogging,
+    parse_mode: ParseMode,
+    mut context: RequestContext,
+    started: Instant,
 ) -> Result<Msg::Response, AuthenticationClientError>
 where
     Msg: ApiRequest,
     <Msg as ApiRequest>::Response: Send,
 {
-    match timeout(std::time::Duration::from_secs(5), rx).await {
+    let response = timeout(std::time::Duration::from_secs(5), rx).await;
+    
+    context.elapsed = started.elapsed();
+    
+    match response {
         Ok(response) => {
-            let body = response??;
-            let response = body.into_response::<Msg>()?;
+            let body = response
+                .map_err(Error::from)
+                .and_then(|response| response)
+                .map_err(|error| match error {
+                    Error::RecvError(_) => error.with_context(context),
+                    error if error.is_disconnected() => error.with_context(context),
+                    error => error,
+                })?;
+            let response = body.into_response::<Msg>(parse_mode)?;
+            
+            if wire_logging.logs_payloads() {
+                wire_log::log_response(wire_logging, <Msg as ApiRequest>::NAME, response.redacted_payload());
+            }
             
             Ok(response)
         },
         Err(_error) => {
-            log::debug!("Timed out waiting for response from {}", <Msg as ApiRequest>::NAME);
-            Err(Error::Timeout.into())
+            debug!("Timed out waiting for response from {}", <Msg as ApiRequest>::NAME);
+            Err(Error::Timeout(context).into())
         },
     }
 }
\ No newline at end of file
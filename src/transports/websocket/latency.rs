@@ -0,0 +1,109 @@
+use super::DisconnectReason;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// An event emitted by a [`super::WebSocketCMTransport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportEvent {
+    /// The round-trip time of a ping exceeded the configured threshold.
+    HighLatency {
+        /// The measured round-trip time.
+        latency: Duration,
+        /// The configured threshold.
+        threshold: Duration,
+    },
+    /// The connection to the CM server was closed. `reason` is `None` if it was closed normally.
+    Disconnected {
+        /// Why the connection was lost.
+        reason: Option<DisconnectReason>,
+    },
+}
+
+/// Tracks round-trip times measured using websocket pings.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyTracker {
+    /// The ID and send time of the last ping sent.
+    pending: Mutex<Option<(u64, Instant)>>,
+    next_id: AtomicU64,
+    /// The last round-trip time in microseconds, or 0 if none has been measured.
+    latency_micros: AtomicU64,
+    last_pong: Mutex<Option<Instant>>,
+    /// The threshold in microseconds, or 0 if not set.
+    threshold_micros: AtomicU64,
+}
+
+impl LatencyTracker {
+    /// Sets the round-trip time above which [`TransportEvent::HighLatency`] is emitted.
+    pub fn set_threshold(&self, threshold: Duration) {
+        self.threshold_micros.store((threshold.as_micros() as u64).max(1), Ordering::Relaxed);
+    }
+    
+    /// Records a ping being sent. Returns the payload to send with it.
+    pub fn ping_sent(&self) -> [u8; 8] {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        
+        *self.pending.lock().unwrap_or_else(|error| error.into_inner()) = Some((id, Instant::now()));
+        id.to_be_bytes()
+    }
+    
+    /// Records a pong being received. Returns the round-trip time if `payload` matches the last 
+    /// ping sent.
+    pub fn pong_received(&self, payload: &[u8]) -> Option<Duration> {
+        let now = Instant::now();
+        let id = u64::from_be_bytes(payload.try_into().ok()?);
+        let (_, sent_at) = self.pending
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .take_if(|(pending_id, _)| *pending_id == id)?;
+        let latency = now.duration_since(sent_at);
+        
+        *self.last_pong.lock().unwrap_or_else(|error| error.into_inner()) = Some(now);
+        // Stored as at least 1 so that 0 can mean unmeasured
+        self.latency_micros.store((latency.as_micros() as u64).max(1), Ordering::Relaxed);
+        
+        Some(latency)
+    }
+    
+    /// Gets the last measured round-trip time.
+    pub fn latency(&self) -> Option<Duration> {
+        match self.latency_micros.load(Ordering::Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+    
+    /// Gets when the last pong was received.
+    pub fn last_pong(&self) -> Option<Instant> {
+        *self.last_pong.lock().unwrap_or_else(|error| error.into_inner())
+    }
+    
+    /// Gets the threshold if `latency` exceeds it.
+    pub fn exceeds_threshold(&self, latency: Duration) -> Option<Duration> {
+        match self.threshold_micros.load(Ordering::Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros)).filter(|threshold| latency > *threshold),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn measures_matching_pong() {
+        let tracker = LatencyTracker::default();
+        let payload = tracker.ping_sent();
+        
+        assert_eq!(tracker.latency(), None);
+        assert!(tracker.pong_received(&[0; 4]).is_none());
+        
+        let latency = tracker.pong_received(&payload).unwrap();
+        
+        assert_eq!(tracker.latency(), Some(Duration::from_micros((latency.as_micros() as u64).max(1))));
+        assert!(tracker.last_pong().is_some());
+        // The ping was already answered
+        assert!(tracker.pong_received(&payload).is_none());
+    }
+}
@@ -1,4 +1,89 @@
+use super::{Error, PROTO_MASK};
+use crate::enums::{EMsg, EResult};
+use crate::proto::steammessages_base::{CMsgProtoBufHeader, CMsgMulti};
+use std::io::Read;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use flate2::read::GzDecoder;
+use protobuf::Message as ProtoMessage;
+
+/// A message received from the CM server that is not a response to a request.
 #[derive(Debug, Clone)]
 pub struct Message {
+    /// The message type.
+    pub emsg: EMsg,
+    /// The result in the message header.
+    pub eresult: EResult,
+    /// The encoded protobuf body.
+    pub body: Bytes,
+}
+
+/// How messages and responses that cannot be parsed are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Messages that cannot be parsed are logged and skipped, and unknown fields in responses are 
+    /// ignored. Suited to production, where Steam adding fields should not break logins.
+    #[default]
+    Lenient,
+    /// Messages that cannot be parsed are sent to the message receiver as errors, and responses 
+    /// containing fields unknown to the generated definitions fail. Suited to CI, to catch 
+    /// changes to the protocol early.
+    Strict,
+}
+
+/// Encodes `msg` with `proto_header` in the format sent to the CM server. The message is encoded 
+/// into a single buffer.
+pub fn encode_message<Msg>(
+    emsg: EMsg,
+    proto_header: &CMsgProtoBufHeader,
+    msg: &Msg,
+) -> Result<Bytes, Error>
+where
+    Msg: ProtoMessage,
+{
+    let header_length = proto_header.compute_size() as usize;
+    let body_length = msg.compute_size() as usize;
+    let mut message = BytesMut::with_capacity(8 + header_length + body_length);
     
-}
\ No newline at end of file
+    message.put_u32_le(emsg as u32 | PROTO_MASK); // 4
+    message.put_u32_le(header_length as u32); // 8
+    
+    let mut writer = message.writer();
+    
+    proto_header.write_to_writer(&mut writer)?;
+    msg.write_to_writer(&mut writer)?;
+    
+    Ok(writer.into_inner().freeze())
+}
+
+/// Splits the body of a `Multi` message into the messages it contains, decompressing it if 
+/// needed. Each message is a view into the payload rather than a copy.
+pub fn split_multi(body: &[u8]) -> Result<Vec<Bytes>, Error> {
+    let mut message = CMsgMulti::parse_from_bytes(body)?;
+    let payload = message.take_message_body();
+    let mut payload = if message.size_unzipped() != 0 {
+        let mut unzipped = Vec::with_capacity(message.size_unzipped() as usize);
+        
+        GzDecoder::new(payload.as_slice()).read_to_end(&mut unzipped)?;
+        Bytes::from(unzipped)
+    } else {
+        Bytes::from(payload)
+    };
+    let mut messages = Vec::new();
+    
+    while payload.remaining() >= 4 {
+        let chunk_size = payload.get_u32_le() as usize;
+        
+        if payload.remaining() < chunk_size {
+            return Err(unexpected_eof());
+        }
+        
+        messages.push(payload.split_to(chunk_size));
+    }
+    
+    Ok(messages)
+}
+
+/// The error for a message that ends early.
+pub(super) fn unexpected_eof() -> Error {
+    Error::OI(std::io::ErrorKind::UnexpectedEof.into())
+}
@@ -1,74 +1,92 @@
-use super::Error;
+use super::{Error, DisconnectReason};
 use super::PROTO_MASK;
-use super::message::Message;
+use super::message::{Message, ParseMode, split_multi, unexpected_eof};
 use super::response::ApiResponseBody;
+use super::wire_log::{self, WireLogging};
+use super::latency::{LatencyTracker, TransportEvent};
 use crate::enums::{EMsg, EResult};
-use crate::proto::steammessages_base::{CMsgProtoBufHeader, CMsgMulti};
+use crate::proto::steammessages_base::CMsgProtoBufHeader;
 use crate::proto::steammessages_clientserver_login::CMsgClientLogonResponse;
-use std::io::{Cursor, Read};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
 use futures::stream::SplitStream;
 use futures::StreamExt;
 use tokio::net::TcpStream;
-use tokio::sync::{oneshot, mpsc};
+use tokio::sync::{broadcast, oneshot, mpsc};
 use tokio_tungstenite::tungstenite;
 use tokio_tungstenite::{WebSocketStream, MaybeTlsStream};
 use dashmap::DashMap;
 use protobuf::Message as ProtoMessage;
-use byteorder::{LittleEndian, ReadBytesExt};
-use flate2::read::GzDecoder;
+use bytes::{Buf, Bytes};
+
+const EVENT_CHANNEL_CAPACITY: usize = 16;
 
 #[derive(Debug)]
 struct MessageData {
     eresult: EResult,
     emsg: EMsg,
-    body: Vec<u8>,
+    body: Bytes,
     jobid_target: u64,
     client_sessionid: i32,
+    steamid: u64,
 }
 
 #[derive(Debug, Clone)]
 pub struct MessageFilter {
     job_id_filters: Arc<DashMap<u64, oneshot::Sender<Result<ApiResponseBody, Error>>>>,
     client_sessionid: Arc<AtomicI32>,
+    steamid: Arc<AtomicU64>,
+    rest_tx: mpsc::Sender<Result<Message, Error>>,
+    wire_logging: WireLogging,
+    parse_mode: ParseMode,
+    closed: Arc<AtomicBool>,
+    pub latency: Arc<LatencyTracker>,
+    last_activity: Arc<Mutex<Instant>>,
+    pub events: broadcast::Sender<TransportEvent>,
 }
 
 impl MessageFilter {
     pub fn new(
-        mut source: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+        source: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
         client_sessionid: Arc<AtomicI32>,
+        steamid: Arc<AtomicU64>,
+        wire_logging: WireLogging,
+        parse_mode: ParseMode,
     ) -> (Self, mpsc::Receiver<Result<Message, Error>>) {
         let (
-            _rest_tx,
+            rest_tx,
             rx,
         ) = mpsc::channel::<Result<Message, Error>>(16);
         let filter = MessageFilter {
             job_id_filters: Default::default(),
             client_sessionid,
+            steamid,
+            rest_tx,
+            wire_logging,
+            parse_mode,
+            closed: Arc::new(AtomicBool::new(false)),
+            latency: Default::default(),
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
         };
-        let filter_send = filter.clone();
+        let reader = tokio::spawn(read_messages(source, filter.clone()));
+        let supervisor = filter.clone();
         
+        // Supervises the read task so that pending requests are failed however it ends
         tokio::spawn(async move {
-            while let Some(res) = source.next().await {
-                match res {
-                    Ok(message) => match message {
-                        tungstenite::Message::Binary(buffer) => {
-                            log::debug!("Got {} bytes", buffer.len());
-                            
-                            if let Err(error) = handle_ws_message(&filter_send, buffer.to_vec()) {
-                                log::warn!("Error handling websocket message: {}", error);
-                            }
-                        },
-                        _ => {
-                            log::debug!("Websocket received message with type other than binary");
-                        },
-                    },
-                    Err(error) => {
-                        log::warn!("Error received from websocket connection {}", error);
-                    },
-                }
-            }
+            let reason = match reader.await {
+                Ok(reason) => reason,
+                Err(error) if error.is_panic() => {
+                    let reason = DisconnectReason::Panicked(panic_message(error.into_panic()));
+                    
+                    warn!("Websocket {reason}");
+                    Some(reason)
+                },
+                Err(_error) => None,
+            };
+            
+            supervisor.disconnect(reason);
         });
         
         (filter, rx)
@@ -80,48 +98,200 @@ impl MessageFilter {
     ) -> oneshot::Receiver<Result<ApiResponseBody, Error>> {
         let (tx, rx) = oneshot::channel();
         self.job_id_filters.insert(id, tx);
+        
+        // The connection closed before the job was added, so it would never be failed
+        if self.is_closed() {
+            if let Some((_, tx)) = self.job_id_filters.remove(&id) {
+                let _ = tx.send(Err(Error::ConnectionClosed));
+            }
+        }
+        
         rx
     }
+    
+    /// Checks whether the connection has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+    
+    /// Gets the number of requests waiting for a response.
+    pub fn pending_jobs(&self) -> usize {
+        self.job_id_filters.len()
+    }
+    
+    /// Gets when a message was last sent or received.
+    pub fn last_activity(&self) -> Instant {
+        *self.last_activity.lock().unwrap_or_else(|error| error.into_inner())
+    }
+    
+    /// Records a message being sent or received.
+    pub fn touch(&self) {
+        *self.last_activity.lock().unwrap_or_else(|error| error.into_inner()) = Instant::now();
+    }
+    
+    /// Stops waiting for a response to `id`, e.g. after the request timed out.
+    pub fn remove_job_id(&self, id: u64) {
+        self.job_id_filters.remove(&id);
+    }
+    
+    /// Marks the connection as closed and fails all requests waiting for a response, rather 
+    /// than leaving them to time out. The receiver for other messages is sent the same error.
+    fn disconnect(&self, reason: Option<DisconnectReason>) {
+        let error = || match &reason {
+            Some(reason) => Error::Disconnected(reason.clone()),
+            None => Error::ConnectionClosed,
+        };
+        
+        self.closed.store(true, Ordering::Relaxed);
+        
+        let jobids = self.job_id_filters
+            .iter()
+            .map(|entry| *entry.key())
+            .collect::<Vec<_>>();
+        
+        for jobid in jobids {
+            if let Some((_, tx)) = self.job_id_filters.remove(&jobid) {
+                let _ = tx.send(Err(error()));
+            }
+        }
+        
+        let _ = self.rest_tx.try_send(Err(error()));
+        // an error only means there are no subscribers
+        let _ = self.events.send(TransportEvent::Disconnected {
+            reason,
+        });
+    }
+    
+    /// Fails the request waiting for a response to `jobid` with `error`. Returns the error if no 
+    /// request is waiting.
+    fn fail_job(&self, jobid: u64, error: Error) -> Result<(), Error> {
+        match self.job_id_filters.remove(&jobid) {
+            Some((_, tx)) => {
+                warn!("Failing request (jobid {jobid}): {error}");
+                let _ = tx.send(Err(error));
+                Ok(())
+            },
+            None => Err(error),
+        }
+    }
+    
+    /// Handles a message that could not be parsed. It is skipped when parsing leniently, and sent 
+    /// to the receiver for other messages when parsing strictly. Responses to requests that 
+    /// cannot be parsed fail the request instead when parsing strictly.
+    fn parse_failed(&self, error: Error) {
+        match self.parse_mode {
+            ParseMode::Lenient => {
+                warn!("Skipping websocket message: {error}");
+            },
+            ParseMode::Strict => {
+                warn!("Error handling websocket message: {error}");
+                let _ = self.rest_tx.try_send(Err(error));
+            },
+        }
+    }
+    
+    /// Records a pong, emitting [`TransportEvent::HighLatency`] if the round-trip time exceeds 
+    /// the threshold.
+    fn handle_pong(&self, payload: &[u8]) {
+        let Some(latency) = self.latency.pong_received(payload) else {
+            return;
+        };
+        
+        debug!("Websocket round-trip time: {latency:?}");
+        
+        if let Some(threshold) = self.latency.exceeds_threshold(latency) {
+            let _ = self.events.send(TransportEvent::HighLatency {
+                latency,
+                threshold,
+            });
+        }
+    }
+}
+
+/// Reads messages from the socket until it is closed. Returns the reason if the connection was 
+/// lost because of an error.
+async fn read_messages(
+    mut source: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    filter: MessageFilter,
+) -> Option<DisconnectReason> {
+    while let Some(res) = source.next().await {
+        if res.is_ok() {
+            filter.touch();
+        }
+        
+        match res {
+            Ok(message) => match message {
+                tungstenite::Message::Binary(buffer) => {
+                    debug!("Got {} bytes", buffer.len());
+                    
+                    match handle_ws_message(&filter, buffer) {
+                        Ok(()) => {},
+                        Err(error @ Error::ClientLogOnResponseTryAnotherCM(_)) => {
+                            warn!("Closing websocket connection: {error}");
+                            return Some(DisconnectReason::Error(error.to_string()));
+                        },
+                        Err(error) => filter.parse_failed(error),
+                    }
+                },
+                tungstenite::Message::Pong(payload) => {
+                    filter.handle_pong(&payload);
+                },
+                _ => {
+                    debug!("Websocket received message with type other than binary");
+                },
+            },
+            Err(error) => {
+                warn!("Error received from websocket connection {}", error);
+                return Some(DisconnectReason::Error(error.to_string()));
+            },
+        }
+    }
+    
+    debug!("Websocket connection closed");
+    None
+}
+
+/// Gets the message from a panic payload.
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".into()
+    }
 }
 
 fn process_multi_message(
     filter: &MessageFilter,
     body_buffer: &[u8],
 ) -> Result<(), Error> {
-    let message = CMsgMulti::parse_from_bytes(body_buffer)?;
-    let payload = message.message_body();
-    log::debug!("Process multi {} bytes", payload.len());
-    let mut s = Vec::new();
-    let payload = if message.size_unzipped() != 0 {
-        GzDecoder::new(payload).read_to_end(&mut s)?;
-        
-        s.as_slice()
-    } else {
-        payload
-    };
-    let mut cursor = Cursor::new(payload);
+    debug!("Process multi {} bytes", body_buffer.len());
     
-    while let Ok(chunk_size) = cursor.read_u32::<LittleEndian>() {
-        let mut chunk_buffer: Vec<u8> = vec![0; chunk_size as usize];
-        
-        cursor.read_exact(&mut chunk_buffer)?;
-        check_ws_message(filter, chunk_buffer)?;
+    let messages = split_multi(body_buffer)
+        .map_err(|error| error.parse(Some(EMsg::Multi), body_buffer.len()))?;
+    
+    for message in messages {
+        handle_ws_message(filter, message)?;
     }
     
     Ok(())
 }
 
-fn parse_message(msg: Vec<u8>) -> Result<MessageData, Error> {
-    let mut cursor = Cursor::new(msg.as_slice());
-    let raw_emsg = cursor.read_u32::<LittleEndian>()?;
-    let header_length = cursor.read_u32::<LittleEndian>()?;
-    let mut header_buffer: Vec<u8> = vec![0; header_length as usize];
+fn parse_message(mut msg: Bytes) -> Result<MessageData, Error> {
+    if msg.remaining() < 8 {
+        return Err(unexpected_eof());
+    }
     
-    cursor.read_exact(&mut header_buffer)?;
+    let raw_emsg = msg.get_u32_le();
+    let header_length = msg.get_u32_le() as usize;
     
-    let mut body: Vec<u8> = Vec::new();
+    if msg.remaining() < header_length {
+        return Err(unexpected_eof());
+    }
     
-    cursor.read_to_end(&mut body)?;
+    let header_buffer = msg.split_to(header_length);
+    let body = msg;
     
     if raw_emsg & PROTO_MASK == 0 {
         return Err(Error::UnexpectedNonProtobufMessage(raw_emsg));
@@ -130,6 +300,7 @@ fn parse_message(msg: Vec<u8>) -> Result<MessageData, Error> {
     let raw_emsg = raw_emsg & !PROTO_MASK;
     let header = CMsgProtoBufHeader::parse_from_bytes(&header_buffer)?;
     let client_sessionid = header.client_sessionid();
+    let steamid = header.steamid();
     let emsg = EMsg::try_from(raw_emsg)
         .map_err(|_| Error::UnknownEMsg(raw_emsg))?;
     let jobid_target = header.jobid_target();
@@ -141,28 +312,65 @@ fn parse_message(msg: Vec<u8>) -> Result<MessageData, Error> {
         emsg,
         jobid_target,
         client_sessionid,
+        steamid,
         body,
     })
 }
 
+/// Reads the job ID a message is a response to from its header, if the header can be parsed.
+fn peek_jobid(msg: &[u8]) -> Option<u64> {
+    let header_length = u32::from_le_bytes(msg.get(4..8)?.try_into().ok()?) as usize;
+    let header_buffer = msg.get(8..8usize.checked_add(header_length)?)?;
+    let jobid_target = CMsgProtoBufHeader::parse_from_bytes(header_buffer).ok()?.jobid_target();
+    
+    Some(jobid_target).filter(|jobid| *jobid != 0)
+}
+
+/// Reads the type of a message from its first four bytes, if it is known.
+fn peek_emsg(msg: &[u8]) -> Option<EMsg> {
+    let raw_emsg = u32::from_le_bytes(msg.get(..4)?.try_into().ok()?);
+    
+    EMsg::try_from(raw_emsg & !PROTO_MASK).ok()
+}
+
 fn check_ws_message(
     filter: &MessageFilter,
-    msg: Vec<u8>,
-) -> Result<Option<(EMsg, Vec<u8>)>, Error> {
+    msg: Bytes,
+) -> Result<Option<(EMsg, EResult, Bytes)>, Error> {
     let MessageData {
         eresult,
         emsg,
         jobid_target,
         client_sessionid,
+        steamid,
         body,
-    } = parse_message(msg)?;
+    } = match parse_message(msg.clone()) {
+        Ok(data) => data,
+        Err(error) => {
+            let error = error.parse(peek_emsg(&msg), msg.len());
+            
+            return match peek_jobid(&msg) {
+                // fail the request rather than leaving it to time out
+                Some(jobid) if filter.parse_mode == ParseMode::Strict => filter
+                    .fail_job(jobid, error)
+                    .map(|_| None),
+                _ => Err(error),
+            };
+        },
+    };
     
     if client_sessionid != 0 && client_sessionid != filter.client_sessionid.load(Ordering::Relaxed) {
-        log::debug!("Got new client sessionid: {client_sessionid}");
+        debug!("Got new client sessionid: {client_sessionid}");
         filter.client_sessionid.store(client_sessionid, Ordering::Relaxed);
     }
     
-    log::debug!("Handle {emsg:?} (jobid {jobid_target})");
+    if steamid != 0 && steamid != filter.steamid.load(Ordering::Relaxed) {
+        debug!("Got new steamid: {steamid}");
+        filter.steamid.store(steamid, Ordering::Relaxed);
+    }
+    
+    debug!("Handle {emsg:?} (jobid {jobid_target})");
+    wire_log::log_received(filter.wire_logging, emsg, jobid_target, body.len());
     
     if jobid_target != 0 {
         if let Some((_, tx)) = filter
@@ -185,32 +393,109 @@ fn check_ws_message(
         }
     }
     
-    Ok(Some((emsg, body)))
+    Ok(Some((emsg, eresult, body)))
 }
 
-fn handle_ws_message(filter: &MessageFilter, msg: Vec<u8>) -> Result<(), Error> {
-    if let Some((emsg, body)) = check_ws_message(filter, msg)? {
+fn handle_ws_message(filter: &MessageFilter, msg: Bytes) -> Result<(), Error> {
+    if let Some((emsg, eresult, body)) = check_ws_message(filter, msg)? {
         // this isn't a response message, so figure out what it is
         match emsg {
-            // The only time we expect to receive ClientLogOnResponse is when the CM is telling us to try another CM
-            EMsg::ClientLogOnResponse => {
-                let logon_response = CMsgClientLogonResponse::parse_from_bytes(&body)?;
-                let eresult =  EResult::try_from(logon_response.eresult())
-                    .map_err(|_| Error::UnknownEResult(logon_response.eresult()))?;
-                
-                log::debug!("Received ClientLogOnResponse with result: {eresult:?}");
-                // websocket connection should be closed
-                
-                return Err(Error::ClientLogOnResponseTryAnotherCM(eresult));
-            },
             EMsg::Multi => {
                 process_multi_message(filter, &body)?;
             },
             emsg => {
-                log::debug!("Received unexpected message: {emsg:?}");
+                let mut try_another_cm = false;
+                
+                if emsg == EMsg::ClientLogOnResponse {
+                    // Without a logon in progress this is the CM telling us to try another CM
+                    let logon_response = CMsgClientLogonResponse::parse_from_bytes(&body)
+                        .map_err(|error| Error::from(error).parse(Some(emsg), body.len()))?;
+                    
+                    debug!("Received ClientLogOnResponse with result: {}", logon_response.eresult());
+                    try_another_cm = eresult == EResult::TryAnotherCM ||
+                        logon_response.eresult() == EResult::TryAnotherCM as i32;
+                }
+                
+                forward_message(filter, Message {
+                    emsg,
+                    eresult,
+                    body,
+                });
+                
+                if try_another_cm {
+                    // the message may have been dropped if the receiver is full, so the 
+                    // connection is ended to make sure callers move to another CM
+                    return Err(Error::ClientLogOnResponseTryAnotherCM(EResult::TryAnotherCM));
+                }
             },
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Forwards a message that is not a response to a request to the receiver for other messages.
+fn forward_message(filter: &MessageFilter, message: Message) {
+    let emsg = message.emsg;
+    
+    match filter.rest_tx.try_send(Ok(message)) {
+        Ok(_) => {},
+        Err(mpsc::error::TrySendError::Full(_)) => {
+            debug!("Dropped {emsg:?}: message receiver is full");
+        },
+        Err(mpsc::error::TrySendError::Closed(_)) => {
+            debug!("Received unexpected message: {emsg:?}");
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::encode_message;
+    use super::super::tests::{connect_local, connect_local_with};
+    use futures::SinkExt;
+    
+    fn logon_response(eresult: EResult) -> tungstenite::Message {
+        let mut header = CMsgProtoBufHeader::new();
+        let mut logon_response = CMsgClientLogonResponse::new();
+        
+        header.set_eresult(eresult as i32);
+        logon_response.set_eresult(eresult as i32);
+        tungstenite::Message::binary(encode_message(EMsg::ClientLogOnResponse, &header, &logon_response).unwrap())
+    }
+    
+    #[tokio::test]
+    async fn fails_pending_jobs_when_told_to_try_another_cm() {
+        let (transport, mut server) = connect_local().await;
+        let pending = transport.filter.on_job_id(1);
+        
+        // fills the receiver for other messages, which is never read
+        for _ in 0..20 {
+            server.send(logon_response(EResult::OK)).await.unwrap();
+        }
+        
+        server.send(logon_response(EResult::TryAnotherCM)).await.unwrap();
+        
+        assert!(matches!(pending.await.unwrap(), Err(Error::Disconnected(_))));
+        assert!(transport.is_closed());
+    }
+    
+    #[tokio::test]
+    async fn fails_jobs_with_unparseable_responses_when_strict() {
+        let (transport, mut server) = connect_local_with(ParseMode::Strict).await;
+        let pending = transport.filter.on_job_id(5);
+        let mut header = CMsgProtoBufHeader::new();
+        
+        header.set_jobid_target(5);
+        // not a known EResult
+        header.set_eresult(-1);
+        
+        let message = encode_message(EMsg::ServiceMethodResponse, &header, &header).unwrap();
+        
+        server.send(tungstenite::Message::binary(message)).await.unwrap();
+        
+        assert!(matches!(pending.await.unwrap(), Err(Error::Parse { .. })));
+        assert!(!transport.is_closed());
+    }
+}
@@ -6,47 +6,112 @@ mod message_filter;
 mod message;
 mod response;
 mod helpers;
+mod builder;
+pub(crate) mod wire_log;
+mod latency;
+mod dial;
+mod priority;
+mod rotating;
+mod bootstrap;
 
-pub use cm_list_cache::Error as CmListError;
-pub use error::Error;
+pub use cm_list_cache::{Error as CmListError, CmListRefresh, CmListRoute};
+pub use error::{Error, RequestContext, DisconnectReason, ConnectAttempt, ConnectReport, ConnectStage, SocksFailure, ProxyAction};
+pub use message::{Message, ParseMode};
+// exported for the benchmarks; not part of the public API
+#[doc(hidden)]
+pub use message::{encode_message, split_multi};
+pub use builder::{WebSocketCMTransportBuilder, DEFAULT_CONNECT_ATTEMPTS, STEAM_CLIENT_USER_AGENT, default_upgrade_headers};
+pub use wire_log::WireLogging;
+pub use latency::TransportEvent;
+pub use dial::IpPreference;
+pub use rotating::{RotatingTransport, RotationPolicy};
 
 use cm_list_cache::CmListCache;
 use message_filter::MessageFilter;
+use priority::PriorityGate;
 use steam_session_proto::steammessages_clientserver_login::CMsgClientHello;
 
 use crate::enums::EMsg;
-use crate::net::ApiRequest;
+use crate::net::{ApiRequest, MessagePriority};
 use crate::proto::steammessages_base::CMsgProtoBufHeader;
 use crate::transports::Transport;
+use crate::hosts::SteamHosts;
 use crate::authentication_client::Error as AuthenticationClientError;
 use std::io::Cursor;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
 use futures::stream::{SplitSink, SplitStream};
 use futures::SinkExt;
 use tokio::net::TcpStream;
-use tokio::sync::{Mutex, oneshot};
+use tokio::sync::{Mutex, broadcast, mpsc, oneshot};
 use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::tungstenite::http::HeaderMap;
 use tokio_tungstenite::{WebSocketStream, MaybeTlsStream};
-use protobuf::Message as ProtoMessage;
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
-use rand::Rng;
+use protobuf::MessageFull;
+use byteorder::{BigEndian, ReadBytesExt};
+use crate::clock::RandomSource;
 use async_trait::async_trait;
+use dashmap::DashMap;
+use steamid_ng::SteamID;
 use lazy_static::lazy_static;
 
+type WebSocketWrite = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>;
+
 pub const PROTOCOL_VERSION: u32 = 65580;
+/// The number of requests waiting for a response above which a transport is considered unhealthy.
+pub const MAX_HEALTHY_BACKLOG: usize = 64;
+/// How often a draining transport checks whether requests are still waiting for a response.
+const DRAIN_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
 pub const PROTO_MASK: u32 = 0x80000000;
 
 lazy_static! {
     pub static ref DEFAULT_CM_LIST: Arc<Mutex<CmListCache>> = Arc::new(tokio::sync::Mutex::new(CmListCache::new()));
+    /// CM lists for directories other than the default, keyed by directory URL.
+    static ref CM_LISTS: DashMap<String, Arc<Mutex<CmListCache>>> = DashMap::new();
+}
+
+/// Gets the shared CM list for `directory`.
+fn cm_list_for_directory(directory: &str) -> Arc<Mutex<CmListCache>> {
+    if directory == crate::hosts::DEFAULT_DIRECTORY_URL {
+        return Arc::clone(&DEFAULT_CM_LIST);
+    }
+    
+    CM_LISTS
+        .entry(directory.to_string())
+        .or_insert_with(|| Arc::new(Mutex::new(CmListCache::with_directory(directory))))
+        .clone()
 }
 
 /// Represents a WebSocket CM transport.
 #[derive(Debug)]
 pub struct WebSocketCMTransport {
-    websocket_write: tokio::sync::Mutex<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>>,
+    websocket_write: Arc<tokio::sync::Mutex<WebSocketWrite>>,
+    priority_gate: PriorityGate,
+    draining: AtomicBool,
+    pending_flush: Arc<AtomicBool>,
+    flush_interval: Option<std::time::Duration>,
+    ping_interval: Option<std::time::Duration>,
+    latency_threshold: Option<std::time::Duration>,
+    connected_at: std::time::Instant,
+    random: Arc<dyn RandomSource>,
     filter: Arc<MessageFilter>,
     client_sessionid: Arc<AtomicI32>,
+    steamid: Arc<AtomicU64>,
+    messages: std::sync::Mutex<Option<mpsc::Receiver<Result<Message, Error>>>>,
+    proxy: Option<crate::transports::Socks5ProxyConfig>,
+    dns_resolver: Option<crate::transports::dns::DohResolver>,
+    cm_list_client: Option<reqwest::Client>,
+    cm_list_refresh: Option<CmListRefresh>,
+    max_connect_attempts: usize,
+    ip_preference: IpPreference,
+    websocket_config: Option<tungstenite::protocol::WebSocketConfig>,
+    tls_connector: Option<builder::TlsConnector>,
+    hosts: SteamHosts,
+    connect_timeout: Option<std::time::Duration>,
+    wire_logging: WireLogging,
+    parse_mode: ParseMode,
+    headers: HeaderMap,
+    endpoint: String,
 }
 
 #[async_trait]
@@ -60,19 +125,53 @@ impl Transport for WebSocketCMTransport {
         Msg: ApiRequest,
         <Msg as ApiRequest>::Response: Send,
     {
-        if let Some(jobid) = self.send_message(
+        let started = std::time::Instant::now();
+        let mut context = RequestContext {
+            request: Some(<Msg as ApiRequest>::NAME),
+            jobid: None,
+            endpoint: Some(self.endpoint.clone()),
+            elapsed: Default::default(),
+        };
+        let jobid = self.send_message_with_priority(
             <Msg as ApiRequest>::KIND,
             msg,
             Some(<Msg as ApiRequest>::NAME),
-        ).await? {
+            <Msg as ApiRequest>::PRIORITY,
+        )
+            .await
+            .map_err(|error| {
+                error.with_context(RequestContext {
+                    elapsed: started.elapsed(),
+                    ..context.clone()
+                })
+            })?;
+        
+        if let Some(jobid) = jobid {
             let filter_rx = self.filter.on_job_id(jobid);
             let (
                 tx,
                 rx,
             ) = oneshot::channel::<Result<Msg::Response, AuthenticationClientError>>();
+            let wire_logging = self.wire_logging;
+            let parse_mode = self.parse_mode;
+            let filter = Arc::clone(&self.filter);
+            
+            context.jobid = Some(jobid);
             
             tokio::spawn(async move {
-                tx.send(helpers::wait_for_response::<Msg>(filter_rx).await).ok();
+                let result = helpers::wait_for_response::<Msg>(
+                    filter_rx,
+                    wire_logging,
+                    parse_mode,
+                    context,
+                    started,
+                ).await;
+                
+                // The response never arrived if this timed out
+                filter.remove_job_id(jobid);
+                
+                crate::metrics::request("websocket", <Msg as ApiRequest>::NAME, started.elapsed(), &result);
+                tx.send(result).ok();
             });
             
             Ok(rx)
@@ -80,81 +179,432 @@ impl Transport for WebSocketCMTransport {
             Err(AuthenticationClientError::NoJob)
         }
     }
+    
+    fn cm_endpoint(&self) -> Option<String> {
+        Some(self.endpoint.clone())
+    }
 }
 
 impl WebSocketCMTransport {
+    /// Creates a new [`WebSocketCMTransportBuilder`] for configuring the proxy, hosts, and 
+    /// timeouts used to connect.
+    pub fn builder() -> WebSocketCMTransportBuilder {
+        WebSocketCMTransportBuilder::new()
+    }
+    
     /// Connects to a CM server.
     pub async fn connect() -> Result<WebSocketCMTransport, Error> {
-        let transport = helpers::connect_to_cm(&DEFAULT_CM_LIST).await?;
-        let mut hello = CMsgClientHello::new();
-        
-        hello.set_protocol_version(PROTOCOL_VERSION);
-        transport.send_message(
-            EMsg::ClientHello,
-            hello,
-            None,
-        ).await?;
-        
-        Ok(transport)
+        Self::builder().connect().await
     }
     
-    /// Connects to a CM server through a SOCKS5 proxy.
+    /// Connects to a CM server through a SOCKS5 proxy. This is shorthand for 
+    /// `WebSocketCMTransport::builder().proxy(proxy.clone()).connect()`.
+    #[deprecated(note = "use `WebSocketCMTransport::builder().proxy(..).connect()` instead")]
     pub async fn connect_with_socks5_proxy(
         proxy: &crate::transports::Socks5ProxyConfig,
     ) -> Result<WebSocketCMTransport, Error> {
-        let transport = helpers::connect_to_cm_with_socks5_proxy(&DEFAULT_CM_LIST, Some(proxy))
-            .await?;
+        Self::builder()
+            .proxy(proxy.clone())
+            .connect()
+            .await
+    }
+    
+    /// Connects to a CM server listed by the Steam directory in `hosts`, optionally through a 
+    /// SOCKS5 proxy. This is shorthand for 
+    /// `WebSocketCMTransport::builder().hosts(hosts.clone()).connect()`, with the proxy set if 
+    /// given.
+    pub async fn connect_with_hosts(
+        hosts: &SteamHosts,
+        proxy: Option<&crate::transports::Socks5ProxyConfig>,
+    ) -> Result<WebSocketCMTransport, Error> {
+        let mut builder = Self::builder()
+            .hosts(hosts.clone());
+        
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        
+        builder.connect().await
+    }
+    
+    /// Sends `ClientHello`.
+    async fn say_hello(&self) -> Result<(), Error> {
         let mut hello = CMsgClientHello::new();
-
+        
         hello.set_protocol_version(PROTOCOL_VERSION);
-        transport
-            .send_message(EMsg::ClientHello, hello, None)
-            .await?;
-
-        Ok(transport)
+        self.send_message(EMsg::ClientHello, hello, None).await?;
+        
+        Ok(())
     }
     
     /// Creates a new [`WebSocketCMTransport`].
     fn new(
         source: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
-        websocket_write: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>,
+        websocket_write: WebSocketWrite,
+        wire_logging: WireLogging,
+        parse_mode: ParseMode,
     ) -> Self {
         let client_sessionid = Arc::new(AtomicI32::new(0));
-        let (filter, _rest) = MessageFilter::new(
+        let steamid = Arc::new(AtomicU64::new(0));
+        let (filter, rest) = MessageFilter::new(
             source,
             client_sessionid.clone(),
+            steamid.clone(),
+            wire_logging,
+            parse_mode,
         );
         
         Self {
-            websocket_write: tokio::sync::Mutex::new(websocket_write),
+            websocket_write: Arc::new(tokio::sync::Mutex::new(websocket_write)),
+            priority_gate: PriorityGate::default(),
+            draining: AtomicBool::new(false),
+            pending_flush: Arc::new(AtomicBool::new(false)),
+            flush_interval: None,
+            ping_interval: None,
+            latency_threshold: None,
+            connected_at: std::time::Instant::now(),
+            random: crate::clock::thread_random(),
             filter: Arc::new(filter),
             client_sessionid,
+            steamid,
+            messages: std::sync::Mutex::new(Some(rest)),
+            proxy: None,
+            dns_resolver: None,
+            cm_list_client: None,
+            cm_list_refresh: None,
+            max_connect_attempts: DEFAULT_CONNECT_ATTEMPTS,
+            ip_preference: IpPreference::default(),
+            websocket_config: None,
+            tls_connector: None,
+            hosts: SteamHosts::default(),
+            connect_timeout: None,
+            wire_logging,
+            parse_mode,
+            headers: builder::default_upgrade_headers(),
+            endpoint: String::new(),
+        }
+    }
+    
+    /// Gets the SOCKS5 proxy this transport is connected through.
+    pub fn proxy(&self) -> Option<&crate::transports::Socks5ProxyConfig> {
+        self.proxy.as_ref()
+    }
+    
+    /// Gets the DNS-over-HTTPS resolver this transport was connected with.
+    pub fn dns_resolver(&self) -> Option<&crate::transports::dns::DohResolver> {
+        self.dns_resolver.as_ref()
+    }
+    
+    /// Gets when and how the CM list this transport picked its server from was last refreshed, 
+    /// as of connecting. The list is shared with other transports using the same directory, so 
+    /// it may have been refreshed by another transport through a different route.
+    pub fn cm_list_refresh(&self) -> Option<&CmListRefresh> {
+        self.cm_list_refresh.as_ref()
+    }
+    
+    /// Gets the endpoint of the CM server this transport is connected to.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+    
+    /// Checks whether the connection to the CM server has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.filter.is_closed()
+    }
+    
+    /// Checks whether the transport is healthy: the socket is open, no more than 
+    /// [`MAX_HEALTHY_BACKLOG`] requests are waiting for a response, and, if pings are enabled, a 
+    /// pong was received within the last three ping intervals. Schedulers can use this to 
+    /// recycle connections before requests start failing.
+    pub fn is_healthy(&self) -> bool {
+        if self.is_closed() || self.is_draining() || self.filter.pending_jobs() > MAX_HEALTHY_BACKLOG {
+            return false;
+        }
+        
+        if let Some(ping_interval) = self.ping_interval {
+            let last_pong = self.filter.latency
+                .last_pong()
+                .unwrap_or(self.connected_at);
+            
+            return last_pong.elapsed() <= ping_interval.saturating_mul(3);
+        }
+        
+        true
+    }
+    
+    /// Gets when a message was last sent to or received from the CM server.
+    pub fn last_activity(&self) -> std::time::Instant {
+        self.filter.last_activity()
+    }
+    
+    /// Gets the number of requests waiting for a response.
+    pub fn pending_requests(&self) -> usize {
+        self.filter.pending_jobs()
+    }
+    
+    /// Gets the hosts this transport was connected with.
+    pub fn hosts(&self) -> &SteamHosts {
+        &self.hosts
+    }
+    
+    /// Connects to a new CM server using the same options as this transport.
+    pub async fn reconnect(&self) -> Result<WebSocketCMTransport, Error> {
+        crate::metrics::cm_reconnect();
+        self.reconnect_builder().connect().await
+    }
+    
+    /// Connects to a new CM server using the same options as this transport, but through 
+    /// `proxy`, or directly if `None`. Use this to move to a fresh IP when the current proxy gets 
+    /// flagged.
+    pub async fn reconnect_with_proxy(
+        &self,
+        proxy: Option<crate::transports::Socks5ProxyConfig>,
+    ) -> Result<WebSocketCMTransport, Error> {
+        let mut builder = self.reconnect_builder();
+        
+        builder.proxy = proxy;
+        crate::metrics::cm_reconnect();
+        builder.connect().await
+    }
+    
+    /// Creates a builder with the options this transport was connected with.
+    fn reconnect_builder(&self) -> WebSocketCMTransportBuilder {
+        WebSocketCMTransportBuilder {
+            proxy: self.proxy.clone(),
+            dns_resolver: self.dns_resolver.clone(),
+            cm_list_client: self.cm_list_client.clone(),
+            max_connect_attempts: self.max_connect_attempts,
+            ip_preference: self.ip_preference,
+            websocket_config: self.websocket_config,
+            tls_connector: self.tls_connector.clone(),
+            hosts: self.hosts.clone(),
+            connect_timeout: self.connect_timeout,
+            wire_logging: self.wire_logging,
+            parse_mode: self.parse_mode,
+            flush_interval: self.flush_interval,
+            ping_interval: self.ping_interval,
+            latency_threshold: self.latency_threshold,
+            random: Some(Arc::clone(&self.random)),
+            headers: self.headers.clone(),
+        }
+    }
+    
+    /// Stops accepting new requests and waits up to `deadline` for requests already sent to get 
+    /// a response. Messages sent after this fail with [`Error::Draining`]. Returns whether every 
+    /// request got a response before the deadline.
+    /// 
+    /// Use this before replacing the transport, e.g. when rotating CM servers, so requests in 
+    /// flight are not cut off and reported as timeouts.
+    pub async fn drain(&self, deadline: std::time::Duration) -> bool {
+        let started = std::time::Instant::now();
+        
+        self.draining.store(true, Ordering::SeqCst);
+        
+        while self.filter.pending_jobs() > 0 && !self.is_closed() {
+            if started.elapsed() >= deadline {
+                debug!("Drain deadline passed with {} requests in flight", self.filter.pending_jobs());
+                return false;
+            }
+            
+            tokio::time::sleep(DRAIN_CHECK_INTERVAL.min(deadline.saturating_sub(started.elapsed()))).await;
+        }
+        
+        self.filter.pending_jobs() == 0
+    }
+    
+    /// Drains the transport with [`Self::drain`] and closes it, then connects to a new CM server 
+    /// using the same options as this transport.
+    pub async fn drain_and_reconnect(&self, deadline: std::time::Duration) -> Result<WebSocketCMTransport, Error> {
+        self.retire(deadline).await;
+        self.reconnect().await
+    }
+    
+    /// Drains the transport with [`Self::drain`], then closes it. The task reading from the 
+    /// socket keeps the connection open until it is closed, so transports being replaced must be 
+    /// retired rather than dropped.
+    pub(crate) async fn retire(&self, deadline: std::time::Duration) {
+        self.drain(deadline).await;
+        
+        if let Err(error) = self.close().await {
+            debug!("Failed to close CM connection to {}: {error}", self.endpoint);
         }
     }
     
-    /// Sends a message to the CM server.
-    async fn send_message<'a, Msg>(
+    /// Checks whether the transport is draining and no longer accepts new requests.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+    
+    /// Starts batching writes. Messages are buffered as they are sent and the buffer is written 
+    /// to the socket every `flush_interval`, so bursts of small messages go out in fewer writes.
+    fn start_flushing(&mut self, flush_interval: std::time::Duration) {
+        // The task stops once the transport is dropped
+        let websocket_write = Arc::downgrade(&self.websocket_write);
+        let pending_flush = Arc::clone(&self.pending_flush);
+        
+        self.flush_interval = Some(flush_interval);
+        
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(flush_interval);
+            
+            loop {
+                ticker.tick().await;
+                
+                let Some(websocket_write) = websocket_write.upgrade() else {
+                    break;
+                };
+                
+                if !pending_flush.swap(false, Ordering::Relaxed) {
+                    continue;
+                }
+                
+                if let Err(error) = websocket_write.lock().await.flush().await {
+                    warn!("Error flushing websocket writes: {error}");
+                    
+                    if matches!(error, tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+    
+    /// Starts sending pings every `ping_interval` to measure the round-trip time.
+    fn start_pinging(&mut self, ping_interval: std::time::Duration, latency_threshold: Option<std::time::Duration>) {
+        // The task stops once the transport is dropped
+        let websocket_write = Arc::downgrade(&self.websocket_write);
+        let filter = Arc::clone(&self.filter);
+        
+        self.ping_interval = Some(ping_interval);
+        self.latency_threshold = latency_threshold;
+        
+        if let Some(latency_threshold) = latency_threshold {
+            filter.latency.set_threshold(latency_threshold);
+        }
+        
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(ping_interval);
+            
+            loop {
+                ticker.tick().await;
+                
+                let Some(websocket_write) = websocket_write.upgrade() else {
+                    break;
+                };
+                
+                if filter.is_closed() {
+                    break;
+                }
+                
+                let payload = filter.latency.ping_sent();
+                let ping = tungstenite::Message::Ping(payload.to_vec().into());
+                
+                if let Err(error) = websocket_write.lock().await.send(ping).await {
+                    warn!("Error sending websocket ping: {error}");
+                }
+            }
+        });
+    }
+    
+    /// Gets the last round-trip time measured using pings. This is `None` until a pong has been 
+    /// received, and always `None` if pings are not enabled with 
+    /// [`WebSocketCMTransportBuilder::ping_interval`].
+    pub fn latency(&self) -> Option<std::time::Duration> {
+        self.filter.latency.latency()
+    }
+    
+    /// Subscribes to events for this transport, such as high latency and the connection being 
+    /// lost. Events emitted before subscribing are not received.
+    pub fn subscribe(&self) -> broadcast::Receiver<TransportEvent> {
+        self.filter.events.subscribe()
+    }
+    
+    /// Writes any messages buffered by write batching to the socket immediately.
+    pub async fn flush(&self) -> Result<(), Error> {
+        self.pending_flush.store(false, Ordering::Relaxed);
+        self.websocket_write.lock().await.flush().await?;
+        
+        Ok(())
+    }
+    
+    /// Stops accepting new requests and closes the connection, sending a close frame to the CM 
+    /// server. Use [`Self::drain`] first to let requests in flight get a response.
+    pub async fn close(&self) -> Result<(), Error> {
+        self.draining.store(true, Ordering::SeqCst);
+        self.websocket_write.lock().await.close().await?;
+        
+        Ok(())
+    }
+    
+    /// Takes the receiver for messages from the CM server that are not responses to requests, 
+    /// such as `ClientLogOnResponse` and `ClientLoggedOff`. This can only be taken once. Messages 
+    /// are dropped while the receiver is full or after it has been dropped.
+    pub fn take_message_receiver(&self) -> Option<mpsc::Receiver<Result<Message, Error>>> {
+        self.messages.lock().ok()?.take()
+    }
+    
+    /// Gets the SteamID sent in message headers.
+    pub fn steamid(&self) -> SteamID {
+        SteamID::from(self.steamid.load(Ordering::Relaxed))
+    }
+    
+    /// Sets the SteamID sent in message headers. This is updated from headers received from the 
+    /// CM server after logging on.
+    pub fn set_steamid(&self, steamid: SteamID) {
+        self.steamid.store(u64::from(steamid), Ordering::Relaxed);
+    }
+    
+    /// Sends a message to the CM server. Returns the job ID if the message is a service method 
+    /// call.
+    pub async fn send_message<Msg>(
         &self,
         emsg: EMsg,
         msg: Msg,
         service_method_name: Option<&'static str>,
     ) -> Result<Option<u64>, Error>
     where
-        Msg: ApiRequest,
+        Msg: MessageFull,
+    {
+        self.send_message_with_priority(emsg, msg, service_method_name, MessagePriority::Normal).await
+    }
+    
+    /// Sends a message to the CM server with `priority`. While writes are backed up, it is 
+    /// written ahead of waiting messages with a lower priority. Returns the job ID if the message 
+    /// is a service method call.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "cm.send_message",
+        skip(self, msg),
+        fields(jobid = tracing::field::Empty),
+    ))]
+    pub async fn send_message_with_priority<Msg>(
+        &self,
+        emsg: EMsg,
+        msg: Msg,
+        service_method_name: Option<&'static str>,
+        priority: MessagePriority,
+    ) -> Result<Option<u64>, Error>
+    where
+        Msg: MessageFull,
     {
-        let mut body = msg.write_to_bytes()?;
+        if self.is_draining() {
+            return Err(Error::Draining);
+        }
+        
         let mut proto_header = CMsgProtoBufHeader::default();
-        let client_sessionid = if emsg != EMsg::ServiceMethodCallFromClientNonAuthed {
-            self.client_sessionid.load(Ordering::Relaxed)
+        let (client_sessionid, steamid) = if emsg != EMsg::ServiceMethodCallFromClientNonAuthed {
+            (
+                self.client_sessionid.load(Ordering::Relaxed),
+                self.steamid.load(Ordering::Relaxed),
+            )
         } else {
-            0
+            (0, 0)
         };
         
-        proto_header.set_steamid(0);
+        proto_header.set_steamid(steamid);
         proto_header.set_client_sessionid(client_sessionid);
         
         let jobid = if emsg == EMsg::ServiceMethodCallFromClientNonAuthed {
-            let mut jobid_buffer = rand::thread_rng().gen::<[u8; 8]>();
+            let mut jobid_buffer: [u8; 8] = self.random.bytes();
             
             jobid_buffer[0] &= 0x7f;
             
@@ -173,32 +623,90 @@ impl WebSocketCMTransport {
         } else {
             None
         };
-        let mut encoded_proto_header = Vec::new();
+        let body_length = msg.compute_size() as usize;
         
-        proto_header.write_to_vec(&mut encoded_proto_header)?;
+        if let Some(jobid) = jobid {
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("jobid", jobid);
+            debug!("Send {emsg:?} ({}; jobid {jobid})", service_method_name.unwrap_or("unnamed"));
+        } else {
+            debug!("Send {emsg:?} ({})", service_method_name.unwrap_or("unnamed"));
+        }
         
-        let mut header: Vec<u8> = Vec::new();
-        let header_length = encoded_proto_header.len() as u32;
+        wire_log::log_sent(self.wire_logging, emsg, jobid, body_length, &msg);
         
-        header.write_u32::<LittleEndian>(emsg as u32 | PROTO_MASK)?; // 4
-        header.write_u32::<LittleEndian>(header_length)?; // 8
+        // The encoded buffer is handed to the websocket without copying
+        let message = tungstenite::Message::binary(encode_message(emsg, &proto_header, &msg)?);
         
-        if let Some(jobid) = jobid {
-            log::debug!("Send {emsg:?} ({}; jobid {jobid})", service_method_name.unwrap_or("unnamed"));
+        let _permit = self.priority_gate.enter(priority).await;
+        let mut websocket_write = self.websocket_write.lock().await;
+        
+        self.filter.touch();
+        
+        if self.flush_interval.is_some() {
+            // Written by the flush task, or sooner if the write buffer fills up
+            websocket_write.feed(message).await?;
+            self.pending_flush.store(true, Ordering::Relaxed);
         } else {
-            log::debug!("Send {emsg:?} ({})", service_method_name.unwrap_or("unnamed"));
+            websocket_write.send(message).await?;
         }
         
-        let mut message: Vec<u8> = Vec::new();
+        Ok(jobid)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use futures::StreamExt;
+    
+    /// Connects a transport to a websocket server on localhost, returning the server's end of the 
+    /// connection.
+    pub(crate) async fn connect_local() -> (WebSocketCMTransport, WebSocketStream<TcpStream>) {
+        connect_local_with(ParseMode::Lenient).await
+    }
+    
+    /// Connects a transport parsing messages with `parse_mode` to a websocket server on localhost.
+    pub(crate) async fn connect_local_with(
+        parse_mode: ParseMode,
+    ) -> (WebSocketCMTransport, WebSocketStream<TcpStream>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (client, server) = tokio::join!(
+            async {
+                let stream = TcpStream::connect(addr).await.unwrap();
+                
+                tokio_tungstenite::client_async(format!("ws://{addr}"), MaybeTlsStream::Plain(stream))
+                    .await
+                    .unwrap()
+                    .0
+            },
+            async {
+                let (stream, _) = listener.accept().await.unwrap();
+                
+                tokio_tungstenite::accept_async(stream).await.unwrap()
+            },
+        );
+        let (websocket_write, source) = client.split();
+        let transport = WebSocketCMTransport::new(
+            source,
+            websocket_write,
+            WireLogging::Off,
+            parse_mode,
+        );
         
-        message.append(&mut header);
-        message.append(&mut encoded_proto_header);
-        message.append(&mut body);
+        (transport, server)
+    }
+    
+    #[tokio::test]
+    async fn retiring_closes_the_connection() {
+        let (transport, mut server) = connect_local().await;
         
-        let message = tungstenite::Message::binary(message);
+        transport.retire(std::time::Duration::from_secs(1)).await;
         
-        self.websocket_write.lock().await.send(message).await?;
+        let message = server.next().await.unwrap().unwrap();
         
-        Ok(jobid)
+        assert!(message.is_close());
+        assert!(transport.is_draining());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,78 @@
+use crate::net::MessagePriority;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Notify;
+
+/// Orders writers waiting for the socket by priority. A writer waits while any writer of a 
+/// higher priority is waiting or writing.
+#[derive(Debug, Default)]
+pub(crate) struct PriorityGate {
+    /// The number of writers waiting or writing, indexed by priority.
+    waiting: [AtomicUsize; 3],
+    notify: Notify,
+}
+
+impl PriorityGate {
+    /// Waits until no writers of a higher priority than `priority` are waiting. The returned 
+    /// permit should be held until the message is written.
+    pub async fn enter(&self, priority: MessagePriority) -> PriorityPermit<'_> {
+        self.waiting[priority as usize].fetch_add(1, Ordering::SeqCst);
+        
+        let permit = PriorityPermit {
+            gate: self,
+            priority,
+        };
+        
+        loop {
+            let mut notified = pin!(self.notify.notified());
+            
+            // registered before checking so a permit dropped in between is not missed
+            notified.as_mut().enable();
+            
+            if !self.is_higher_waiting(priority) {
+                return permit;
+            }
+            
+            notified.await;
+        }
+    }
+    
+    fn is_higher_waiting(&self, priority: MessagePriority) -> bool {
+        self.waiting[priority as usize + 1..]
+            .iter()
+            .any(|waiting| waiting.load(Ordering::SeqCst) > 0)
+    }
+}
+
+/// Marks a writer as waiting or writing until dropped.
+#[derive(Debug)]
+pub(crate) struct PriorityPermit<'a> {
+    gate: &'a PriorityGate,
+    priority: MessagePriority,
+}
+
+impl Drop for PriorityPermit<'_> {
+    fn drop(&mut self) {
+        self.gate.waiting[self.priority as usize].fetch_sub(1, Ordering::SeqCst);
+        self.gate.notify.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    
+    #[tokio::test]
+    async fn waits_for_higher_priority() {
+        let gate = PriorityGate::default();
+        let urgent = gate.enter(MessagePriority::Urgent).await;
+        let mut bulk = pin!(gate.enter(MessagePriority::Bulk));
+        
+        assert!(futures::poll!(bulk.as_mut()).is_pending());
+        
+        drop(urgent);
+        
+        tokio::time::timeout(Duration::from_millis(10), bulk).await.unwrap();
+    }
+}
@@ -1,26 +1,31 @@
-use super::Error;
-use crate::enums::EResult;
+use super::{Error, ParseMode};
+use crate::enums::{EMsg, EResult};
 use crate::net::{ApiRequest, ApiResponse};
-use bytes::BytesMut;
-use bytes::Buf;
+use bytes::{Buf, Bytes};
 
 #[derive(Debug, Clone)]
 pub struct ApiResponseBody {
     pub eresult: Option<EResult>,
     pub error_message: Option<String>,
-    pub body: Option<Vec<u8>>,
+    pub body: Option<Bytes>,
 }
 
 impl ApiResponseBody {
-    pub fn into_response<Msg>(self) -> Result<Msg::Response, Error>
+    pub fn into_response<Msg>(self, parse_mode: ParseMode) -> Result<Msg::Response, Error>
     where
         Msg: ApiRequest,
         <Msg as ApiRequest>::Response: Send,
     {
         if let Some(body) = self.body {
-            let bytes = BytesMut::from(body.as_slice());
-            let mut reader = bytes.reader();
-            let response = Msg::Response::parse_from_reader(&mut reader)?;
+            let length = body.len();
+            let mut reader = body.reader();
+            let response = Msg::Response::parse_from_reader(&mut reader)
+                .map_err(|error| Error::from(error).parse(Some(EMsg::ServiceMethodResponse), length))?;
+            
+            if parse_mode == ParseMode::Strict && response.has_unknown_fields() {
+                return Err(Error::UnknownFields(<Msg as ApiRequest>::NAME)
+                    .parse(Some(EMsg::ServiceMethodResponse), length));
+            }
             
             Ok(response)
         } else if let Some(eresult) = self.eresult {
@@ -0,0 +1,251 @@
+use super::{Error, WebSocketCMTransport};
+use crate::authentication_client::Error as AuthenticationClientError;
+use crate::net::ApiRequest;
+use crate::transports::Transport;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+/// When a [`RotatingTransport`] moves to a new CM server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// Rotate once the connection is this old.
+    pub max_age: Option<Duration>,
+    /// Rotate once this many requests have been sent over the connection.
+    pub max_requests: Option<u64>,
+    /// How long to wait for requests in flight on the old connection to get a response.
+    pub drain_deadline: Duration,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        Self {
+            max_age: None,
+            max_requests: None,
+            drain_deadline: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RotationPolicy {
+    /// Creates a new [`RotationPolicy`] that never rotates on its own.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Rotates once the connection is `max_age` old.
+    pub fn every(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+    
+    /// Rotates once `max_requests` requests have been sent over the connection.
+    pub fn after_requests(mut self, max_requests: u64) -> Self {
+        self.max_requests = Some(max_requests.max(1));
+        self
+    }
+    
+    /// Sets how long to wait for requests in flight on the old connection to get a response. 
+    /// Defaults to 10 seconds.
+    pub fn drain_deadline(mut self, drain_deadline: Duration) -> Self {
+        self.drain_deadline = drain_deadline;
+        self
+    }
+    
+    fn is_due(&self, age: Duration, requests: u64) -> bool {
+        self.max_age.is_some_and(|max_age| age >= max_age) ||
+        self.max_requests.is_some_and(|max_requests| requests >= max_requests)
+    }
+}
+
+/// A [`Transport`] that moves to a new CM server according to a [`RotationPolicy`], or when 
+/// [`RotatingTransport::rotate`] is called, without tearing down the session using it. Long-lived 
+/// connections through some proxies degrade over time.
+/// 
+/// The new connection is made in the background while requests continue on the old one. Once it 
+/// is ready, new requests go to it and the old connection is drained, so requests in flight are 
+/// not cut off.
+/// 
+/// # Examples
+/// ```no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use steam_session::LoginSession;
+/// use steam_session::enums::EAuthTokenPlatformType;
+/// use steam_session::transports::WebSocketCMTransport;
+/// use steam_session::transports::websocket::{RotatingTransport, RotationPolicy};
+/// use std::time::Duration;
+/// 
+/// let transport = RotatingTransport::new(
+///     WebSocketCMTransport::connect().await?,
+///     RotationPolicy::new().every(Duration::from_secs(6 * 60 * 60)),
+/// );
+/// let session = LoginSession::builder(
+///     transport,
+///     EAuthTokenPlatformType::k_EAuthTokenPlatformType_WebBrowser,
+/// ).build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RotatingTransport {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    current: RwLock<Current>,
+    policy: RotationPolicy,
+    requests: AtomicU64,
+    rotating: AtomicBool,
+}
+
+#[derive(Debug)]
+struct Current {
+    transport: Arc<WebSocketCMTransport>,
+    connected_at: Instant,
+}
+
+#[async_trait]
+impl Transport for RotatingTransport {
+    async fn send_request<Msg>(
+        &self,
+        msg: Msg,
+        access_token: Option<String>,
+    ) -> Result<oneshot::Receiver<Result<Msg::Response, AuthenticationClientError>>, AuthenticationClientError> 
+    where
+        Msg: ApiRequest,
+        <Msg as ApiRequest>::Response: Send,
+    {
+        let requests = self.inner.requests.fetch_add(1, Ordering::Relaxed) + 1;
+        let (transport, connected_at) = self.inner.current();
+        
+        if self.inner.policy.is_due(connected_at.elapsed(), requests) {
+            let inner = Arc::clone(&self.inner);
+            
+            tokio::spawn(async move {
+                if let Err(error) = inner.rotate().await {
+                    warn!("Failed to rotate CM connection: {error}");
+                }
+            });
+        }
+        
+        match transport.send_request(msg.clone(), access_token.clone()).await {
+            // the connection was swapped out after it was picked
+            Err(AuthenticationClientError::WebSocketCM(error)) if error.is_draining() => {
+                self.inner.current().0.send_request(msg, access_token).await
+            },
+            result => result,
+        }
+    }
+    
+    fn cm_endpoint(&self) -> Option<String> {
+        Some(self.inner.current().0.endpoint().to_owned())
+    }
+}
+
+impl RotatingTransport {
+    /// Creates a new [`RotatingTransport`] starting with `transport`.
+    pub fn new(transport: WebSocketCMTransport, policy: RotationPolicy) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                current: RwLock::new(Current {
+                    transport: Arc::new(transport),
+                    connected_at: Instant::now(),
+                }),
+                policy,
+                requests: AtomicU64::new(0),
+                rotating: AtomicBool::new(false),
+            }),
+        }
+    }
+    
+    /// Gets the transport requests are currently sent over.
+    pub fn transport(&self) -> Arc<WebSocketCMTransport> {
+        self.inner.current().0
+    }
+    
+    /// Gets the rotation policy.
+    pub fn policy(&self) -> &RotationPolicy {
+        &self.inner.policy
+    }
+    
+    /// Moves to a new CM server now. Completes once the old connection has been drained. Does 
+    /// nothing if a rotation is already in progress.
+    pub async fn rotate(&self) -> Result<(), Error> {
+        self.inner.rotate().await
+    }
+}
+
+impl Inner {
+    fn current(&self) -> (Arc<WebSocketCMTransport>, Instant) {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        
+        (Arc::clone(&current.transport), current.connected_at)
+    }
+    
+    fn replace(&self, transport: Arc<WebSocketCMTransport>) -> Arc<WebSocketCMTransport> {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        
+        self.requests.store(0, Ordering::Relaxed);
+        current.connected_at = Instant::now();
+        std::mem::replace(&mut current.transport, transport)
+    }
+    
+    async fn rotate(&self) -> Result<(), Error> {
+        if self.rotating.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        
+        let (old, _) = self.current();
+        let new = match old.reconnect().await {
+            Ok(new) => new,
+            Err(error) => {
+                // wait out another period before trying again rather than retrying on every request
+                let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+                
+                self.requests.store(0, Ordering::Relaxed);
+                current.connected_at = Instant::now();
+                self.rotating.store(false, Ordering::SeqCst);
+                return Err(error);
+            },
+        };
+        
+        self.swap(new).await;
+        
+        Ok(())
+    }
+    
+    /// Moves requests to `new`, then drains and closes the old connection.
+    async fn swap(&self, new: WebSocketCMTransport) {
+        let old = self.replace(Arc::new(new));
+        
+        debug!("Rotating CM connection from {} to {}", old.endpoint(), self.current().0.endpoint());
+        self.rotating.store(false, Ordering::SeqCst);
+        old.retire(self.policy.drain_deadline).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::connect_local;
+    use futures::StreamExt;
+    
+    #[tokio::test]
+    async fn closes_the_old_connection_after_rotating() {
+        let (old, mut old_server) = connect_local().await;
+        let (new, _new_server) = connect_local().await;
+        let transport = RotatingTransport::new(old, RotationPolicy::new());
+        let old = transport.transport();
+        
+        transport.inner.swap(new).await;
+        
+        let message = old_server.next().await.unwrap().unwrap();
+        
+        assert!(message.is_close());
+        assert!(!Arc::ptr_eq(&old, &transport.transport()));
+        assert!(!transport.transport().is_draining());
+    }
+}
@@ -0,0 +1,250 @@
+//! Logging of the messages sent to and received from the CM server, for debugging the protocol.
+
+use crate::enums::EMsg;
+use protobuf::MessageFull;
+use protobuf::reflect::{ReflectValueBox, ReflectValueRef, RuntimeFieldType, RuntimeType};
+use protobuf::MessageDyn;
+
+/// Fields with names containing any of these are cleared before a payload is dumped.
+const REDACTED_FIELDS: &[&str] = &[
+    "token",
+    "password",
+    "secret",
+    "signature",
+    "nonce",
+    "code",
+    "guard_data",
+    "new_guard_data",
+];
+
+/// How much of the traffic to and from the CM server is logged. Wire logs are emitted at the 
+/// `debug` level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireLogging {
+    /// Nothing is logged.
+    #[default]
+    Off,
+    /// The EMsg, job ID, and size of each message are logged.
+    Summary,
+    /// The summary is logged along with a hex dump of each message body with a known type. Fields 
+    /// with names containing `token`, `password`, `secret`, `signature`, `nonce`, `code`, or 
+    /// `guard_data` are cleared before dumping.
+    Payloads,
+}
+
+impl WireLogging {
+    /// Whether message summaries are logged.
+    pub fn logs_summary(&self) -> bool {
+        !matches!(self, Self::Off)
+    }
+    
+    /// Whether message bodies are dumped.
+    pub fn logs_payloads(&self) -> bool {
+        matches!(self, Self::Payloads)
+    }
+}
+
+/// Logs a message being sent.
+pub(crate) fn log_sent<Msg>(
+    wire_logging: WireLogging,
+    emsg: EMsg,
+    jobid: Option<u64>,
+    size: usize,
+    msg: &Msg,
+)
+where
+    Msg: MessageFull,
+{
+    if !wire_logging.logs_summary() {
+        return;
+    }
+    
+    let name = Msg::descriptor().name().to_string();
+    
+    match jobid {
+        Some(jobid) => debug!("wire > {emsg:?} {name} (jobid {jobid}; {size} bytes)"),
+        None => debug!("wire > {emsg:?} {name} ({size} bytes)"),
+    }
+    
+    if wire_logging.logs_payloads() {
+        debug!("wire > {name}\n{}", hex_dump(&redacted_payload(msg)));
+    }
+}
+
+/// Logs a message being received.
+pub(crate) fn log_received(
+    wire_logging: WireLogging,
+    emsg: EMsg,
+    jobid: u64,
+    size: usize,
+) {
+    if !wire_logging.logs_summary() {
+        return;
+    }
+    
+    if jobid != 0 {
+        debug!("wire < {emsg:?} (jobid {jobid}; {size} bytes)");
+    } else {
+        debug!("wire < {emsg:?} ({size} bytes)");
+    }
+}
+
+/// Logs the body of a response to a request named `name`.
+pub(crate) fn log_response(
+    wire_logging: WireLogging,
+    name: &str,
+    payload: Option<Vec<u8>>,
+) {
+    if !wire_logging.logs_payloads() {
+        return;
+    }
+    
+    if let Some(payload) = payload {
+        debug!("wire < {name}\n{}", hex_dump(&payload));
+    }
+}
+
+/// Encodes `msg` with sensitive fields cleared.
+pub fn redacted_payload<Msg>(msg: &Msg) -> Vec<u8>
+where
+    Msg: MessageFull,
+{
+    let mut msg = msg.clone();
+    
+    scrub(&mut msg);
+    msg.write_to_bytes().unwrap_or_default()
+}
+
+/// Clears sensitive fields in `msg`, including in nested messages.
+fn scrub(msg: &mut dyn MessageDyn) {
+    for field in msg.descriptor_dyn().fields() {
+        let name = field.name();
+        
+        if REDACTED_FIELDS.iter().any(|redacted| name.contains(redacted)) {
+            field.clear_field(msg);
+            continue;
+        }
+        
+        match field.runtime_field_type() {
+            RuntimeFieldType::Singular(RuntimeType::Message(_)) if field.has_field(msg) => {
+                scrub(field.mut_message(msg));
+            },
+            RuntimeFieldType::Repeated(RuntimeType::Message(_)) => {
+                let mut repeated = field.mut_repeated(msg);
+                
+                for i in 0..repeated.len() {
+                    let mut element = match repeated.get(i) {
+                        ReflectValueRef::Message(element) => element.clone_box(),
+                        _ => continue,
+                    };
+                    
+                    scrub(&mut *element);
+                    repeated.set(i, ReflectValueBox::Message(element));
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Formats `bytes` as lines of 16 hex-encoded bytes prefixed with their offset.
+fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            
+            format!("{:08x}  {hex}", i * 16)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::steammessages_clientserver_login::CMsgClientLogon;
+    use crate::proto::steammessages_auth_steamclient::{
+        CAuthentication_UpdateAuthSessionWithSteamGuardCode_Request,
+        CAuthentication_PollAuthSessionStatus_Response,
+    };
+    use protobuf::Message;
+    use protobuf::descriptor::{FileDescriptorSet, FileDescriptorProto, SourceCodeInfo};
+    
+    #[test]
+    fn clears_sensitive_fields() {
+        let mut logon = CMsgClientLogon::new();
+        
+        logon.set_account_name(String::from("username"));
+        logon.set_access_token(String::from("eyAidHlwIjogIkpXVCIsICJhbGciOiAiRWREU0EiIH0"));
+        
+        let scrubbed = CMsgClientLogon::parse_from_bytes(&redacted_payload(&logon)).unwrap();
+        
+        assert_eq!(scrubbed.account_name(), "username");
+        assert!(!scrubbed.has_access_token());
+    }
+    
+    #[test]
+    fn clears_guard_codes_and_data() {
+        let mut request = CAuthentication_UpdateAuthSessionWithSteamGuardCode_Request::new();
+        
+        request.set_client_id(1);
+        request.set_code(String::from("ABC12"));
+        
+        let scrubbed = CAuthentication_UpdateAuthSessionWithSteamGuardCode_Request::parse_from_bytes(
+            &redacted_payload(&request),
+        ).unwrap();
+        
+        assert_eq!(scrubbed.client_id(), 1);
+        assert!(!scrubbed.has_code());
+        
+        let mut response = CAuthentication_PollAuthSessionStatus_Response::new();
+        
+        response.set_account_name(String::from("username"));
+        response.set_new_guard_data(String::from("guard data"));
+        
+        let scrubbed = CAuthentication_PollAuthSessionStatus_Response::parse_from_bytes(
+            &redacted_payload(&response),
+        ).unwrap();
+        
+        assert_eq!(scrubbed.account_name(), "username");
+        assert!(!scrubbed.has_new_guard_data());
+    }
+    
+    #[test]
+    fn clears_sensitive_fields_in_repeated_messages() {
+        let mut file = FileDescriptorProto::new();
+        
+        file.set_name(String::from("file.proto"));
+        file.source_code_info = Some(SourceCodeInfo::new()).into();
+        
+        let mut set = FileDescriptorSet::new();
+        
+        set.file.push(file.clone());
+        set.file.push(file);
+        
+        let scrubbed = FileDescriptorSet::parse_from_bytes(&redacted_payload(&set)).unwrap();
+        
+        assert_eq!(scrubbed.file.len(), 2);
+        
+        for file in &scrubbed.file {
+            assert_eq!(file.name(), "file.proto");
+            assert!(file.source_code_info.is_none());
+        }
+    }
+    
+    #[test]
+    fn formats_hex_dump() {
+        let bytes = (0..18).collect::<Vec<u8>>();
+        
+        assert_eq!(
+            hex_dump(&bytes),
+            "00000000  00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n00000010  10 11",
+        );
+    }
+}
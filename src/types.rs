@@ -1 +1 @@
-pub type DateTime = chrono::DateTime<chrono::Utc>;
\ No newline at end of file
+pub type DateTime = chrono::DateTime<chrono::Utc>;
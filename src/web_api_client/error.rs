@@ -0,0 +1,20 @@
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("reqwest error: {}", .0)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Login session error: {}", .0)]
+    LoginSession(#[from] crate::login_session::LoginSessionError),
+    #[error("Request was unauthorized after renewing the access token")]
+    Unauthorized,
+}
+
+impl Error {
+    /// Gets the kind of error.
+    pub fn kind(&self) -> crate::ErrorKind {
+        match self {
+            Self::Reqwest(error) => crate::error::reqwest_kind(error),
+            Self::LoginSession(error) => error.kind(),
+            Self::Unauthorized => crate::ErrorKind::Token,
+        }
+    }
+}
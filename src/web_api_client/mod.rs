@@ -0,0 +1,226 @@
+//! # WebAPI Client
+//! 
+//! A thin client for calling authenticated Steam WebAPI methods such as 
+//! `IPlayerService/GetOwnedGames` using the access token of a [`LoginSession`].
+
+mod error;
+
+pub use error::Error;
+
+use crate::login_session::LoginSession;
+use crate::hosts::SteamHosts;
+use crate::transports::Transport;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// How the access token is attached to requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccessTokenPlacement {
+    /// As the `access_token` query string parameter.
+    #[default]
+    QueryParameter,
+    /// As a bearer token in the `Authorization` header.
+    AuthorizationHeader,
+}
+
+/// A client for calling Steam WebAPI methods that require an access token. If a request is 
+/// rejected with 401 Unauthorized, the access token is renewed through the session and the 
+/// request is retried once.
+#[derive(Debug)]
+pub struct WebApiClient<T> {
+    session: LoginSession<T>,
+    client: Client,
+    placement: AccessTokenPlacement,
+    hosts: SteamHosts,
+}
+
+impl<T> WebApiClient<T>
+where
+    T: Transport,
+{
+    /// Creates a new [`WebApiClient`] using the HTTP client of `session`. The session must have a 
+    /// refresh token.
+    pub fn new(session: LoginSession<T>) -> Self {
+        let client = session.client().clone();
+        let hosts = session.hosts().clone();
+        
+        Self {
+            session,
+            client,
+            placement: AccessTokenPlacement::default(),
+            hosts,
+        }
+    }
+    
+    /// Sets the HTTP client used for requests.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+    
+    /// Sets how the access token is attached to requests.
+    pub fn with_placement(mut self, placement: AccessTokenPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+    
+    /// Sets the hosts requests are sent to. Defaults to the hosts of the session.
+    pub fn with_hosts(mut self, hosts: SteamHosts) -> Self {
+        self.hosts = hosts;
+        self
+    }
+    
+    /// Gets a reference to the session.
+    pub fn session(&self) -> &LoginSession<T> {
+        &self.session
+    }
+    
+    /// Gets a mutable reference to the session.
+    pub fn session_mut(&mut self) -> &mut LoginSession<T> {
+        &mut self.session
+    }
+    
+    /// Consumes the client, returning the session.
+    pub fn into_session(self) -> LoginSession<T> {
+        self.session
+    }
+    
+    /// Sends a GET request to `interface/method/v{version}` with `query` as the query string.
+    /// 
+    /// # Examples
+    /// ```no_run
+    /// # async fn example<T: steam_session::transports::Transport>(
+    /// #     client: &mut steam_session::web_api_client::WebApiClient<T>,
+    /// # ) -> Result<(), steam_session::web_api_client::Error> {
+    /// let response = client.get(
+    ///     "IPlayerService",
+    ///     "GetOwnedGames",
+    ///     1,
+    ///     &[("steamid", "76561197960287930")],
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get<Q>(
+        &mut self,
+        interface: &str,
+        method: &str,
+        version: u32,
+        query: &Q,
+    ) -> Result<Response, Error>
+    where
+        Q: Serialize + ?Sized,
+    {
+        self.send(Method::GET, interface, method, version, |request| request.query(query)).await
+    }
+    
+    /// Sends a POST request to `interface/method/v{version}` with `form` as the form body.
+    pub async fn post<F>(
+        &mut self,
+        interface: &str,
+        method: &str,
+        version: u32,
+        form: &F,
+    ) -> Result<Response, Error>
+    where
+        F: Serialize + ?Sized,
+    {
+        self.send(Method::POST, interface, method, version, |request| request.form(form)).await
+    }
+    
+    /// Sends a GET request and deserializes the JSON response.
+    pub async fn get_json<Q, R>(
+        &mut self,
+        interface: &str,
+        method: &str,
+        version: u32,
+        query: &Q,
+    ) -> Result<R, Error>
+    where
+        Q: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let response = self.get(interface, method, version, query).await?;
+        
+        Ok(response.error_for_status()?.json::<R>().await?)
+    }
+    
+    /// Sends a POST request and deserializes the JSON response.
+    pub async fn post_json<F, R>(
+        &mut self,
+        interface: &str,
+        method: &str,
+        version: u32,
+        form: &F,
+    ) -> Result<R, Error>
+    where
+        F: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let response = self.post(interface, method, version, form).await?;
+        
+        Ok(response.error_for_status()?.json::<R>().await?)
+    }
+    
+    /// Sends a request, renewing the access token and retrying once if it is rejected.
+    async fn send<B>(
+        &mut self,
+        method: Method,
+        interface: &str,
+        name: &str,
+        version: u32,
+        build: B,
+    ) -> Result<Response, Error>
+    where
+        B: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let url = self.hosts.api_url(&format!("{interface}/{name}/v{version}/"));
+        
+        if self.session.get_access_token().is_none() {
+            self.session.refresh_access_token().await?;
+        }
+        
+        let response = self.send_once(method.clone(), &url, &build).await?;
+        
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        
+        debug!("{interface}/{name} returned 401, renewing access token");
+        self.session.refresh_access_token().await?;
+        
+        let response = self.send_once(method, &url, &build).await?;
+        
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Err(Error::Unauthorized);
+        }
+        
+        Ok(response)
+    }
+    
+    async fn send_once<B>(
+        &self,
+        method: Method,
+        url: &str,
+        build: &B,
+    ) -> Result<Response, Error>
+    where
+        B: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let access_token = self.session.get_access_token()
+            .map(String::as_str)
+            .unwrap_or_default();
+        let request = build(self.client.request(method, url));
+        let request = match self.placement {
+            AccessTokenPlacement::QueryParameter => {
+                request.query(&[("access_token", access_token)])
+            },
+            AccessTokenPlacement::AuthorizationHeader => {
+                request.bearer_auth(access_token)
+            },
+        };
+        
+        Ok(request.send().await?)
+    }
+}